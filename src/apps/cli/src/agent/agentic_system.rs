@@ -21,17 +21,13 @@ pub struct AgenticSystem {
     pub event_queue: Arc<events::EventQueue>,
 }
 
-/// Initialize Agentic system
-pub async fn init_agentic_system() -> Result<AgenticSystem> {
-    tracing::info!("Initializing Agentic system");
-
-    let _ai_client_factory = AIClientFactory::get_global().await?;
-
-    let event_queue = Arc::new(events::EventQueue::new(Default::default()));
-    let event_router = Arc::new(events::EventRouter::new());
-
+/// Initialize the session manager over persisted sessions
+///
+/// Session management does not need an AI client, so commands that only work on
+/// stored sessions can use this without initializing the whole system.
+pub fn init_session_manager() -> Result<Arc<session::SessionManager>> {
     let path_manager = try_get_path_manager_arc()?;
-    let persistence_manager = Arc::new(persistence::PersistenceManager::new(path_manager.clone())?);
+    let persistence_manager = Arc::new(persistence::PersistenceManager::new(path_manager)?);
 
     let history_manager = Arc::new(session::MessageHistoryManager::new(
         persistence_manager.clone(),
@@ -49,12 +45,24 @@ pub async fn init_agentic_system() -> Result<AgenticSystem> {
         },
     ));
 
-    let session_manager = Arc::new(session::SessionManager::new(
-        history_manager.clone(),
+    Ok(Arc::new(session::SessionManager::new(
+        history_manager,
         compression_manager,
-        persistence_manager.clone(),
+        persistence_manager,
         Default::default(),
-    ));
+    )))
+}
+
+/// Initialize Agentic system
+pub async fn init_agentic_system() -> Result<AgenticSystem> {
+    tracing::info!("Initializing Agentic system");
+
+    let _ai_client_factory = AIClientFactory::get_global().await?;
+
+    let event_queue = Arc::new(events::EventQueue::new(Default::default()));
+    let event_router = Arc::new(events::EventRouter::new());
+
+    let session_manager = init_session_manager()?;
 
     let tool_registry = tools::registry::get_global_tool_registry();
     let tool_state_manager = Arc::new(tools::pipeline::ToolStateManager::new(event_queue.clone()));
@@ -6,10 +6,10 @@ use anyhow::Result;
 use tokio::sync::mpsc;
 use std::sync::Arc;
 
-use super::{Agent, AgentEvent, AgentResponse};
+use super::{Agent, AgentEvent, AgentResponse, TokenUsage};
 use crate::session::{ToolCall, ToolCallStatus};
 use bitfun_core::agentic::coordination::ConversationCoordinator;
-use bitfun_core::agentic::core::SessionConfig;
+use bitfun_core::agentic::core::{Message, MessageContent, MessageRole, SessionConfig};
 use bitfun_core::agentic::events::EventQueue;
 use bitfun_events::{AgenticEvent as CoreEvent, ToolEventData};
 
@@ -19,7 +19,8 @@ pub struct CoreAgentAdapter {
     agent_type: String,
     coordinator: Arc<ConversationCoordinator>,
     event_queue: Arc<EventQueue>,
-    session_id: Option<String>,
+    /// Core session the messages go to; created on the first message unless resumed
+    session_id: tokio::sync::Mutex<Option<String>>,
 }
 
 impl CoreAgentAdapter {
@@ -38,12 +39,30 @@ impl CoreAgentAdapter {
             agent_type: agent_type.clone(),
             coordinator,
             event_queue,
-            session_id: None,
+            session_id: tokio::sync::Mutex::new(None),
         }
     }
     
-    async fn ensure_session(&mut self) -> Result<String> {
-        if let Some(session_id) = &self.session_id {
+    /// Restore a persisted core session so that following messages continue it
+    ///
+    /// Returns the text messages of the restored history (compressed history stays compressed).
+    pub async fn resume_session(&self, session_id: &str) -> Result<Vec<(MessageRole, String)>> {
+        let session = self.coordinator.restore_session(session_id).await?;
+        tracing::info!(
+            "Resumed session: {} ({} turns)",
+            session.session_id,
+            session.dialog_turn_ids.len()
+        );
+        
+        let messages = self.coordinator.get_messages(&session.session_id).await?;
+        *self.session_id.lock().await = Some(session.session_id);
+        
+        Ok(messages.iter().filter_map(message_text).collect())
+    }
+    
+    async fn ensure_session(&self) -> Result<String> {
+        let mut current = self.session_id.lock().await;
+        if let Some(session_id) = current.as_ref() {
             return Ok(session_id.clone());
         }
         
@@ -53,7 +72,7 @@ impl CoreAgentAdapter {
             SessionConfig::default(),
         ).await?;
         
-        self.session_id = Some(session.session_id.clone());
+        *current = Some(session.session_id.clone());
         tracing::info!("Created session: {}", session.session_id);
         
         Ok(session.session_id)
@@ -67,15 +86,7 @@ impl Agent for CoreAgentAdapter {
         message: String,
         event_tx: mpsc::UnboundedSender<AgentEvent>,
     ) -> Result<AgentResponse> {
-        let mut self_mut = Self {
-            name: self.name.clone(),
-            agent_type: self.agent_type.clone(),
-            coordinator: self.coordinator.clone(),
-            event_queue: self.event_queue.clone(),
-            session_id: self.session_id.clone(),
-        };
-        
-        let session_id = self_mut.ensure_session().await?;
+        let session_id = self.ensure_session().await?;
         tracing::info!("Processing message: {}", message);
         
         let _ = event_tx.send(AgentEvent::Thinking);
@@ -89,6 +100,7 @@ impl Agent for CoreAgentAdapter {
         
         let mut accumulated_text = String::new();
         let mut tool_map: std::collections::HashMap<String, ToolCall> = std::collections::HashMap::new();
+        let mut token_usage = TokenUsage::default();
         
         let event_queue = self.event_queue.clone();
         let session_id_clone = session_id.clone();
@@ -116,6 +128,12 @@ impl Agent for CoreAgentAdapter {
                         let _ = event_tx.send(AgentEvent::TextChunk(text));
                     }
                     
+                    CoreEvent::TokenUsageUpdated { input_tokens, output_tokens, total_tokens, .. } => {
+                        token_usage.input_tokens += input_tokens;
+                        token_usage.output_tokens += output_tokens.unwrap_or(0);
+                        token_usage.total_tokens += total_tokens;
+                    }
+                    
                     CoreEvent::ToolEvent { tool_event, .. } => {
                         match tool_event {
                             ToolEventData::EarlyDetected { tool_id, tool_name } => {
@@ -165,6 +183,7 @@ impl Agent for CoreAgentAdapter {
                                 });
                                 
                                 let _ = event_tx.send(AgentEvent::ToolCallStart {
+                                    tool_id,
                                     tool_name,
                                     parameters: params,
                                 });
@@ -177,8 +196,10 @@ impl Agent for CoreAgentAdapter {
                                 }
                                 
                                 let _ = event_tx.send(AgentEvent::ToolCallProgress {
+                                    tool_id,
                                     tool_name,
                                     message,
+                                    percentage,
                                 });
                             }
                             
@@ -221,9 +242,11 @@ impl Agent for CoreAgentAdapter {
                                 }
                                 
                                 let _ = event_tx.send(AgentEvent::ToolCallComplete {
+                                    tool_id,
                                     tool_name,
                                     result: result_str,
                                     success: true,
+                                    duration_ms: Some(duration_ms),
                                 });
                             }
                             
@@ -234,9 +257,11 @@ impl Agent for CoreAgentAdapter {
                                 }
                                 
                                 let _ = event_tx.send(AgentEvent::ToolCallComplete {
+                                    tool_id,
                                     tool_name,
                                     result: error,
                                     success: false,
+                                    duration_ms: None,
                                 });
                             }
                             
@@ -251,14 +276,26 @@ impl Agent for CoreAgentAdapter {
                         }
                     }
                     
+                    CoreEvent::ModelSwitched { from_model, to_model, reason, .. } => {
+                        tracing::warn!("Model switched: {} -> {} ({})", from_model, to_model, reason);
+                        let _ = event_tx.send(AgentEvent::ModelSwitched {
+                            from_model,
+                            to_model,
+                            reason,
+                        });
+                    }
+                    
                     CoreEvent::DialogTurnCompleted { .. } => {
                         tracing::info!("Dialog turn completed");
                         let _ = event_tx.send(AgentEvent::Done);
                         let tool_calls: Vec<ToolCall> = tool_map.into_values().collect();
                         
                         return Ok(AgentResponse {
+                            session_id: session_id.clone(),
+                            text: accumulated_text,
                             tool_calls,
                             success: true,
+                            token_usage,
                         });
                     }
                     
@@ -268,8 +305,11 @@ impl Agent for CoreAgentAdapter {
                         let tool_calls: Vec<ToolCall> = tool_map.into_values().collect();
                         
                         return Ok(AgentResponse {
+                            session_id: session_id.clone(),
+                            text: accumulated_text,
                             tool_calls,
                             success: false,
+                            token_usage,
                         });
                     }
                     
@@ -279,8 +319,11 @@ impl Agent for CoreAgentAdapter {
                         let tool_calls: Vec<ToolCall> = tool_map.into_values().collect();
                         
                         return Ok(AgentResponse {
+                            session_id: session_id.clone(),
+                            text: accumulated_text,
                             tool_calls,
                             success: false,
+                            token_usage,
                         });
                     }
                     
@@ -296,3 +339,16 @@ impl Agent for CoreAgentAdapter {
         &self.name
     }
 }
+
+/// Role and text of a user or assistant message; tool and system messages are skipped
+fn message_text(message: &Message) -> Option<(MessageRole, String)> {
+    if !matches!(message.role, MessageRole::User | MessageRole::Assistant) {
+        return None;
+    }
+    let text = match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Mixed { text, .. } => text.clone(),
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then(|| (message.role.clone(), text))
+}
@@ -20,19 +20,31 @@ pub enum AgentEvent {
     TextChunk(String),
     /// Tool call started
     ToolCallStart {
+        tool_id: String,
         tool_name: String,
         parameters: serde_json::Value,
     },
     /// Tool call in progress
     ToolCallProgress {
+        tool_id: String,
         tool_name: String,
         message: String,
+        /// Progress percentage (0 - 100)
+        percentage: f32,
     },
     /// Tool call completed
     ToolCallComplete {
+        tool_id: String,
         tool_name: String,
         result: String,
         success: bool,
+        duration_ms: Option<u64>,
+    },
+    /// Switched to a fallback model
+    ModelSwitched {
+        from_model: String,
+        to_model: String,
+        reason: String,
     },
     /// Done
     Done,
@@ -40,13 +52,27 @@ pub enum AgentEvent {
     Error(String),
 }
 
+/// Token usage accumulated over all model rounds of a turn
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub total_tokens: usize,
+}
+
 /// Agent response
 #[derive(Debug, Clone)]
 pub struct AgentResponse {
+    /// Core session the turn ran in
+    pub session_id: String,
+    /// Assistant text of the turn
+    pub text: String,
     /// Tool call list
     pub tool_calls: Vec<ToolCall>,
     /// Whether successful
     pub success: bool,
+    /// Token usage of the turn
+    pub token_usage: TokenUsage,
 }
 
 /// Agent interface
@@ -15,8 +15,11 @@ use clap::{Parser, Subcommand};
 use anyhow::{Context, Result};
 
 use config::CliConfig;
+use modes::batch::BatchMode;
 use modes::chat::ChatMode;
-use modes::exec::ExecMode;
+use modes::exec::{ExecMode, OutputFormat};
+use modes::mcp::McpServeMode;
+use modes::tool::ToolMode;
 
 #[derive(Parser)]
 #[command(name = "bitfun")]
@@ -42,11 +45,19 @@ enum Commands {
         /// Workspace path
         #[arg(short, long)]
         workspace: Option<String>,
+        
+        /// Continue an agent session (ID, or "last" for the most recent)
+        #[arg(long, value_name = "ID|last")]
+        resume: Option<String>,
+        
+        /// Continue the most recent agent session (same as --resume last)
+        #[arg(long = "continue", conflicts_with = "resume")]
+        continue_last: bool,
     },
     
     /// Execute single command
     Exec {
-        /// User message
+        /// User message ("-" reads it from stdin)
         message: String,
         
         /// Agent type
@@ -57,8 +68,12 @@ enum Commands {
         #[arg(short, long)]
         workspace: Option<String>,
         
-        /// Output in JSON format (script-friendly)
-        #[arg(long)]
+        /// Output format: text, json (final result object) or stream-json (one JSON event per line)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output_format: OutputFormat,
+        
+        /// Shorthand for --output-format json
+        #[arg(long, conflicts_with = "output_format")]
         json: bool,
         
         /// Output git diff patch after execution (for SWE-bench evaluation)
@@ -70,13 +85,45 @@ enum Commands {
         /// Tool execution requires confirmation (default: no confirmation to avoid blocking non-interactive mode)
         #[arg(long)]
         confirm: bool,
+        
+        /// Continue an agent session (ID, or "last" for the most recent)
+        #[arg(long, value_name = "ID|last")]
+        resume: Option<String>,
+        
+        /// Continue the most recent agent session (same as --resume last)
+        #[arg(long = "continue", conflicts_with = "resume")]
+        continue_last: bool,
+        
+        /// Write a machine-readable run report to this file (used by batch mode)
+        #[arg(long, hide = true)]
+        result_file: Option<String>,
     },
     
     /// Execute batch tasks
     Batch {
-        /// Task configuration file path
+        /// Task configuration file path (YAML or JSON)
         #[arg(short, long)]
         tasks: String,
+        
+        /// Maximum number of tasks running at the same time (overrides the task file)
+        #[arg(short, long)]
+        concurrency: Option<usize>,
+        
+        /// Default agent type for tasks that do not set one
+        #[arg(short, long, default_value = "agentic")]
+        agent: String,
+        
+        /// Save the JSON summary to this file instead of printing it
+        #[arg(short, long)]
+        output: Option<String>,
+        
+        /// Directory for per-task logs
+        #[arg(long)]
+        log_dir: Option<String>,
+        
+        /// Tool execution requires confirmation
+        #[arg(long)]
+        confirm: bool,
     },
     
     /// Session management
@@ -85,6 +132,12 @@ enum Commands {
         action: SessionAction,
     },
     
+    /// Project hook management
+    Hooks {
+        #[command(subcommand)]
+        action: HooksAction,
+    },
+    
     /// Configuration management
     Config {
         #[command(subcommand)]
@@ -94,17 +147,62 @@ enum Commands {
     /// Invoke tool directly
     Tool {
         /// Tool name
-        name: String,
+        #[arg(required_unless_present = "list")]
+        name: Option<String>,
         
-        /// Tool parameters (JSON)
+        /// Tool parameters (JSON, "@file" to read from a file, "-" to read from stdin)
         #[arg(short, long)]
         params: Option<String>,
+        
+        /// List all registered tools with their input schemas
+        #[arg(short, long)]
+        list: bool,
+        
+        /// Workspace path
+        #[arg(short, long)]
+        workspace: Option<String>,
+        
+        /// Output in JSON format (script-friendly)
+        #[arg(long)]
+        json: bool,
+        
+        /// Start configured MCP servers so their tools are available
+        #[arg(long)]
+        mcp: bool,
+    },
+    
+    /// Act as an MCP server
+    Mcp {
+        #[command(subcommand)]
+        action: McpAction,
     },
     
     /// Health check
     Health,
 }
 
+#[derive(Subcommand)]
+enum McpAction {
+    /// Publish workspace tools to MCP clients (stdio unless --http is given)
+    Serve {
+        /// Workspace path
+        #[arg(short, long)]
+        workspace: Option<String>,
+        
+        /// Serve streamable HTTP on this address ("host:port" or a port on 127.0.0.1)
+        #[arg(long)]
+        http: Option<String>,
+        
+        /// Comma-separated tools to publish (default: Read,Grep,Glob,LS,ReadLints,Git,GetFileDiff,LSP)
+        #[arg(long, value_delimiter = ',')]
+        tools: Option<Vec<String>>,
+        
+        /// Mode whose permission rules apply to tool calls
+        #[arg(short, long, default_value = "agentic")]
+        agent: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum SessionAction {
     /// List all sessions
@@ -119,6 +217,42 @@ enum SessionAction {
         /// Session ID
         id: String,
     },
+    /// Fork an agent session after a dialog turn into a new session
+    Fork {
+        /// Agent session ID
+        id: String,
+        
+        /// Turn to fork after (1-based, default: the latest turn)
+        #[arg(short, long)]
+        turn: Option<usize>,
+        
+        /// Also fork the file snapshot state so both branches can be rolled back independently
+        #[arg(long)]
+        snapshots: bool,
+        
+        /// Workspace path (for the conversation history and snapshots of the session)
+        #[arg(short, long)]
+        workspace: Option<String>,
+    },
+    /// Show the fork lineage of agent sessions as a tree
+    Tree {
+        /// Only show the tree containing this session
+        id: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum HooksAction {
+    /// Show the hooks of the workspace's .bitfun/settings.json and approve them
+    Approve {
+        /// Workspace path
+        #[arg(short, long)]
+        workspace: Option<String>,
+        
+        /// Approve without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -181,6 +315,7 @@ async fn main() -> Result<()> {
     } else {
         tracing_subscriber::fmt()
             .with_max_level(log_level)
+            .with_writer(std::io::stderr)
             .with_target(false)
             .init();
     }
@@ -194,7 +329,8 @@ async fn main() -> Result<()> {
     });
     
     match cli.command {
-        Some(Commands::Chat { agent, workspace }) => {
+        Some(Commands::Chat { agent, workspace, resume, continue_last }) => {
+            let resume = if continue_last { Some("last".to_string()) } else { resume };
             let (workspace, mut startup_terminal) = if workspace.is_none() {
                 use ui::startup::StartupPage;
                 
@@ -273,6 +409,13 @@ async fn main() -> Result<()> {
             }
             
             let mut chat_mode = ChatMode::new(config, agent, workspace, &agentic_system);
+            if let Some(ref target) = resume {
+                let session_id = modes::sessions::resolve_resume_target(target).await?;
+                chat_mode
+                    .resume(&session_id)
+                    .await
+                    .with_context(|| format!("Failed to resume session: {}", session_id))?;
+            }
             let chat_result = chat_mode.run(startup_terminal);
 
             if let Some(ref svc) = config_service {
@@ -284,66 +427,98 @@ async fn main() -> Result<()> {
             chat_result?;
         }
         
-        Some(Commands::Exec { message, agent, workspace, json: _, output_patch, confirm }) => {
-            let workspace_path_resolved = if let Some(ref ws) = workspace {
-                use std::path::PathBuf;
-                if ws == "." {
-                    std::env::current_dir().ok()
+        Some(Commands::Exec { message, agent, workspace, output_format, json, output_patch, confirm, resume, continue_last, result_file }) => {
+            let output_format = if json { OutputFormat::Json } else { output_format };
+            let resume = if continue_last { Some("last".to_string()) } else { resume };
+            let result_file = result_file.map(std::path::PathBuf::from);
+            let mut config_service = None;
+            let mut original_skip_confirmation = false;
+
+            // Setup failures are reported like run failures, so JSON output always ends with a result
+            let setup = async {
+                let message = if message == "-" {
+                    let mut input = String::new();
+                    std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)
+                        .context("Failed to read message from stdin")?;
+                    let input = input.trim().to_string();
+                    if input.is_empty() {
+                        anyhow::bail!("No message on stdin");
+                    }
+                    input
                 } else {
-                    Some(PathBuf::from(ws))
-                }
-            } else {
-                std::env::current_dir().ok()
-            };
+                    message
+                };
+                let workspace_path_resolved = if let Some(ref ws) = workspace {
+                    use std::path::PathBuf;
+                    if ws == "." {
+                        std::env::current_dir().ok()
+                    } else {
+                        Some(PathBuf::from(ws))
+                    }
+                } else {
+                    std::env::current_dir().ok()
+                };
             
-            if let Some(ref ws_path) = workspace_path_resolved {
-                use bitfun_core::infrastructure::set_workspace_path;
-                set_workspace_path(Some(ws_path.clone()));
-                tracing::info!("Workspace path set: {:?}", ws_path);
-            }
+                if let Some(ref ws_path) = workspace_path_resolved {
+                    use bitfun_core::infrastructure::set_workspace_path;
+                    set_workspace_path(Some(ws_path.clone()));
+                    tracing::info!("Workspace path set: {:?}", ws_path);
+                }
             
-            bitfun_core::service::config::initialize_global_config()
-                .await
-                .context("Failed to initialize global config service")?;
-            tracing::info!("Global config service initialized");
+                bitfun_core::service::config::initialize_global_config()
+                    .await
+                    .context("Failed to initialize global config service")?;
+                tracing::info!("Global config service initialized");
 
-            let config_service = bitfun_core::service::config::get_global_config_service()
-                .await
-                .ok();
-            let original_skip_confirmation = if let Some(ref svc) = config_service {
-                let ai_config: bitfun_core::service::config::types::AIConfig =
-                    svc.get_config(Some("ai")).await.unwrap_or_default();
-                ai_config.skip_tool_confirmation
-            } else {
-                false
-            };
-            if let Some(ref svc) = config_service {
-                let desired_skip = !confirm;
-                if let Err(e) = svc.set_config("ai.skip_tool_confirmation", desired_skip).await {
-                    tracing::warn!("Failed to set tool confirmation toggle, continuing: {}", e);
+                config_service = bitfun_core::service::config::get_global_config_service()
+                    .await
+                    .ok();
+                original_skip_confirmation = if let Some(ref svc) = config_service {
+                    let ai_config: bitfun_core::service::config::types::AIConfig =
+                        svc.get_config(Some("ai")).await.unwrap_or_default();
+                    ai_config.skip_tool_confirmation
+                } else {
+                    false
+                };
+                if let Some(ref svc) = config_service {
+                    let desired_skip = !confirm;
+                    if let Err(e) = svc.set_config("ai.skip_tool_confirmation", desired_skip).await {
+                        tracing::warn!("Failed to set tool confirmation toggle, continuing: {}", e);
+                    }
                 }
-            }
             
-            use bitfun_core::infrastructure::ai::AIClientFactory;
-            AIClientFactory::initialize_global()
-                .await
-                .context("Failed to initialize global AIClientFactory")?;
-            tracing::info!("Global AI client factory initialized");
+                use bitfun_core::infrastructure::ai::AIClientFactory;
+                AIClientFactory::initialize_global()
+                    .await
+                    .context("Failed to initialize global AIClientFactory")?;
+                tracing::info!("Global AI client factory initialized");
             
-            let agentic_system = agent::agentic_system::init_agentic_system()
-                .await
-                .context("Failed to initialize agentic system")?;
-            tracing::info!("Agentic system initialized");
+                let agentic_system = agent::agentic_system::init_agentic_system()
+                    .await
+                    .context("Failed to initialize agentic system")?;
+                tracing::info!("Agentic system initialized");
             
-            let mut exec_mode = ExecMode::new(
-                config, 
-                message, 
-                agent, 
-                &agentic_system,
-                workspace_path_resolved,
-                output_patch,
-            );
-            let run_result = exec_mode.run().await;
+                let exec_mode = ExecMode::new(
+                    config, 
+                    message, 
+                    agent, 
+                    &agentic_system,
+                    workspace_path_resolved,
+                    output_patch,
+                )
+                .with_result_file(result_file.clone())
+                .with_output_format(output_format)
+                .with_resume(resume);
+                Ok::<_, anyhow::Error>(exec_mode)
+            }
+            .await;
+            let run_result = match setup {
+                Ok(mut exec_mode) => exec_mode.run().await,
+                Err(e) => {
+                    modes::exec::report_setup_failure(output_format, result_file.as_deref(), &e);
+                    Err(e)
+                }
+            };
 
             if let Some(ref svc) = config_service {
                 let _ = svc
@@ -354,26 +529,96 @@ async fn main() -> Result<()> {
             run_result?;
         }
         
-        Some(Commands::Batch { tasks }) => {
-            println!("Executing batch tasks...");
-            println!("Tasks file: {}", tasks);
-            println!("\nWarning: Batch execution feature coming soon");
+        Some(Commands::Batch { tasks, concurrency, agent, output, log_dir, confirm }) => {
+            use std::path::PathBuf;
+            
+            let batch_mode = BatchMode::new(
+                PathBuf::from(tasks),
+                concurrency,
+                agent,
+                confirm,
+                log_dir.map(PathBuf::from),
+            );
+            let summary = batch_mode.run().await?;
+            
+            let summary_json = serde_json::to_string_pretty(&summary)?;
+            if let Some(output_path) = output {
+                std::fs::write(&output_path, &summary_json)
+                    .with_context(|| format!("Failed to write batch summary: {}", output_path))?;
+                println!("Batch complete: {}/{} succeeded", summary.succeeded, summary.total);
+                println!("Summary saved to: {}", output_path);
+            } else {
+                println!("{}", summary_json);
+            }
+            
+            if summary.failed > 0 {
+                anyhow::bail!("{} of {} batch tasks failed", summary.failed, summary.total);
+            }
         }
         
         Some(Commands::Sessions { action }) => {
-            handle_session_action(action)?;
+            handle_session_action(action).await?;
+        }
+        
+        Some(Commands::Hooks { action: HooksAction::Approve { workspace, yes } }) => {
+            use std::path::PathBuf;
+            
+            let workspace_path = match workspace {
+                Some(ws) if ws != "." => PathBuf::from(ws),
+                _ => std::env::current_dir().context("Failed to get current directory")?,
+            };
+            
+            bitfun_core::service::config::initialize_global_config()
+                .await
+                .context("Failed to initialize global config service")?;
+            
+            modes::hooks::approve(&workspace_path, yes).await?;
         }
         
         Some(Commands::Config { action }) => {
             handle_config_action(action, &config)?;
         }
         
-        Some(Commands::Tool { name, params }) => {
-            println!("Invoking tool: {}", name);
-            if let Some(p) = params {
-                println!("Parameters: {}", p);
+        Some(Commands::Tool { name, params, list, workspace, json, mcp }) => {
+            use std::path::PathBuf;
+            use bitfun_core::infrastructure::set_workspace_path;
+            
+            let workspace_path = match workspace {
+                Some(ws) if ws != "." => Some(PathBuf::from(ws)),
+                _ => std::env::current_dir().ok(),
+            };
+            set_workspace_path(workspace_path.clone());
+            tracing::info!("Workspace path set: {:?}", workspace_path);
+            
+            bitfun_core::service::config::initialize_global_config()
+                .await
+                .context("Failed to initialize global config service")?;
+            
+            let mut tool_mode = ToolMode::new(json, mcp);
+            match name {
+                Some(name) if !list => tool_mode.invoke(&name, params).await?,
+                _ => tool_mode.list().await?,
             }
-            println!("\nWarning: Tool invocation feature coming soon");
+        }
+        
+        Some(Commands::Mcp { action: McpAction::Serve { workspace, http, tools, agent } }) => {
+            use std::path::PathBuf;
+            use bitfun_core::infrastructure::set_workspace_path;
+            
+            let workspace_path = match workspace {
+                Some(ws) if ws != "." => Some(PathBuf::from(ws)),
+                _ => std::env::current_dir().ok(),
+            };
+            set_workspace_path(workspace_path.clone());
+            tracing::info!("Workspace path set: {:?}", workspace_path);
+            
+            bitfun_core::service::config::initialize_global_config()
+                .await
+                .context("Failed to initialize global config service")?;
+            
+            McpServeMode::new(tools, agent, http)?
+                .run(workspace_path)
+                .await?;
         }
         
         Some(Commands::Health) => {
@@ -472,7 +717,7 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-fn handle_session_action(action: SessionAction) -> Result<()> {
+async fn handle_session_action(action: SessionAction) -> Result<()> {
     match action {
         SessionAction::List => {
             use session::Session;
@@ -543,6 +788,21 @@ fn handle_session_action(action: SessionAction) -> Result<()> {
             Session::delete(&id)?;
             println!("Deleted session: {}", id);
         }
+        
+        SessionAction::Fork { id, turn, snapshots, workspace } => {
+            use std::path::PathBuf;
+            
+            let workspace = match workspace {
+                Some(ws) if ws == "." => std::env::current_dir().ok(),
+                Some(ws) => Some(PathBuf::from(ws)),
+                None => None,
+            };
+            modes::sessions::fork(&id, turn, snapshots, workspace).await?;
+        }
+        
+        SessionAction::Tree { id } => {
+            modes::sessions::tree(id.as_deref()).await?;
+        }
     }
     
     Ok(())
@@ -0,0 +1,480 @@
+/// Batch mode implementation
+///
+/// Runs every task of a YAML/JSON task file through exec mode.
+/// Each task is executed in its own `exec` child process (the core keeps the
+/// workspace path in process-global state), with bounded concurrency.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::agent::TokenUsage;
+use crate::modes::exec::ExecReport;
+
+/// Task file, either a bare list of tasks or an object with defaults
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TaskFile {
+    List(Vec<BatchTask>),
+    Full {
+        /// Maximum number of tasks running at the same time
+        #[serde(default)]
+        concurrency: Option<usize>,
+        /// Default agent type for tasks that do not set one
+        #[serde(default)]
+        agent: Option<String>,
+        tasks: Vec<BatchTask>,
+    },
+}
+
+/// Single task entry
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchTask {
+    /// Task ID (defaults to "task-<n>")
+    #[serde(default)]
+    pub id: Option<String>,
+    /// User message
+    pub prompt: String,
+    /// Agent type
+    #[serde(default)]
+    pub agent: Option<String>,
+    /// Workspace path (relative paths are resolved against the task file directory)
+    #[serde(default)]
+    pub workspace: Option<String>,
+    /// Patch output file (relative paths are resolved against the task file directory)
+    #[serde(default)]
+    pub output_patch: Option<String>,
+    /// Kill the task after this many seconds
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchTaskStatus {
+    Success,
+    Failed,
+    Timeout,
+}
+
+/// Result of a single task
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchTaskResult {
+    pub id: String,
+    pub status: BatchTaskStatus,
+    pub duration_ms: u64,
+    pub tool_calls: usize,
+    pub token_usage: TokenUsage,
+    pub patch_path: Option<String>,
+    pub exit_code: Option<i32>,
+    pub error: Option<String>,
+}
+
+/// Summary of the whole batch run
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchSummary {
+    pub tasks_file: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub duration_ms: u64,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchTaskResult>,
+}
+
+pub struct BatchMode {
+    tasks_file: PathBuf,
+    concurrency: Option<usize>,
+    default_agent: String,
+    confirm: bool,
+    log_dir: Option<PathBuf>,
+}
+
+impl BatchMode {
+    pub fn new(
+        tasks_file: PathBuf,
+        concurrency: Option<usize>,
+        default_agent: String,
+        confirm: bool,
+        log_dir: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            tasks_file,
+            concurrency,
+            default_agent,
+            confirm,
+            log_dir,
+        }
+    }
+
+    fn load_tasks(&self) -> Result<(Vec<BatchTask>, usize)> {
+        let content = std::fs::read_to_string(&self.tasks_file)
+            .with_context(|| format!("Failed to read tasks file: {:?}", self.tasks_file))?;
+
+        let is_json = self.tasks_file
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let task_file: TaskFile = if is_json {
+            serde_json::from_str(&content).context("Failed to parse JSON tasks file")?
+        } else {
+            serde_yaml::from_str(&content).context("Failed to parse YAML tasks file")?
+        };
+
+        let (mut tasks, file_concurrency, file_agent) = match task_file {
+            TaskFile::List(tasks) => (tasks, None, None),
+            TaskFile::Full { concurrency, agent, tasks } => (tasks, concurrency, agent),
+        };
+
+        let base_dir = self.tasks_file
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let mut seen_ids = HashSet::new();
+
+        for (index, task) in tasks.iter_mut().enumerate() {
+            let id = task.id.clone().unwrap_or_else(|| format!("task-{}", index + 1));
+            if !seen_ids.insert(id.clone()) {
+                anyhow::bail!("Duplicate task id: {}", id);
+            }
+            task.id = Some(id);
+
+            if task.agent.is_none() {
+                task.agent = Some(file_agent.clone().unwrap_or_else(|| self.default_agent.clone()));
+            }
+            task.workspace = task.workspace.as_deref().map(|p| resolve_path(&base_dir, p));
+            task.output_patch = task.output_patch.as_deref().map(|p| resolve_path(&base_dir, p));
+        }
+
+        let concurrency = self.concurrency.or(file_concurrency).unwrap_or(1).max(1);
+        Ok((tasks, concurrency))
+    }
+
+    pub async fn run(&self) -> Result<BatchSummary> {
+        let (tasks, concurrency) = self.load_tasks()?;
+        let total = tasks.len();
+
+        tracing::info!("Running batch: tasks={}, concurrency={}", total, concurrency);
+        eprintln!("Running {} tasks (concurrency: {})", total, concurrency);
+
+        if let Some(ref log_dir) = self.log_dir {
+            std::fs::create_dir_all(log_dir)
+                .with_context(|| format!("Failed to create log directory: {:?}", log_dir))?;
+        }
+
+        let exe = std::env::current_exe().context("Failed to locate bitfun executable")?;
+        let batch_id = uuid::Uuid::new_v4().to_string();
+        let semaphore = Arc::new(Semaphore::new(concurrency));
+        let started_at = chrono::Utc::now();
+        let start = Instant::now();
+        let mut join_set = JoinSet::new();
+
+        for (index, task) in tasks.into_iter().enumerate() {
+            let semaphore = semaphore.clone();
+            let exe = exe.clone();
+            let confirm = self.confirm;
+            let log_dir = self.log_dir.clone();
+            let result_file = std::env::temp_dir()
+                .join(format!("bitfun-batch-{}-{}.json", batch_id, index));
+
+            join_set.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let result = run_task(&exe, &task, confirm, log_dir.as_deref(), &result_file).await;
+                (index, result)
+            });
+        }
+
+        let mut results: Vec<Option<BatchTaskResult>> = vec![None; total];
+        let mut finished = 0;
+
+        while let Some(joined) = join_set.join_next().await {
+            let (index, result) = joined.context("Batch task panicked")?;
+            finished += 1;
+            eprintln!(
+                "[{}/{}] {} {:?} ({:.1}s)",
+                finished,
+                total,
+                result.id,
+                result.status,
+                result.duration_ms as f64 / 1000.0
+            );
+            results[index] = Some(result);
+        }
+
+        let results: Vec<BatchTaskResult> = results.into_iter().flatten().collect();
+        let succeeded = results
+            .iter()
+            .filter(|r| r.status == BatchTaskStatus::Success)
+            .count();
+
+        Ok(BatchSummary {
+            tasks_file: self.tasks_file.display().to_string(),
+            started_at,
+            duration_ms: start.elapsed().as_millis() as u64,
+            total,
+            succeeded,
+            failed: total - succeeded,
+            results,
+        })
+    }
+}
+
+fn resolve_path(base_dir: &Path, path: &str) -> String {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.display().to_string()
+    } else {
+        base_dir.join(path).display().to_string()
+    }
+}
+
+async fn run_task(
+    exe: &Path,
+    task: &BatchTask,
+    confirm: bool,
+    log_dir: Option<&Path>,
+    result_file: &Path,
+) -> BatchTaskResult {
+    let id = task.id.clone().unwrap_or_default();
+    let start = Instant::now();
+
+    let mut result = BatchTaskResult {
+        id: id.clone(),
+        status: BatchTaskStatus::Failed,
+        duration_ms: 0,
+        tool_calls: 0,
+        token_usage: TokenUsage::default(),
+        patch_path: None,
+        exit_code: None,
+        error: None,
+    };
+
+    let mut command = Command::new(exe);
+    command.arg("exec");
+    if let Some(ref agent) = task.agent {
+        command.args(["--agent", agent]);
+    }
+    if let Some(ref workspace) = task.workspace {
+        command.args(["--workspace", workspace]);
+    }
+    if let Some(ref output_patch) = task.output_patch {
+        command.args(["--output-patch", output_patch]);
+    }
+    if confirm {
+        command.arg("--confirm");
+    }
+    command
+        .arg("--result-file")
+        .arg(result_file)
+        .arg("--")
+        .arg(&task.prompt)
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+
+    match log_dir.map(|dir| std::fs::File::create(dir.join(format!("{}.log", id)))) {
+        Some(Ok(log_file)) => {
+            let stderr = log_file.try_clone().map(Stdio::from).unwrap_or_else(|_| Stdio::null());
+            command.stdout(Stdio::from(log_file)).stderr(stderr);
+        }
+        Some(Err(e)) => {
+            tracing::warn!("Failed to create log file for task {}: {}", id, e);
+            command.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+        None => {
+            command.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+    }
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            result.error = Some(format!("Failed to start task: {}", e));
+            return result;
+        }
+    };
+
+    let wait_result = match task.timeout_secs {
+        Some(secs) => tokio::time::timeout(Duration::from_secs(secs), child.wait()).await.ok(),
+        None => Some(child.wait().await),
+    };
+
+    match wait_result {
+        Some(Ok(status)) => {
+            result.exit_code = status.code();
+        }
+        Some(Err(e)) => {
+            result.error = Some(format!("Failed to wait for task: {}", e));
+        }
+        None => {
+            let _ = child.kill().await;
+            result.status = BatchTaskStatus::Timeout;
+            result.error = Some(format!(
+                "Task timed out after {}s",
+                task.timeout_secs.unwrap_or_default()
+            ));
+        }
+    }
+
+    match std::fs::read_to_string(result_file)
+        .ok()
+        .and_then(|json| serde_json::from_str::<ExecReport>(&json).ok())
+    {
+        Some(report) => {
+            if result.status != BatchTaskStatus::Timeout {
+                result.status = if report.success {
+                    BatchTaskStatus::Success
+                } else {
+                    BatchTaskStatus::Failed
+                };
+            }
+            result.tool_calls = report.tool_calls;
+            result.token_usage = report.token_usage;
+            result.patch_path = report.patch_path;
+            if result.error.is_none() {
+                result.error = report.error;
+            }
+        }
+        None => {
+            if result.error.is_none() {
+                result.error = Some(format!(
+                    "Task exited without a result (exit code: {:?})",
+                    result.exit_code
+                ));
+            }
+        }
+    }
+    let _ = std::fs::remove_file(result_file);
+
+    result.duration_ms = start.elapsed().as_millis() as u64;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bitfun-batch-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn load(dir: &Path, file_name: &str, content: &str) -> Result<(Vec<BatchTask>, usize)> {
+        let tasks_file = dir.join(file_name);
+        std::fs::write(&tasks_file, content).unwrap();
+        BatchMode::new(tasks_file, None, "agentic".to_string(), false, None).load_tasks()
+    }
+
+    #[test]
+    fn loads_well_formed_task_files() {
+        let dir = temp_dir("load");
+
+        let (tasks, concurrency) = load(
+            &dir,
+            "tasks.yaml",
+            "- prompt: fix the build\n  workspace: repo\n  output_patch: out/fix.patch\n- id: docs\n  prompt: write docs\n  agent: docs\n  workspace: /abs/repo\n",
+        )
+        .unwrap();
+        assert_eq!(concurrency, 1);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].id.as_deref(), Some("task-1"));
+        assert_eq!(tasks[0].agent.as_deref(), Some("agentic"));
+        assert_eq!(tasks[0].workspace, Some(dir.join("repo").display().to_string()));
+        assert_eq!(tasks[0].output_patch, Some(dir.join("out/fix.patch").display().to_string()));
+        assert_eq!(tasks[1].id.as_deref(), Some("docs"));
+        assert_eq!(tasks[1].agent.as_deref(), Some("docs"));
+        assert_eq!(tasks[1].workspace.as_deref(), Some("/abs/repo"));
+
+        let (tasks, concurrency) = load(
+            &dir,
+            "tasks.json",
+            r#"{"concurrency": 4, "agent": "review", "tasks": [{"prompt": "a", "timeout_secs": 30}, {"prompt": "b", "agent": "fix"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(concurrency, 4);
+        assert_eq!(tasks[0].agent.as_deref(), Some("review"));
+        assert_eq!(tasks[0].timeout_secs, Some(30));
+        assert_eq!(tasks[1].id.as_deref(), Some("task-2"));
+        assert_eq!(tasks[1].agent.as_deref(), Some("fix"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rejects_malformed_task_files() {
+        let dir = temp_dir("malformed");
+
+        let error = load(&dir, "tasks.json", r#"[{"prompt": "a"},"#).unwrap_err();
+        assert!(error.to_string().contains("Failed to parse JSON tasks file"));
+        // Tasks without a prompt are not tasks
+        let error = load(&dir, "tasks.yaml", "- id: a\n  agent: fix\n").unwrap_err();
+        assert!(error.to_string().contains("Failed to parse YAML tasks file"));
+        let error = load(&dir, "tasks.yaml", "- id: a\n  prompt: x\n- id: a\n  prompt: y\n").unwrap_err();
+        assert_eq!(error.to_string(), "Duplicate task id: a");
+        let error = load(&dir, "tasks.yaml", "- prompt: x\n- id: task-1\n  prompt: y\n").unwrap_err();
+        assert_eq!(error.to_string(), "Duplicate task id: task-1");
+
+        let missing = BatchMode::new(dir.join("missing.yaml"), None, "agentic".to_string(), false, None);
+        assert!(missing.load_tasks().unwrap_err().to_string().contains("Failed to read tasks file"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn reports_failures_per_task() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("run");
+        // Stands in for `bitfun exec`: writes a failed report, or exits without one
+        let exe = dir.join("fake-bitfun");
+        std::fs::write(
+            &exe,
+            r#"#!/bin/sh
+case "$*" in
+  *"-- no result"*) exit 3 ;;
+esac
+while [ "$1" != "--result-file" ]; do shift; done
+printf '%s' '{"success":false,"error":"model unavailable","duration_ms":1,"tool_calls":2,"token_usage":{"input_tokens":3,"output_tokens":4,"total_tokens":7},"patch_path":null}' > "$2"
+exit 1
+"#,
+        )
+        .unwrap();
+        std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let task = |id: &str, prompt: &str| BatchTask {
+            id: Some(id.to_string()),
+            prompt: prompt.to_string(),
+            agent: None,
+            workspace: None,
+            output_patch: None,
+            timeout_secs: None,
+        };
+
+        let result_file = dir.join("failed.json");
+        let failed = run_task(&exe, &task("failed", "fix it"), false, None, &result_file).await;
+        assert_eq!(failed.status, BatchTaskStatus::Failed);
+        assert_eq!(failed.exit_code, Some(1));
+        assert_eq!(failed.error.as_deref(), Some("model unavailable"));
+        assert_eq!(failed.tool_calls, 2);
+        assert_eq!(failed.token_usage.total_tokens, 7);
+        assert!(!result_file.exists());
+
+        let silent = run_task(&exe, &task("silent", "no result"), false, None, &dir.join("silent.json")).await;
+        assert_eq!(silent.status, BatchTaskStatus::Failed);
+        assert_eq!(silent.exit_code, Some(3));
+        assert_eq!(silent.error.as_deref(), Some("Task exited without a result (exit code: Some(3))"));
+
+        let missing = run_task(&dir.join("missing"), &task("missing", "x"), false, None, &dir.join("missing.json")).await;
+        assert_eq!(missing.status, BatchTaskStatus::Failed);
+        assert!(missing.error.unwrap().starts_with("Failed to start task"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -17,6 +17,7 @@ use crate::ui::chat::ChatView;
 use crate::ui::theme::Theme;
 use crate::ui::{init_terminal, restore_terminal};
 use crate::agent::{Agent, core_adapter::CoreAgentAdapter, agentic_system::AgenticSystem};
+use bitfun_core::agentic::core::MessageRole;
 use uuid;
 
 /// Chat mode exit reason
@@ -32,7 +33,9 @@ pub struct ChatMode {
     config: CliConfig,
     agent_name: String,
     workspace: Option<String>,
-    agent: Arc<dyn Agent>,
+    agent: Arc<CoreAgentAdapter>,
+    /// Text history of a resumed session, shown when the chat starts
+    resumed_history: Vec<(MessageRole, String)>,
 }
 
 impl ChatMode {
@@ -47,15 +50,22 @@ impl ChatMode {
             agent_name.clone(),
             agentic_system.coordinator.clone(),
             agentic_system.event_queue.clone(),
-        )) as Arc<dyn Agent>;
+        ));
         
         Self {
             config,
             agent_name,
             workspace,
             agent,
+            resumed_history: Vec::new(),
         }
     }
+    
+    /// Continue a persisted core session instead of starting a new one
+    pub async fn resume(&mut self, session_id: &str) -> Result<()> {
+        self.resumed_history = self.agent.resume_session(session_id).await?;
+        Ok(())
+    }
 
     pub fn run(
         &mut self,
@@ -77,6 +87,14 @@ impl ChatMode {
             _ => Theme::dark(),
         };
         let mut chat_view = ChatView::new(session, theme);
+        for (role, text) in self.resumed_history.drain(..) {
+            if role == MessageRole::User {
+                chat_view.session.add_message("user".to_string(), text);
+            } else {
+                chat_view.session.add_message("assistant".to_string(), String::new());
+                chat_view.session.update_last_message_text_flow(text, false);
+            }
+        }
 
         let rt_handle = tokio::runtime::Handle::current();
         let (response_tx, mut response_rx) = mpsc::unbounded_channel::<crate::agent::AgentResponse>();
@@ -107,7 +125,7 @@ impl ChatMode {
                         );
                     }
                     
-                    AgentEvent::ToolCallStart { tool_name, parameters } => {
+                    AgentEvent::ToolCallStart { tool_name, parameters, .. } => {
                         if !current_assistant_message_text.is_empty() {
                             chat_view.session.update_last_message_text_flow(
                                 current_assistant_message_text.clone(),
@@ -131,7 +149,7 @@ impl ChatMode {
                         chat_view.session.add_tool_to_last_message(tool_call);
                     }
                     
-                    AgentEvent::ToolCallProgress { tool_name, message } => {
+                    AgentEvent::ToolCallProgress { tool_name, message, .. } => {
                         for (tool_id, tool) in current_tool_map.iter() {
                             if tool.tool_name == tool_name {
                                 let tid = tool_id.clone();
@@ -143,7 +161,7 @@ impl ChatMode {
                         }
                     }
                     
-                    AgentEvent::ToolCallComplete { tool_name, result, success } => {
+                    AgentEvent::ToolCallComplete { tool_name, result, success, .. } => {
                         for (tool_id, tool) in current_tool_map.iter_mut() {
                             if tool.tool_name == tool_name && tool.status == ToolCallStatus::Running {
                                 tool.status = if success {
@@ -178,6 +196,13 @@ impl ChatMode {
                         chat_view.set_status(Some(format!("Error: {}", err)));
                     }
                     
+                    AgentEvent::ModelSwitched { from_model, to_model, .. } => {
+                        chat_view.set_status(Some(format!(
+                            "{} unavailable, switched to {}",
+                            from_model, to_model
+                        )));
+                    }
+                    
                     _ => {}
                 }
             }
@@ -298,8 +323,11 @@ impl ChatMode {
                                 tracing::error!("Agent processing failed: {}", e);
                                 let _ = stream_tx_clone.send(crate::agent::AgentEvent::Error(e.to_string()));
                                 let _ = resp_tx.send(crate::agent::AgentResponse {
+                                    session_id: String::new(),
+                                    text: String::new(),
                                     tool_calls: vec![],
                                     success: false,
+                                    token_usage: Default::default(),
                                 });
                             }
                         }
@@ -3,20 +3,97 @@
 /// Single command execution mode
 
 use anyhow::Result;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::mpsc;
 use crate::config::CliConfig;
-use crate::agent::{Agent, AgentEvent, core_adapter::CoreAgentAdapter, agentic_system::AgenticSystem};
+use crate::agent::{Agent, AgentEvent, TokenUsage, core_adapter::CoreAgentAdapter, agentic_system::AgenticSystem};
+use crate::session::{ToolCall, ToolCallStatus};
+
+/// Output format of `bitfun exec`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// A single JSON result object at the end of the run
+    Json,
+    /// One JSON object per line for every agent event, then the result object
+    StreamJson,
+}
+
+/// Machine-readable result of a single exec run
+///
+/// Written with `--result-file`, and printed as the final object with `--output-format json|stream-json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecReport {
+    /// Whether the dialog turn completed successfully
+    pub success: bool,
+    /// Error message if the run failed
+    pub error: Option<String>,
+    /// Core session the turn ran in
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Assistant text of the turn
+    #[serde(default)]
+    pub text: String,
+    /// Wall-clock duration (milliseconds)
+    pub duration_ms: u64,
+    /// Number of tool calls
+    pub tool_calls: usize,
+    /// Tool calls by outcome and by tool name
+    #[serde(default)]
+    pub tool_stats: ToolStats,
+    /// Token usage of the run
+    pub token_usage: TokenUsage,
+    /// Path of the saved patch file, if any
+    pub patch_path: Option<String>,
+    /// Git diff of the workspace after the run (JSON output formats only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch: Option<String>,
+}
+
+/// Tool call statistics of a run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolStats {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Number of calls per tool name
+    pub by_tool: BTreeMap<String, usize>,
+}
+
+impl ToolStats {
+    fn from_calls(calls: &[ToolCall]) -> Self {
+        let mut stats = Self::default();
+        for call in calls {
+            match call.status {
+                ToolCallStatus::Success => stats.succeeded += 1,
+                ToolCallStatus::Failed | ToolCallStatus::Rejected | ToolCallStatus::Cancelled => stats.failed += 1,
+                _ => {}
+            }
+            *stats.by_tool.entry(call.tool_name.clone()).or_default() += 1;
+        }
+        stats
+    }
+}
 
 pub struct ExecMode {
     #[allow(dead_code)]
     config: CliConfig,
     message: String,
-    agent: Arc<dyn Agent>,
+    agent: Arc<CoreAgentAdapter>,
     workspace_path: Option<PathBuf>,
     /// None: no patch output, Some("-"): output to stdout, Some(path): save to file
     output_patch: Option<String>,
+    /// Where to write the ExecReport JSON after the run
+    result_file: Option<PathBuf>,
+    output_format: OutputFormat,
+    /// Agent session to continue (ID or "last")
+    resume: Option<String>,
 }
 
 impl ExecMode {
@@ -33,7 +110,7 @@ impl ExecMode {
             agent_type,
             agentic_system.coordinator.clone(),
             agentic_system.event_queue.clone(),
-        )) as Arc<dyn Agent>;
+        ));
         
         Self {
             config,
@@ -41,9 +118,29 @@ impl ExecMode {
             agent,
             workspace_path,
             output_patch,
+            result_file: None,
+            output_format: OutputFormat::Text,
+            resume: None,
         }
     }
     
+    /// Run the message as the next turn of an existing agent session
+    pub fn with_resume(mut self, resume: Option<String>) -> Self {
+        self.resume = resume;
+        self
+    }
+    
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+    
+    /// Write a machine-readable ExecReport to the given path after the run
+    pub fn with_result_file(mut self, result_file: Option<PathBuf>) -> Self {
+        self.result_file = result_file;
+        self
+    }
+    
     fn get_git_diff(&self) -> Option<String> {
         let workspace = self.workspace_path.as_ref()?;
         
@@ -68,10 +165,36 @@ impl ExecMode {
     }
 
     pub async fn run(&mut self) -> Result<()> {
+        let start = Instant::now();
+        let mut report = ExecReport::default();
+        let result = self.run_inner(&mut report).await;
+        
+        report.duration_ms = start.elapsed().as_millis() as u64;
+        if let Err(ref e) = result {
+            report.success = false;
+            report.error = Some(e.to_string());
+        }
+        emit_report(&report, self.output_format, self.result_file.as_deref());
+        
+        result
+    }
+
+    async fn run_inner(&mut self, report: &mut ExecReport) -> Result<()> {
         tracing::info!("Executing command, Agent: {}, Message: {}", self.agent.name(), self.message);
 
-        println!("Executing: {}", self.message);
-        println!();
+        let text_output = self.output_format == OutputFormat::Text;
+        if let Some(ref target) = self.resume {
+            let session_id = crate::modes::sessions::resolve_resume_target(target).await?;
+            self.agent.resume_session(&session_id).await?;
+            report.session_id = Some(session_id.clone());
+            if text_output {
+                println!("Resuming session: {}", session_id);
+            }
+        }
+        if text_output {
+            println!("Executing: {}", self.message);
+            println!();
+        }
 
         let (event_tx, mut event_rx) = mpsc::unbounded_channel();
         let agent = self.agent.clone();
@@ -80,36 +203,19 @@ impl ExecMode {
         let handle = tokio::spawn(async move { agent.process_message(message, event_tx).await });
 
         while let Some(event) = event_rx.recv().await {
-            match event {
-                AgentEvent::Thinking => {
-                    println!("Thinking...");
-                }
-                AgentEvent::TextChunk(chunk) => {
-                    print!("{}", chunk);
-                    use std::io::Write;
-                    std::io::stdout().flush().ok();
-                }
-                AgentEvent::ToolCallStart { tool_name, parameters: _ } => {
-                    println!("\nTool call: {}", tool_name);
-                }
-                AgentEvent::ToolCallProgress { tool_name: _, message } => {
-                    println!("   In progress: {}", message);
-                }
-                AgentEvent::ToolCallComplete { tool_name, result, success } => {
-                    if success {
-                        println!("   [+] {}: {}", tool_name, result);
-                    } else {
-                        println!("   [x] {}: {}", tool_name, result);
-                    }
-                }
-                AgentEvent::Done => {
-                    println!("\n");
-                    break;
-                }
-                AgentEvent::Error(err) => {
-                    eprintln!("\nError: {}", err);
-                    break;
-                }
+            if let AgentEvent::Error(ref err) = event {
+                report.error = Some(err.clone());
+            }
+            let finished = matches!(event, AgentEvent::Done | AgentEvent::Error(_));
+            
+            match self.output_format {
+                OutputFormat::Text => print_event(event),
+                OutputFormat::StreamJson => println!("{}", event_to_json(event)),
+                OutputFormat::Json => {}
+            }
+            
+            if finished {
+                break;
             }
         }
 
@@ -117,25 +223,50 @@ impl ExecMode {
         
         match result {
             Ok(Ok(response)) => {
-                if response.success {
-                    println!("Execution complete");
-                    if !response.tool_calls.is_empty() {
-                        println!("\nTool call statistics: {} tools invoked", response.tool_calls.len());
+                report.success = response.success;
+                report.session_id = Some(response.session_id);
+                report.text = response.text;
+                report.tool_calls = response.tool_calls.len();
+                report.tool_stats = ToolStats::from_calls(&response.tool_calls);
+                report.token_usage = response.token_usage;
+                if text_output {
+                    if response.success {
+                        println!("Execution complete");
+                        if !response.tool_calls.is_empty() {
+                            println!("\nTool call statistics: {} tools invoked", response.tool_calls.len());
+                        }
+                    } else {
+                        println!("Execution failed");
                     }
-                } else {
-                    println!("Execution failed");
                 }
             }
             Ok(Err(e)) => {
-                eprintln!("Execution failed: {}", e);
+                if text_output {
+                    eprintln!("Execution failed: {}", e);
+                }
                 return Err(e);
             }
             Err(e) => {
-                eprintln!("Task failed: {}", e);
+                if text_output {
+                    eprintln!("Task failed: {}", e);
+                }
                 return Err(e.into());
             }
         }
         
+        if !text_output {
+            report.patch = self.get_git_diff();
+            if let (Some(patch), Some(target)) = (&report.patch, &self.output_patch) {
+                if target != "-" {
+                    match std::fs::write(target, patch) {
+                        Ok(_) => report.patch_path = Some(target.clone()),
+                        Err(e) => eprintln!("Failed to save patch: {}", e),
+                    }
+                }
+            }
+            return Ok(());
+        }
+        
         if let Some(ref output_target) = self.output_patch {
             println!("\n--- Generating Patch ---");
             if let Some(patch) = self.get_git_diff() {
@@ -148,6 +279,7 @@ impl ExecMode {
                 } else {
                     match std::fs::write(output_target, &patch) {
                         Ok(_) => {
+                            report.patch_path = Some(output_target.clone());
                             println!("Patch saved to: {}", output_target);
                             println!("({} bytes)", patch.len());
                         }
@@ -168,3 +300,220 @@ impl ExecMode {
     }
 }
 
+/// Report an error that occurred before the run started, such as a failed initialization
+///
+/// JSON output formats end with a result object even then, and the result file is written.
+pub fn report_setup_failure(output_format: OutputFormat, result_file: Option<&Path>, error: &anyhow::Error) {
+    emit_report(&setup_failure_report(error), output_format, result_file);
+}
+
+fn setup_failure_report(error: &anyhow::Error) -> ExecReport {
+    ExecReport {
+        success: false,
+        error: Some(format!("{:#}", error)),
+        ..Default::default()
+    }
+}
+
+/// Write the report to the result file and, in JSON output formats, print the result object
+fn emit_report(report: &ExecReport, output_format: OutputFormat, result_file: Option<&Path>) {
+    if let Some(path) = result_file {
+        match serde_json::to_string_pretty(report) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    eprintln!("Failed to write result file {:?}: {}", path, e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize result: {}", e),
+        }
+    }
+    
+    if output_format != OutputFormat::Text {
+        match result_object(report) {
+            Ok(result_object) => println!("{}", result_object),
+            Err(e) => eprintln!("Failed to serialize result: {}", e),
+        }
+    }
+}
+
+/// The final object of the JSON output formats
+fn result_object(report: &ExecReport) -> serde_json::Result<Value> {
+    let mut result_object = serde_json::to_value(report)?;
+    result_object["type"] = json!("result");
+    Ok(result_object)
+}
+
+fn print_event(event: AgentEvent) {
+    match event {
+        AgentEvent::Thinking => {
+            println!("Thinking...");
+        }
+        AgentEvent::TextChunk(chunk) => {
+            print!("{}", chunk);
+            use std::io::Write;
+            std::io::stdout().flush().ok();
+        }
+        AgentEvent::ToolCallStart { tool_name, .. } => {
+            println!("\nTool call: {}", tool_name);
+        }
+        AgentEvent::ToolCallProgress { message, .. } => {
+            println!("   In progress: {}", message);
+        }
+        AgentEvent::ToolCallComplete { tool_name, result, success, .. } => {
+            if success {
+                println!("   [+] {}: {}", tool_name, result);
+            } else {
+                println!("   [x] {}: {}", tool_name, result);
+            }
+        }
+        AgentEvent::ModelSwitched { from_model, to_model, reason } => {
+            println!("\nModel switched: {} -> {} ({})", from_model, to_model, reason);
+        }
+        AgentEvent::Done => {
+            println!("\n");
+        }
+        AgentEvent::Error(err) => {
+            eprintln!("\nError: {}", err);
+        }
+    }
+}
+
+/// One `stream-json` line
+fn event_to_json(event: AgentEvent) -> Value {
+    match event {
+        AgentEvent::Thinking => json!({ "type": "thinking" }),
+        AgentEvent::TextChunk(text) => json!({ "type": "text", "text": text }),
+        AgentEvent::ToolCallStart { tool_id, tool_name, parameters } => json!({
+            "type": "tool_start",
+            "tool_id": tool_id,
+            "tool_name": tool_name,
+            "parameters": parameters,
+        }),
+        AgentEvent::ToolCallProgress { tool_id, tool_name, message, percentage } => json!({
+            "type": "tool_progress",
+            "tool_id": tool_id,
+            "tool_name": tool_name,
+            "message": message,
+            "percentage": percentage,
+        }),
+        AgentEvent::ToolCallComplete { tool_id, tool_name, result, success, duration_ms } => {
+            // Results arrive as serialized JSON; keep them structured when they are
+            let result = serde_json::from_str::<Value>(&result).unwrap_or(Value::String(result));
+            json!({
+                "type": "tool_complete",
+                "tool_id": tool_id,
+                "tool_name": tool_name,
+                "success": success,
+                "result": result,
+                "duration_ms": duration_ms,
+            })
+        }
+        AgentEvent::ModelSwitched { from_model, to_model, reason } => json!({
+            "type": "model_switched",
+            "from_model": from_model,
+            "to_model": to_model,
+            "reason": reason,
+        }),
+        AgentEvent::Done => json!({ "type": "done" }),
+        AgentEvent::Error(error) => json!({ "type": "error", "error": error }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_to_json_uses_stream_json_shapes() {
+        assert_eq!(event_to_json(AgentEvent::Thinking), json!({ "type": "thinking" }));
+        assert_eq!(
+            event_to_json(AgentEvent::TextChunk("hi".to_string())),
+            json!({ "type": "text", "text": "hi" })
+        );
+        assert_eq!(
+            event_to_json(AgentEvent::ToolCallStart {
+                tool_id: "t1".to_string(),
+                tool_name: "Read".to_string(),
+                parameters: json!({ "file_path": "a.rs" }),
+            }),
+            json!({
+                "type": "tool_start",
+                "tool_id": "t1",
+                "tool_name": "Read",
+                "parameters": { "file_path": "a.rs" },
+            })
+        );
+        assert_eq!(
+            event_to_json(AgentEvent::ToolCallComplete {
+                tool_id: "t1".to_string(),
+                tool_name: "Read".to_string(),
+                result: r#"{"lines":3}"#.to_string(),
+                success: true,
+                duration_ms: Some(12),
+            }),
+            json!({
+                "type": "tool_complete",
+                "tool_id": "t1",
+                "tool_name": "Read",
+                "success": true,
+                "result": { "lines": 3 },
+                "duration_ms": 12,
+            })
+        );
+        // Plain-text results stay strings
+        assert_eq!(
+            event_to_json(AgentEvent::ToolCallComplete {
+                tool_id: "t2".to_string(),
+                tool_name: "Bash".to_string(),
+                result: "permission denied".to_string(),
+                success: false,
+                duration_ms: None,
+            })["result"],
+            json!("permission denied")
+        );
+        assert_eq!(event_to_json(AgentEvent::Done), json!({ "type": "done" }));
+        assert_eq!(
+            event_to_json(AgentEvent::Error("boom".to_string())),
+            json!({ "type": "error", "error": "boom" })
+        );
+    }
+
+    #[test]
+    fn result_object_has_type_and_report_fields() {
+        let report = ExecReport {
+            success: true,
+            session_id: Some("s1".to_string()),
+            text: "done".to_string(),
+            duration_ms: 5,
+            tool_calls: 1,
+            ..Default::default()
+        };
+        let object = result_object(&report).unwrap();
+        assert_eq!(object["type"], "result");
+        assert_eq!(object["success"], true);
+        assert_eq!(object["error"], Value::Null);
+        assert_eq!(object["session_id"], "s1");
+        assert_eq!(object["text"], "done");
+        assert_eq!(object["tool_calls"], 1);
+        assert_eq!(object["tool_stats"], json!({ "succeeded": 0, "failed": 0, "by_tool": {} }));
+        assert_eq!(object["token_usage"]["total_tokens"], 0);
+        assert!(object.get("patch").is_none());
+    }
+
+    #[test]
+    fn setup_failure_is_a_failed_result() {
+        let error = anyhow::anyhow!("missing API key").context("Failed to initialize global AIClientFactory");
+        let object = result_object(&setup_failure_report(&error)).unwrap();
+        assert_eq!(object["type"], "result");
+        assert_eq!(object["success"], false);
+        assert_eq!(object["error"], "Failed to initialize global AIClientFactory: missing API key");
+        assert_eq!(object["session_id"], Value::Null);
+
+        let path = std::env::temp_dir().join(format!("bitfun-exec-result-{}.json", std::process::id()));
+        report_setup_failure(OutputFormat::Text, Some(&path), &error);
+        let written: ExecReport = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(!written.success);
+        assert_eq!(written.error.as_deref(), object["error"].as_str());
+    }
+}
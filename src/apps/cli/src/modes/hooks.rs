@@ -0,0 +1,95 @@
+/// Project hook commands
+///
+/// Hooks from a workspace's `.bitfun/settings.json` only run after the user approved them
+
+use anyhow::{Context, Result};
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use bitfun_core::agentic::hooks::{approve_project_hooks, project_hooks};
+use bitfun_core::service::config::types::HooksConfig;
+
+/// Show the project hooks of `workspace` and approve them after confirmation
+pub async fn approve(workspace: &Path, yes: bool) -> Result<()> {
+    let Some((hooks, hash)) = project_hooks(workspace) else {
+        println!("No project hooks in {}", workspace.display());
+        return Ok(());
+    };
+
+    println!("Project hooks of {}:\n", workspace.display());
+    for line in hook_lines(&hooks) {
+        println!("  {}", line);
+    }
+    println!();
+
+    if !yes {
+        print!("These commands will run on your machine during agent sessions. Approve? [y/N] ");
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().lock().read_line(&mut answer)?;
+        if !is_yes(&answer) {
+            println!("Project hooks not approved");
+            return Ok(());
+        }
+    }
+
+    approve_project_hooks(workspace, &hash)
+        .await
+        .context("Failed to approve project hooks")?;
+    println!("Project hooks approved");
+    Ok(())
+}
+
+/// One line per hook command: event, matcher and command
+fn hook_lines(hooks: &HooksConfig) -> Vec<String> {
+    let groups = [
+        ("PreToolUse", &hooks.pre_tool_use),
+        ("PostToolUse", &hooks.post_tool_use),
+        ("TurnEnd", &hooks.turn_end),
+        ("SessionStart", &hooks.session_start),
+    ];
+    groups
+        .into_iter()
+        .flat_map(|(event, commands)| {
+            commands.iter().map(move |hook| match hook.matcher.as_deref() {
+                Some(matcher) if !matcher.is_empty() => {
+                    format!("{} [{}]: {}", event, matcher, hook.command)
+                }
+                _ => format!("{}: {}", event, hook.command),
+            })
+        })
+        .collect()
+}
+
+fn is_yes(answer: &str) -> bool {
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitfun_core::service::config::types::HookCommandConfig;
+
+    #[test]
+    fn lists_every_hook_command() {
+        let hooks = HooksConfig {
+            pre_tool_use: vec![HookCommandConfig {
+                matcher: Some("Write|Edit".to_string()),
+                command: "./check.sh".to_string(),
+                timeout_secs: None,
+            }],
+            turn_end: vec![HookCommandConfig {
+                command: "make lint".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            hook_lines(&hooks),
+            vec!["PreToolUse [Write|Edit]: ./check.sh", "TurnEnd: make lint"]
+        );
+        assert!(is_yes(" Y\n"));
+        assert!(!is_yes("\n"));
+    }
+}
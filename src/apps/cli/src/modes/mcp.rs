@@ -0,0 +1,62 @@
+/// MCP server mode
+///
+/// Publishes selected registry tools to other MCP clients over stdio or streamable HTTP
+
+use anyhow::{Context, Result};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use bitfun_core::service::mcp::serve::{serve_http, serve_stdio, MCPToolServer, DEFAULT_SERVED_TOOLS};
+
+pub struct McpServeMode {
+    tools: Vec<String>,
+    agent: String,
+    /// Listen address for streamable HTTP; stdio when unset
+    http: Option<SocketAddr>,
+}
+
+impl McpServeMode {
+    pub fn new(tools: Option<Vec<String>>, agent: String, http: Option<String>) -> Result<Self> {
+        let tools = tools
+            .filter(|tools| !tools.is_empty())
+            .unwrap_or_else(|| DEFAULT_SERVED_TOOLS.iter().map(|t| t.to_string()).collect());
+        let http = http
+            .map(|addr| parse_listen_addr(&addr))
+            .transpose()?;
+
+        Ok(Self { tools, agent, http })
+    }
+
+    pub async fn run(self, workspace: Option<PathBuf>) -> Result<()> {
+        if self.tools.iter().any(|t| t == "LSP") {
+            if let Some(workspace) = workspace {
+                if let Err(e) = bitfun_core::service::lsp::open_workspace(workspace).await {
+                    tracing::warn!("Failed to open LSP workspace, LSP tool calls may fail: {}", e);
+                }
+            }
+        }
+
+        tracing::info!("Serving tools over MCP: {}", self.tools.join(", "));
+        let server = Arc::new(MCPToolServer::new(self.tools).with_mode(self.agent));
+
+        match self.http {
+            Some(addr) => tokio::select! {
+                result = serve_http(server, addr) => result.context("MCP HTTP server failed")?,
+                _ = tokio::signal::ctrl_c() => tracing::info!("MCP server interrupted"),
+            },
+            None => serve_stdio(server).await.context("MCP stdio server failed")?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Accept `host:port` or a bare port (bound to 127.0.0.1)
+fn parse_listen_addr(addr: &str) -> Result<SocketAddr> {
+    if let Ok(port) = addr.parse::<u16>() {
+        return Ok(SocketAddr::from(([127, 0, 0, 1], port)));
+    }
+    addr.parse()
+        .with_context(|| format!("Invalid listen address: {} (expected host:port or port)", addr))
+}
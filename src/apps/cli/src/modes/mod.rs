@@ -1,4 +1,9 @@
 /// Different interaction modes
 
+pub mod batch;
 pub mod chat;
 pub mod exec;
+pub mod hooks;
+pub mod mcp;
+pub mod sessions;
+pub mod tool;
@@ -0,0 +1,269 @@
+/// Agent session commands
+///
+/// Forking and lineage of the sessions persisted by the agentic system
+
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use bitfun_core::agentic::core::{Session, SessionSummary};
+use bitfun_core::agentic::persistence::PersistenceManager;
+use bitfun_core::infrastructure::try_get_path_manager_arc;
+
+use crate::agent::agentic_system::init_session_manager;
+
+/// Fork `session_id` after turn `turn` (1-based; latest turn when `None`)
+pub async fn fork(
+    session_id: &str,
+    turn: Option<usize>,
+    with_snapshots: bool,
+    workspace: Option<PathBuf>,
+) -> Result<()> {
+    if let Some(ref workspace) = workspace {
+        bitfun_core::infrastructure::set_workspace_path(Some(workspace.clone()));
+
+        if with_snapshots {
+            bitfun_core::service::snapshot::initialize_global_snapshot_manager(
+                workspace.clone(),
+                None,
+            )
+            .await
+            .context("Failed to initialize snapshot system")?;
+        }
+    } else if with_snapshots {
+        anyhow::bail!("--snapshots requires --workspace");
+    }
+
+    let session_manager = init_session_manager()?;
+    let source = session_manager
+        .restore_session(session_id)
+        .await
+        .with_context(|| format!("Session not found: {}", session_id))?;
+
+    let turn_count = source.dialog_turn_ids.len();
+    if turn_count == 0 {
+        anyhow::bail!("Session has no dialog turns to fork from");
+    }
+    let turn = turn.unwrap_or(turn_count);
+    if turn == 0 || turn > turn_count {
+        anyhow::bail!("Turn must be between 1 and {}", turn_count);
+    }
+
+    let fork = session_manager
+        .fork_session(session_id, turn - 1, with_snapshots)
+        .await
+        .context("Failed to fork session")?;
+
+    println!("Forked session: {} (ID: {})", fork.session_name, fork.session_id);
+    println!("   Parent: {} | Turns: {}", session_id, fork.dialog_turn_ids.len());
+    if with_snapshots {
+        println!("   File snapshot state forked");
+    }
+
+    Ok(())
+}
+
+/// Print the fork lineage of all sessions, or only the tree containing `session_id`
+pub async fn tree(session_id: Option<&str>) -> Result<()> {
+    let persistence_manager = PersistenceManager::new(try_get_path_manager_arc()?)?;
+    let summaries = persistence_manager.list_sessions().await?;
+
+    if summaries.is_empty() {
+        println!("No agent sessions");
+        return Ok(());
+    }
+
+    let lines = lineage_lines(&summaries, session_id);
+    if lines.is_empty() {
+        anyhow::bail!("Session not found: {}", session_id.unwrap_or_default());
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// Resolve a `--resume` target to an agent session ID; `last` is the most recently active
+/// session of the current workspace
+pub async fn resolve_resume_target(target: &str) -> Result<String> {
+    if target != "last" {
+        return Ok(target.to_string());
+    }
+
+    let workspace = bitfun_core::infrastructure::get_workspace_path()
+        .or_else(|| std::env::current_dir().ok())
+        .context("Cannot determine the current workspace")?;
+    let persistence_manager = PersistenceManager::new(try_get_path_manager_arc()?)?;
+    let summaries = persistence_manager.list_sessions().await?;
+    latest_in_workspace(&summaries, &Session::workspace_key(&workspace)).with_context(|| {
+        format!(
+            "No agent session to resume in workspace {}",
+            workspace.display()
+        )
+    })
+}
+
+/// Most recently active session recorded for `workspace`
+fn latest_in_workspace(summaries: &[SessionSummary], workspace: &str) -> Option<String> {
+    summaries
+        .iter()
+        .filter(|s| s.workspace_path.as_deref() == Some(workspace))
+        .max_by_key(|s| s.last_activity_at)
+        .map(|s| s.session_id.clone())
+}
+
+/// Render sessions as a tree by fork lineage
+///
+/// Sessions whose parent no longer exists are shown as roots.
+fn lineage_lines(summaries: &[SessionSummary], focus: Option<&str>) -> Vec<String> {
+    let by_id: HashMap<&str, &SessionSummary> = summaries
+        .iter()
+        .map(|s| (s.session_id.as_str(), s))
+        .collect();
+
+    let mut children: HashMap<&str, Vec<&SessionSummary>> = HashMap::new();
+    let mut roots = Vec::new();
+    for summary in summaries {
+        match summary.parent_session_id.as_deref() {
+            Some(parent) if by_id.contains_key(parent) => {
+                children.entry(parent).or_default().push(summary)
+            }
+            _ => roots.push(summary),
+        }
+    }
+    for list in children.values_mut() {
+        list.sort_by_key(|s| s.created_at);
+    }
+
+    if let Some(focus) = focus {
+        // Walk up to the root of the focused session
+        let Some(mut current) = by_id.get(focus).copied() else {
+            return Vec::new();
+        };
+        let mut seen = HashSet::new();
+        while let Some(parent) = current
+            .parent_session_id
+            .as_deref()
+            .and_then(|id| by_id.get(id).copied())
+        {
+            if !seen.insert(parent.session_id.as_str()) {
+                break;
+            }
+            current = parent;
+        }
+        roots = vec![current];
+    }
+
+    let mut lines = Vec::new();
+    for root in roots {
+        lines.push(describe(root));
+        push_children(root, &children, "", &mut lines);
+    }
+    lines
+}
+
+fn push_children(
+    parent: &SessionSummary,
+    children: &HashMap<&str, Vec<&SessionSummary>>,
+    prefix: &str,
+    lines: &mut Vec<String>,
+) {
+    let Some(list) = children.get(parent.session_id.as_str()) else {
+        return;
+    };
+
+    for (i, child) in list.iter().enumerate() {
+        let last = i + 1 == list.len();
+        let (branch, indent) = if last { ("└─ ", "   ") } else { ("├─ ", "│  ") };
+        lines.push(format!("{}{}{}", prefix, branch, describe(child)));
+        push_children(child, children, &format!("{}{}", prefix, indent), lines);
+    }
+}
+
+fn describe(summary: &SessionSummary) -> String {
+    let fork_point = summary
+        .fork_turn_index
+        .map(|index| format!(", forked after turn {}", index + 1))
+        .unwrap_or_default();
+    format!(
+        "{} (ID: {}, turns: {}{})",
+        summary.session_name, summary.session_id, summary.turn_count, fork_point
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitfun_core::agentic::core::SessionState;
+    use std::time::{Duration, SystemTime};
+
+    fn summary(id: &str, parent: Option<&str>, order: u64) -> SessionSummary {
+        let created_at = SystemTime::UNIX_EPOCH + Duration::from_secs(order);
+        SessionSummary {
+            session_id: id.to_string(),
+            session_name: id.to_string(),
+            agent_type: "agentic".to_string(),
+            turn_count: 2,
+            created_at,
+            last_activity_at: created_at,
+            state: SessionState::Idle,
+            parent_session_id: parent.map(str::to_string),
+            fork_turn_index: parent.map(|_| 0),
+            child_session_ids: vec![],
+            workspace_path: Some("/work/a".to_string()),
+        }
+    }
+
+    #[test]
+    fn renders_lineage_tree() {
+        let summaries = vec![
+            summary("root", None, 0),
+            summary("b", Some("root"), 2),
+            summary("a", Some("root"), 1),
+            summary("a1", Some("a"), 3),
+            summary("orphan", Some("deleted"), 4),
+        ];
+
+        let lines = lineage_lines(&summaries, None);
+        assert_eq!(
+            lines,
+            vec![
+                "root (ID: root, turns: 2)",
+                "├─ a (ID: a, turns: 2, forked after turn 1)",
+                "│  └─ a1 (ID: a1, turns: 2, forked after turn 1)",
+                "└─ b (ID: b, turns: 2, forked after turn 1)",
+                "orphan (ID: orphan, turns: 2, forked after turn 1)",
+            ]
+        );
+
+        let focused = lineage_lines(&summaries, Some("a1"));
+        assert_eq!(focused.len(), 4);
+        assert!(focused[0].starts_with("root "));
+        assert!(lineage_lines(&summaries, Some("missing")).is_empty());
+    }
+
+    #[test]
+    fn resumes_latest_session_of_the_workspace() {
+        let mut other = summary("other", None, 5);
+        other.workspace_path = Some("/work/b".to_string());
+        let mut legacy = summary("legacy", None, 6);
+        legacy.workspace_path = None;
+        let summaries = vec![
+            summary("old", None, 1),
+            summary("new", None, 3),
+            other,
+            legacy,
+        ];
+
+        assert_eq!(
+            latest_in_workspace(&summaries, "/work/a").as_deref(),
+            Some("new")
+        );
+        assert_eq!(
+            latest_in_workspace(&summaries, "/work/b").as_deref(),
+            Some("other")
+        );
+        assert!(latest_in_workspace(&summaries, "/work/c").is_none());
+    }
+}
@@ -0,0 +1,287 @@
+/// Tool mode implementation
+///
+/// Invokes a registered tool directly, without an LLM round trip
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+
+use bitfun_core::agentic::tools::framework::{ToolResult, ToolUseContext};
+use bitfun_core::agentic::tools::registry::get_global_tool_registry;
+use bitfun_core::service::mcp::MCPService;
+use tokio_util::sync::CancellationToken;
+
+pub struct ToolMode {
+    /// Output JSON instead of human-readable text
+    json: bool,
+    /// Start configured MCP servers so their tools are registered
+    with_mcp: bool,
+    mcp_service: Option<MCPService>,
+}
+
+impl ToolMode {
+    pub fn new(json: bool, with_mcp: bool) -> Self {
+        Self {
+            json,
+            with_mcp,
+            mcp_service: None,
+        }
+    }
+
+    async fn init_mcp(&mut self) -> Result<()> {
+        if !self.with_mcp {
+            return Ok(());
+        }
+
+        let config_service = bitfun_core::service::config::get_global_config_service()
+            .await
+            .context("Failed to get global config service")?;
+        let mcp_service = MCPService::new(config_service).context("Failed to create MCP service")?;
+
+        if let Err(e) = mcp_service.server_manager().initialize_all().await {
+            eprintln!("Warning: Failed to initialize MCP servers: {}", e);
+        }
+
+        self.mcp_service = Some(mcp_service);
+        Ok(())
+    }
+
+    async fn shutdown_mcp(&self) {
+        if let Some(ref mcp_service) = self.mcp_service {
+            if let Err(e) = mcp_service.server_manager().shutdown().await {
+                tracing::warn!("Failed to shut down MCP servers: {}", e);
+            }
+        }
+    }
+
+    /// Print every registered tool with its input schema
+    pub async fn list(&mut self) -> Result<()> {
+        self.init_mcp().await?;
+
+        let tools = {
+            let registry = get_global_tool_registry();
+            let guard = registry.read().await;
+            guard.get_all_tools()
+        };
+
+        let mut entries = Vec::with_capacity(tools.len());
+        for tool in &tools {
+            entries.push(json!({
+                "name": tool.name(),
+                "description": tool.description().await.unwrap_or_default(),
+                "readonly": tool.is_readonly(),
+                "enabled": tool.is_enabled().await,
+                "input_schema": tool.input_schema(),
+            }));
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&entries)?);
+        } else {
+            println!("Registered tools (total {})\n", entries.len());
+            for entry in &entries {
+                let description = entry["description"].as_str().unwrap_or("");
+                println!(
+                    "{}{}",
+                    entry["name"].as_str().unwrap_or(""),
+                    if entry["readonly"].as_bool() == Some(true) { " (readonly)" } else { "" }
+                );
+                if let Some(first_line) = description.lines().next() {
+                    println!("  {}", first_line);
+                }
+                println!("  Input schema:");
+                for line in serde_json::to_string_pretty(&entry["input_schema"])?.lines() {
+                    println!("    {}", line);
+                }
+                println!();
+            }
+        }
+
+        self.shutdown_mcp().await;
+        Ok(())
+    }
+
+    /// Validate the input and call the tool
+    pub async fn invoke(&mut self, name: &str, params: Option<String>) -> Result<()> {
+        if name.starts_with("mcp_") {
+            self.with_mcp = true;
+        }
+        self.init_mcp().await?;
+
+        let result = self.invoke_inner(name, params).await;
+        self.shutdown_mcp().await;
+        result
+    }
+
+    async fn invoke_inner(&self, name: &str, params: Option<String>) -> Result<()> {
+        let results = call_tool(name, params).await?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        } else {
+            for result in &results {
+                print_result(result);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Look up the tool, validate the parameters and call it
+async fn call_tool(name: &str, params: Option<String>) -> Result<Vec<ToolResult>> {
+    let tool = {
+        let registry = get_global_tool_registry();
+        let guard = registry.read().await;
+        guard.get_tool(name)
+    }
+    .ok_or_else(|| anyhow::anyhow!("Tool not found: {} (use `tool --list` to see available tools)", name))?;
+
+    let input = parse_params(params)?;
+    let context = build_context();
+    tracing::debug!(
+        "Invoking tool directly: tool_name={}, tool_call_id={:?}",
+        name,
+        context.tool_call_id
+    );
+
+    let validation = tool.validate_input(&input, Some(&context)).await;
+    if !validation.result {
+        anyhow::bail!(
+            "Invalid input for {}: {}",
+            name,
+            validation.message.unwrap_or_else(|| "validation failed".to_string())
+        );
+    }
+
+    tokio::select! {
+        results = tool.call(&input, &context) => results,
+        _ = tokio::signal::ctrl_c() => {
+            anyhow::bail!("Tool execution interrupted");
+        }
+    }
+    .with_context(|| format!("Tool {} failed", name))
+}
+
+/// Parse `--params`: inline JSON, `@path` to read a file, or `-` to read stdin
+///
+/// Tool inputs are JSON objects, so any other JSON value is rejected.
+fn parse_params(params: Option<String>) -> Result<Value> {
+    let raw = match params {
+        None => return Ok(json!({})),
+        Some(p) if p == "-" => {
+            let mut buffer = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut buffer)
+                .context("Failed to read parameters from stdin")?;
+            buffer
+        }
+        Some(p) => match p.strip_prefix('@') {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read parameters file: {}", path))?,
+            None => p,
+        },
+    };
+
+    let params: Value = serde_json::from_str(&raw).context("Parameters must be valid JSON")?;
+    if !params.is_object() {
+        anyhow::bail!("Parameters must be a JSON object, got: {}", params);
+    }
+    Ok(params)
+}
+
+fn build_context() -> ToolUseContext {
+    let id = uuid::Uuid::new_v4().to_string();
+
+    ToolUseContext {
+        tool_call_id: Some(format!("cli-tool-{}", id)),
+        message_id: None,
+        agent_type: Some("agentic".to_string()),
+        session_id: Some(format!("cli-tool-session-{}", id)),
+        dialog_turn_id: Some(format!("cli-tool-turn-{}", id)),
+        safe_mode: None,
+        abort_controller: None,
+        read_file_timestamps: Default::default(),
+        options: None,
+        response_state: None,
+        image_context_provider: None,
+        subagent_parent_info: None,
+        cancellation_token: Some(CancellationToken::new()),
+    }
+}
+
+fn print_result(result: &ToolResult) {
+    match result {
+        ToolResult::Result { data, result_for_assistant } => {
+            match result_for_assistant {
+                Some(text) if !text.is_empty() => println!("{}", text),
+                _ => println!("{}", value_to_text(data)),
+            }
+        }
+        ToolResult::Progress { content, .. } => {
+            println!("[progress] {}", value_to_text(content));
+        }
+        ToolResult::StreamChunk { data, .. } => {
+            print!("{}", value_to_text(data));
+        }
+    }
+}
+
+fn value_to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_params() {
+        assert_eq!(parse_params(None).unwrap(), json!({}));
+        assert_eq!(
+            parse_params(Some(r#"{"file_path": "a.rs"}"#.to_string())).unwrap(),
+            json!({ "file_path": "a.rs" })
+        );
+
+        let path = std::env::temp_dir().join(format!("bitfun-tool-params-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"limit": 3}"#).unwrap();
+        let from_file = parse_params(Some(format!("@{}", path.display())));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(from_file.unwrap(), json!({ "limit": 3 }));
+
+        let error = parse_params(Some(r#"{"file_path": "#.to_string())).unwrap_err();
+        assert_eq!(error.to_string(), "Parameters must be valid JSON");
+        let error = parse_params(Some("@/nonexistent/params.json".to_string())).unwrap_err();
+        assert_eq!(error.to_string(), "Failed to read parameters file: /nonexistent/params.json");
+        for non_object in [r#"["a.rs"]"#, r#""a.rs""#, "42", "null"] {
+            let error = parse_params(Some(non_object.to_string())).unwrap_err();
+            assert!(error.to_string().starts_with("Parameters must be a JSON object"), "{}", non_object);
+        }
+    }
+
+    #[tokio::test]
+    async fn calls_tools_directly() {
+        let error = call_tool("NoSuchTool", None).await.unwrap_err();
+        assert!(error.to_string().starts_with("Tool not found: NoSuchTool"));
+
+        let error = call_tool("Read", Some("[]".to_string())).await.unwrap_err();
+        assert!(error.to_string().starts_with("Parameters must be a JSON object"));
+        let error = call_tool("Read", None).await.unwrap_err();
+        assert_eq!(error.to_string(), "Invalid input for Read: file_path is required");
+
+        let path = std::env::temp_dir().join(format!("bitfun-tool-read-{}.txt", std::process::id()));
+        std::fs::write(&path, "first\nsecond\n").unwrap();
+        let params = json!({ "file_path": path.display().to_string() }).to_string();
+        let results = call_tool("Read", Some(params)).await;
+        let _ = std::fs::remove_file(&path);
+
+        let results = results.unwrap();
+        assert_eq!(results.len(), 1);
+        let ToolResult::Result { data, .. } = &results[0] else {
+            panic!("expected a final result, got {:?}", results[0]);
+        };
+        assert_eq!(data["total_lines"], 2);
+        assert!(data["content"].as_str().unwrap().contains("second"));
+    }
+}
@@ -54,6 +54,14 @@ pub struct StartDialogTurnResponse {
     pub message: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartPlanExecutionRequest {
+    pub session_id: String,
+    pub plan_file_path: String,
+    pub turn_id: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetSessionRequest {
@@ -194,6 +202,22 @@ pub async fn start_dialog_turn(
     })
 }
 
+#[tauri::command]
+pub async fn start_plan_execution(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: StartPlanExecutionRequest,
+) -> Result<StartDialogTurnResponse, String> {
+    coordinator
+        .start_plan_execution(request.session_id, request.plan_file_path, request.turn_id)
+        .await
+        .map_err(|e| format!("Failed to start plan execution: {}", e))?;
+
+    Ok(StartDialogTurnResponse {
+        success: true,
+        message: "Plan execution started".to_string(),
+    })
+}
+
 #[tauri::command]
 pub async fn cancel_dialog_turn(
     coordinator: State<'_, Arc<ConversationCoordinator>>,
@@ -64,6 +64,7 @@ pub async fn set_config(
                 || request.path.starts_with("ai.default_models")
                 || request.path.starts_with("ai.agent_models")
                 || request.path.starts_with("ai.proxy")
+                || request.path.starts_with("ai.retry")
             {
                 state.ai_client_factory.invalidate_cache();
                 info!(
@@ -505,3 +506,24 @@ pub async fn sync_tool_configs(_state: State<'_, AppState>) -> Result<Value, Str
         }
     }
 }
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveProjectHooksRequest {
+    pub workspace_path: String,
+    pub hash: String,
+}
+
+#[tauri::command]
+pub async fn approve_project_hooks(request: ApproveProjectHooksRequest) -> Result<(), String> {
+    let workspace = std::path::Path::new(&request.workspace_path);
+    bitfun_core::agentic::hooks::approve_project_hooks(workspace, &request.hash)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to approve project hooks: workspace={}, error={}",
+                request.workspace_path, e
+            );
+            format!("Failed to approve project hooks: {}", e)
+        })
+}
@@ -3,7 +3,7 @@
 use bitfun_core::infrastructure::{get_workspace_path, try_get_path_manager_arc};
 use bitfun_core::service::snapshot::{
     ensure_global_snapshot_manager, initialize_global_snapshot_manager, OperationType,
-    SnapshotConfig,
+    SnapshotConfig, SnapshotError,
 };
 use log::warn;
 use serde::{Deserialize, Serialize};
@@ -30,6 +30,9 @@ pub struct RollbackSessionRequest {
     pub session_id: String,
     #[serde(default)]
     pub delete_session: bool, // Whether to also delete the session (default false)
+    /// Also revert changes attributed by a workspace scan
+    #[serde(default)]
+    pub confirm_detected: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +41,50 @@ pub struct RollbackTurnRequest {
     pub turn_index: usize,
     #[serde(default)]
     pub delete_turns: bool,
+    /// Also revert changes attributed by a workspace scan
+    #[serde(default)]
+    pub confirm_detected: bool,
+}
+
+/// Error of the rollback commands
+///
+/// `code` tells the frontend which errors it can resolve (e.g. by asking the user and
+/// retrying with `confirm_detected`) without parsing `message`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackError {
+    pub code: &'static str,
+    pub message: String,
+    /// Files changed outside wrapped tools that the rollback would revert
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub detected_changes: Vec<String>,
+}
+
+impl RollbackError {
+    /// The rollback needs the user's confirmation to revert `detected_changes`
+    pub const DETECTED_CHANGES_NEED_CONFIRMATION: &'static str =
+        "DETECTED_CHANGES_NEED_CONFIRMATION";
+    pub const ROLLBACK_FAILED: &'static str = "ROLLBACK_FAILED";
+
+    fn failed(message: String) -> Self {
+        Self {
+            code: Self::ROLLBACK_FAILED,
+            message,
+            detected_changes: Vec::new(),
+        }
+    }
+
+    fn from_snapshot_error(context: &str, error: SnapshotError) -> Self {
+        let message = format!("{}: {}", context, error);
+        match error {
+            SnapshotError::DetectedChangesNeedConfirmation(paths) => Self {
+                code: Self::DETECTED_CHANGES_NEED_CONFIRMATION,
+                message,
+                detected_changes: paths,
+            },
+            _ => Self::failed(message),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -189,14 +236,14 @@ pub async fn record_file_change(
 pub async fn rollback_session(
     app_handle: AppHandle,
     request: RollbackSessionRequest,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, RollbackError> {
     let manager = ensure_global_snapshot_manager()
-        .map_err(|e| format!("Failed to get snapshot manager: {}", e))?;
+        .map_err(|e| RollbackError::failed(format!("Failed to get snapshot manager: {}", e)))?;
 
     let restored_files = manager
-        .rollback_session(&request.session_id)
+        .rollback_session(&request.session_id, request.confirm_detected)
         .await
-        .map_err(|e| format!("Failed to rollback session: {}", e))?;
+        .map_err(|e| RollbackError::from_snapshot_error("Failed to rollback session", e))?;
 
     let restored_files_str: Vec<String> = restored_files
         .iter()
@@ -219,14 +266,18 @@ pub async fn rollback_session(
 pub async fn rollback_to_turn(
     app_handle: AppHandle,
     request: RollbackTurnRequest,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, RollbackError> {
     let manager = ensure_global_snapshot_manager()
-        .map_err(|e| format!("Failed to get snapshot manager: {}", e))?;
+        .map_err(|e| RollbackError::failed(format!("Failed to get snapshot manager: {}", e)))?;
 
     let restored_files = manager
-        .rollback_to_turn(&request.session_id, request.turn_index)
+        .rollback_to_turn(
+            &request.session_id,
+            request.turn_index,
+            request.confirm_detected,
+        )
         .await
-        .map_err(|e| format!("Failed to rollback turn: {}", e))?;
+        .map_err(|e| RollbackError::from_snapshot_error("Failed to rollback turn", e))?;
 
     let restored_files_str: Vec<String> = restored_files
         .iter()
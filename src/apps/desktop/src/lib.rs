@@ -238,6 +238,7 @@ pub async fn run() {
             theme::show_main_window,
             api::agentic_api::create_session,
             api::agentic_api::start_dialog_turn,
+            api::agentic_api::start_plan_execution,
             api::agentic_api::cancel_dialog_turn,
             api::agentic_api::delete_session,
             api::agentic_api::restore_session,
@@ -484,6 +485,7 @@ pub async fn run() {
             api::prompt_template_api::import_prompt_templates,
             api::prompt_template_api::reset_prompt_templates,
             api::config_api::sync_tool_configs,
+            api::config_api::approve_project_hooks,
             api::terminal_api::terminal_get_shells,
             api::terminal_api::terminal_create,
             api::terminal_api::terminal_get,
@@ -0,0 +1,131 @@
+//! Agentic System Initialization for Server
+//!
+//! Initialize the complete agentic system and the event loop that pushes
+//! agentic events to subscribed WebSocket clients
+
+use anyhow::Result;
+use bitfun_core::infrastructure::ai::AIClientFactory;
+use std::sync::Arc;
+
+use bitfun_core::agentic::coordination;
+use bitfun_core::agentic::events;
+use bitfun_core::agentic::execution;
+use bitfun_core::agentic::persistence;
+use bitfun_core::agentic::session;
+use bitfun_core::agentic::tools;
+use bitfun_core::infrastructure::try_get_path_manager_arc;
+
+use crate::event_hub::EventHub;
+
+/// Agentic system state
+pub struct AgenticSystem {
+    pub coordinator: Arc<coordination::ConversationCoordinator>,
+    pub event_queue: Arc<events::EventQueue>,
+    pub event_router: Arc<events::EventRouter>,
+}
+
+/// Initialize Agentic system
+pub async fn init_agentic_system() -> Result<AgenticSystem> {
+    tracing::info!("Initializing Agentic system");
+
+    let _ai_client_factory = AIClientFactory::get_global().await?;
+
+    let event_queue = Arc::new(events::EventQueue::new(Default::default()));
+    let event_router = Arc::new(events::EventRouter::new());
+
+    let path_manager = try_get_path_manager_arc()?;
+    let persistence_manager = Arc::new(persistence::PersistenceManager::new(path_manager.clone())?);
+
+    let history_manager = Arc::new(session::MessageHistoryManager::new(
+        persistence_manager.clone(),
+        session::HistoryConfig {
+            enable_persistence: false,
+            ..Default::default()
+        },
+    ));
+
+    let compression_manager = Arc::new(session::CompressionManager::new(
+        persistence_manager.clone(),
+        session::CompressionConfig {
+            enable_persistence: false,
+            ..Default::default()
+        },
+    ));
+
+    let session_manager = Arc::new(session::SessionManager::new(
+        history_manager,
+        compression_manager,
+        persistence_manager,
+        Default::default(),
+    ));
+
+    let tool_registry = tools::registry::get_global_tool_registry();
+    let tool_state_manager = Arc::new(tools::pipeline::ToolStateManager::new(event_queue.clone()));
+    let tool_pipeline = Arc::new(tools::pipeline::ToolPipeline::new(
+        tool_registry,
+        tool_state_manager,
+        None,
+    ));
+
+    let stream_processor = Arc::new(execution::StreamProcessor::new(event_queue.clone()));
+    let round_executor = Arc::new(execution::RoundExecutor::new(
+        stream_processor,
+        event_queue.clone(),
+        tool_pipeline.clone(),
+    ));
+    let execution_engine = Arc::new(execution::ExecutionEngine::new(
+        round_executor,
+        event_queue.clone(),
+        session_manager.clone(),
+        Default::default(),
+    ));
+
+    let coordinator = Arc::new(coordination::ConversationCoordinator::new(
+        session_manager,
+        execution_engine,
+        tool_pipeline,
+        event_queue.clone(),
+        event_router.clone(),
+    ));
+
+    coordination::ConversationCoordinator::set_global(coordinator.clone());
+    tracing::info!("Agentic system initialization complete");
+
+    Ok(AgenticSystem {
+        coordinator,
+        event_queue,
+        event_router,
+    })
+}
+
+/// Drain the event queue, route events to internal subscribers and push them to WebSocket clients
+pub fn start_event_loop(
+    event_queue: Arc<events::EventQueue>,
+    event_router: Arc<events::EventRouter>,
+    event_hub: Arc<EventHub>,
+) {
+    tokio::spawn(async move {
+        loop {
+            event_queue.wait_for_events().await;
+
+            loop {
+                let batch = event_queue.dequeue_batch(10).await;
+                if batch.is_empty() {
+                    break;
+                }
+
+                for envelope in batch {
+                    let router = event_router.clone();
+                    let env_clone = envelope.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = router.route(env_clone).await {
+                            tracing::warn!("Internal event routing failed: {:?}", e);
+                        }
+                    });
+
+                    event_hub.broadcast(envelope.event).await;
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,281 @@
+/// Access control
+///
+/// Every API and WebSocket request must carry the server's access token, either as
+/// `Authorization: Bearer <token>` or, for WebSocket upgrades where browsers cannot set
+/// headers, as a `token` query parameter. Cross-origin access is limited to local origins
+/// plus the origins listed in `BITFUN_ALLOWED_ORIGINS`.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use url::{Host, Url};
+
+/// Environment variable holding a fixed access token
+const TOKEN_ENV: &str = "BITFUN_SERVER_TOKEN";
+/// Environment variable holding extra allowed origins, comma separated
+const ALLOWED_ORIGINS_ENV: &str = "BITFUN_ALLOWED_ORIGINS";
+
+/// Access token and origin allowlist
+#[derive(Debug, Clone)]
+pub struct ServerAuth {
+    token: String,
+    /// Whether the token was generated at startup rather than configured
+    pub generated: bool,
+    allowed_origins: Arc<Vec<String>>,
+}
+
+impl ServerAuth {
+    /// Reads the token and allowed origins from the environment, generating a token if unset
+    pub fn from_env() -> Self {
+        let configured = std::env::var(TOKEN_ENV)
+            .ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty());
+        let generated = configured.is_none();
+        let token = configured.unwrap_or_else(|| {
+            format!(
+                "{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            )
+        });
+
+        let allowed_origins = std::env::var(ALLOWED_ORIGINS_ENV)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .filter_map(|origin| {
+                let normalized = parse_origin(origin).map(|url| serialize_origin(&url));
+                if normalized.is_none() {
+                    tracing::warn!(
+                        "Ignoring invalid origin in {}: {}",
+                        ALLOWED_ORIGINS_ENV,
+                        origin
+                    );
+                }
+                normalized
+            })
+            .collect();
+
+        Self::new(token, generated, allowed_origins)
+    }
+
+    /// `allowed_origins` are serialized origins such as `https://example.com:8443`
+    pub fn new(token: String, generated: bool, allowed_origins: Vec<String>) -> Self {
+        Self {
+            token,
+            generated,
+            allowed_origins: Arc::new(allowed_origins),
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Local origins and the configured ones
+    ///
+    /// Origins are parsed and compared by scheme, host and port, never by prefix.
+    pub fn is_allowed_origin(&self, origin: &str) -> bool {
+        let Some(url) = parse_origin(origin) else {
+            return false;
+        };
+        is_local_host(&url) || {
+            let origin = serialize_origin(&url);
+            self.allowed_origins
+                .iter()
+                .any(|allowed| *allowed == origin)
+        }
+    }
+
+    /// CORS layer that only admits allowed origins
+    pub fn cors_layer(&self) -> CorsLayer {
+        let auth = self.clone();
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+                origin
+                    .to_str()
+                    .map(|origin| auth.is_allowed_origin(origin))
+                    .unwrap_or(false)
+            }))
+            .allow_methods(Any)
+            .allow_headers(Any)
+    }
+
+    fn is_authorized(&self, headers: &HeaderMap, query: Option<&str>) -> bool {
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(str::trim);
+        let query_token = query.and_then(|query| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+        });
+
+        bearer
+            .into_iter()
+            .chain(query_token)
+            .any(|candidate| constant_time_eq(candidate.as_bytes(), self.token.as_bytes()))
+    }
+}
+
+/// Rejects requests without the access token or from disallowed origins
+///
+/// This also guards WebSocket upgrades, whose Origin header browsers always send.
+pub async fn require_auth(
+    State(auth): State<ServerAuth>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // CORS preflight carries no credentials; the CORS layer answers it
+    if request.method() == axum::http::Method::OPTIONS {
+        return next.run(request).await;
+    }
+
+    if let Some(origin) = request
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|v| v.to_str().ok())
+    {
+        if !auth.is_allowed_origin(origin) {
+            tracing::warn!("Rejected request from origin: {}", origin);
+            return StatusCode::FORBIDDEN.into_response();
+        }
+    }
+
+    if !auth.is_authorized(request.headers(), request.uri().query()) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Parse an Origin header; origins without a host such as `null` are rejected
+fn parse_origin(origin: &str) -> Option<Url> {
+    Url::parse(origin).ok().filter(|url| url.host().is_some())
+}
+
+/// `scheme://host[:port]`, with the scheme's default port omitted
+fn serialize_origin(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{}://{}:{}", url.scheme(), host, port),
+        None => format!("{}://{}", url.scheme(), host),
+    }
+}
+
+/// Whether an origin names this machine
+fn is_local_host(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(Host::Ipv4(ip)) => ip == Ipv4Addr::LOCALHOST,
+        Some(Host::Ipv6(ip)) => ip == Ipv6Addr::LOCALHOST,
+        None => false,
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "secret-token";
+
+    fn app() -> Router {
+        let auth = ServerAuth::new(
+            TOKEN.to_string(),
+            false,
+            vec!["https://app.example.com".to_string()],
+        );
+        Router::new()
+            .route("/ws", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(auth.clone(), require_auth))
+            .layer(auth.cors_layer())
+    }
+
+    async fn status(uri: &str, headers: &[(header::HeaderName, &str)]) -> StatusCode {
+        let mut request = Request::builder().uri(uri);
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+        app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_or_wrong_token() {
+        let bearer = format!("Bearer {}", TOKEN);
+        assert_eq!(status("/ws", &[]).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            status("/ws", &[(header::AUTHORIZATION, "Bearer wrong")]).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status("/ws?token=wrong", &[]).await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        assert_eq!(
+            status("/ws", &[(header::AUTHORIZATION, &bearer)]).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(&format!("/ws?foo=1&token={}", TOKEN), &[]).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_disallowed_origins() {
+        let uri = format!("/ws?token={}", TOKEN);
+        for origin in [
+            "http://localhost:5173",
+            "http://127.0.0.1",
+            "http://[::1]:8080",
+            "https://app.example.com",
+            "https://app.example.com:443/",
+        ] {
+            assert_eq!(
+                status(&uri, &[(header::ORIGIN, origin)]).await,
+                StatusCode::OK,
+                "{}",
+                origin
+            );
+        }
+        for origin in [
+            "http://[::1]evil.com",
+            "http://localhost.evil.com",
+            "http://127.0.0.1.evil.com",
+            "https://app.example.com.evil.com",
+            "http://app.example.com",
+            "null",
+        ] {
+            assert_eq!(
+                status(&uri, &[(header::ORIGIN, origin)]).await,
+                StatusCode::FORBIDDEN,
+                "{}",
+                origin
+            );
+        }
+    }
+}
@@ -0,0 +1,135 @@
+/// Event hub
+///
+/// Tracks connected WebSocket clients and the sessions they subscribed to,
+/// and pushes agentic events to them through the WebSocket transport adapter.
+/// It is also the emitter of the global event system, so backend notifications
+/// (e.g. project hooks waiting for approval) reach every connected client.
+
+use async_trait::async_trait;
+use bitfun_events::{AgenticEvent, EventEmitter};
+use bitfun_transport::{TransportAdapter, WebSocketTransportAdapter};
+use dashmap::DashMap;
+use std::collections::HashSet;
+
+/// Subscribing to this session ID receives events of all sessions
+pub const ALL_SESSIONS: &str = "*";
+
+struct ClientEntry {
+    adapter: WebSocketTransportAdapter,
+    sessions: HashSet<String>,
+}
+
+impl ClientEntry {
+    fn is_subscribed(&self, session_id: Option<&str>) -> bool {
+        if self.sessions.contains(ALL_SESSIONS) {
+            return true;
+        }
+        session_id.is_some_and(|id| self.sessions.contains(id))
+    }
+}
+
+#[derive(Default)]
+pub struct EventHub {
+    clients: DashMap<String, ClientEntry>,
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a connected client
+    pub fn register_client(&self, client_id: &str, adapter: WebSocketTransportAdapter) {
+        self.clients.insert(
+            client_id.to_string(),
+            ClientEntry {
+                adapter,
+                sessions: HashSet::new(),
+            },
+        );
+        tracing::debug!("WebSocket client registered: client_id={}", client_id);
+    }
+
+    /// Remove a disconnected client
+    pub fn unregister_client(&self, client_id: &str) {
+        self.clients.remove(client_id);
+        tracing::debug!("WebSocket client unregistered: client_id={}", client_id);
+    }
+
+    /// Subscribe a client to a session's events ("*" for all sessions)
+    pub fn subscribe(&self, client_id: &str, session_id: &str) -> bool {
+        match self.clients.get_mut(client_id) {
+            Some(mut entry) => {
+                entry.sessions.insert(session_id.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Unsubscribe a client from a session's events
+    pub fn unsubscribe(&self, client_id: &str, session_id: &str) -> bool {
+        match self.clients.get_mut(client_id) {
+            Some(mut entry) => entry.sessions.remove(session_id),
+            None => false,
+        }
+    }
+
+    /// Current subscriptions of a client
+    pub fn subscriptions(&self, client_id: &str) -> Vec<String> {
+        self.clients
+            .get(client_id)
+            .map(|entry| entry.sessions.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Push an event to every client subscribed to its session
+    pub async fn broadcast(&self, event: AgenticEvent) {
+        let session_id = event.session_id().map(str::to_string);
+
+        // Collect adapters first to avoid holding DashMap guards across await points
+        let targets: Vec<(String, WebSocketTransportAdapter)> = self
+            .clients
+            .iter()
+            .filter(|entry| entry.value().is_subscribed(session_id.as_deref()))
+            .map(|entry| (entry.key().clone(), entry.value().adapter.clone()))
+            .collect();
+
+        for (client_id, adapter) in targets {
+            if let Err(e) = adapter
+                .emit_event(session_id.as_deref().unwrap_or(""), event.clone())
+                .await
+            {
+                tracing::warn!(
+                    "Failed to push event to WebSocket client: client_id={}, error={}",
+                    client_id,
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventEmitter for EventHub {
+    /// Push a backend notification to every connected client
+    async fn emit(&self, event_name: &str, payload: serde_json::Value) -> anyhow::Result<()> {
+        let targets: Vec<(String, WebSocketTransportAdapter)> = self
+            .clients
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().adapter.clone()))
+            .collect();
+
+        for (client_id, adapter) in targets {
+            if let Err(e) = adapter.emit_generic(event_name, payload.clone()).await {
+                tracing::warn!(
+                    "Failed to push event to WebSocket client: client_id={}, event={}, error={}",
+                    client_id,
+                    event_name,
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -3,23 +3,36 @@
 /// Web server with support for:
 /// - RESTful API
 /// - WebSocket real-time communication
+/// - Agentic sessions and dialog turns (REST and WebSocket)
 /// - Static file serving (frontend)
 
 use axum::{
-    routing::get,
+    middleware,
+    routing::{delete, get, post},
     Router,
     Json,
 };
 use serde::Serialize;
 use std::net::SocketAddr;
-use tower_http::cors::CorsLayer;
-use anyhow::Result;
+use std::sync::Arc;
+use anyhow::{Context, Result};
 
+use bitfun_core::agentic::coordination::ConversationCoordinator;
+
+mod agentic_system;
+mod auth;
+mod event_hub;
 mod routes;
 
+use auth::ServerAuth;
+use event_hub::EventHub;
+
 /// Application state
 #[derive(Clone)]
-pub struct AppState {}
+pub struct AppState {
+    pub coordinator: Arc<ConversationCoordinator>,
+    pub event_hub: Arc<EventHub>,
+}
 
 /// Health check response
 #[derive(Serialize)]
@@ -46,20 +59,94 @@ async fn main() -> Result<()> {
 
     tracing::info!("BitFun Server v{}", env!("CARGO_PKG_VERSION"));
 
-    let app_state = AppState {};
+    // Workspace defaults to the current directory
+    let workspace_path = match std::env::var_os("BITFUN_WORKSPACE") {
+        Some(path) => Some(std::path::PathBuf::from(path)),
+        None => std::env::current_dir().ok(),
+    };
+    bitfun_core::infrastructure::set_workspace_path(workspace_path.clone());
+    tracing::info!("Workspace path set: {:?}", workspace_path);
+
+    bitfun_core::service::config::initialize_global_config()
+        .await
+        .context("Failed to initialize global config service")?;
+    tracing::info!("Global config service initialized");
+
+    bitfun_core::infrastructure::ai::AIClientFactory::initialize_global()
+        .await
+        .context("Failed to initialize global AIClientFactory")?;
+    tracing::info!("Global AI client factory initialized");
+
+    let agentic_system = agentic_system::init_agentic_system()
+        .await
+        .context("Failed to initialize agentic system")?;
+
+    let event_hub = Arc::new(EventHub::new());
+    bitfun_core::infrastructure::events::get_global_event_system()
+        .set_emitter(event_hub.clone())
+        .await;
+    agentic_system::start_event_loop(
+        agentic_system.event_queue.clone(),
+        agentic_system.event_router.clone(),
+        event_hub.clone(),
+    );
+
+    let auth = ServerAuth::from_env();
+    let app_state = AppState {
+        coordinator: agentic_system.coordinator,
+        event_hub,
+    };
+
+    // Everything except the health checks requires the access token
+    let protected = Router::new()
+        .route("/api/v1/info", get(routes::api::api_info))
+        .route(
+            "/api/v1/sessions",
+            get(routes::agentic::list_sessions_handler).post(routes::agentic::create_session_handler),
+        )
+        .route("/api/v1/sessions/:session_id", delete(routes::agentic::delete_session_handler))
+        .route(
+            "/api/v1/sessions/:session_id/restore",
+            post(routes::agentic::restore_session_handler),
+        )
+        .route(
+            "/api/v1/sessions/:session_id/messages",
+            get(routes::agentic::get_session_messages_handler),
+        )
+        .route(
+            "/api/v1/sessions/:session_id/turns",
+            post(routes::agentic::start_dialog_turn_handler),
+        )
+        .route(
+            "/api/v1/sessions/:session_id/turns/:turn_id/cancel",
+            post(routes::agentic::cancel_dialog_turn_handler),
+        )
+        .route("/api/v1/tools/:tool_id/confirm", post(routes::agentic::confirm_tool_handler))
+        .route("/api/v1/tools/:tool_id/reject", post(routes::agentic::reject_tool_handler))
+        .route("/ws", get(routes::websocket::websocket_handler))
+        .route_layer(middleware::from_fn_with_state(auth.clone(), auth::require_auth));
 
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/api/v1/health", get(health_check))
-        .route("/api/v1/info", get(routes::api::api_info))
-        .route("/ws", get(routes::websocket::websocket_handler))
-        .layer(CorsLayer::permissive())
+        .merge(protected)
+        .layer(auth.cors_layer())
         .with_state(app_state);
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
     tracing::info!("Server started: http://{}", addr);
     tracing::info!("WebSocket endpoint: ws://{}/ws", addr);
     tracing::info!("Health check: http://{}/health", addr);
+    if auth.generated {
+        tracing::info!(
+            "Access token (set BITFUN_SERVER_TOKEN to fix it): {}",
+            auth.token()
+        );
+        tracing::info!(
+            "Send it as 'Authorization: Bearer <token>', or connect to ws://{}/ws?token=<token>",
+            addr
+        );
+    }
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
     axum::serve(listener, app).await?;
@@ -0,0 +1,437 @@
+/// Agentic API routes
+///
+/// Exposes the conversation coordinator (sessions, dialog turns, tool confirmation).
+/// Each operation is shared by the REST endpoints and the WebSocket commands.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use bitfun_core::agentic::core::{Message, MessageContent, MessageRole, Session, SessionConfig};
+use bitfun_core::util::errors::BitFunError;
+
+use crate::AppState;
+
+/// API error, converted to an HTTP status code and a JSON body
+#[derive(Debug)]
+pub struct ApiError(pub BitFunError);
+
+impl From<BitFunError> for ApiError {
+    fn from(error: BitFunError) -> Self {
+        Self(error)
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self.0 {
+            BitFunError::NotFound(_) => StatusCode::NOT_FOUND,
+            BitFunError::Validation(_) | BitFunError::Deserialization(_) => StatusCode::BAD_REQUEST,
+            BitFunError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, Json(serde_json::json!({ "error": self.0.to_string() }))).into_response()
+    }
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSessionRequest {
+    pub session_id: Option<String>,
+    pub session_name: Option<String>,
+    pub agent_type: Option<String>,
+    pub config: Option<SessionConfigDTO>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionConfigDTO {
+    pub max_context_tokens: Option<usize>,
+    pub auto_compact: Option<bool>,
+    pub enable_tools: Option<bool>,
+    pub safe_mode: Option<bool>,
+    pub max_turns: Option<usize>,
+    pub enable_context_compression: Option<bool>,
+    pub compression_threshold: Option<f32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResponse {
+    pub session_id: String,
+    pub session_name: String,
+    pub agent_type: String,
+    pub state: String,
+    pub turn_count: usize,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionIdRequest {
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartDialogTurnRequest {
+    pub session_id: String,
+    pub user_input: String,
+    pub agent_type: Option<String>,
+    pub turn_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartDialogTurnBody {
+    pub user_input: String,
+    pub agent_type: Option<String>,
+    pub turn_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartDialogTurnResponse {
+    pub session_id: String,
+    pub turn_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelDialogTurnRequest {
+    pub session_id: String,
+    pub dialog_turn_id: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmToolRequest {
+    #[serde(default)]
+    pub tool_id: String,
+    pub updated_input: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectToolRequest {
+    #[serde(default)]
+    pub tool_id: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageDTO {
+    pub id: String,
+    pub role: String,
+    pub content: serde_json::Value,
+    pub timestamp: u64,
+}
+
+// ============ Operations (shared by REST and WebSocket) ============
+
+pub async fn create_session(
+    state: &AppState,
+    request: CreateSessionRequest,
+) -> ApiResult<SessionResponse> {
+    let defaults = SessionConfig::default();
+    let config = request
+        .config
+        .map(|c| SessionConfig {
+            max_context_tokens: c.max_context_tokens.unwrap_or(defaults.max_context_tokens),
+            auto_compact: c.auto_compact.unwrap_or(defaults.auto_compact),
+            enable_tools: c.enable_tools.unwrap_or(defaults.enable_tools),
+            safe_mode: c.safe_mode.unwrap_or(defaults.safe_mode),
+            max_turns: c.max_turns.unwrap_or(defaults.max_turns),
+            enable_context_compression: c
+                .enable_context_compression
+                .unwrap_or(defaults.enable_context_compression),
+            compression_threshold: c
+                .compression_threshold
+                .unwrap_or(defaults.compression_threshold),
+        })
+        .unwrap_or_default();
+
+    let session_name = request.session_name.unwrap_or_else(|| {
+        format!("Server Session - {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"))
+    });
+    let agent_type = request.agent_type.unwrap_or_else(|| "agentic".to_string());
+
+    let session = state
+        .coordinator
+        .create_session_with_id(request.session_id, session_name, agent_type, config)
+        .await?;
+
+    Ok(session_to_response(session))
+}
+
+pub async fn list_sessions(state: &AppState) -> ApiResult<Vec<SessionResponse>> {
+    let summaries = state.coordinator.list_sessions().await?;
+
+    Ok(summaries
+        .into_iter()
+        .map(|summary| SessionResponse {
+            session_id: summary.session_id,
+            session_name: summary.session_name,
+            agent_type: summary.agent_type,
+            state: format!("{:?}", summary.state),
+            turn_count: summary.turn_count,
+            created_at: system_time_to_unix_secs(summary.created_at),
+        })
+        .collect())
+}
+
+pub async fn restore_session(state: &AppState, session_id: &str) -> ApiResult<SessionResponse> {
+    let session = state.coordinator.restore_session(session_id).await?;
+    Ok(session_to_response(session))
+}
+
+pub async fn delete_session(state: &AppState, session_id: &str) -> ApiResult<()> {
+    state.coordinator.delete_session(session_id).await?;
+    Ok(())
+}
+
+pub async fn get_session_messages(state: &AppState, session_id: &str) -> ApiResult<Vec<MessageDTO>> {
+    let messages = state.coordinator.get_messages(session_id).await?;
+    Ok(messages.into_iter().map(message_to_dto).collect())
+}
+
+pub async fn start_dialog_turn(
+    state: &AppState,
+    request: StartDialogTurnRequest,
+) -> ApiResult<StartDialogTurnResponse> {
+    let agent_type = match request.agent_type {
+        Some(agent_type) => agent_type,
+        None => state
+            .coordinator
+            .get_session_manager()
+            .get_session(&request.session_id)
+            .map(|session| session.agent_type)
+            .ok_or_else(|| {
+                BitFunError::NotFound(format!("Session not found: {}", request.session_id))
+            })?,
+    };
+    // Generate the turn ID here so the client can correlate events and cancel the turn
+    let turn_id = request
+        .turn_id
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    state
+        .coordinator
+        .start_dialog_turn(
+            request.session_id.clone(),
+            request.user_input,
+            Some(turn_id.clone()),
+            agent_type,
+        )
+        .await?;
+
+    Ok(StartDialogTurnResponse {
+        session_id: request.session_id,
+        turn_id,
+    })
+}
+
+pub async fn cancel_dialog_turn(state: &AppState, request: CancelDialogTurnRequest) -> ApiResult<()> {
+    state
+        .coordinator
+        .cancel_dialog_turn(&request.session_id, &request.dialog_turn_id)
+        .await?;
+    Ok(())
+}
+
+pub async fn confirm_tool(state: &AppState, request: ConfirmToolRequest) -> ApiResult<()> {
+    state
+        .coordinator
+        .confirm_tool(&request.tool_id, request.updated_input)
+        .await?;
+    Ok(())
+}
+
+pub async fn reject_tool(state: &AppState, request: RejectToolRequest) -> ApiResult<()> {
+    let reason = request
+        .reason
+        .unwrap_or_else(|| "User rejected".to_string());
+    state.coordinator.reject_tool(&request.tool_id, reason).await?;
+    Ok(())
+}
+
+// ============ REST handlers ============
+
+/// POST /api/v1/sessions
+pub async fn create_session_handler(
+    State(state): State<AppState>,
+    Json(request): Json<CreateSessionRequest>,
+) -> ApiResult<(StatusCode, Json<SessionResponse>)> {
+    let session = create_session(&state, request).await?;
+    Ok((StatusCode::CREATED, Json(session)))
+}
+
+/// GET /api/v1/sessions
+pub async fn list_sessions_handler(
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<SessionResponse>>> {
+    Ok(Json(list_sessions(&state).await?))
+}
+
+/// POST /api/v1/sessions/:session_id/restore
+pub async fn restore_session_handler(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> ApiResult<Json<SessionResponse>> {
+    Ok(Json(restore_session(&state, &session_id).await?))
+}
+
+/// DELETE /api/v1/sessions/:session_id
+pub async fn delete_session_handler(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> ApiResult<StatusCode> {
+    delete_session(&state, &session_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/sessions/:session_id/messages
+pub async fn get_session_messages_handler(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> ApiResult<Json<Vec<MessageDTO>>> {
+    Ok(Json(get_session_messages(&state, &session_id).await?))
+}
+
+/// POST /api/v1/sessions/:session_id/turns
+pub async fn start_dialog_turn_handler(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Json(body): Json<StartDialogTurnBody>,
+) -> ApiResult<(StatusCode, Json<StartDialogTurnResponse>)> {
+    let response = start_dialog_turn(
+        &state,
+        StartDialogTurnRequest {
+            session_id,
+            user_input: body.user_input,
+            agent_type: body.agent_type,
+            turn_id: body.turn_id,
+        },
+    )
+    .await?;
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+/// POST /api/v1/sessions/:session_id/turns/:turn_id/cancel
+pub async fn cancel_dialog_turn_handler(
+    State(state): State<AppState>,
+    Path((session_id, dialog_turn_id)): Path<(String, String)>,
+) -> ApiResult<StatusCode> {
+    cancel_dialog_turn(
+        &state,
+        CancelDialogTurnRequest {
+            session_id,
+            dialog_turn_id,
+        },
+    )
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/tools/:tool_id/confirm
+pub async fn confirm_tool_handler(
+    State(state): State<AppState>,
+    Path(tool_id): Path<String>,
+    body: Option<Json<ConfirmToolRequest>>,
+) -> ApiResult<StatusCode> {
+    let mut request = body.map(|Json(b)| b).unwrap_or_default();
+    request.tool_id = tool_id;
+    confirm_tool(&state, request).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/tools/:tool_id/reject
+pub async fn reject_tool_handler(
+    State(state): State<AppState>,
+    Path(tool_id): Path<String>,
+    body: Option<Json<RejectToolRequest>>,
+) -> ApiResult<StatusCode> {
+    let mut request = body.map(|Json(b)| b).unwrap_or_default();
+    request.tool_id = tool_id;
+    reject_tool(&state, request).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============ Conversions ============
+
+fn session_to_response(session: Session) -> SessionResponse {
+    SessionResponse {
+        session_id: session.session_id,
+        session_name: session.session_name,
+        agent_type: session.agent_type,
+        state: format!("{:?}", session.state),
+        turn_count: session.dialog_turn_ids.len(),
+        created_at: system_time_to_unix_secs(session.created_at),
+    }
+}
+
+fn message_to_dto(message: Message) -> MessageDTO {
+    let role = match message.role {
+        MessageRole::User => "user",
+        MessageRole::Assistant => "assistant",
+        MessageRole::Tool => "tool",
+        MessageRole::System => "system",
+    };
+
+    let content = match message.content {
+        MessageContent::Text(text) => serde_json::json!({ "type": "text", "text": text }),
+        MessageContent::ToolResult {
+            tool_id,
+            tool_name,
+            result,
+            result_for_assistant,
+            is_error,
+        } => serde_json::json!({
+            "type": "tool_result",
+            "tool_id": tool_id,
+            "tool_name": tool_name,
+            "result": result,
+            "result_for_assistant": result_for_assistant,
+            "is_error": is_error,
+        }),
+        MessageContent::Mixed {
+            reasoning_content,
+            text,
+            tool_calls,
+        } => serde_json::json!({
+            "type": "mixed",
+            "reasoning_content": reasoning_content,
+            "text": text,
+            "tool_calls": tool_calls,
+        }),
+    };
+
+    MessageDTO {
+        id: message.id,
+        role: role.to_string(),
+        content,
+        timestamp: system_time_to_unix_secs(message.timestamp),
+    }
+}
+
+fn system_time_to_unix_secs(time: std::time::SystemTime) -> u64 {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
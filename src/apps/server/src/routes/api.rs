@@ -37,6 +37,51 @@ pub async fn api_info(State(_state): State<AppState>) -> Json<ApiInfo> {
                 method: "GET".to_string(),
                 description: "API info".to_string(),
             },
+            EndpointInfo {
+                path: "/api/v1/sessions".to_string(),
+                method: "GET".to_string(),
+                description: "List sessions".to_string(),
+            },
+            EndpointInfo {
+                path: "/api/v1/sessions".to_string(),
+                method: "POST".to_string(),
+                description: "Create session".to_string(),
+            },
+            EndpointInfo {
+                path: "/api/v1/sessions/:session_id".to_string(),
+                method: "DELETE".to_string(),
+                description: "Delete session".to_string(),
+            },
+            EndpointInfo {
+                path: "/api/v1/sessions/:session_id/restore".to_string(),
+                method: "POST".to_string(),
+                description: "Restore persisted session".to_string(),
+            },
+            EndpointInfo {
+                path: "/api/v1/sessions/:session_id/messages".to_string(),
+                method: "GET".to_string(),
+                description: "Get session messages".to_string(),
+            },
+            EndpointInfo {
+                path: "/api/v1/sessions/:session_id/turns".to_string(),
+                method: "POST".to_string(),
+                description: "Start dialog turn".to_string(),
+            },
+            EndpointInfo {
+                path: "/api/v1/sessions/:session_id/turns/:turn_id/cancel".to_string(),
+                method: "POST".to_string(),
+                description: "Cancel dialog turn".to_string(),
+            },
+            EndpointInfo {
+                path: "/api/v1/tools/:tool_id/confirm".to_string(),
+                method: "POST".to_string(),
+                description: "Confirm pending tool call".to_string(),
+            },
+            EndpointInfo {
+                path: "/api/v1/tools/:tool_id/reject".to_string(),
+                method: "POST".to_string(),
+                description: "Reject pending tool call".to_string(),
+            },
             EndpointInfo {
                 path: "/ws".to_string(),
                 method: "WebSocket".to_string(),
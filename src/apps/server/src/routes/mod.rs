@@ -4,3 +4,4 @@
 
 pub mod websocket;
 pub mod api;
+pub mod agentic;
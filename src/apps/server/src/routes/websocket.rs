@@ -2,7 +2,7 @@
 ///
 /// Implements real-time bidirectional communication with frontend:
 /// - Command request/response (JSON RPC format)
-/// - Event push (streaming output, tool calls, etc.)
+/// - Event push (streaming output, tool calls, etc.) for subscribed sessions
 
 use axum::{
     extract::{
@@ -12,9 +12,15 @@ use axum::{
     response::Response,
 };
 use futures_util::{SinkExt, StreamExt};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use anyhow::Result;
+use tokio::sync::mpsc;
 
+use bitfun_transport::adapters::WsMessage as OutgoingMessage;
+use bitfun_transport::WebSocketTransportAdapter;
+
+use crate::event_hub::ALL_SESSIONS;
+use crate::routes::agentic;
 use crate::AppState;
 
 /// WebSocket message protocol (JSON RPC 2.0 style)
@@ -54,6 +60,8 @@ pub struct ErrorInfo {
 }
 
 /// WebSocket connection handler
+///
+/// The token and origin of the upgrade request are checked by `auth::require_auth`.
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
@@ -65,36 +73,54 @@ pub async fn websocket_handler(
 /// Handle a single WebSocket connection
 async fn handle_socket(socket: WebSocket, state: AppState) {
     let (mut sender, mut receiver) = socket.split();
+    let client_id = uuid::Uuid::new_v4().to_string();
+
+    // Responses and pushed events share one outgoing channel
+    let (tx, mut rx) = mpsc::unbounded_channel::<OutgoingMessage>();
+    state
+        .event_hub
+        .register_client(&client_id, WebSocketTransportAdapter::new(tx.clone()));
 
-    tracing::info!("WebSocket connection established");
+    tracing::info!("WebSocket connection established: client_id={}", client_id);
+
+    let writer = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            let message = match message {
+                OutgoingMessage::Text(text) => Message::Text(text),
+                OutgoingMessage::Binary(data) => Message::Binary(data),
+                OutgoingMessage::Close => Message::Close(None),
+            };
+            if sender.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
 
     let welcome_msg = WsMessage::Event {
         event: "connection_established".to_string(),
         payload: serde_json::json!({
             "server": "BitFun Server",
             "version": env!("CARGO_PKG_VERSION"),
+            "clientId": client_id,
             "timestamp": chrono::Utc::now().timestamp(),
         }),
     };
-
-    if let Ok(json) = serde_json::to_string(&welcome_msg) {
-        let _ = sender.send(Message::Text(json)).await;
-    }
+    send_message(&tx, &welcome_msg);
 
     while let Some(msg) = receiver.next().await {
         match msg {
             Ok(Message::Text(text)) => {
                 tracing::debug!("Received text message: {}", text);
-                if let Err(e) = handle_text_message(&mut sender, &text, &state).await {
+                if let Err(e) = handle_text_message(&tx, &client_id, &text, &state).await {
                     tracing::error!("Failed to handle message: {:?}", e);
                 }
             }
             Ok(Message::Binary(data)) => {
                 tracing::debug!("Received binary message: {} bytes", data.len());
             }
-            Ok(Message::Ping(data)) => {
+            Ok(Message::Ping(_)) => {
+                // Pong replies are sent automatically by axum
                 tracing::trace!("Received Ping");
-                let _ = sender.send(Message::Pong(data)).await;
             }
             Ok(Message::Pong(_)) => {
                 tracing::trace!("Received Pong");
@@ -110,12 +136,26 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
         }
     }
 
-    tracing::info!("WebSocket connection closed");
+    state.event_hub.unregister_client(&client_id);
+    drop(tx);
+    writer.abort();
+
+    tracing::info!("WebSocket connection closed: client_id={}", client_id);
+}
+
+fn send_message(tx: &mpsc::UnboundedSender<OutgoingMessage>, message: &WsMessage) {
+    match serde_json::to_string(message) {
+        Ok(json) => {
+            let _ = tx.send(OutgoingMessage::Text(json));
+        }
+        Err(e) => tracing::error!("Failed to serialize WebSocket message: {}", e),
+    }
 }
 
 /// Handle text message
 async fn handle_text_message(
-    sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+    tx: &mpsc::UnboundedSender<OutgoingMessage>,
+    client_id: &str,
     text: &str,
     state: &AppState,
 ) -> Result<()> {
@@ -125,7 +165,7 @@ async fn handle_text_message(
         WsMessage::Request { id, method, params } => {
             tracing::info!("Handling request: method={}, id={}", method, id);
 
-            let result = handle_command(&method, params, state).await;
+            let result = handle_command(&method, params, client_id, state).await;
 
             let response = match result {
                 Ok(data) => WsMessage::Response {
@@ -144,8 +184,7 @@ async fn handle_text_message(
                 },
             };
 
-            let json = serde_json::to_string(&response)?;
-            sender.send(Message::Text(json)).await?;
+            send_message(tx, &response);
         }
         WsMessage::Event { event, .. } => {
             tracing::debug!("Received event: {}", event);
@@ -158,11 +197,47 @@ async fn handle_text_message(
     Ok(())
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscribeParams {
+    /// Session ID, or "*" for all sessions
+    #[serde(default = "all_sessions")]
+    session_id: String,
+}
+
+/// Same shape as the desktop `approve_project_hooks` command
+#[derive(Debug, Deserialize)]
+struct ApproveProjectHooksParams {
+    request: ApproveProjectHooksRequest,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApproveProjectHooksRequest {
+    workspace_path: String,
+    hash: String,
+}
+
+fn all_sessions() -> String {
+    ALL_SESSIONS.to_string()
+}
+
+fn parse_params<T: DeserializeOwned>(params: serde_json::Value) -> Result<T> {
+    // Requests without parameters may send `null`
+    let params = if params.is_null() {
+        serde_json::json!({})
+    } else {
+        params
+    };
+    serde_json::from_value(params).map_err(|e| anyhow::anyhow!("Invalid params: {}", e))
+}
+
 /// Handle specific commands
 async fn handle_command(
     method: &str,
-    _params: serde_json::Value,
-    _state: &AppState,
+    params: serde_json::Value,
+    client_id: &str,
+    state: &AppState,
 ) -> Result<serde_json::Value> {
     match method {
         "ping" => {
@@ -171,6 +246,67 @@ async fn handle_command(
                 "timestamp": chrono::Utc::now().timestamp(),
             }))
         }
+        "subscribe" => {
+            let params: SubscribeParams = parse_params(params)?;
+            state.event_hub.subscribe(client_id, &params.session_id);
+            Ok(serde_json::json!({ "subscriptions": state.event_hub.subscriptions(client_id) }))
+        }
+        "unsubscribe" => {
+            let params: SubscribeParams = parse_params(params)?;
+            state.event_hub.unsubscribe(client_id, &params.session_id);
+            Ok(serde_json::json!({ "subscriptions": state.event_hub.subscriptions(client_id) }))
+        }
+        "create_session" => {
+            let session = agentic::create_session(state, parse_params(params)?).await?;
+            // Creating a session over this connection implies interest in its events
+            state.event_hub.subscribe(client_id, &session.session_id);
+            Ok(serde_json::to_value(session)?)
+        }
+        "list_sessions" => Ok(serde_json::to_value(agentic::list_sessions(state).await?)?),
+        "restore_session" => {
+            let params: agentic::SessionIdRequest = parse_params(params)?;
+            let session = agentic::restore_session(state, &params.session_id).await?;
+            state.event_hub.subscribe(client_id, &session.session_id);
+            Ok(serde_json::to_value(session)?)
+        }
+        "delete_session" => {
+            let params: agentic::SessionIdRequest = parse_params(params)?;
+            agentic::delete_session(state, &params.session_id).await?;
+            state.event_hub.unsubscribe(client_id, &params.session_id);
+            Ok(serde_json::json!({ "success": true }))
+        }
+        "get_session_messages" => {
+            let params: agentic::SessionIdRequest = parse_params(params)?;
+            Ok(serde_json::to_value(
+                agentic::get_session_messages(state, &params.session_id).await?,
+            )?)
+        }
+        "start_dialog_turn" => {
+            let request: agentic::StartDialogTurnRequest = parse_params(params)?;
+            state.event_hub.subscribe(client_id, &request.session_id);
+            Ok(serde_json::to_value(agentic::start_dialog_turn(state, request).await?)?)
+        }
+        "cancel_dialog_turn" => {
+            agentic::cancel_dialog_turn(state, parse_params(params)?).await?;
+            Ok(serde_json::json!({ "success": true }))
+        }
+        "confirm_tool" => {
+            agentic::confirm_tool(state, parse_params(params)?).await?;
+            Ok(serde_json::json!({ "success": true }))
+        }
+        "reject_tool" => {
+            agentic::reject_tool(state, parse_params(params)?).await?;
+            Ok(serde_json::json!({ "success": true }))
+        }
+        "approve_project_hooks" => {
+            let params: ApproveProjectHooksParams = parse_params(params)?;
+            bitfun_core::agentic::hooks::approve_project_hooks(
+                std::path::Path::new(&params.request.workspace_path),
+                &params.request.hash,
+            )
+            .await?;
+            Ok(serde_json::json!({ "success": true }))
+        }
         _ => {
             tracing::warn!("Unknown command: {}", method);
             Err(anyhow::anyhow!("Unknown command: {}", method))
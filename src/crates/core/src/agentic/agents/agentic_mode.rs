@@ -14,15 +14,21 @@ impl AgenticMode {
                 "Read".to_string(),
                 "Write".to_string(),
                 "Edit".to_string(),
+                "MultiEdit".to_string(),
                 "Delete".to_string(),
                 "Bash".to_string(),
+                "BashOutput".to_string(),
+                "BashJobs".to_string(),
                 "Grep".to_string(),
                 "Glob".to_string(),
                 "WebSearch".to_string(),
                 "TodoWrite".to_string(),
+                "UpdatePlan".to_string(),
                 "IdeControl".to_string(),
                 "MermaidInteractive".to_string(),
                 "ReadLints".to_string(),
+                "LSP".to_string(),
+                "Memory".to_string(),
                 "AnalyzeImage".to_string(),
                 "Skill".to_string(),
                 "AskUserQuestion".to_string(),
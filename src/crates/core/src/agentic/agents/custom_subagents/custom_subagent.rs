@@ -1,5 +1,5 @@
-use crate::agentic::agents::Agent;
 use crate::agentic::agents::PromptBuilder;
+use crate::agentic::agents::{Agent, SubagentIsolation};
 use crate::util::errors::{BitFunError, BitFunResult};
 use crate::util::FrontMatterMarkdown;
 use async_trait::async_trait;
@@ -26,6 +26,8 @@ pub struct CustomSubagent {
     pub enabled: bool,
     /// Model ID to use, default "primary"
     pub model: String,
+    /// Where the subagent runs, default in the parent's workspace
+    pub isolation: SubagentIsolation,
 }
 
 #[async_trait]
@@ -50,8 +52,15 @@ impl Agent for CustomSubagent {
         ""
     }
 
-    async fn build_prompt(&self, workspace_path: &str) -> BitFunResult<String> {
-        let prompt_builder = PromptBuilder::new(workspace_path);
+    async fn build_prompt(
+        &self,
+        workspace_path: &str,
+        memory_query: Option<&str>,
+        active_plan: Option<&str>,
+    ) -> BitFunResult<String> {
+        let prompt_builder = PromptBuilder::new(workspace_path)
+            .with_memory_query(memory_query)
+            .with_active_plan(active_plan);
 
         let prompt = prompt_builder
             .build_prompt_from_template(&self.prompt)
@@ -67,6 +76,10 @@ impl Agent for CustomSubagent {
     fn is_readonly(&self) -> bool {
         self.readonly
     }
+
+    fn isolation(&self) -> SubagentIsolation {
+        self.isolation
+    }
 }
 
 impl CustomSubagent {
@@ -89,6 +102,7 @@ impl CustomSubagent {
             kind,
             enabled: true,
             model: "primary".to_string(),
+            isolation: SubagentIsolation::None,
         }
     }
 
@@ -126,6 +140,16 @@ impl CustomSubagent {
             .unwrap_or(Self::DEFAULT_MODEL)
             .to_string();
 
+        let isolation = match metadata.get("isolation").and_then(|v| v.as_str()) {
+            Some(value) => SubagentIsolation::parse(value).ok_or_else(|| {
+                BitFunError::Agent(format!(
+                    "Invalid isolation '{}', expected 'none' or 'worktree'",
+                    value
+                ))
+            })?,
+            None => SubagentIsolation::None,
+        };
+
         Ok(Self {
             name,
             description,
@@ -136,6 +160,7 @@ impl CustomSubagent {
             kind,
             enabled,
             model,
+            isolation,
         })
     }
 
@@ -195,6 +220,12 @@ impl CustomSubagent {
                 Value::String(model.to_string()),
             );
         }
+        if self.isolation != SubagentIsolation::None {
+            metadata.insert(
+                Value::String("isolation".into()),
+                Value::String(self.isolation.as_str().into()),
+            );
+        }
         let metadata = Value::Mapping(metadata);
         FrontMatterMarkdown::save(&self.path, &metadata, &self.prompt)
             .map_err(|e| BitFunError::Agent(e))
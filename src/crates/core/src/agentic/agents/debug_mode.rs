@@ -281,8 +281,15 @@ impl Agent for DebugMode {
         "debug_mode"
     }
 
-    async fn build_prompt(&self, workspace_path: &str) -> BitFunResult<String> {
-        let prompt_components = PromptBuilder::new(workspace_path);
+    async fn build_prompt(
+        &self,
+        workspace_path: &str,
+        memory_query: Option<&str>,
+        active_plan: Option<&str>,
+    ) -> BitFunResult<String> {
+        let prompt_components = PromptBuilder::new(workspace_path)
+            .with_memory_query(memory_query)
+            .with_active_plan(active_plan);
         let env_info = prompt_components.get_env_info();
 
         let debug_config = self.get_debug_config().await;
@@ -354,8 +361,11 @@ Below is a snapshot of the current workspace's file structure.
             "Read".to_string(),
             "Write".to_string(),
             "Edit".to_string(),
+            "MultiEdit".to_string(),
             "Delete".to_string(),
             "Bash".to_string(),
+            "BashOutput".to_string(),
+            "BashJobs".to_string(),
             "Grep".to_string(),
             "Glob".to_string(),
             "WebSearch".to_string(),
@@ -364,6 +374,8 @@ Below is a snapshot of the current workspace's file structure.
             "MermaidInteractive".to_string(),
             "Log".to_string(),
             "ReadLints".to_string(),
+            "LSP".to_string(),
+            "Memory".to_string(),
         ]
     }
 
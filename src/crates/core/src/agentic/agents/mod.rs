@@ -60,8 +60,18 @@ pub trait Agent: Send + Sync + 'static {
     }
 
     /// Build the system prompt for this agent
-    async fn build_prompt(&self, workspace_path: &str) -> BitFunResult<String> {
-        let prompt_components = PromptBuilder::new(workspace_path);
+    ///
+    /// `memory_query` selects the memory points that are included and `active_plan` is the
+    /// plan file the session is executing (see `PromptBuilder`).
+    async fn build_prompt(
+        &self,
+        workspace_path: &str,
+        memory_query: Option<&str>,
+        active_plan: Option<&str>,
+    ) -> BitFunResult<String> {
+        let prompt_components = PromptBuilder::new(workspace_path)
+            .with_memory_query(memory_query)
+            .with_active_plan(active_plan);
 
         let system_prompt_template =
             get_embedded_prompt(self.prompt_template_name()).ok_or_else(|| {
@@ -79,9 +89,15 @@ pub trait Agent: Send + Sync + 'static {
     }
 
     /// Get the system prompt for this agent
-    async fn get_system_prompt(&self, workspace_path: Option<&str>) -> BitFunResult<String> {
+    async fn get_system_prompt(
+        &self,
+        workspace_path: Option<&str>,
+        memory_query: Option<&str>,
+        active_plan: Option<&str>,
+    ) -> BitFunResult<String> {
         if let Some(workspace_path) = workspace_path {
-            self.build_prompt(workspace_path).await
+            self.build_prompt(workspace_path, memory_query, active_plan)
+                .await
         } else {
             Err(BitFunError::Agent("Workspace path is required".to_string()))
         }
@@ -113,4 +129,36 @@ pub trait Agent: Send + Sync + 'static {
     fn is_readonly(&self) -> bool {
         false
     }
+
+    /// Where this agent runs when launched as a subagent
+    fn isolation(&self) -> SubagentIsolation {
+        SubagentIsolation::None
+    }
+}
+
+/// Where a subagent runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubagentIsolation {
+    /// In the parent's workspace
+    #[default]
+    None,
+    /// In a temporary git worktree of the workspace, so it can write in parallel with others
+    Worktree,
+}
+
+impl SubagentIsolation {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(Self::None),
+            "worktree" => Some(Self::Worktree),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Worktree => "worktree",
+        }
+    }
 }
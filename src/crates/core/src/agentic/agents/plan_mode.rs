@@ -19,6 +19,7 @@ impl PlanMode {
                 "Glob".to_string(),
                 "AskUserQuestion".to_string(),
                 "CreatePlan".to_string(),
+                "UpdatePlan".to_string(),
             ],
         }
     }
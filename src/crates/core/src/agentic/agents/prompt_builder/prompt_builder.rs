@@ -1,9 +1,10 @@
 //! System prompts module providing main dialogue and agent dialogue prompts
 use crate::agentic::util::get_formatted_files_list;
 use crate::infrastructure::try_get_path_manager_arc;
-use crate::service::ai_memory::AIMemoryManager;
+use crate::service::ai_memory::{format_memories_prompt, recall_memories, AIMemoryManager};
 use crate::service::ai_rules::get_global_ai_rules_service;
 use crate::service::config::global::GlobalConfigManager;
+use crate::service::plan::load_current_plan;
 use crate::service::project_context::ProjectContextService;
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, warn};
@@ -16,12 +17,17 @@ const PLACEHOLDER_PROJECT_LAYOUT: &str = "{PROJECT_LAYOUT}";
 // const PLACEHOLDER_PROJECT_CONTEXT_FILES: &str = "{PROJECT_CONTEXT_FILES}";
 const PLACEHOLDER_RULES: &str = "{RULES}";
 const PLACEHOLDER_MEMORIES: &str = "{MEMORIES}";
+const PLACEHOLDER_ACTIVE_PLAN: &str = "{ACTIVE_PLAN}";
 const PLACEHOLDER_LANGUAGE_PREFERENCE: &str = "{LANGUAGE_PREFERENCE}";
 const PLACEHOLDER_VISUAL_MODE: &str = "{VISUAL_MODE}";
 
 pub struct PromptBuilder {
     pub workspace_path: String,
     pub file_tree_max_entries: usize,
+    /// Text that memories are ranked against (usually the current user message)
+    pub memory_query: Option<String>,
+    /// Plan file the session is executing
+    pub active_plan: Option<String>,
 }
 
 impl PromptBuilder {
@@ -29,9 +35,21 @@ impl PromptBuilder {
         Self {
             workspace_path: workspace_path.replace("\\", "/"),
             file_tree_max_entries: 200,
+            memory_query: None,
+            active_plan: None,
         }
     }
 
+    pub fn with_memory_query(mut self, memory_query: Option<&str>) -> Self {
+        self.memory_query = memory_query.map(str::to_string);
+        self
+    }
+
+    pub fn with_active_plan(mut self, active_plan: Option<&str>) -> Self {
+        self.active_plan = active_plan.map(str::to_string);
+        self
+    }
+
     /// Provide complete environment information
     pub fn get_env_info(&self) -> String {
         let os_name = std::env::consts::OS;
@@ -100,6 +118,9 @@ These files are maintained by the user and should NOT be modified unless explici
     }
 
     /// Load AI memories from disk and format as prompt
+    ///
+    /// Memories from the user and project stores are ranked together against
+    /// `memory_query`, and only the most relevant ones are included.
     pub async fn load_ai_memories(&self) -> Option<String> {
         let path_manager = match try_get_path_manager_arc() {
             Ok(pm) => pm,
@@ -109,22 +130,84 @@ These files are maintained by the user and should NOT be modified unless explici
             }
         };
 
-        let memory_manager = match AIMemoryManager::new(path_manager).await {
-            Ok(mm) => mm,
+        let mut managers = Vec::new();
+        match AIMemoryManager::new(path_manager.clone()).await {
+            Ok(mm) => managers.push(mm),
+            Err(e) => warn!("Failed to create AIMemoryManager: {}", e),
+        }
+        let project_storage = Path::new(&self.workspace_path)
+            .join(".bitfun")
+            .join("ai_memories.json");
+        if project_storage.exists() {
+            match AIMemoryManager::new_project(path_manager, &self.workspace_path).await {
+                Ok(mm) => managers.push(mm),
+                Err(e) => warn!("Failed to create project AIMemoryManager: {}", e),
+            }
+        }
+
+        let mut candidates = Vec::new();
+        for manager in &managers {
+            match manager.get_enabled_memories().await {
+                Ok(memories) => {
+                    candidates.extend(memories.into_iter().map(|m| (manager.scope(), m)))
+                }
+                Err(e) => warn!("Failed to load memories: {}", e),
+            }
+        }
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let recalled = recall_memories(candidates, self.memory_query.as_deref()).await;
+        format_memories_prompt(&recalled)
+    }
+
+    /// Load the active plan from disk and format as prompt
+    ///
+    /// The plan is re-read every turn, so it reflects revisions and completed steps and stays
+    /// in context after the conversation is compressed or the session is restored. A plan that
+    /// no longer parses is reported to the model so it can repair the file.
+    pub async fn load_active_plan(&self) -> Option<String> {
+        let plan_path = self.active_plan.as_ref()?;
+        let plan = match load_current_plan(Path::new(plan_path)).await {
+            Ok(plan) => plan,
             Err(e) => {
-                warn!("Failed to create AIMemoryManager: {}", e);
-                return None;
+                warn!(
+                    "Failed to load active plan: path={}, error={}",
+                    plan_path, e
+                );
+                return Some(format!(
+                    "# Active Plan\nActive plan {} could not be parsed: {}\nRepair the plan file's front-matter before continuing with the plan.\n\n",
+                    plan_path, e
+                ));
             }
         };
 
-        match memory_manager.get_memories_for_prompt().await {
-            Ok(Some(prompt)) => Some(prompt),
-            Ok(None) => None,
-            Err(e) => {
-                warn!("Failed to load memories: {}", e);
-                None
+        let mut prompt = format!(
+            r#"# Active Plan
+You are executing the plan in {} (version {}): {}
+- Track progress with the TodoWrite tool, using the plan's step IDs as todo IDs. Step statuses in the plan file are updated from your todos automatically.
+- Work through the steps in order and respect their dependencies.
+- If the plan needs to change, revise it with the UpdatePlan tool and keep existing step IDs for steps that remain. Each revision is saved as a new version of the plan.
+
+<plan_steps>
+"#,
+            plan_path, plan.version, plan.overview
+        );
+        for step in &plan.steps {
+            prompt.push_str(&format!(
+                "- [{}] {}: {}",
+                step.status.as_str(),
+                step.id,
+                step.content
+            ));
+            if !step.dependencies.is_empty() {
+                prompt.push_str(&format!(" (depends on: {})", step.dependencies.join(", ")));
             }
+            prompt.push('\n');
         }
+        prompt.push_str("</plan_steps>\n\n");
+        Some(prompt)
     }
 
     /// Load AI rules from disk and format as prompt
@@ -221,6 +304,7 @@ Prefer MermaidInteractive tool when available, otherwise output Mermaid code blo
     /// - `{PROJECT_CONTEXT_FILES}` - Project context files (AGENTS.md, CLAUDE.md, etc.)
     /// - `{RULES}` - AI rules
     /// - `{MEMORIES}` - AI memories
+    /// - `{ACTIVE_PLAN}` - Plan the session is executing, with step statuses
     /// - `{VISUAL_MODE}` - Visual mode instruction (Mermaid diagrams, read from global config)
     ///
     /// If a placeholder is not in the template, corresponding content will not be added
@@ -292,6 +376,12 @@ Prefer MermaidInteractive tool when available, otherwise output Mermaid code blo
             result = result.replace(PLACEHOLDER_MEMORIES, &memories);
         }
 
+        // Replace {ACTIVE_PLAN}
+        if result.contains(PLACEHOLDER_ACTIVE_PLAN) {
+            let active_plan = self.load_active_plan().await.unwrap_or_default();
+            result = result.replace(PLACEHOLDER_ACTIVE_PLAN, &active_plan);
+        }
+
         // Replace {VISUAL_MODE}
         if result.contains(PLACEHOLDER_VISUAL_MODE) {
             let visual_mode = self.get_visual_mode_instruction().await;
@@ -1,6 +1,6 @@
 use super::{
     Agent, AgenticMode, CodeReviewAgent, DebugMode, ExploreAgent, FileFinderAgent,
-    GenerateDocAgent, PlanMode,
+    GenerateDocAgent, PlanMode, SubagentIsolation,
 };
 use crate::agentic::agents::custom_subagents::{
     CustomSubagent, CustomSubagentKind, CustomSubagentLoader,
@@ -350,6 +350,16 @@ impl AgentRegistry {
         Some(entry.agent.is_readonly())
     }
 
+    /// Default isolation of a subagent; None if `id` is not a subagent
+    pub fn get_subagent_isolation(&self, id: &str) -> Option<SubagentIsolation> {
+        let map = self.read_agents();
+        let entry = map.get(id)?;
+        if entry.category != AgentCategory::SubAgent {
+            return None;
+        }
+        Some(entry.agent.isolation())
+    }
+
     /// get all subagent information (including source and enabled status, used for TaskTool, frontend subagent list etc.)
     /// - built-in subagent: read enabled status from global configuration ai.subagent_configs
     /// - custom subagent: read enabled and model configuration from custom_config cache
@@ -3,16 +3,19 @@
 //! Top-level component that integrates all subsystems and provides a unified interface
 
 use crate::agentic::agents::get_agent_registry;
+use crate::agentic::coordination::{SubagentWorktree, WorktreeOutcome};
 use crate::agentic::core::{
     Message, MessageContent, ProcessingPhase, Session, SessionConfig, SessionState, SessionSummary,
     TurnStats,
 };
 use crate::agentic::events::{
-    AgenticEvent, EventPriority, EventQueue, EventRouter, EventSubscriber,
+    AgenticEvent, EventPriority, EventQueue, EventRouter, EventSubscriber, ToolEventData,
 };
 use crate::agentic::execution::{ExecutionContext, ExecutionEngine};
 use crate::agentic::session::SessionManager;
 use crate::agentic::tools::pipeline::{SubagentParentInfo, ToolPipeline};
+use crate::infrastructure::{get_workspace_path, with_workspace_path};
+use crate::service::plan::load_plan;
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, error, info, warn};
 use std::sync::Arc;
@@ -28,6 +31,8 @@ pub struct SubagentResult {
     pub text: String,
     /// Tool call arguments for ending the conversation
     pub tool_arguments: Option<serde_json::Value>,
+    /// Changes left in the subagent's worktree, if it ran isolated and changed anything
+    pub worktree: Option<WorktreeOutcome>,
 }
 
 /// Cancel token cleanup guard
@@ -283,6 +288,11 @@ impl ConversationCoordinator {
         // Pass turn_index (for operation history/rollback)
         context_vars.insert("turn_index".to_string(), turn_index.to_string());
 
+        // Pass the plan being executed (pinned in the system prompt, synced by TodoWrite)
+        if let Some(active_plan) = &session.active_plan {
+            context_vars.insert("active_plan".to_string(), active_plan.clone());
+        }
+
         let execution_context = ExecutionContext {
             session_id: session_id.clone(),
             dialog_turn_id: turn_id.clone(),
@@ -391,6 +401,44 @@ impl ConversationCoordinator {
         Ok(())
     }
 
+    /// Start executing a plan created in plan mode
+    ///
+    /// Makes the plan the session's active plan, so it is pinned in the system prompt of every
+    /// following turn, and starts an agentic turn that works through its steps.
+    pub async fn start_plan_execution(
+        &self,
+        session_id: String,
+        plan_path: String,
+        turn_id: Option<String>,
+    ) -> BitFunResult<()> {
+        let plan_path = match std::path::Path::new(&plan_path) {
+            path if path.is_absolute() => path.to_path_buf(),
+            path => get_workspace_path()
+                .map(|workspace| workspace.join(path))
+                .unwrap_or_else(|| path.to_path_buf()),
+        };
+        let plan = load_plan(&plan_path).await?;
+        let plan_path = plan_path.to_string_lossy().to_string();
+        info!(
+            "Starting plan execution: session_id={}, plan={}, version={}, steps={}",
+            session_id,
+            plan_path,
+            plan.version,
+            plan.steps.len()
+        );
+
+        self.session_manager
+            .set_active_plan(&session_id, Some(plan_path.clone()))
+            .await?;
+
+        let user_input = format!(
+            "Execute the plan in {}. Work through its steps in order, respecting dependencies.",
+            plan_path
+        );
+        self.start_dialog_turn(session_id, user_input, turn_id, "agentic".to_string())
+            .await
+    }
+
     /// Cancel dialog turn execution
     /// Immediately set state to Idle to allow new dialog, old turn ends naturally via cancel token
     pub async fn cancel_dialog_turn(
@@ -472,6 +520,18 @@ impl ConversationCoordinator {
         self.session_manager.restore_session(session_id).await
     }
 
+    /// Fork a session after the given turn (inclusive)
+    pub async fn fork_session(
+        &self,
+        session_id: &str,
+        turn_index: usize,
+        fork_snapshots: bool,
+    ) -> BitFunResult<Session> {
+        self.session_manager
+            .fork_session(session_id, turn_index, fork_snapshots)
+            .await
+    }
+
     /// List all sessions
     pub async fn list_sessions(&self) -> BitFunResult<Vec<SessionSummary>> {
         self.session_manager.list_sessions().await
@@ -521,6 +581,24 @@ impl ConversationCoordinator {
         self.tool_pipeline.cancel_tool(tool_id, reason).await
     }
 
+    /// Emit an event for a running tool, for tools that report progress on their own
+    /// (e.g. MCP tools relaying `notifications/progress`)
+    pub async fn emit_tool_event(
+        &self,
+        session_id: String,
+        turn_id: String,
+        tool_event: ToolEventData,
+        subagent_parent_info: Option<SubagentParentInfo>,
+    ) {
+        self.emit_event(AgenticEvent::ToolEvent {
+            session_id,
+            turn_id,
+            tool_event,
+            subagent_parent_info: subagent_parent_info.map(|info| info.into()),
+        })
+        .await;
+    }
+
     /// Execute subagent task directly
     /// DialogTurnStarted event not needed for now
     ///
@@ -668,9 +746,70 @@ impl ConversationCoordinator {
         Ok(SubagentResult {
             text: response_text,
             tool_arguments,
+            worktree: None,
         })
     }
 
+    /// Execute a subagent in a temporary git worktree of the workspace
+    ///
+    /// The subagent's tools resolve paths against the worktree, and its snapshot session is
+    /// separate from the parent's. The worktree is removed when the subagent changed nothing.
+    pub async fn execute_isolated_subagent(
+        &self,
+        agent_type: String,
+        task_description: String,
+        subagent_parent_info: SubagentParentInfo,
+        context: Option<std::collections::HashMap<String, String>>,
+        cancel_token: Option<&CancellationToken>,
+    ) -> BitFunResult<SubagentResult> {
+        let workspace = get_workspace_path().ok_or_else(|| {
+            BitFunError::tool("Worktree isolation requires an open workspace".to_string())
+        })?;
+        let worktree = SubagentWorktree::create(&workspace).await?;
+
+        let task_description = format!(
+            "{}\n\nYou are working in an isolated git worktree at {}. Make all changes there; \
+             they are returned to the parent agent when you finish.",
+            task_description,
+            worktree.path().display()
+        );
+        let result = with_workspace_path(
+            worktree.path().to_path_buf(),
+            self.execute_subagent(
+                agent_type,
+                task_description,
+                subagent_parent_info,
+                context,
+                cancel_token,
+            ),
+        )
+        .await;
+
+        let (path, branch) = (
+            worktree.path().display().to_string(),
+            worktree.branch().to_string(),
+        );
+        let outcome = worktree.finish().await;
+        let mut result = match result {
+            Ok(result) => result,
+            Err(e) => {
+                // Changes of a failed run stay in the worktree; tell the caller where
+                return Err(match outcome {
+                    Ok(Some(_)) => with_note(
+                        e,
+                        &format!(
+                            "subagent changes retained in worktree {} on branch {}",
+                            path, branch
+                        ),
+                    ),
+                    _ => e,
+                });
+            }
+        };
+        result.worktree = outcome?;
+        Ok(result)
+    }
+
     /// Clean up subagent session resources
     ///
     /// Release resources occupied by subagent session (sandbox, etc.) and delete session
@@ -781,3 +920,13 @@ static GLOBAL_COORDINATOR: OnceLock<Arc<ConversationCoordinator>> = OnceLock::ne
 pub fn get_global_coordinator() -> Option<Arc<ConversationCoordinator>> {
     GLOBAL_COORDINATOR.get().cloned()
 }
+
+/// Appends `note` to an error's message, keeping cancellations recognizable
+fn with_note(error: BitFunError, note: &str) -> BitFunError {
+    match error {
+        BitFunError::Cancelled(message) => {
+            BitFunError::Cancelled(format!("{} ({})", message, note))
+        }
+        other => BitFunError::tool(format!("{} ({})", other, note)),
+    }
+}
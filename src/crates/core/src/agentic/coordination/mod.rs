@@ -4,9 +4,11 @@
 
 pub mod coordinator;
 pub mod state_manager;
+pub mod subagent_worktree;
 
 pub use coordinator::*;
 pub use state_manager::*;
+pub use subagent_worktree::{SubagentWorktree, WorktreeOutcome};
 
 pub use coordinator::get_global_coordinator;
 
@@ -0,0 +1,259 @@
+//! Temporary git worktrees for isolated subagents
+//!
+//! A subagent with worktree isolation runs in `<repo>/.worktrees/<branch>` on a fresh branch
+//! created from HEAD, so several writing subagents can run in parallel without touching the
+//! parent's files. When it finishes, its changes are staged and either handed back as a diff
+//! or, if there are none, the worktree and branch are removed. A worktree dropped before it
+//! finished (e.g. because the run was aborted) is removed if clean and reported otherwise.
+
+use crate::service::git::{execute_git_command, GitService};
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::{debug, warn};
+use std::path::{Path, PathBuf};
+
+const BRANCH_PREFIX: &str = "bitfun-subagent-";
+
+/// Changes left behind by an isolated subagent
+#[derive(Debug, Clone)]
+pub struct WorktreeOutcome {
+    /// Branch checked out in the worktree
+    pub branch: String,
+    /// Worktree directory, kept so the changes can be inspected or committed
+    pub path: String,
+    /// Staged changes relative to the commit the worktree started from
+    pub diff: String,
+}
+
+/// A worktree created for one subagent run
+pub struct SubagentWorktree {
+    repo_path: PathBuf,
+    path: PathBuf,
+    branch: String,
+    /// Set once `finish` has decided what happens to the worktree
+    finished: bool,
+}
+
+impl SubagentWorktree {
+    /// Creates a worktree on a new branch from the repository's HEAD.
+    pub async fn create(repo_path: &Path) -> BitFunResult<Self> {
+        let is_repository = GitService::is_repository(repo_path).await.unwrap_or(false);
+        if !is_repository {
+            return Err(BitFunError::tool(format!(
+                "Worktree isolation requires a git repository: {}",
+                repo_path.display()
+            )));
+        }
+
+        exclude_worktree_dir(repo_path).await;
+
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let branch = format!("{}{}", BRANCH_PREFIX, &id[..8]);
+        let info = GitService::add_worktree(repo_path, &branch, true)
+            .await
+            .map_err(|e| BitFunError::service(format!("Failed to create worktree: {}", e)))?;
+        debug!(
+            "Created subagent worktree: path={}, branch={}",
+            info.path, branch
+        );
+
+        Ok(Self {
+            repo_path: repo_path.to_path_buf(),
+            path: PathBuf::from(info.path),
+            branch,
+            finished: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn branch(&self) -> &str {
+        &self.branch
+    }
+
+    /// Stages everything in the worktree and returns the result.
+    ///
+    /// Returns None and removes the worktree and its branch when nothing changed.
+    pub async fn finish(mut self) -> BitFunResult<Option<WorktreeOutcome>> {
+        let path = self.path.to_string_lossy().to_string();
+        let git = |args: &'static [&'static str]| {
+            let path = path.clone();
+            let branch = self.branch.clone();
+            async move {
+                execute_git_command(&path, args).await.map_err(|e| {
+                    BitFunError::service(format!(
+                        "Failed to collect changes of worktree {} (branch {}): {}",
+                        path, branch, e
+                    ))
+                })
+            }
+        };
+
+        git(&["add", "-A"]).await?;
+        let diff = git(&["diff", "--cached", "--binary"]).await?;
+
+        self.finished = true;
+        if diff.trim().is_empty() {
+            remove_worktree(&self.repo_path, &self.path, &self.branch).await;
+            return Ok(None);
+        }
+
+        Ok(Some(WorktreeOutcome {
+            branch: self.branch.clone(),
+            path,
+            diff,
+        }))
+    }
+}
+
+impl Drop for SubagentWorktree {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let (repo_path, path, branch) = (
+            self.repo_path.clone(),
+            self.path.clone(),
+            self.branch.clone(),
+        );
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    let status =
+                        execute_git_command(&path.to_string_lossy(), &["status", "--porcelain"])
+                            .await;
+                    match status {
+                        Ok(status) if status.trim().is_empty() => {
+                            remove_worktree(&repo_path, &path, &branch).await
+                        }
+                        _ => warn!(
+                            "Subagent worktree retained: path={}, branch={}",
+                            path.display(),
+                            branch
+                        ),
+                    }
+                });
+            }
+            Err(_) => warn!(
+                "Subagent worktree retained: path={}, branch={}",
+                path.display(),
+                branch
+            ),
+        }
+    }
+}
+
+/// Removes a worktree and its branch.
+async fn remove_worktree(repo_path: &Path, path: &Path, branch: &str) {
+    let path = path.to_string_lossy();
+    if let Err(e) = GitService::remove_worktree(repo_path, &path, true).await {
+        warn!(
+            "Failed to remove subagent worktree: path={}, error={}",
+            path, e
+        );
+        return;
+    }
+    if let Err(e) = GitService::delete_branch(repo_path, branch, true).await {
+        warn!(
+            "Failed to delete subagent branch: branch={}, error={}",
+            branch, e
+        );
+    }
+    debug!("Removed unchanged subagent worktree: path={}", path);
+}
+
+/// Keeps `.worktrees/` out of the parent's status and diffs.
+async fn exclude_worktree_dir(repo_path: &Path) {
+    let repo = repo_path.to_string_lossy();
+    let Ok(git_dir) = execute_git_command(&repo, &["rev-parse", "--git-common-dir"]).await else {
+        return;
+    };
+    let exclude_path = repo_path.join(git_dir.trim()).join("info").join("exclude");
+
+    let existing = tokio::fs::read_to_string(&exclude_path)
+        .await
+        .unwrap_or_default();
+    if existing.lines().any(|line| line.trim() == "/.worktrees/") {
+        return;
+    }
+
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str("/.worktrees/\n");
+    if let Some(parent) = exclude_path.parent() {
+        let _ = tokio::fs::create_dir_all(parent).await;
+    }
+    if let Err(e) = tokio::fs::write(&exclude_path, content).await {
+        warn!(
+            "Failed to exclude .worktrees from git: path={}, error={}",
+            exclude_path.display(),
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn init_repo() -> PathBuf {
+        let repo = std::env::temp_dir().join(format!("bitfun-worktree-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(repo.join("a.txt"), "one\n").unwrap();
+        let path = repo.to_string_lossy().to_string();
+        for args in [
+            &["init", "-q"][..],
+            &["add", "-A"],
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-qm",
+                "init",
+            ],
+        ] {
+            execute_git_command(&path, args).await.unwrap();
+        }
+        repo
+    }
+
+    #[tokio::test]
+    async fn test_finish_keeps_changed_worktree_only() {
+        let repo = init_repo().await;
+
+        let unchanged = SubagentWorktree::create(&repo).await.unwrap();
+        let unchanged_path = unchanged.path().to_path_buf();
+        assert!(unchanged.finish().await.unwrap().is_none());
+        assert!(!unchanged_path.exists());
+
+        let changed = SubagentWorktree::create(&repo).await.unwrap();
+        std::fs::write(changed.path().join("a.txt"), "two\n").unwrap();
+        let outcome = changed.finish().await.unwrap().unwrap();
+        assert!(outcome.diff.contains("+two"));
+        assert!(outcome.branch.starts_with(BRANCH_PREFIX));
+
+        let status = execute_git_command(&repo.to_string_lossy(), &["status", "--porcelain"])
+            .await
+            .unwrap();
+        assert!(status.is_empty());
+
+        // An abandoned clean worktree is removed in the background
+        let abandoned = SubagentWorktree::create(&repo).await.unwrap();
+        let abandoned_path = abandoned.path().to_path_buf();
+        drop(abandoned);
+        for _ in 0..50 {
+            if !abandoned_path.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert!(!abandoned_path.exists());
+
+        let _ = std::fs::remove_dir_all(&repo);
+    }
+}
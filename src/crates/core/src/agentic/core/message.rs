@@ -47,11 +47,17 @@ pub struct MessageMetadata {
     pub turn_id: Option<String>,
     pub round_id: Option<String>,
     pub tokens: Option<usize>,
+    /// Tokenizer that produced `tokens`; a count without one is never reused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
     #[serde(skip)] // Not serialized, auxiliary field for runtime use only
     pub keep_thinking: bool,
     /// Anthropic extended thinking signature (for passing back in multi-turn conversations)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thinking_signature: Option<String>,
+    /// Id of the response that produced this message (OpenAI Responses API state chaining)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_id: Option<String>,
 }
 
 impl From<Message> for AIMessage {
@@ -64,6 +70,7 @@ impl From<Message> for AIMessage {
         };
         let keep_thinking = msg.metadata.keep_thinking;
         let thinking_signature = msg.metadata.thinking_signature.clone();
+        let response_id = msg.metadata.response_id.clone();
 
         match msg.content {
             MessageContent::Text(text) => {
@@ -90,6 +97,7 @@ impl From<Message> for AIMessage {
                     tool_calls: None,
                     tool_call_id: None,
                     name: None,
+                    response_id,
                 }
             }
             MessageContent::Mixed {
@@ -145,6 +153,7 @@ impl From<Message> for AIMessage {
                     tool_calls: converted_tool_calls,
                     tool_call_id: None,
                     name: None,
+                    response_id,
                 }
             }
             MessageContent::ToolResult {
@@ -179,6 +188,7 @@ impl From<Message> for AIMessage {
                     tool_calls: None,
                     tool_call_id: Some(tool_id),
                     name: Some(tool_name),
+                    response_id: None,
                 }
             }
         }
@@ -303,15 +313,47 @@ impl Message {
         self
     }
 
-    /// Get message's token count
+    /// Set the id of the response that produced this message (OpenAI Responses API)
+    pub fn with_response_id(mut self, response_id: Option<String>) -> Self {
+        self.metadata.response_id = response_id;
+        self
+    }
+
+    /// Get message's token count with the default estimate
     pub fn get_tokens(&mut self) -> usize {
+        self.get_tokens_with(&TokenCounter::default())
+    }
+
+    /// Get message's token count with a model-specific counter
+    ///
+    /// The cached count is reused only if it was produced by the same tokenizer and with the
+    /// same thinking visibility, since reasoning content is dropped from older turns.
+    pub fn get_tokens_with(&mut self, counter: &TokenCounter) -> usize {
+        let cache_key = if self.metadata.keep_thinking && self.has_reasoning_content() {
+            format!("{}+thinking", counter.tokenizer_name())
+        } else {
+            counter.tokenizer_name().to_string()
+        };
         if let Some(tokens) = self.metadata.tokens {
-            return tokens;
+            if self.metadata.tokenizer.as_deref() == Some(cache_key.as_str()) {
+                return tokens;
+            }
         }
-        let tokens = TokenCounter::estimate_message_tokens(&AIMessage::from(&*self));
+        let tokens = counter.count_message(&AIMessage::from(&*self));
         self.metadata.tokens = Some(tokens);
+        self.metadata.tokenizer = Some(cache_key);
         tokens
     }
+
+    fn has_reasoning_content(&self) -> bool {
+        matches!(
+            &self.content,
+            MessageContent::Mixed {
+                reasoning_content: Some(reasoning),
+                ..
+            } if !reasoning.is_empty()
+        )
+    }
 }
 
 impl ToString for MessageContent {
@@ -400,3 +442,23 @@ impl From<ToolCall> for AIToolCall {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::token_counter::TokenizerKind;
+
+    #[test]
+    fn cached_tokens_are_reused_for_the_same_tokenizer_only() {
+        let mut message = Message::user("Count these tokens, please".to_string());
+        message.metadata.tokens = Some(1000);
+        assert_ne!(message.get_tokens(), 1000);
+        assert_eq!(message.metadata.tokenizer.as_deref(), Some("estimate"));
+
+        message.metadata.tokens = Some(1000);
+        assert_eq!(message.get_tokens(), 1000);
+        let counter = TokenCounter::new(TokenizerKind::Cl100k);
+        assert_ne!(message.get_tokens_with(&counter), 1000);
+        assert_ne!(message.get_tokens(), 1000);
+    }
+}
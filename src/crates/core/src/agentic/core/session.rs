@@ -1,5 +1,6 @@
 use super::state::SessionState;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::time::SystemTime;
 use uuid::Uuid;
 
@@ -28,6 +29,24 @@ pub struct Session {
     /// Context compression related
     pub compression_state: CompressionState,
 
+    /// Lineage: session this one was forked from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_session_id: Option<String>,
+    /// Lineage: last parent turn included in the fork
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fork_turn_index: Option<usize>,
+    /// Lineage: sessions forked from this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub child_session_ids: Vec<String>,
+
+    /// Plan file being executed; pinned in the system prompt of every turn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_plan: Option<String>,
+
+    /// Workspace the session works in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_path: Option<String>,
+
     /// Lifecycle
     pub created_at: SystemTime,
     pub updated_at: SystemTime,
@@ -71,6 +90,11 @@ impl Session {
             state: SessionState::Idle,
             config,
             compression_state: CompressionState::default(),
+            parent_session_id: None,
+            fork_turn_index: None,
+            child_session_ids: vec![],
+            active_plan: None,
+            workspace_path: None,
             created_at: now,
             updated_at: now,
             last_activity_at: now,
@@ -93,11 +117,24 @@ impl Session {
             state: SessionState::Idle,
             config,
             compression_state: CompressionState::default(),
+            parent_session_id: None,
+            fork_turn_index: None,
+            child_session_ids: vec![],
+            active_plan: None,
+            workspace_path: None,
             created_at: now,
             updated_at: now,
             last_activity_at: now,
         }
     }
+
+    /// Value recorded as `workspace_path` for a workspace directory
+    pub fn workspace_key(path: &Path) -> String {
+        std::fs::canonicalize(path)
+            .unwrap_or_else(|_| path.to_path_buf())
+            .to_string_lossy()
+            .to_string()
+    }
 }
 
 /// Session configuration
@@ -137,4 +174,34 @@ pub struct SessionSummary {
     pub created_at: SystemTime,
     pub last_activity_at: SystemTime,
     pub state: SessionState,
+    /// Session this one was forked from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_session_id: Option<String>,
+    /// Last parent turn included in the fork
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fork_turn_index: Option<usize>,
+    /// Sessions forked from this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub child_session_ids: Vec<String>,
+    /// Workspace the session works in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_path: Option<String>,
+}
+
+impl From<&Session> for SessionSummary {
+    fn from(session: &Session) -> Self {
+        Self {
+            session_id: session.session_id.clone(),
+            session_name: session.session_name.clone(),
+            agent_type: session.agent_type.clone(),
+            turn_count: session.dialog_turn_ids.len(),
+            created_at: session.created_at,
+            last_activity_at: session.last_activity_at,
+            state: session.state.clone(),
+            parent_session_id: session.parent_session_id.clone(),
+            fork_turn_index: session.fork_turn_index,
+            child_session_ids: session.child_session_ids.clone(),
+            workspace_path: session.workspace_path.clone(),
+        }
+    }
 }
@@ -5,10 +5,11 @@
 use super::round_executor::RoundExecutor;
 use super::types::{ExecutionContext, ExecutionResult, RoundContext};
 use crate::agentic::agents::get_agent_registry;
-use crate::agentic::core::{Message, MessageHelper};
+use crate::agentic::core::{Message, MessageContent, MessageHelper, MessageRole};
 use crate::agentic::events::{AgenticEvent, EventPriority, EventQueue};
+use crate::agentic::hooks::{format_reminder, HookEvent, HookInput, HookRunner};
 use crate::agentic::session::SessionManager;
-use crate::agentic::tools::{get_all_registered_tools, SubagentParentInfo};
+use crate::agentic::tools::{get_all_registered_tools, mcp_tools_revision, SubagentParentInfo};
 use crate::infrastructure::ai::get_global_ai_client_factory;
 use crate::infrastructure::get_workspace_path;
 use crate::util::errors::{BitFunError, BitFunResult};
@@ -64,6 +65,7 @@ impl ExecutionEngine {
         messages: Vec<Message>,
         current_tokens: usize,
         context_window: usize,
+        token_counter: &TokenCounter,
         tool_definitions: &Option<Vec<ToolDefinition>>,
         system_prompt_message: Message,
     ) -> BitFunResult<Option<(usize, Vec<Message>, Vec<AIMessage>)>> {
@@ -81,7 +83,7 @@ impl ExecutionEngine {
         let old_messages_len = messages.len();
         // Preprocess turns
         let (turn_index_to_keep, turns) = compression_manager
-            .preprocess_turns(session_id, context_window, messages)
+            .preprocess_turns(session_id, context_window, messages, token_counter)
             .await?;
         if turn_index_to_keep == 0 {
             return Ok(None);
@@ -136,10 +138,8 @@ impl ExecutionEngine {
                 // Recalculate tokens after compression
                 let new_ai_messages: Vec<AIMessage> =
                     MessageHelper::convert_messages(&new_messages);
-                let compressed_tokens = TokenCounter::estimate_request_tokens(
-                    &new_ai_messages,
-                    tool_definitions.as_deref(),
-                );
+                let compressed_tokens =
+                    token_counter.count_request(&new_ai_messages, tool_definitions.as_deref());
 
                 // Emit compression completed event
                 self.emit_event(
@@ -236,7 +236,7 @@ impl ExecutionEngine {
             dialog_turn_id
         );
 
-        // Things that remain constant in a dialog turn: 1.agent, 2.system prompt, 3.tools, 4.ai client
+        // Things that remain constant in a dialog turn: 1.agent, 2.system prompt, 3.tools, 4.ai client (until it falls back)
         // 1. Get current agent
         let agent_registry = get_agent_registry();
         let current_agent = agent_registry
@@ -253,11 +253,22 @@ impl ExecutionEngine {
             "Building system prompt from agent: {}",
             current_agent.name()
         );
+        // Memory points are ranked against the latest user message
+        let memory_query = initial_messages
+            .iter()
+            .rev()
+            .find(|m| m.role == MessageRole::User && matches!(m.content, MessageContent::Text(_)))
+            .map(message_text);
         let system_prompt = {
             let workspace_path = get_workspace_path();
             let workspace_str = workspace_path.as_ref().map(|p| p.display().to_string());
+            let active_plan = context.context.get("active_plan").map(String::as_str);
             current_agent
-                .get_system_prompt(workspace_str.as_deref())
+                .get_system_prompt(
+                    workspace_str.as_deref(),
+                    memory_query.as_deref(),
+                    active_plan,
+                )
                 .await?
         };
         debug!("System prompt built, length: {} bytes", system_prompt.len());
@@ -267,9 +278,30 @@ impl ExecutionEngine {
         let mut messages = vec![system_prompt_message.clone()];
         messages.extend(initial_messages);
 
+        // Lifecycle hooks only apply to top-level dialog turns, not subagents
+        let hooks = if context.subagent_parent_info.is_none() {
+            HookRunner::load().await
+        } else {
+            HookRunner::default()
+        };
+
+        // SessionStart hooks run before the first dialog turn of a session
+        if context.turn_index == 0 && hooks.has_hooks(HookEvent::SessionStart, None) {
+            let input = HookInput::new(HookEvent::SessionStart, context.session_id.clone())
+                .with_turn(context.dialog_turn_id.clone(), agent_type.clone());
+            let outcome = hooks.run(input).await;
+            if outcome.is_blocked() {
+                warn!("SessionStart hooks cannot block, ignoring: session_id={}", context.session_id);
+            }
+            if let Some(reminder) = outcome.context_reminder() {
+                self.push_hook_message(&context, &mut messages, reminder).await;
+            }
+        }
+
         let mut round_index = 0;
         let mut total_tools = 0;
         let mut last_assistant_message = Message::assistant("".to_string());
+        let mut turn_end_hook_active = false;
 
         // Save the last token usage statistics
         let mut last_usage: Option<crate::util::types::ai::GeminiUsage> = None;
@@ -300,7 +332,8 @@ impl ExecutionEngine {
             .get("enable_tools")
             .and_then(|v| v.parse::<bool>().ok())
             .unwrap_or(true);
-        let (available_tools, tool_definitions) = if enable_tools {
+        let mut tools_revision = mcp_tools_revision();
+        let (mut available_tools, mut tool_definitions) = if enable_tools {
             debug!(
                 "Agent tools: agent={}, tool_count={}",
                 agent_type,
@@ -339,7 +372,7 @@ impl ExecutionEngine {
         })?;
 
         // Get AI client by model ID
-        let ai_client = ai_client_factory
+        let mut ai_client = ai_client_factory
             .get_client_resolved(&model_id)
             .await
             .map_err(|e| {
@@ -348,10 +381,35 @@ impl ExecutionEngine {
                     model_id, e
                 ))
             })?;
+
+        // Fallback models, used in order once the current model is unavailable
+        let mut fallback_clients: Vec<_> = ai_client_factory
+            .get_fallback_clients_by_agent(&agent_type)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to resolve fallback models: agent={}, error={}", agent_type, e);
+                Vec::new()
+            })
+            .into_iter()
+            .filter(|client| !Arc::ptr_eq(client, &ai_client))
+            .collect();
+
         // Get configuration for whether to support preserving historical thinking content
-        let enable_thinking = ai_client.config.enable_thinking_process;
-        let support_preserved_thinking = ai_client.config.support_preserved_thinking;
-        let context_window = ai_client.config.context_window as usize;
+        let mut enable_thinking = ai_client.config.enable_thinking_process;
+        let mut support_preserved_thinking = ai_client.config.support_preserved_thinking;
+        let mut context_window = ai_client.config.context_window as usize;
+
+        // Count tokens with the model's tokenizer; tool definitions only change with MCP tools
+        let mut token_counter = TokenCounter::for_config(&ai_client.config);
+        let mut tool_definition_tokens = tool_definitions
+            .as_deref()
+            .map(|tools| token_counter.count_tool_definitions(tools))
+            .unwrap_or(0);
+        debug!(
+            "Token counter: tokenizer={}, tool_definition_tokens={}",
+            token_counter.tokenizer_name(),
+            tool_definition_tokens
+        );
 
         // Loop to execute model rounds
         loop {
@@ -364,6 +422,24 @@ impl ExecutionEngine {
                 break;
             }
 
+            // MCP servers changed their tools: use the new tool set from this round on
+            if enable_tools && tools_revision != mcp_tools_revision() {
+                tools_revision = mcp_tools_revision();
+                (available_tools, tool_definitions) = self
+                    .get_available_tools_and_definitions(&allowed_tools)
+                    .await;
+                tool_definition_tokens = tool_definitions
+                    .as_deref()
+                    .map(|tools| token_counter.count_tool_definitions(tools))
+                    .unwrap_or(0);
+                info!(
+                    "Tool set changed, refreshed for round {}: session={}, tool_count={}",
+                    round_index,
+                    context.session_id,
+                    available_tools.len()
+                );
+            }
+
             MessageHelper::compute_keep_thinking_flags(
                 &mut messages,
                 enable_thinking,
@@ -372,8 +448,13 @@ impl ExecutionEngine {
             let mut ai_messages = MessageHelper::convert_messages(&messages);
 
             // Check and compress before sending AI request
-            let current_tokens =
-                TokenCounter::estimate_request_tokens(&ai_messages, tool_definitions.as_deref());
+            // Per-message counts are cached, so only new messages are tokenized each round
+            let current_tokens = messages
+                .iter_mut()
+                .map(|message| message.get_tokens_with(&token_counter))
+                .sum::<usize>()
+                + TokenCounter::REQUEST_OVERHEAD
+                + tool_definition_tokens;
             debug!(
                 "Round {} token usage before send: {} / {} tokens ({:.1}%)",
                 round_index,
@@ -409,6 +490,7 @@ impl ExecutionEngine {
                         messages.clone(),
                         current_tokens,
                         context_window,
+                        &token_counter,
                         &tool_definitions,
                         system_prompt_message.clone(),
                     )
@@ -474,7 +556,43 @@ impl ExecutionEngine {
                     tool_definitions.clone(),
                     Some(context_window),
                 )
-                .await?;
+                .await;
+
+            let mut round_result = match round_result {
+                Ok(result) => result,
+                Err(BitFunError::ModelUnavailable(reason)) if !fallback_clients.is_empty() => {
+                    // Retry the round on the next model; it stays in use for the rest of the turn
+                    let next_client = fallback_clients.remove(0);
+                    warn!(
+                        "Model unavailable, switching to fallback: session_id={}, from={}, to={}, reason={}",
+                        context.session_id, ai_client.config.name, next_client.config.name, reason
+                    );
+                    self.emit_event(
+                        AgenticEvent::ModelSwitched {
+                            session_id: context.session_id.clone(),
+                            turn_id: context.dialog_turn_id.clone(),
+                            from_model: ai_client.config.name.clone(),
+                            to_model: next_client.config.name.clone(),
+                            reason,
+                            subagent_parent_info: event_subagent_parent_info.clone(),
+                        },
+                        EventPriority::High,
+                    )
+                    .await;
+
+                    ai_client = next_client;
+                    enable_thinking = ai_client.config.enable_thinking_process;
+                    support_preserved_thinking = ai_client.config.support_preserved_thinking;
+                    context_window = ai_client.config.context_window as usize;
+                    token_counter = TokenCounter::for_config(&ai_client.config);
+                    tool_definition_tokens = tool_definitions
+                        .as_deref()
+                        .map(|tools| token_counter.count_tool_definitions(tools))
+                        .unwrap_or(0);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
 
             debug!(
                 "Model round completed: round_index={}, has_more_rounds={}, tool_calls={}",
@@ -482,6 +600,12 @@ impl ExecutionEngine {
                 round_result.has_more_rounds,
                 round_result.tool_calls.len()
             );
+
+            // Count new messages before saving them so the cached counts persist with the history
+            round_result.assistant_message.get_tokens_with(&token_counter);
+            for tool_result_msg in round_result.tool_result_messages.iter_mut() {
+                tool_result_msg.get_tokens_with(&token_counter);
+            }
             last_assistant_message = round_result.assistant_message.clone();
 
             // Save the last token usage statistics (update each time, keep the last one)
@@ -527,6 +651,35 @@ impl ExecutionEngine {
                     "Model round {} ended, reason: {:?}",
                     round_index, round_result.finish_reason
                 );
+
+                // TurnEnd hooks may inject context or make the model keep working
+                if hooks.has_hooks(HookEvent::TurnEnd, None) {
+                    let mut input = HookInput::new(HookEvent::TurnEnd, context.session_id.clone())
+                        .with_turn(context.dialog_turn_id.clone(), agent_type.clone());
+                    input.final_response = Some(message_text(&round_result.assistant_message));
+                    input.turn_end_hook_active = Some(turn_end_hook_active);
+                    let outcome = hooks.run(input).await;
+
+                    let mut parts = outcome.additional_context.clone();
+                    if let Some(ref reason) = outcome.blocked {
+                        parts.push(format!("TurnEnd hook feedback: {}", reason));
+                    }
+                    if let Some(reminder) = format_reminder(&parts) {
+                        self.push_hook_message(&context, &mut messages, reminder).await;
+                    }
+
+                    if outcome.is_blocked()
+                        && self.round_executor.has_active_dialog_turn(&dialog_turn_id)
+                    {
+                        info!(
+                            "TurnEnd hook blocked, continuing dialog turn: dialog_turn_id={}",
+                            dialog_turn_id
+                        );
+                        turn_end_hook_active = true;
+                        round_index += 1;
+                        continue;
+                    }
+                }
                 break;
             }
 
@@ -709,6 +862,7 @@ impl ExecutionEngine {
                 "Grep",
                 "Read",
                 "Edit",
+                "MultiEdit",
                 "Write",
                 "Delete",
                 "WebFetch",
@@ -731,8 +885,36 @@ impl ExecutionEngine {
         (enabled_tool_names, Some(tool_definitions))
     }
 
+    /// Add hook-provided context to the conversation (and persist it for later turns)
+    async fn push_hook_message(
+        &self,
+        context: &ExecutionContext,
+        messages: &mut Vec<Message>,
+        reminder: String,
+    ) {
+        let message = Message::user(reminder).with_turn_id(context.dialog_turn_id.clone());
+        messages.push(message.clone());
+
+        if let Err(e) = self
+            .session_manager
+            .add_message(&context.session_id, message)
+            .await
+        {
+            warn!("Failed to save hook context message: {}", e);
+        }
+    }
+
     /// Emit event
     async fn emit_event(&self, event: AgenticEvent, priority: EventPriority) {
         let _ = self.event_queue.enqueue(event, Some(priority)).await;
     }
 }
+
+/// Plain text of a message
+fn message_text(message: &Message) -> String {
+    match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Mixed { text, .. } => text.clone(),
+        MessageContent::ToolResult { .. } => String::new(),
+    }
+}
@@ -9,7 +9,7 @@ use crate::agentic::events::{AgenticEvent, EventPriority, EventQueue};
 use crate::agentic::tools::pipeline::{ToolExecutionContext, ToolExecutionOptions, ToolPipeline};
 use crate::agentic::tools::registry::get_global_tool_registry;
 use crate::agentic::MessageContent;
-use crate::infrastructure::ai::AIClient;
+use crate::infrastructure::ai::{AIClient, RetryPolicy};
 use crate::service::config::GlobalConfigManager;
 use crate::util::errors::{BitFunError, BitFunResult};
 use crate::util::types::Message as AIMessage;
@@ -17,7 +17,6 @@ use crate::util::types::ToolDefinition;
 use dashmap::DashMap;
 use log::{debug, error, warn};
 use std::sync::Arc;
-use std::time::Duration;
 use tokio_util::sync::CancellationToken;
 
 /// Round executor
@@ -30,9 +29,6 @@ pub struct RoundExecutor {
 }
 
 impl RoundExecutor {
-    const MAX_RETRIES_WITHOUT_OUTPUT: usize = 1;
-    const RETRY_BASE_DELAY_MS: u64 = 500;
-
     pub fn new(
         stream_processor: Arc<StreamProcessor>,
         event_queue: Arc<EventQueue>,
@@ -88,7 +84,10 @@ impl RoundExecutor {
         )
         .await;
 
-        let max_attempts = Self::MAX_RETRIES_WITHOUT_OUTPUT + 1;
+        // Request-level retries happen in the client; this loop retries streams
+        // that were interrupted or empty before producing any output
+        let retry_policy = ai_client.retry_policy().clone();
+        let max_attempts = retry_policy.max_attempts();
         let mut attempt_index = 0usize;
         let stream_result = loop {
             debug!(
@@ -109,22 +108,8 @@ impl RoundExecutor {
                 Err(e) => {
                     error!("AI request failed: {}", e);
                     let err_msg = e.to_string();
-                    let can_retry = attempt_index < max_attempts - 1
-                        && Self::is_transient_network_error(&err_msg);
-                    if can_retry {
-                        let delay_ms = Self::retry_delay_ms(attempt_index);
-                        warn!(
-                            "Retrying request after transient error with no output: session_id={}, round_id={}, attempt={}/{}, delay_ms={}, error={}",
-                            context.session_id,
-                            round_id,
-                            attempt_index + 1,
-                            max_attempts,
-                            delay_ms,
-                            err_msg
-                        );
-                        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
-                        attempt_index += 1;
-                        continue;
+                    if RetryPolicy::is_transient_error(&err_msg) {
+                        return Err(BitFunError::ModelUnavailable(err_msg));
                     }
                     return Err(BitFunError::AIClient(err_msg));
                 }
@@ -168,16 +153,16 @@ impl RoundExecutor {
                 Ok(result) => {
                     let no_effective_output = !result.has_effective_output;
                     if no_effective_output && attempt_index < max_attempts - 1 {
-                        let delay_ms = Self::retry_delay_ms(attempt_index);
+                        let delay = retry_policy.delay_for(attempt_index as u32, None);
                         warn!(
                             "Retrying stream because no effective output was received: session_id={}, round_id={}, attempt={}/{}, delay_ms={}",
                             context.session_id,
                             round_id,
                             attempt_index + 1,
                             max_attempts,
-                            delay_ms
+                            delay.as_millis()
                         );
-                        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                        tokio::time::sleep(delay).await;
                         attempt_index += 1;
                         continue;
                     }
@@ -185,24 +170,26 @@ impl RoundExecutor {
                 }
                 Err(stream_err) => {
                     let err_msg = stream_err.error.to_string();
-                    let can_retry = !stream_err.has_effective_output
-                        && attempt_index < max_attempts - 1
-                        && Self::is_transient_network_error(&err_msg);
-                    if can_retry {
-                        let delay_ms = Self::retry_delay_ms(attempt_index);
+                    let transient_without_output = !stream_err.has_effective_output
+                        && RetryPolicy::is_transient_error(&err_msg);
+                    if transient_without_output && attempt_index < max_attempts - 1 {
+                        let delay = retry_policy.delay_for(attempt_index as u32, None);
                         warn!(
                             "Retrying stream after transient error with no effective output: session_id={}, round_id={}, attempt={}/{}, delay_ms={}, error={}",
                             context.session_id,
                             round_id,
                             attempt_index + 1,
                             max_attempts,
-                            delay_ms,
+                            delay.as_millis(),
                             err_msg
                         );
-                        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                        tokio::time::sleep(delay).await;
                         attempt_index += 1;
                         continue;
                     }
+                    if transient_without_output {
+                        return Err(BitFunError::ModelUnavailable(err_msg));
+                    }
                     return Err(stream_err.error);
                 }
             }
@@ -293,7 +280,8 @@ impl RoundExecutor {
             )
             .with_turn_id(context.dialog_turn_id.clone())
             .with_round_id(round_id.clone())
-            .with_thinking_signature(stream_result.thinking_signature.clone());
+            .with_thinking_signature(stream_result.thinking_signature.clone())
+            .with_response_id(stream_result.response_id.clone());
 
             debug!("Returning RoundResult: has_more_rounds=false");
 
@@ -415,7 +403,8 @@ impl RoundExecutor {
         )
         .with_turn_id(context.dialog_turn_id.clone())
         .with_round_id(round_id.clone())
-        .with_thinking_signature(stream_result.thinking_signature.clone());
+        .with_thinking_signature(stream_result.thinking_signature.clone())
+        .with_response_id(stream_result.response_id.clone());
 
         debug!(
             "Tool execution completed, creating message: assistant_msg_len={}, tool_results={}",
@@ -523,90 +512,4 @@ impl RoundExecutor {
     async fn emit_event(&self, event: AgenticEvent, priority: EventPriority) {
         let _ = self.event_queue.enqueue(event, Some(priority)).await;
     }
-
-    fn retry_delay_ms(attempt_index: usize) -> u64 {
-        Self::RETRY_BASE_DELAY_MS * (1u64 << attempt_index.min(3))
-    }
-
-    fn is_transient_network_error(error_message: &str) -> bool {
-        let msg = error_message.to_lowercase();
-
-        let non_retryable_keywords = [
-            "invalid api key",
-            "unauthorized",
-            "forbidden",
-            "model not found",
-            "unsupported model",
-            "invalid request",
-            "bad request",
-            "prompt is too long",
-            "content policy",
-            "proxy authentication required",
-            "client error 400",
-            "client error 401",
-            "client error 403",
-            "client error 404",
-            "client error 422",
-            "sse parsing error",
-            "schema error",
-            "unknown api format",
-        ];
-
-        let transient_keywords = [
-            "transport error",
-            "error decoding response body",
-            "stream closed before response completed",
-            "stream processing error",
-            "sse stream error",
-            "sse error",
-            "sse timeout",
-            "stream data timeout",
-            "timeout",
-            "connection reset",
-            "broken pipe",
-            "unexpected eof",
-            "connection refused",
-            "temporarily unavailable",
-            "gateway timeout",
-            "proxy",
-            "tunnel",
-            "dns",
-            "network",
-            "econnreset",
-            "econnrefused",
-            "etimedout",
-            "rate limit",
-            "too many requests",
-            "429",
-        ];
-
-        if non_retryable_keywords.iter().any(|k| msg.contains(k)) {
-            return false;
-        }
-
-        transient_keywords.iter().any(|k| msg.contains(k))
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::RoundExecutor;
-
-    #[test]
-    fn detects_transient_stream_transport_error() {
-        let msg = "Error: Stream processing error: SSE Error: Transport Error: Error decoding response body";
-        assert!(RoundExecutor::is_transient_network_error(msg));
-    }
-
-    #[test]
-    fn rejects_non_retryable_auth_error() {
-        let msg = "OpenAI Streaming API client error 401: unauthorized";
-        assert!(!RoundExecutor::is_transient_network_error(msg));
-    }
-
-    #[test]
-    fn rejects_sse_schema_error() {
-        let msg = "Stream processing error: SSE data schema error: missing field choices";
-        assert!(!RoundExecutor::is_transient_network_error(msg));
-    }
 }
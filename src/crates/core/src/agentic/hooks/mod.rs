@@ -0,0 +1,9 @@
+//! Lifecycle hooks
+//!
+//! User-configured shell commands run at PreToolUse / PostToolUse / TurnEnd / SessionStart
+
+pub mod hook_runner;
+pub mod types;
+
+pub use hook_runner::{approve_project_hooks, project_hooks, HookRunner, PROJECT_HOOKS_APPROVAL_EVENT};
+pub use types::*;
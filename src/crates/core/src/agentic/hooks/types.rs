@@ -0,0 +1,145 @@
+//! Hook type definitions
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// Point of the agent lifecycle where hooks run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HookEvent {
+    PreToolUse,
+    PostToolUse,
+    TurnEnd,
+    SessionStart,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PreToolUse => "PreToolUse",
+            Self::PostToolUse => "PostToolUse",
+            Self::TurnEnd => "TurnEnd",
+            Self::SessionStart => "SessionStart",
+        }
+    }
+
+    pub fn is_tool_event(&self) -> bool {
+        matches!(self, Self::PreToolUse | Self::PostToolUse)
+    }
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// JSON written to the hook command's stdin
+#[derive(Debug, Clone, Serialize)]
+pub struct HookInput {
+    pub hook_event_name: HookEvent,
+    pub session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dialog_turn_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_input: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_result: Option<Value>,
+    /// Final assistant text of the turn (TurnEnd)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_response: Option<String>,
+    /// The turn is already continuing because a TurnEnd hook blocked it (TurnEnd)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_end_hook_active: Option<bool>,
+}
+
+impl HookInput {
+    pub fn new(event: HookEvent, session_id: impl Into<String>) -> Self {
+        Self {
+            hook_event_name: event,
+            session_id: session_id.into(),
+            dialog_turn_id: None,
+            agent_type: None,
+            workspace: None,
+            tool_name: None,
+            tool_id: None,
+            tool_input: None,
+            tool_result: None,
+            final_response: None,
+            turn_end_hook_active: None,
+        }
+    }
+
+    pub fn with_turn(mut self, dialog_turn_id: impl Into<String>, agent_type: impl Into<String>) -> Self {
+        self.dialog_turn_id = Some(dialog_turn_id.into());
+        self.agent_type = Some(agent_type.into());
+        self
+    }
+
+    pub fn with_tool(mut self, tool_name: impl Into<String>, tool_id: impl Into<String>, tool_input: Value) -> Self {
+        self.tool_name = Some(tool_name.into());
+        self.tool_id = Some(tool_id.into());
+        self.tool_input = Some(tool_input);
+        self
+    }
+}
+
+/// JSON a hook command may print to stdout (exit code 0)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HookOutputJson {
+    /// `"block"` blocks the tool call (PreToolUse) or continues the turn (TurnEnd)
+    pub decision: Option<String>,
+    /// Reason shown to the model when blocking
+    pub reason: Option<String>,
+    /// Replacement tool input (PreToolUse)
+    pub updated_input: Option<Value>,
+    /// Extra context for the next model round
+    pub additional_context: Option<String>,
+}
+
+/// Combined result of all hooks run for an event
+#[derive(Debug, Clone, Default)]
+pub struct HookOutcome {
+    /// Set when a hook blocked; holds the reason
+    pub blocked: Option<String>,
+    /// Rewritten tool input (PreToolUse)
+    pub updated_input: Option<Value>,
+    /// Context to inject into the next model round
+    pub additional_context: Vec<String>,
+}
+
+impl HookOutcome {
+    pub fn is_blocked(&self) -> bool {
+        self.blocked.is_some()
+    }
+
+    /// Context formatted as a system reminder, `None` if there is none
+    pub fn context_reminder(&self) -> Option<String> {
+        format_reminder(&self.additional_context)
+    }
+}
+
+/// Wrap hook-provided text in a `<system-reminder>` block
+pub fn format_reminder(parts: &[String]) -> Option<String> {
+    let text = parts
+        .iter()
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    if text.is_empty() {
+        None
+    } else {
+        Some(format!("<system-reminder>\n{}\n</system-reminder>", text))
+    }
+}
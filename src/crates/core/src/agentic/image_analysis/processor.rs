@@ -293,10 +293,11 @@ impl ImageAnalyzer {
                     tool_calls: None,
                     tool_call_id: None,
                     name: None,
+                    response_id: None,
                 }
             }
-            "anthropic" => {
-                // Anthropic format (content is an array)
+            "anthropic" | "gemini" => {
+                // Anthropic format (content is an array); the Gemini converter maps it to inlineData
                 Message {
                     role: "user".to_string(),
                     content: Some(serde_json::to_string(&json!([
@@ -318,6 +319,7 @@ impl ImageAnalyzer {
                     tool_calls: None,
                     tool_call_id: None,
                     name: None,
+                    response_id: None,
                 }
             }
             _ => {
@@ -19,6 +19,9 @@ pub mod tools;
 // Coordination module
 pub mod coordination;
 
+// Lifecycle hooks module
+pub mod hooks;
+
 // Image analysis module
 pub mod image_analysis;
 
@@ -279,15 +279,7 @@ impl PersistenceManager {
 
                 match self.load_session(&session_id).await {
                     Ok(session) => {
-                        summaries.push(SessionSummary {
-                            session_id: session.session_id.clone(),
-                            session_name: session.session_name.clone(),
-                            agent_type: session.agent_type.clone(),
-                            turn_count: session.dialog_turn_ids.len(),
-                            created_at: session.created_at,
-                            last_activity_at: session.last_activity_at,
-                            state: session.state.clone(),
-                        });
+                        summaries.push(SessionSummary::from(&session));
                     }
                     Err(e) => {
                         warn!(
@@ -523,6 +515,91 @@ impl PersistenceManager {
         Ok(())
     }
 
+    // ============ Session fork ============
+
+    /// Copy the persisted data of `source_session_id` up to `turn_index` (inclusive) into the
+    /// already-built `target` session: turn context snapshots, messages, compressed history and
+    /// dialog turns. Returns the model context at the fork point.
+    pub async fn fork_session(
+        &self,
+        source_session_id: &str,
+        target: &Session,
+        turn_index: usize,
+    ) -> BitFunResult<Vec<Message>> {
+        let context = self
+            .load_turn_context_snapshot(source_session_id, turn_index)
+            .await?
+            .ok_or_else(|| {
+                BitFunError::NotFound(format!(
+                    "turn context snapshot not found: session_id={} turn={}",
+                    source_session_id, turn_index
+                ))
+            })?;
+
+        let target_id = target.session_id.as_str();
+        self.ensure_session_dir(target_id).await?;
+
+        // 1. Turn context snapshots
+        for index in 0..turn_index {
+            if let Some(messages) = self
+                .load_turn_context_snapshot(source_session_id, index)
+                .await?
+            {
+                self.save_turn_context_snapshot(target_id, index, &messages)
+                    .await?;
+            }
+        }
+        self.save_turn_context_snapshot(target_id, turn_index, &context)
+            .await?;
+
+        // 2. Messages of the kept turns
+        let kept_turns: std::collections::HashSet<&str> =
+            target.dialog_turn_ids.iter().map(String::as_str).collect();
+        let messages = self.load_messages(source_session_id).await?;
+        for message in messages.iter().filter(|m| {
+            m.metadata
+                .turn_id
+                .as_deref()
+                .is_none_or(|turn_id| kept_turns.contains(turn_id))
+        }) {
+            self.append_message(target_id, message).await?;
+        }
+
+        // 3. Compressed history is the model context at the fork point
+        if self
+            .load_compressed_messages(source_session_id)
+            .await?
+            .is_some()
+        {
+            self.save_compressed_messages(target_id, &context).await?;
+        }
+
+        // 4. Dialog turns
+        for turn_id in &target.dialog_turn_ids {
+            match self.load_dialog_turn(source_session_id, turn_id).await {
+                Ok(mut turn) => {
+                    turn.session_id = target_id.to_string();
+                    self.save_dialog_turn(&turn).await?;
+                }
+                Err(e) => debug!(
+                    "Dialog turn not persisted, skipping: session_id={}, turn_id={}, error={}",
+                    source_session_id, turn_id, e
+                ),
+            }
+        }
+
+        self.save_session(target).await?;
+
+        info!(
+            "Session data forked: source={}, target={}, turn_index={}, context_messages={}",
+            source_session_id,
+            target_id,
+            turn_index,
+            context.len()
+        );
+        Ok(context)
+    }
+
     // ============ Dialog turn persistence ============
 
     /// Save dialog turn
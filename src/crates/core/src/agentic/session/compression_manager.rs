@@ -6,6 +6,7 @@ use crate::agentic::core::{Message, MessageHelper, MessageRole};
 use crate::agentic::persistence::PersistenceManager;
 use crate::infrastructure::ai::{get_global_ai_client_factory, AIClient};
 use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::token_counter::TokenCounter;
 use crate::util::types::Message as AIMessage;
 use anyhow;
 use dashmap::DashMap;
@@ -127,11 +128,15 @@ impl CompressionManager {
 
     /// Returns (turn_index_to_keep, turns)
     /// If turn_index_to_keep is 0, no compression is needed
+    ///
+    /// Turns are measured with `token_counter`, the tokenizer of the model whose context
+    /// window triggered the compression.
     pub async fn preprocess_turns(
         &self,
         session_id: &str,
         context_window: usize,
         mut messages: Vec<Message>,
+        token_counter: &TokenCounter,
     ) -> BitFunResult<(usize, Vec<TurnWithTokens>)> {
         debug!(
             "Starting session context compression: session_id={}",
@@ -163,7 +168,11 @@ impl CompressionManager {
         let turns_count = turns_messages.len();
         let turns_tokens: Vec<usize> = turns_messages
             .iter_mut()
-            .map(|turn| turn.iter_mut().map(|m| m.get_tokens()).sum::<usize>())
+            .map(|turn| {
+                turn.iter_mut()
+                    .map(|m| m.get_tokens_with(token_counter))
+                    .sum::<usize>()
+            })
             .collect();
         // Print message count and token count for each turn
         {
@@ -271,6 +280,12 @@ impl CompressionManager {
             }
         }
 
+        // The server-side response chain still holds the uncompressed history,
+        // so chained requests must restart from the compressed messages
+        for message in &mut compressed_messages {
+            message.metadata.response_id = None;
+        }
+
         // Update compression history
         self.compressed_histories
             .insert(session_id.to_string(), compressed_messages.clone());
@@ -108,11 +108,12 @@ impl SessionManager {
             )));
         }
 
-        let session = if let Some(id) = session_id {
+        let mut session = if let Some(id) = session_id {
             Session::new_with_id(id, session_name, agent_type.clone(), config)
         } else {
             Session::new(session_name, agent_type.clone(), config)
         };
+        session.workspace_path = current_workspace();
         let session_id = session.session_id.clone();
 
         // 1. Add to memory
@@ -182,6 +183,31 @@ impl SessionManager {
 
     /// Delete session (cascade delete all resources)
     pub async fn delete_session(&self, session_id: &str) -> BitFunResult<()> {
+        // 0. Detach from the parent session's lineage
+        let parent_session_id = match self.get_session(session_id) {
+            Some(session) => session.parent_session_id,
+            None if self.config.enable_persistence => self
+                .persistence_manager
+                .load_session(session_id)
+                .await
+                .ok()
+                .and_then(|session| session.parent_session_id),
+            None => None,
+        };
+        if let Some(parent_session_id) = parent_session_id {
+            if let Err(e) = self
+                .update_persisted_session(&parent_session_id, |parent| {
+                    parent.child_session_ids.retain(|id| id != session_id);
+                })
+                .await
+            {
+                debug!(
+                    "Parent session not updated: parent_session_id={}, error={}",
+                    parent_session_id, e
+                );
+            }
+        }
+
         // 1. Clean up snapshot system resources (including physical snapshot files)
         if let Some(snapshot_manager) = get_global_snapshot_manager() {
             let snapshot_service = snapshot_manager.get_snapshot_service();
@@ -231,7 +257,11 @@ impl SessionManager {
             }
         }
 
-        // 5. Clean up associated Terminal session
+        // 5. Clean up background jobs and the associated Terminal session
+        crate::agentic::tools::implementations::bash_jobs::get_bash_job_manager()
+            .remove_session_jobs(session_id)
+            .await;
+
         use crate::service::terminal::TerminalApi;
         if let Ok(terminal_api) = TerminalApi::from_singleton() {
             let binding = terminal_api.session_manager().binding();
@@ -363,6 +393,16 @@ impl SessionManager {
             context_msg_count
         );
 
+        // Sessions saved before workspaces were recorded belong to the workspace they resume in
+        if session.workspace_path.is_none() {
+            if let Some(workspace_path) = current_workspace() {
+                session.workspace_path = Some(workspace_path);
+                if self.config.enable_persistence {
+                    self.persistence_manager.save_session(&session).await?;
+                }
+            }
+        }
+
         // 4. Add to memory (will overwrite if already exists)
         self.sessions
             .insert(session_id.to_string(), session.clone());
@@ -428,6 +468,149 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Fork a session after `turn_index` (inclusive) into a new, independent session
+    ///
+    /// The fork gets the parent's persisted messages, compressed history and turn context
+    /// snapshots up to that turn. With `fork_snapshots`, the file operation history of those
+    /// turns is copied as well, so each branch can roll back its own changes.
+    pub async fn fork_session(
+        &self,
+        session_id: &str,
+        turn_index: usize,
+        fork_snapshots: bool,
+    ) -> BitFunResult<Session> {
+        if !self.config.enable_persistence {
+            return Err(BitFunError::Validation(
+                "Session forking requires persistence to be enabled".to_string(),
+            ));
+        }
+
+        let source = match self.get_session(session_id) {
+            Some(session) => session,
+            None => self.persistence_manager.load_session(session_id).await?,
+        };
+        if turn_index >= source.dialog_turn_ids.len() {
+            return Err(BitFunError::Validation(format!(
+                "Turn index out of range: session_id={}, turn_index={}, turn_count={}",
+                session_id,
+                turn_index,
+                source.dialog_turn_ids.len()
+            )));
+        }
+
+        // 1. Build the fork and copy persisted data
+        let mut fork = Session::new(
+            format!("{} (fork)", source.session_name),
+            source.agent_type.clone(),
+            source.config.clone(),
+        );
+        fork.dialog_turn_ids = source.dialog_turn_ids[..=turn_index].to_vec();
+        fork.compression_state = source.compression_state.clone();
+        fork.parent_session_id = Some(source.session_id.clone());
+        fork.fork_turn_index = Some(turn_index);
+        fork.active_plan = source.active_plan.clone();
+        fork.workspace_path = source.workspace_path.clone();
+        let fork_id = fork.session_id.clone();
+
+        self.persistence_manager
+            .fork_session(session_id, &fork, turn_index)
+            .await?;
+
+        // 2. Record the child on the parent
+        self.update_persisted_session(session_id, |parent| {
+            parent.child_session_ids.push(fork_id.clone());
+        })
+        .await?;
+
+        // 3. Fork the file operation history
+        if fork_snapshots {
+            if let Some(snapshot_manager) = get_global_snapshot_manager() {
+                match snapshot_manager
+                    .fork_session(session_id, &fork_id, turn_index)
+                    .await
+                {
+                    Ok(count) => debug!(
+                        "Snapshot operations forked: session_id={}, operations={}",
+                        fork_id, count
+                    ),
+                    Err(e) => warn!("Failed to fork snapshot state: {}", e),
+                }
+            } else {
+                debug!("Snapshot system not initialized, skipping snapshot fork");
+            }
+        }
+
+        // 4. Fork the conversation history shown in the UI
+        if let Some(workspace_path) = get_workspace_path() {
+            match ConversationPersistenceManager::new(
+                self.persistence_manager.path_manager().clone(),
+                workspace_path,
+            )
+            .await
+            {
+                Ok(conversation_manager) => {
+                    if let Err(e) = conversation_manager
+                        .fork_session(session_id, &fork_id, &fork.session_name, turn_index)
+                        .await
+                    {
+                        warn!("Failed to fork conversation history: {}", e);
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to create ConversationPersistenceManager: {}", e);
+                }
+            }
+        }
+
+        // 5. Load the fork into memory
+        let fork = self.restore_session(&fork_id).await?;
+
+        info!(
+            "Session forked: parent={}, fork={}, turn_index={}",
+            session_id, fork_id, turn_index
+        );
+
+        Ok(fork)
+    }
+
+    /// Set or clear the plan a session is executing
+    pub async fn set_active_plan(
+        &self,
+        session_id: &str,
+        plan_path: Option<String>,
+    ) -> BitFunResult<()> {
+        debug!(
+            "Setting active plan: session_id={}, plan={:?}",
+            session_id, plan_path
+        );
+        self.update_persisted_session(session_id, |session| {
+            session.active_plan = plan_path;
+            session.updated_at = SystemTime::now();
+        })
+        .await
+    }
+
+    /// Update fields of a session, in memory if loaded, and in storage
+    async fn update_persisted_session(
+        &self,
+        session_id: &str,
+        update: impl FnOnce(&mut Session),
+    ) -> BitFunResult<()> {
+        let session = if let Some(mut session) = self.sessions.get_mut(session_id) {
+            update(&mut session);
+            session.clone()
+        } else {
+            let mut session = self.persistence_manager.load_session(session_id).await?;
+            update(&mut session);
+            session
+        };
+
+        if self.config.enable_persistence {
+            self.persistence_manager.save_session(&session).await?;
+        }
+        Ok(())
+    }
+
     /// List all sessions
     pub async fn list_sessions(&self) -> BitFunResult<Vec<SessionSummary>> {
         if self.config.enable_persistence {
@@ -436,18 +619,7 @@ impl SessionManager {
             let summaries: Vec<_> = self
                 .sessions
                 .iter()
-                .map(|entry| {
-                    let session = entry.value();
-                    SessionSummary {
-                        session_id: session.session_id.clone(),
-                        session_name: session.session_name.clone(),
-                        agent_type: session.agent_type.clone(),
-                        turn_count: session.dialog_turn_ids.len(),
-                        created_at: session.created_at,
-                        last_activity_at: session.last_activity_at,
-                        state: session.state.clone(),
-                    }
-                })
+                .map(|entry| SessionSummary::from(entry.value()))
                 .collect();
             Ok(summaries)
         }
@@ -680,6 +852,7 @@ impl SessionManager {
                 tool_calls: None,
                 tool_call_id: None,
                 name: None,
+                response_id: None,
             },
             Message {
                 role: "user".to_string(),
@@ -689,6 +862,7 @@ impl SessionManager {
                 tool_calls: None,
                 tool_call_id: None,
                 name: None,
+                response_id: None,
             },
         ];
 
@@ -740,8 +914,10 @@ impl SessionManager {
             loop {
                 ticker.tick().await;
 
-                for entry in sessions.iter() {
-                    let session = entry.value();
+                // Clone first so no map guard is held across the save
+                let snapshot: Vec<Session> =
+                    sessions.iter().map(|entry| entry.value().clone()).collect();
+                for session in &snapshot {
                     if let Err(e) = persistence.save_session(session).await {
                         error!(
                             "Failed to auto-save session: session_id={}, error={}",
@@ -798,3 +974,8 @@ impl SessionManager {
         debug!("Cleanup task started");
     }
 }
+
+/// Workspace recorded on sessions created or restored now
+fn current_workspace() -> Option<String> {
+    get_workspace_path().map(|path| Session::workspace_key(&path))
+}
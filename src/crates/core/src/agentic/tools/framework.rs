@@ -2,6 +2,7 @@
 use super::image_context::ImageContextProviderRef;
 use super::pipeline::SubagentParentInfo;
 use crate::util::errors::BitFunResult;
+pub use crate::util::types::ResponseState;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -46,13 +47,6 @@ pub struct ToolOptions {
     pub custom_data: Option<HashMap<String, Value>>,
 }
 
-/// Response state - for model state management like GPT-5
-#[derive(Debug, Clone)]
-pub struct ResponseState {
-    pub previous_response_id: Option<String>,
-    pub conversation_id: Option<String>,
-}
-
 /// Validation result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
@@ -277,8 +277,10 @@ impl AnalyzeImageTool {
                 tool_calls: None,
                 tool_call_id: None,
                 name: None,
+                response_id: None,
             },
-            "anthropic" => Message {
+            // The Gemini converter maps Anthropic image blocks to inlineData
+            "anthropic" | "gemini" => Message {
                 role: "user".to_string(),
                 content: Some(serde_json::to_string(&json!([
                     {
@@ -299,6 +301,7 @@ impl AnalyzeImageTool {
                 tool_calls: None,
                 tool_call_id: None,
                 name: None,
+                response_id: None,
             },
             _ => {
                 return Err(BitFunError::validation(format!(
@@ -666,6 +669,7 @@ Important Notes:
             custom_headers_mode: vision_model.custom_headers_mode.clone(),
             skip_ssl_verify: vision_model.skip_ssl_verify,
             custom_request_body,
+            tokenizer: vision_model.tokenizer.clone(),
         };
 
         let ai_client = Arc::new(AIClient::new(model_config));
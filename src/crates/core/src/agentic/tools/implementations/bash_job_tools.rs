@@ -0,0 +1,268 @@
+//! Companion tools for background Bash jobs
+//!
+//! - BashOutput: read new output of a job
+//! - BashJobs: list jobs, send signals, kill jobs
+
+use super::bash_jobs::{get_bash_job_manager, BashJobInfo, BashJobStatus};
+use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tool_runtime::util::ansi_cleaner::strip_ansi;
+
+/// Maximum characters of output returned per read
+const MAX_OUTPUT_LENGTH: usize = 30000;
+
+fn chat_session_id(context: &ToolUseContext) -> BitFunResult<&str> {
+    context
+        .session_id
+        .as_deref()
+        .ok_or_else(|| BitFunError::tool("session_id is required for background jobs".to_string()))
+}
+
+fn render_status(info: &BashJobInfo) -> String {
+    let status = match info.status {
+        BashJobStatus::Running => "running",
+        BashJobStatus::Completed => "completed",
+        BashJobStatus::Failed => "failed",
+        BashJobStatus::Killed => "killed",
+    };
+    let mut result = format!("<job_id>{}</job_id><status>{}</status>", info.job_id, status);
+    if let Some(exit_code) = info.exit_code {
+        result.push_str(&format!("<exit_code>{}</exit_code>", exit_code));
+    }
+    result
+}
+
+/// BashOutput tool
+pub struct BashOutputTool;
+
+impl BashOutputTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Tool for BashOutputTool {
+    fn name(&self) -> &str {
+        "BashOutput"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(format!(
+            r#"Reads output from a background job started with the Bash tool's `run_in_background` option.
+
+Usage notes:
+  - Returns only the output produced since the last BashOutput call for the same job, along with the job status ("running", "completed", "failed" or "killed") and exit code once finished
+  - Use it to check on dev servers, watchers or long test runs while continuing with other work
+  - If more than {MAX_OUTPUT_LENGTH} characters of new output are available, only the most recent output is returned
+  - Use BashJobs to list jobs or to stop them"#
+        ))
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "job_id": {
+                    "type": "string",
+                    "description": "The ID of the background job, as returned by the Bash tool"
+                }
+            },
+            "required": ["job_id"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        true
+    }
+
+    fn needs_permissions(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let job_id = input
+            .get("job_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| BitFunError::tool("job_id is required".to_string()))?;
+
+        let job_output = get_bash_job_manager().read_output(chat_session_id(context)?, job_id)?;
+
+        let cleaned_output = strip_ansi(&job_output.output);
+        let output_len = cleaned_output.chars().count();
+        let (output, truncated) = if output_len > MAX_OUTPUT_LENGTH {
+            let tail: String = cleaned_output
+                .chars()
+                .skip(output_len - MAX_OUTPUT_LENGTH)
+                .collect();
+            (tail, true)
+        } else {
+            (cleaned_output, job_output.truncated)
+        };
+
+        let mut result_for_assistant = render_status(&job_output.info);
+        if output.is_empty() {
+            result_for_assistant.push_str("<output>(no new output)</output>");
+        } else if truncated {
+            result_for_assistant.push_str(&format!(
+                "<output truncated=\"true\">{}</output>",
+                output
+            ));
+        } else {
+            result_for_assistant.push_str(&format!("<output>{}</output>", output));
+        }
+        for violation in &job_output.sandbox_violations {
+            result_for_assistant.push_str(&format!(
+                "<sandbox_violation>{}</sandbox_violation>",
+                violation
+            ));
+        }
+
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "job": job_output.info,
+                "output": output,
+                "truncated": truncated,
+                "sandbox_violations": job_output.sandbox_violations,
+            }),
+            result_for_assistant: Some(result_for_assistant),
+        }])
+    }
+}
+
+/// BashJobs tool
+pub struct BashJobsTool;
+
+impl BashJobsTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn action(input: Option<&Value>) -> Option<&str> {
+        input?.get("action")?.as_str()
+    }
+}
+
+#[async_trait]
+impl Tool for BashJobsTool {
+    fn name(&self) -> &str {
+        "BashJobs"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(
+            r#"Manages background jobs started with the Bash tool's `run_in_background` option.
+
+Actions:
+  - "list": List the jobs of this session with their status
+  - "signal": Send a signal to a job's foreground process. Supported signals: "SIGINT" (default, like Ctrl+C) and "SIGQUIT"
+  - "kill": Stop a job immediately by closing its terminal. Its output can still be read with BashOutput
+
+Usage notes:
+  - Prefer "signal" with SIGINT to let a process shut down cleanly, and use "kill" if it does not stop
+  - Stop jobs you no longer need, such as dev servers started for a test"#
+                .to_string(),
+        )
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "signal", "kill"],
+                    "description": "The action to perform"
+                },
+                "job_id": {
+                    "type": "string",
+                    "description": "The ID of the background job. Required for signal and kill"
+                },
+                "signal": {
+                    "type": "string",
+                    "enum": ["SIGINT", "SIGQUIT"],
+                    "description": "Signal to send. Default is SIGINT"
+                }
+            },
+            "required": ["action"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        false
+    }
+
+    fn is_concurrency_safe(&self, input: Option<&Value>) -> bool {
+        Self::action(input) == Some("list")
+    }
+
+    fn needs_permissions(&self, input: Option<&Value>) -> bool {
+        Self::action(input) != Some("list")
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let chat_session_id = chat_session_id(context)?;
+        let manager = get_bash_job_manager();
+        let job_id = || {
+            input
+                .get("job_id")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| BitFunError::tool("job_id is required".to_string()))
+        };
+
+        let (data, result_for_assistant) = match Self::action(Some(input)) {
+            Some("list") => {
+                let jobs = manager.list(chat_session_id);
+                let text = if jobs.is_empty() {
+                    "No background jobs.".to_string()
+                } else {
+                    jobs.iter()
+                        .map(|job| format!("{}<command>{}</command>", render_status(job), job.command))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                (json!({ "jobs": jobs }), text)
+            }
+            Some("signal") => {
+                let signal = input
+                    .get("signal")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("SIGINT");
+                if !matches!(signal, "SIGINT" | "SIGQUIT") {
+                    return Err(BitFunError::tool(format!("Unsupported signal: {}", signal)));
+                }
+                let job = manager.signal(chat_session_id, job_id()?, signal).await?;
+                let text = format!("{}<message>Sent {}</message>", render_status(&job), signal);
+                (json!({ "job": job }), text)
+            }
+            Some("kill") => {
+                let job = manager.kill(chat_session_id, job_id()?).await?;
+                (json!({ "job": job }), render_status(&job))
+            }
+            other => {
+                return Err(BitFunError::tool(format!("Unknown action: {:?}", other)));
+            }
+        };
+
+        Ok(vec![ToolResult::Result {
+            data,
+            result_for_assistant: Some(result_for_assistant),
+        }])
+    }
+}
@@ -0,0 +1,439 @@
+//! Background jobs started by the Bash tool
+//!
+//! Each job runs in its own terminal session so the chat session's bound terminal stays
+//! free for foreground commands. Job terminals are registered in `TerminalSessionBinding`
+//! under an owner id derived from the chat session, and are closed with it.
+
+use crate::infrastructure::get_workspace_path;
+use crate::util::errors::{BitFunError, BitFunResult};
+use dashmap::DashMap;
+use futures::StreamExt;
+use log::{debug, warn};
+use serde::Serialize;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use terminal_core::shell::ShellType;
+use terminal_core::{
+    CommandStreamEvent, ExecuteCommandRequest, SandboxPolicy, SignalRequest, TerminalApi,
+    TerminalBindingOptions,
+};
+use tool_runtime::util::ansi_cleaner::strip_ansi;
+
+/// Output kept per job; older output is dropped first
+const MAX_JOB_OUTPUT_BYTES: usize = 1024 * 1024;
+
+static GLOBAL_BASH_JOB_MANAGER: OnceLock<Arc<BashJobManager>> = OnceLock::new();
+
+/// Get the global background job manager
+pub fn get_bash_job_manager() -> Arc<BashJobManager> {
+    GLOBAL_BASH_JOB_MANAGER
+        .get_or_init(|| Arc::new(BashJobManager::new()))
+        .clone()
+}
+
+/// Background job status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BashJobStatus {
+    Running,
+    Completed,
+    Failed,
+    Killed,
+}
+
+/// Serializable view of a job
+#[derive(Debug, Clone, Serialize)]
+pub struct BashJobInfo {
+    pub job_id: String,
+    pub command: String,
+    pub status: BashJobStatus,
+    pub exit_code: Option<i32>,
+    pub terminal_session_id: String,
+    pub working_directory: String,
+    /// Unix timestamp (milliseconds)
+    pub started_at: u64,
+    pub elapsed_ms: u64,
+}
+
+/// New output of a job since the previous read
+#[derive(Debug, Clone)]
+pub struct BashJobOutput {
+    pub info: BashJobInfo,
+    pub output: String,
+    /// Whether output was dropped before it could be read
+    pub truncated: bool,
+    /// Operations blocked by the sandbox, found in the new output
+    pub sandbox_violations: Vec<String>,
+}
+
+struct JobState {
+    status: BashJobStatus,
+    exit_code: Option<i32>,
+    finished_at: Option<Instant>,
+    /// Retained output; `dropped` bytes before it were discarded
+    output: String,
+    dropped: usize,
+    /// Absolute byte offset read so far
+    read_offset: usize,
+}
+
+impl JobState {
+    fn total_len(&self) -> usize {
+        self.dropped + self.output.len()
+    }
+
+    fn push_output(&mut self, data: &str) {
+        self.output.push_str(data);
+        if self.output.len() > MAX_JOB_OUTPUT_BYTES {
+            let mut cut = self.output.len() - MAX_JOB_OUTPUT_BYTES;
+            while !self.output.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.output.drain(..cut);
+            self.dropped += cut;
+        }
+    }
+
+    /// Take output not read yet
+    fn take_unread(&mut self) -> (String, bool) {
+        let truncated = self.read_offset < self.dropped;
+        let start = self.read_offset.saturating_sub(self.dropped);
+        let unread = self.output.get(start..).unwrap_or_default().to_string();
+        self.read_offset = self.total_len();
+        (unread, truncated)
+    }
+
+    fn finish(&mut self, status: BashJobStatus, exit_code: Option<i32>) {
+        // A killed job stays killed when its stream ends afterwards
+        if self.status == BashJobStatus::Running {
+            self.status = status;
+            self.exit_code = exit_code;
+            self.finished_at = Some(Instant::now());
+        }
+    }
+}
+
+struct BashJob {
+    id: String,
+    chat_session_id: String,
+    terminal_session_id: String,
+    command: String,
+    working_directory: String,
+    sandbox: Option<SandboxPolicy>,
+    started_at: SystemTime,
+    started_instant: Instant,
+    state: Mutex<JobState>,
+}
+
+impl BashJob {
+    fn owner_id(&self) -> String {
+        job_owner_id(&self.chat_session_id, &self.id)
+    }
+
+    fn info(&self) -> BashJobInfo {
+        let state = self.state.lock().unwrap();
+        let elapsed = state
+            .finished_at
+            .unwrap_or_else(Instant::now)
+            .duration_since(self.started_instant);
+        BashJobInfo {
+            job_id: self.id.clone(),
+            command: self.command.clone(),
+            status: state.status,
+            exit_code: state.exit_code,
+            terminal_session_id: self.terminal_session_id.clone(),
+            working_directory: self.working_directory.clone(),
+            started_at: self
+                .started_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            elapsed_ms: elapsed.as_millis() as u64,
+        }
+    }
+}
+
+/// Binding owner id of a job terminal
+fn job_owner_id(chat_session_id: &str, job_id: &str) -> String {
+    format!("{}:{}", chat_session_id, job_id)
+}
+
+/// Background job manager
+pub struct BashJobManager {
+    jobs: DashMap<String, Arc<BashJob>>,
+}
+
+impl BashJobManager {
+    fn new() -> Self {
+        Self {
+            jobs: DashMap::new(),
+        }
+    }
+
+    /// Start a command in a new terminal and return immediately
+    ///
+    /// The job starts in the current directory of the chat session's bound terminal,
+    /// falling back to the workspace.
+    pub async fn start(
+        &self,
+        chat_session_id: &str,
+        command: &str,
+        shell_type: Option<ShellType>,
+        sandbox: Option<SandboxPolicy>,
+    ) -> BitFunResult<BashJobInfo> {
+        let terminal_api = TerminalApi::from_singleton()
+            .map_err(|e| BitFunError::tool(format!("Terminal not initialized: {}", e)))?;
+        let binding = terminal_api.session_manager().binding();
+
+        let bound_cwd = match binding.get(chat_session_id) {
+            Some(terminal_session_id) => terminal_api
+                .get_session(&terminal_session_id)
+                .await
+                .ok()
+                .map(|s| s.cwd),
+            None => None,
+        };
+        let working_directory = bound_cwd
+            .filter(|cwd| !cwd.is_empty())
+            .or_else(|| get_workspace_path().map(|p| p.to_string_lossy().to_string()));
+
+        let job_id = format!("job-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let terminal_session_id = binding
+            .get_or_create(
+                &job_owner_id(chat_session_id, &job_id),
+                TerminalBindingOptions {
+                    working_directory: working_directory.clone(),
+                    session_name: Some(format!("Job-{}", &job_id[4..])),
+                    shell_type,
+                    sandbox: sandbox.clone(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to create Terminal session: {}", e)))?;
+
+        let session = terminal_api
+            .get_session(&terminal_session_id)
+            .await
+            .map_err(|e| BitFunError::tool(format!("Terminal session not found: {}", e)))?;
+        let working_directory = Some(session.cwd)
+            .filter(|cwd| !cwd.is_empty())
+            .or(working_directory)
+            .unwrap_or_default();
+
+        let job = Arc::new(BashJob {
+            id: job_id.clone(),
+            chat_session_id: chat_session_id.to_string(),
+            terminal_session_id: terminal_session_id.clone(),
+            command: command.to_string(),
+            working_directory,
+            sandbox,
+            started_at: SystemTime::now(),
+            started_instant: Instant::now(),
+            state: Mutex::new(JobState {
+                status: BashJobStatus::Running,
+                exit_code: None,
+                finished_at: None,
+                output: String::new(),
+                dropped: 0,
+                read_offset: 0,
+            }),
+        });
+        self.jobs.insert(job_id.clone(), job.clone());
+
+        let mut stream = terminal_api.execute_command_stream(ExecuteCommandRequest {
+            session_id: terminal_session_id,
+            command: command.to_string(),
+            timeout_ms: None,
+            prevent_history: Some(true),
+        });
+
+        let task_job = job.clone();
+        tokio::spawn(async move {
+            while let Some(event) = stream.next().await {
+                match event {
+                    CommandStreamEvent::Started { command_id } => {
+                        debug!(
+                            "Background job started: job_id={}, command_id={}",
+                            task_job.id, command_id
+                        );
+                    }
+                    CommandStreamEvent::Output { data } => {
+                        task_job.state.lock().unwrap().push_output(&data);
+                    }
+                    CommandStreamEvent::Completed {
+                        exit_code,
+                        total_output,
+                    } => {
+                        debug!(
+                            "Background job completed: job_id={}, exit_code={:?}",
+                            task_job.id, exit_code
+                        );
+                        let mut state = task_job.state.lock().unwrap();
+                        // The final output may include data not streamed yet
+                        if let Some(rest) = total_output.get(state.total_len()..) {
+                            state.push_output(rest);
+                        }
+                        state.finish(BashJobStatus::Completed, exit_code);
+                        return;
+                    }
+                    CommandStreamEvent::Error { message } => {
+                        warn!(
+                            "Background job failed: job_id={}, error={}",
+                            task_job.id, message
+                        );
+                        let mut state = task_job.state.lock().unwrap();
+                        state.push_output(&format!("\n[{}]\n", message));
+                        state.finish(BashJobStatus::Failed, None);
+                        return;
+                    }
+                }
+            }
+            task_job
+                .state
+                .lock()
+                .unwrap()
+                .finish(BashJobStatus::Completed, None);
+        });
+
+        Ok(job.info())
+    }
+
+    fn get_job(&self, chat_session_id: &str, job_id: &str) -> BitFunResult<Arc<BashJob>> {
+        self.jobs
+            .get(job_id)
+            .map(|job| job.value().clone())
+            .filter(|job| job.chat_session_id == chat_session_id)
+            .ok_or_else(|| BitFunError::tool(format!("Background job not found: {}", job_id)))
+    }
+
+    /// Read output produced since the previous read
+    pub fn read_output(&self, chat_session_id: &str, job_id: &str) -> BitFunResult<BashJobOutput> {
+        let job = self.get_job(chat_session_id, job_id)?;
+        let (output, truncated) = job.state.lock().unwrap().take_unread();
+        let sandbox_violations = job
+            .sandbox
+            .as_ref()
+            .map(|policy| {
+                policy
+                    .detect_violations(&strip_ansi(&output))
+                    .iter()
+                    .map(|violation| violation.describe(policy))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(BashJobOutput {
+            info: job.info(),
+            output,
+            truncated,
+            sandbox_violations,
+        })
+    }
+
+    /// List the jobs of a chat session, oldest first
+    pub fn list(&self, chat_session_id: &str) -> Vec<BashJobInfo> {
+        let mut jobs: Vec<BashJobInfo> = self
+            .jobs
+            .iter()
+            .filter(|job| job.chat_session_id == chat_session_id)
+            .map(|job| job.info())
+            .collect();
+        jobs.sort_by_key(|job| job.started_at);
+        jobs
+    }
+
+    /// Send a signal (e.g. SIGINT) to the job's foreground process
+    pub async fn signal(
+        &self,
+        chat_session_id: &str,
+        job_id: &str,
+        signal: &str,
+    ) -> BitFunResult<BashJobInfo> {
+        let job = self.get_job(chat_session_id, job_id)?;
+        let terminal_api = TerminalApi::from_singleton()
+            .map_err(|e| BitFunError::tool(format!("Terminal not initialized: {}", e)))?;
+        terminal_api
+            .signal(SignalRequest {
+                session_id: job.terminal_session_id.clone(),
+                signal: signal.to_string(),
+            })
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to send {}: {}", signal, e)))?;
+        Ok(job.info())
+    }
+
+    /// Kill a job by closing its terminal; its output stays readable
+    pub async fn kill(&self, chat_session_id: &str, job_id: &str) -> BitFunResult<BashJobInfo> {
+        let job = self.get_job(chat_session_id, job_id)?;
+        job.state
+            .lock()
+            .unwrap()
+            .finish(BashJobStatus::Killed, None);
+        self.close_terminal(&job).await;
+        Ok(job.info())
+    }
+
+    /// Kill and forget all jobs of a chat session
+    pub async fn remove_session_jobs(&self, chat_session_id: &str) {
+        let jobs: Vec<Arc<BashJob>> = self
+            .jobs
+            .iter()
+            .filter(|job| job.chat_session_id == chat_session_id)
+            .map(|job| job.value().clone())
+            .collect();
+
+        for job in jobs {
+            job.state
+                .lock()
+                .unwrap()
+                .finish(BashJobStatus::Killed, None);
+            self.close_terminal(&job).await;
+            self.jobs.remove(&job.id);
+        }
+    }
+
+    async fn close_terminal(&self, job: &BashJob) {
+        let Ok(terminal_api) = TerminalApi::from_singleton() else {
+            return;
+        };
+        let binding = terminal_api.session_manager().binding();
+        if let Err(e) = binding.remove(&job.owner_id()).await {
+            warn!(
+                "Failed to close background job terminal: job_id={}, error={}",
+                job.id, e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BashJobStatus, JobState, MAX_JOB_OUTPUT_BYTES};
+
+    #[test]
+    fn reads_output_incrementally_and_reports_dropped_output() {
+        let mut state = JobState {
+            status: BashJobStatus::Running,
+            exit_code: None,
+            finished_at: None,
+            output: String::new(),
+            dropped: 0,
+            read_offset: 0,
+        };
+
+        state.push_output("compiling\n");
+        assert_eq!(state.take_unread(), ("compiling\n".to_string(), false));
+        state.push_output("ready\n");
+        assert_eq!(state.take_unread(), ("ready\n".to_string(), false));
+        assert_eq!(state.take_unread(), (String::new(), false));
+
+        state.push_output(&"x".repeat(MAX_JOB_OUTPUT_BYTES + 10));
+        let (unread, truncated) = state.take_unread();
+        assert!(truncated);
+        assert_eq!(unread.len(), MAX_JOB_OUTPUT_BYTES);
+
+        state.finish(BashJobStatus::Killed, None);
+        state.finish(BashJobStatus::Completed, Some(0));
+        assert_eq!(state.status, BashJobStatus::Killed);
+    }
+}
@@ -1,19 +1,23 @@
+use super::bash_jobs::get_bash_job_manager;
 use crate::agentic::tools::framework::{
     Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
 };
 use crate::infrastructure::events::event_system::get_global_event_system;
-use crate::infrastructure::get_workspace_path;
+use crate::infrastructure::{get_path_manager_arc, get_workspace_path};
 use crate::service::config::global::get_global_config_service;
+use crate::service::config::types::TerminalSandboxConfig;
 use crate::util::errors::{BitFunError, BitFunResult};
 use crate::util::types::event::ToolExecutionProgressInfo;
 use async_trait::async_trait;
 use futures::StreamExt;
 use log::{debug, error};
 use serde_json::{json, Value};
+use std::path::PathBuf;
 use std::time::Instant;
 use terminal_core::shell::{ShellDetector, ShellType};
 use terminal_core::{
-    CommandStreamEvent, ExecuteCommandRequest, SignalRequest, TerminalApi, TerminalBindingOptions,
+    CommandStreamEvent, ExecuteCommandRequest, SandboxPolicy, SignalRequest, TerminalApi,
+    TerminalBindingOptions,
 };
 use tool_runtime::util::ansi_cleaner::strip_ansi;
 
@@ -102,7 +106,79 @@ impl BashTool {
         }
     }
 
-    fn render_result(&self, output_text: &str, interrupted: bool, exit_code: i32) -> String {
+    /// Build the sandbox policy if the command sandbox is enabled.
+    /// Fails instead of running unsandboxed when the sandbox is unavailable.
+    pub(crate) async fn resolve_sandbox() -> BitFunResult<Option<SandboxPolicy>> {
+        let Some(config) = Self::sandbox_config().await else {
+            return Ok(None);
+        };
+        if !SandboxPolicy::is_supported() {
+            return Err(BitFunError::tool(
+                "Command sandbox is enabled but unavailable: it requires Linux with bubblewrap (bwrap) installed"
+                    .to_string(),
+            ));
+        }
+
+        let default_writable_paths = Self::default_writable_paths();
+        for dir in &default_writable_paths {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                debug!("Failed to create sandbox writable dir {}: {}", dir.display(), e);
+            }
+        }
+        Ok(Some(Self::sandbox_policy(&config, default_writable_paths)))
+    }
+
+    /// Sandbox config, if the command sandbox is enabled
+    async fn sandbox_config() -> Option<TerminalSandboxConfig> {
+        let config = get_global_config_service()
+            .await
+            .ok()?
+            .get_config::<TerminalSandboxConfig>(Some("terminal.sandbox"))
+            .await
+            .unwrap_or_default();
+        config.enabled.then_some(config)
+    }
+
+    /// Writable directories of every sandbox: the workspace and BitFun's temp dirs
+    fn default_writable_paths() -> Vec<PathBuf> {
+        let path_manager = get_path_manager_arc();
+        let mut writable_paths = Vec::new();
+        if let Some(workspace) = get_workspace_path() {
+            writable_paths.push(path_manager.project_temp_dir(&workspace));
+            writable_paths.push(workspace);
+        }
+        writable_paths.push(path_manager.temp_dir());
+        writable_paths
+    }
+
+    fn sandbox_policy(
+        config: &TerminalSandboxConfig,
+        mut writable_paths: Vec<PathBuf>,
+    ) -> SandboxPolicy {
+        let expand = |path: &String| match (path.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => PathBuf::from(path),
+        };
+
+        writable_paths.extend(config.writable_paths.iter().map(expand));
+        let mut policy = SandboxPolicy::new(writable_paths, config.allow_network);
+        // BitFun's own config holds API keys and MCP OAuth tokens
+        policy
+            .hidden_paths
+            .push(get_path_manager_arc().user_config_dir());
+        policy
+            .hidden_paths
+            .extend(config.hidden_paths.iter().map(expand));
+        policy
+    }
+
+    fn render_result(
+        &self,
+        output_text: &str,
+        interrupted: bool,
+        exit_code: i32,
+        sandbox_violations: &[String],
+    ) -> String {
         let mut result_string = String::new();
 
         // Exit code
@@ -123,6 +199,14 @@ impl BashTool {
             }
         }
 
+        // Operations blocked by the sandbox
+        for violation in sandbox_violations {
+            result_string.push_str(&format!(
+                "<sandbox_violation>{}</sandbox_violation>",
+                violation
+            ));
+        }
+
         // Interruption notice
         if interrupted {
             result_string.push_str(
@@ -132,6 +216,43 @@ impl BashTool {
 
         result_string
     }
+
+    /// Start the command as a background job and return its id right away
+    async fn start_background_job(
+        &self,
+        command_str: &str,
+        chat_session_id: &str,
+        sandbox: Option<SandboxPolicy>,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let shell_type = Self::resolve_shell().await.shell_type;
+        let sandboxed = sandbox.is_some();
+        let job = get_bash_job_manager()
+            .start(chat_session_id, command_str, shell_type, sandbox)
+            .await?;
+
+        debug!(
+            "Bash tool started background job: {}, command: {}, session_id: {}",
+            job.job_id, command_str, chat_session_id
+        );
+
+        let result_for_assistant = format!(
+            "<job_id>{}</job_id><status>running</status><message>Command started in the background. Use BashOutput with this job_id to read its output and BashJobs to stop it.</message>",
+            job.job_id
+        );
+
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "success": true,
+                "command": command_str,
+                "background": true,
+                "sandboxed": sandboxed,
+                "job_id": job.job_id,
+                "working_directory": job.working_directory,
+                "terminal_session_id": job.terminal_session_id,
+            }),
+            result_for_assistant: Some(result_for_assistant),
+        }])
+    }
 }
 
 #[async_trait]
@@ -142,12 +263,33 @@ impl Tool for BashTool {
 
     async fn description(&self) -> BitFunResult<String> {
         let shell_info = Self::resolve_shell().await.display_name;
+        // Only describes the policy; directories are created when a command runs
+        let policy = match Self::sandbox_config().await {
+            Some(config) if SandboxPolicy::is_supported() => Some(Self::sandbox_policy(
+                &config,
+                Self::default_writable_paths(),
+            )),
+            _ => None,
+        };
+        let sandbox_info = match policy {
+            Some(policy) => format!(
+                "\nSandbox: Commands run in a sandbox. They can only write to {}, /tmp is private, credential directories are hidden{}. Operations blocked by the sandbox are reported in <sandbox_violation> tags; do not try to work around them.\n",
+                policy
+                    .writable_paths
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                if policy.allow_network { "" } else { " and network access is disabled" }
+            ),
+            None => String::new(),
+        };
 
         Ok(format!(
             r#"Executes a given command in a persistent shell session with optional timeout, ensuring proper handling and security measures.
 
 Shell Environment: {shell_info}
-
+{sandbox_info}
 IMPORTANT: This tool is for terminal operations like git, npm, docker, etc. DO NOT use it for file operations (reading, writing, editing, searching, finding files) - use the specialized tools for this instead.
 
 Before executing the command, please follow these steps:
@@ -172,6 +314,7 @@ Usage notes:
   - You can specify an optional timeout in milliseconds.
   - It is very helpful if you write a clear, concise description of what this command does in 5-10 words.
   - If the output exceeds {MAX_OUTPUT_LENGTH} characters, output will be truncated before being returned to you.
+  - You can use the `run_in_background` parameter to run the command in the background, for example a dev server, a file watcher or a long test run. It returns a job ID immediately; use the BashOutput tool with that ID to read new output and the BashJobs tool to list or stop jobs. Background commands run in their own terminal, start in the current directory of this session's shell and have no timeout. Do not append `&` to run commands in the background.
 
   - Avoid using this tool with the `find`, `grep`, `cat`, `head`, `tail`, `sed`, `awk`, or `echo` commands, unless explicitly instructed or when these commands are truly necessary for the task. Instead, always prefer using the dedicated tools for these commands:
     - File search: Use Glob (NOT find or ls)
@@ -207,6 +350,10 @@ Usage notes:
                     "type": "number",
                     "description": "Optional timeout in milliseconds (max 600000)"
                 },
+                "run_in_background": {
+                    "type": "boolean",
+                    "description": "Set to true to run this command in the background. Use BashOutput to read the output later."
+                },
                 "description": {
                     "type": "string",
                     "description": "Clear, concise description of what this command does in 5-10 words, in active voice. Examples:\nInput: ls\nOutput: List files in current directory\n\nInput: git status\nOutput: Show working tree status\n\nInput: npm install\nOutput: Install package dependencies\n\nInput: mkdir foo\nOutput: Create directory 'foo'"
@@ -323,6 +470,18 @@ Usage notes:
             .unwrap_or_else(|| format!("bash_{}", uuid::Uuid::new_v4()));
         let tool_name = self.name().to_string();
 
+        let sandbox = Self::resolve_sandbox().await?;
+
+        if input
+            .get("run_in_background")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            return self
+                .start_background_job(command_str, chat_session_id, sandbox)
+                .await;
+        }
+
         debug!(
             "Bash tool executing command: {}, session_id: {}, tool_id: {}",
             command_str, chat_session_id, tool_use_id
@@ -350,6 +509,8 @@ Usage notes:
                         &chat_session_id[..8.min(chat_session_id.len())]
                     )),
                     shell_type,
+                    // The whole shell runs in the sandbox, so shell state persists
+                    sandbox: sandbox.clone(),
                     ..Default::default()
                 },
             )
@@ -357,10 +518,10 @@ Usage notes:
             .map_err(|e| BitFunError::tool(format!("Failed to create Terminal session: {}", e)))?;
 
         // Get actual working directory
-        let working_directory = terminal_api
-            .get_session(&terminal_session_id)
-            .await
-            .map(|s| s.cwd)
+        let session_info = terminal_api.get_session(&terminal_session_id).await.ok();
+        let working_directory = session_info
+            .as_ref()
+            .map(|s| s.cwd.clone())
             .unwrap_or_default();
 
         debug!(
@@ -479,6 +640,17 @@ Usage notes:
         // 5. Build result
         let execution_time_ms = start_time.elapsed().as_millis() as u64;
 
+        let sandbox_violations: Vec<String> = sandbox
+            .as_ref()
+            .map(|policy| {
+                policy
+                    .detect_violations(&strip_ansi(&accumulated_output))
+                    .iter()
+                    .map(|violation| violation.describe(policy))
+                    .collect()
+            })
+            .unwrap_or_default();
+
         let result_data = json!({
             "success": final_exit_code.unwrap_or(-1) == 0,
             "command": command_str,
@@ -488,6 +660,8 @@ Usage notes:
             "working_directory": working_directory,
             "execution_time_ms": execution_time_ms,
             "terminal_session_id": terminal_session_id,
+            "sandboxed": sandbox.is_some(),
+            "sandbox_violations": sandbox_violations,
         });
 
         // Generate result for AI
@@ -495,6 +669,7 @@ Usage notes:
             &accumulated_output,
             was_interrupted,
             final_exit_code.unwrap_or(-1),
+            &sandbox_violations,
         );
 
         Ok(vec![ToolResult::Result {
@@ -4,30 +4,10 @@
 
 use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
 use crate::infrastructure::{get_path_manager_arc, get_workspace_path};
+use crate::service::plan::{save_plan, PlanDocument, PlanStep, PlanStepStatus};
 use crate::util::errors::{BitFunError, BitFunResult};
 use async_trait::async_trait;
-use serde::Serialize;
 use serde_json::{json, Value};
-use tokio::fs;
-
-/// YAML frontmatter structure for Plan files
-#[derive(Serialize)]
-struct PlanFrontmatter {
-    name: String,
-    overview: String,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    todos: Vec<TodoItem>,
-}
-
-/// Todo item structure
-#[derive(Serialize)]
-struct TodoItem {
-    id: String,
-    content: String,
-    status: String,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    dependencies: Vec<String>,
-}
 
 /// CreatePlan tool - create plan file
 pub struct CreatePlanTool;
@@ -67,12 +47,13 @@ You should provide a structured list of implementation todos:
 - Each todo needs:
     - A clear, unique ID (e.g., "setup-auth", "implement-ui", "add-tests")
     - A descriptive content explaining what needs to be done
+- When the plan is executed, these IDs are reused as todo IDs so progress is recorded in the plan file
 
 UPDATING THE PLAN:
 - This tool creates a NEW plan file each time it is called
 - The plan file URI will be returned in the tool result
-- To update an existing plan, read and edit the plan file directly using your file editing tools
-- Do NOT call CreatePlan again to update an existing plan
+- To update an existing plan, use the UpdatePlan tool with the plan file path; each update is saved as a new plan version
+- Do NOT call CreatePlan again to update an existing plan, and do not edit the plan file with your file editing tools
 
 Additional guidelines:
 - Avoid asking clarifying questions in the plan itself. Ask them before calling this tool. Present these to the user using the AskUserQuestion tool.
@@ -198,37 +179,19 @@ Additional guidelines:
             .await
             .map_err(|e| BitFunError::tool(format!("Failed to create plans directory: {}", e)))?;
 
-        // Generate file content
-        let file_content = generate_plan_file_content(name, overview, plan, todos);
-
-        // Write file
-        fs::write(&plan_file_path, &file_content)
-            .await
-            .map_err(|e| BitFunError::tool(format!("Failed to write plan file: {}", e)))?;
+        let mut document = PlanDocument::new(name, overview, plan, parse_steps(todos))?;
+        save_plan(&plan_file_path, &mut document).await?;
 
         let plan_file_path_str = plan_file_path.to_string_lossy().to_string();
 
-        // Process todos for return result
-        let processed_todos: Vec<Value> = if let Some(todos_arr) = todos {
-            todos_arr
-                .iter()
-                .map(|todo| {
-                    let mut todo_obj = todo.clone();
-                    if let Some(obj) = todo_obj.as_object_mut() {
-                        // Add default status
-                        if !obj.contains_key("status") {
-                            obj.insert("status".to_string(), json!("pending"));
-                        }
-                    }
-                    todo_obj
-                })
-                .collect()
-        } else {
-            vec![]
-        };
+        let processed_todos: Vec<Value> = document
+            .steps
+            .iter()
+            .map(|step| serde_json::to_value(step).unwrap_or_default())
+            .collect();
 
         let result_for_assistant = format!(
-            "Plan file created at: {}\nYou can read the plan contents from this file. To update the plan, use your file editing tools directly on this file.",
+            "Plan file created at: {}\nYou can read the plan contents from this file. To update the plan, use the UpdatePlan tool with this file path.",
             plan_file_path_str
         );
 
@@ -238,6 +201,7 @@ Additional guidelines:
             "plan_file_name": plan_file_name,
             "name": name,
             "overview": overview,
+            "version": document.version,
             "todos": processed_todos
         });
 
@@ -248,15 +212,9 @@ Additional guidelines:
     }
 }
 
-/// Generate plan file content
-fn generate_plan_file_content(
-    name: &str,
-    overview: &str,
-    plan: &str,
-    todos: Option<&Vec<Value>>,
-) -> String {
-    // Convert todos
-    let todos_vec: Vec<TodoItem> = todos
+/// Convert input todos to plan steps
+pub(crate) fn parse_steps(todos: Option<&Vec<Value>>) -> Vec<PlanStep> {
+    todos
         .map(|arr| {
             arr.iter()
                 .filter_map(|todo| {
@@ -272,25 +230,14 @@ fn generate_plan_file_content(
                         })
                         .unwrap_or_default();
 
-                    Some(TodoItem {
+                    Some(PlanStep {
                         id: id.to_string(),
                         content: content.to_string(),
-                        status: "pending".to_string(),
+                        status: PlanStepStatus::Pending,
                         dependencies,
                     })
                 })
                 .collect()
         })
-        .unwrap_or_default();
-
-    let frontmatter = PlanFrontmatter {
-        name: name.to_string(),
-        overview: overview.to_string(),
-        todos: todos_vec,
-    };
-
-    // Serialize frontmatter using serde_yaml
-    let yaml = serde_yaml::to_string(&frontmatter).unwrap_or_default();
-
-    format!("---\n{}---\n\n{}", yaml, plan)
+        .unwrap_or_default()
 }
@@ -0,0 +1,1249 @@
+//! LSP tool - code navigation through language servers
+//!
+//! Responsibilities:
+//! - Go to definition, find references, hover and symbol outlines
+//! - Address positions by line/column or by symbol name
+//! - Preview rename edits and apply them through the snapshot system
+
+use async_trait::async_trait;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use similar::TextDiff;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::infrastructure::get_workspace_path;
+use crate::service::lsp::file_sync::detect_language;
+use crate::service::lsp::{get_workspace_manager, ServerStatus, WorkspaceLspManager};
+use crate::service::snapshot::{get_global_snapshot_manager, OperationType};
+use crate::util::errors::{BitFunError, BitFunResult};
+
+/// LSP tool
+pub struct LspTool;
+
+impl LspTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Supported operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LspOperation {
+    Definition,
+    References,
+    Hover,
+    Symbols,
+    WorkspaceSymbols,
+    Rename,
+}
+
+/// LSP input parameters
+#[derive(Debug, Deserialize)]
+struct LspInput {
+    operation: LspOperation,
+
+    /// File path (relative to the workspace or absolute)
+    #[serde(default)]
+    path: Option<String>,
+
+    /// Line number (starting from 1)
+    #[serde(default)]
+    line: Option<u32>,
+
+    /// Column number (starting from 1)
+    #[serde(default)]
+    column: Option<u32>,
+
+    /// Symbol name, used instead of (or together with) `line`
+    #[serde(default)]
+    symbol: Option<String>,
+
+    /// New name for `rename`
+    #[serde(default)]
+    new_name: Option<String>,
+
+    /// Apply rename edits instead of previewing them
+    #[serde(default)]
+    apply: bool,
+
+    #[serde(default = "default_max_results")]
+    max_results: usize,
+}
+
+fn default_max_results() -> usize {
+    100
+}
+
+/// A location in a file
+#[derive(Debug, Serialize)]
+struct LocationItem {
+    path: String,
+    /// Line number (starting from 1)
+    line: u32,
+    /// Column number (starting from 1)
+    column: u32,
+    preview: String,
+}
+
+/// A symbol from a document outline or a workspace search
+#[derive(Debug, Clone, Serialize)]
+struct SymbolItem {
+    name: String,
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+    /// Names of enclosing symbols, outermost first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    containers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    /// Line number (starting from 1)
+    line: u32,
+    /// UTF-16 offset in the line, as reported by the server
+    #[serde(skip)]
+    character: u32,
+}
+
+/// A text edit with a 0-based LSP range (UTF-16 character offsets)
+#[derive(Debug, Clone, PartialEq)]
+struct TextEdit {
+    start: (u32, u32),
+    end: (u32, u32),
+    new_text: String,
+}
+
+/// A document opened on its language server
+struct OpenedDocument {
+    path: PathBuf,
+    uri: String,
+    language: String,
+    content: String,
+}
+
+#[async_trait]
+impl Tool for LspTool {
+    fn name(&self) -> &str {
+        "LSP"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(
+            r#"Navigate code semantically through the language server (LSP) of a file's language.
+
+Operations:
+- "definition": Find where the symbol at a position is defined
+- "references": Find all references to the symbol at a position (including its declaration)
+- "hover": Show type information and documentation for the symbol at a position
+- "symbols": List the symbol outline of a file
+- "workspace_symbols": Search symbols by name across the workspace (uses "symbol" as the query)
+- "rename": Rename the symbol at a position across all files. Returns a preview of the edits by default; call again with "apply": true to write them
+
+Addressing a position:
+- "line" and "column" (both starting from 1), OR
+- "symbol" alone: the symbol is looked up in the file outline (qualified names like "Type::method" or "Type.method" are supported), falling back to its first occurrence in the file, OR
+- "line" and "symbol": the first occurrence of the symbol on that line
+
+Usage Guidelines:
+- Prefer this tool over Grep for finding definitions and usages: it resolves re-exports, overloads and shadowed names correctly
+- Paths are relative to the workspace (absolute paths also work)
+- The language server is started on first use; a server that is still indexing may return incomplete results, so retry if a result looks empty
+- Rename edits applied by this tool are recorded in the snapshot system and can be reviewed like other file edits"#
+                .to_string(),
+        )
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["definition", "references", "hover", "symbols", "workspace_symbols", "rename"],
+                    "description": "The navigation operation to perform."
+                },
+                "path": {
+                    "type": "string",
+                    "description": "File to operate on. Required for every operation except workspace_symbols, where it selects the language server."
+                },
+                "line": {
+                    "type": "integer",
+                    "description": "Line number, starting from 1."
+                },
+                "column": {
+                    "type": "integer",
+                    "description": "Column number, starting from 1."
+                },
+                "symbol": {
+                    "type": "string",
+                    "description": "Symbol name to address instead of a column, or the query for workspace_symbols."
+                },
+                "new_name": {
+                    "type": "string",
+                    "description": "New symbol name. Required for rename."
+                },
+                "apply": {
+                    "type": "boolean",
+                    "description": "For rename: write the edits instead of previewing them. Default is false."
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum number of locations or symbols to return. Default is 100."
+                }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        false
+    }
+
+    fn is_concurrency_safe(&self, input: Option<&Value>) -> bool {
+        !input.is_some_and(Self::is_applied_rename)
+    }
+
+    fn needs_permissions(&self, input: Option<&Value>) -> bool {
+        input.is_some_and(Self::is_applied_rename)
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let params: LspInput = serde_json::from_value(input.clone())
+            .map_err(|e| BitFunError::tool(format!("Invalid input: {}", e)))?;
+
+        let workspace = get_workspace_path().ok_or_else(|| {
+            BitFunError::tool("Workspace not set. Please open a workspace first.".to_string())
+        })?;
+
+        let manager = get_workspace_manager(workspace.clone())
+            .await
+            .map_err(|e| {
+                BitFunError::tool(format!(
+                    "LSP manager not found for workspace: {}. Error: {}",
+                    workspace.display(),
+                    e
+                ))
+            })?;
+
+        if params.operation == LspOperation::WorkspaceSymbols {
+            return self.workspace_symbols(&params, &workspace, &manager).await;
+        }
+
+        let raw_path = params.path.as_deref().ok_or_else(|| {
+            BitFunError::tool("`path` is required for this operation".to_string())
+        })?;
+        let doc = self.open_document(raw_path, &workspace, &manager).await?;
+
+        if params.operation == LspOperation::Symbols {
+            let raw = manager
+                .get_document_symbols(&doc.language, &doc.uri)
+                .await
+                .map_err(|e| BitFunError::tool(format!("documentSymbol failed: {}", e)))?;
+            return Ok(self.symbols_result(&params, &doc, &workspace, flatten_symbols(&raw)));
+        }
+
+        let (line, character) = self.resolve_position(&params, &doc, &manager).await?;
+        debug!(
+            "LSP tool resolved position: operation={:?} path={} line={} character={}",
+            params.operation,
+            doc.path.display(),
+            line,
+            character
+        );
+
+        match params.operation {
+            LspOperation::Definition | LspOperation::References => {
+                let raw = if params.operation == LspOperation::Definition {
+                    manager
+                        .goto_definition(&doc.language, &doc.uri, line, character)
+                        .await
+                } else {
+                    manager
+                        .find_references(&doc.language, &doc.uri, line, character)
+                        .await
+                }
+                .map_err(|e| BitFunError::tool(format!("LSP request failed: {}", e)))?;
+                Ok(self.locations_result(&params, &doc, &workspace, line, &raw).await)
+            }
+            LspOperation::Hover => {
+                let raw = manager
+                    .get_hover(&doc.language, &doc.uri, line, character)
+                    .await
+                    .map_err(|e| BitFunError::tool(format!("hover failed: {}", e)))?;
+                let text = raw.get("contents").map(marked_text).unwrap_or_default();
+                let result_for_assistant = if text.trim().is_empty() {
+                    "No hover information at this position.".to_string()
+                } else {
+                    text.clone()
+                };
+                Ok(vec![ToolResult::Result {
+                    data: json!({
+                        "operation": "hover",
+                        "path": relative_display(&doc.path, &workspace),
+                        "line": line + 1,
+                        "hover": text,
+                    }),
+                    result_for_assistant: Some(result_for_assistant),
+                }])
+            }
+            LspOperation::Rename => {
+                self.rename(input, &params, &doc, &workspace, &manager, context, (line, character))
+                    .await
+            }
+            LspOperation::Symbols | LspOperation::WorkspaceSymbols => unreachable!(),
+        }
+    }
+}
+
+impl LspTool {
+    fn is_applied_rename(input: &Value) -> bool {
+        input.get("operation").and_then(|v| v.as_str()) == Some("rename")
+            && input.get("apply").and_then(|v| v.as_bool()).unwrap_or(false)
+    }
+
+    /// Resolve a file, start its language server and open it there
+    async fn open_document(
+        &self,
+        raw_path: &str,
+        workspace: &Path,
+        manager: &Arc<WorkspaceLspManager>,
+    ) -> BitFunResult<OpenedDocument> {
+        let path = if Path::new(raw_path).is_absolute() {
+            PathBuf::from(raw_path)
+        } else {
+            workspace.join(raw_path)
+        };
+        if !path.is_file() {
+            return Err(BitFunError::tool(format!(
+                "File does not exist: {}",
+                path.display()
+            )));
+        }
+
+        let language = detect_language(&path);
+        if language == "plaintext" {
+            return Err(BitFunError::tool(format!(
+                "No language server available for {}",
+                path.display()
+            )));
+        }
+
+        manager
+            .ensure_server_running(&language)
+            .await
+            .map_err(|e| {
+                BitFunError::tool(format!(
+                    "Failed to start {} language server: {}",
+                    language, e
+                ))
+            })?;
+
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to read {}: {}", path.display(), e)))?;
+        let uri = format!("file://{}", path.display());
+        manager
+            .open_document(uri.clone(), language.clone(), content.clone())
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to open document: {}", e)))?;
+
+        Ok(OpenedDocument {
+            path,
+            uri,
+            language,
+            content,
+        })
+    }
+
+    /// Resolve the input to a 0-based LSP position
+    async fn resolve_position(
+        &self,
+        params: &LspInput,
+        doc: &OpenedDocument,
+        manager: &Arc<WorkspaceLspManager>,
+    ) -> BitFunResult<(u32, u32)> {
+        let symbol = params.symbol.as_deref().filter(|s| !s.trim().is_empty());
+
+        if let Some(line) = params.line {
+            let index = line.checked_sub(1).ok_or_else(|| {
+                BitFunError::tool("`line` starts from 1".to_string())
+            })?;
+            let text = doc.content.lines().nth(index as usize).ok_or_else(|| {
+                BitFunError::tool(format!(
+                    "Line {} is out of range ({} lines)",
+                    line,
+                    doc.content.lines().count()
+                ))
+            })?;
+
+            let character = if let Some(column) = params.column {
+                column_to_utf16(text, column)
+            } else if let Some(symbol) = symbol {
+                let name = symbol_parts(symbol).pop().unwrap_or(symbol);
+                let byte = find_word(text, name, 0).ok_or_else(|| {
+                    BitFunError::tool(format!("Symbol `{}` not found on line {}", name, line))
+                })?;
+                byte_to_utf16(text, byte)
+            } else {
+                byte_to_utf16(text, text.len() - text.trim_start().len())
+            };
+            return Ok((index, character));
+        }
+
+        let symbol = symbol.ok_or_else(|| {
+            BitFunError::tool("Provide `line` (and optionally `column`) or `symbol`".to_string())
+        })?;
+
+        let outline = match manager.get_document_symbols(&doc.language, &doc.uri).await {
+            Ok(raw) => flatten_symbols(&raw),
+            Err(e) => {
+                warn!("documentSymbol failed, searching file text instead: {}", e);
+                Vec::new()
+            }
+        };
+        if let Some(item) = find_symbol(&outline, symbol) {
+            // Some servers report the start of the declaration rather than the name
+            let character = doc
+                .content
+                .lines()
+                .nth(item.line as usize - 1)
+                .and_then(|text| {
+                    let from = utf16_to_byte(text, item.character);
+                    find_word(text, &item.name, from).map(|byte| byte_to_utf16(text, byte))
+                })
+                .unwrap_or(item.character);
+            return Ok((item.line - 1, character));
+        }
+
+        let name = symbol_parts(symbol).pop().unwrap_or(symbol);
+        for (index, text) in doc.content.lines().enumerate() {
+            if let Some(byte) = find_word(text, name, 0) {
+                return Ok((index as u32, byte_to_utf16(text, byte)));
+            }
+        }
+
+        Err(BitFunError::tool(format!(
+            "Symbol `{}` not found in {}",
+            symbol,
+            doc.path.display()
+        )))
+    }
+
+    async fn locations_result(
+        &self,
+        params: &LspInput,
+        doc: &OpenedDocument,
+        workspace: &Path,
+        line: u32,
+        raw: &Value,
+    ) -> Vec<ToolResult> {
+        let mut locations = collect_locations(raw);
+        let total = locations.len();
+        locations.truncate(params.max_results);
+
+        let mut contents: HashMap<PathBuf, Option<String>> = HashMap::new();
+        contents.insert(doc.path.clone(), Some(doc.content.clone()));
+
+        let mut items = Vec::new();
+        for (uri, (loc_line, loc_character)) in locations {
+            let path = uri_to_path(&uri).unwrap_or_else(|| PathBuf::from(&uri));
+            if !contents.contains_key(&path) {
+                let content = tokio::fs::read_to_string(&path).await.ok();
+                contents.insert(path.clone(), content);
+            }
+            let text = contents
+                .get(&path)
+                .and_then(|c| c.as_deref())
+                .and_then(|c| c.lines().nth(loc_line as usize))
+                .unwrap_or("");
+            items.push(LocationItem {
+                path: relative_display(&path, workspace),
+                line: loc_line + 1,
+                column: utf16_to_column(text, loc_character),
+                preview: text.trim().to_string(),
+            });
+        }
+
+        let what = if params.operation == LspOperation::Definition {
+            "definition"
+        } else {
+            "reference"
+        };
+        let result_for_assistant = if items.is_empty() {
+            format!("No {}s found.", what)
+        } else {
+            let mut text = format!(
+                "Found {} {}{}:\n",
+                total,
+                what,
+                if total == 1 { "" } else { "s" }
+            );
+            for item in &items {
+                text.push_str(&format!(
+                    "{}:{}:{}: {}\n",
+                    item.path, item.line, item.column, item.preview
+                ));
+            }
+            if total > items.len() {
+                text.push_str(&format!("... and {} more\n", total - items.len()));
+            }
+            text
+        };
+
+        vec![ToolResult::Result {
+            data: json!({
+                "operation": what,
+                "path": relative_display(&doc.path, workspace),
+                "line": line + 1,
+                "total": total,
+                "locations": items,
+            }),
+            result_for_assistant: Some(result_for_assistant),
+        }]
+    }
+
+    fn symbols_result(
+        &self,
+        params: &LspInput,
+        doc: &OpenedDocument,
+        workspace: &Path,
+        symbols: Vec<SymbolItem>,
+    ) -> Vec<ToolResult> {
+        let total = symbols.len();
+        let symbols: Vec<SymbolItem> = symbols.into_iter().take(params.max_results).collect();
+        let path = relative_display(&doc.path, workspace);
+
+        let mut text = if symbols.is_empty() {
+            format!("No symbols found in {}.", path)
+        } else {
+            format!("Symbols in {}:\n", path)
+        };
+        for symbol in &symbols {
+            text.push_str(&format!(
+                "{}{} {} (line {}){}\n",
+                "  ".repeat(symbol.containers.len()),
+                symbol.kind,
+                symbol.name,
+                symbol.line,
+                symbol
+                    .detail
+                    .as_deref()
+                    .filter(|d| !d.is_empty())
+                    .map(|d| format!(" - {}", d))
+                    .unwrap_or_default()
+            ));
+        }
+        if total > symbols.len() {
+            text.push_str(&format!("... and {} more\n", total - symbols.len()));
+        }
+
+        vec![ToolResult::Result {
+            data: json!({
+                "operation": "symbols",
+                "path": path,
+                "total": total,
+                "symbols": symbols,
+            }),
+            result_for_assistant: Some(text),
+        }]
+    }
+
+    async fn workspace_symbols(
+        &self,
+        params: &LspInput,
+        workspace: &Path,
+        manager: &Arc<WorkspaceLspManager>,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let query = params
+            .symbol
+            .as_deref()
+            .filter(|s| !s.trim().is_empty())
+            .ok_or_else(|| {
+                BitFunError::tool("`symbol` is required for workspace_symbols".to_string())
+            })?;
+
+        let languages = match params.path.as_deref() {
+            Some(raw_path) => vec![self.open_document(raw_path, workspace, manager).await?.language],
+            None => manager
+                .get_all_server_states()
+                .await
+                .into_iter()
+                .filter(|(_, state)| state.status == ServerStatus::Running)
+                .map(|(language, _)| language)
+                .collect(),
+        };
+        if languages.is_empty() {
+            return Err(BitFunError::tool(
+                "No language server is running. Pass `path` of a file in the target language to start one."
+                    .to_string(),
+            ));
+        }
+
+        let mut symbols = Vec::new();
+        for language in &languages {
+            match manager.workspace_symbols(language, query).await {
+                Ok(raw) => symbols.extend(flatten_symbols(&raw)),
+                Err(e) => warn!("workspace/symbol failed: language={} error={}", language, e),
+            }
+        }
+        for symbol in &mut symbols {
+            if let Some(path) = symbol.path.take() {
+                let path = uri_to_path(&path).unwrap_or_else(|| PathBuf::from(&path));
+                symbol.path = Some(relative_display(&path, workspace));
+            }
+        }
+        symbols.dedup_by(|a, b| a.name == b.name && a.path == b.path && a.line == b.line);
+
+        let total = symbols.len();
+        symbols.truncate(params.max_results);
+
+        let mut text = if symbols.is_empty() {
+            format!("No workspace symbols match `{}`.", query)
+        } else {
+            format!("Found {} symbols matching `{}`:\n", total, query)
+        };
+        for symbol in &symbols {
+            let container = symbol
+                .containers
+                .last()
+                .map(|c| format!(" in {}", c))
+                .unwrap_or_default();
+            text.push_str(&format!(
+                "{} {}{} - {}:{}\n",
+                symbol.kind,
+                symbol.name,
+                container,
+                symbol.path.as_deref().unwrap_or("?"),
+                symbol.line
+            ));
+        }
+        if total > symbols.len() {
+            text.push_str(&format!("... and {} more\n", total - symbols.len()));
+        }
+
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "operation": "workspace_symbols",
+                "query": query,
+                "total": total,
+                "symbols": symbols,
+            }),
+            result_for_assistant: Some(text),
+        }])
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn rename(
+        &self,
+        input: &Value,
+        params: &LspInput,
+        doc: &OpenedDocument,
+        workspace: &Path,
+        manager: &Arc<WorkspaceLspManager>,
+        context: &ToolUseContext,
+        (line, character): (u32, u32),
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let new_name = params
+            .new_name
+            .as_deref()
+            .filter(|n| !n.trim().is_empty())
+            .ok_or_else(|| BitFunError::tool("`new_name` is required for rename".to_string()))?;
+
+        let raw = manager
+            .rename(&doc.language, &doc.uri, line, character, new_name)
+            .await
+            .map_err(|e| BitFunError::tool(format!("rename failed: {}", e)))?;
+        if raw.is_null() {
+            return Err(BitFunError::tool(
+                "The language server returned no edits for this rename".to_string(),
+            ));
+        }
+
+        // Compute every new file content before touching the disk
+        let mut files = Vec::new();
+        for (uri, edits) in parse_workspace_edit(&raw)? {
+            let path = uri_to_path(&uri)
+                .ok_or_else(|| BitFunError::tool(format!("Unsupported document URI: {}", uri)))?;
+            let old_content = if path == doc.path {
+                doc.content.clone()
+            } else {
+                tokio::fs::read_to_string(&path).await.map_err(|e| {
+                    BitFunError::tool(format!("Failed to read {}: {}", path.display(), e))
+                })?
+            };
+            let new_content = apply_text_edits(&old_content, &edits)?;
+            files.push((uri, path, edits.len(), old_content, new_content));
+        }
+
+        let outside: Vec<String> = files
+            .iter()
+            .filter(|(_, path, ..)| !path.starts_with(workspace))
+            .map(|(_, path, ..)| path.display().to_string())
+            .collect();
+
+        let edit_count: usize = files.iter().map(|(_, _, count, ..)| count).sum();
+        let mut diffs = String::new();
+        let mut file_summaries = Vec::new();
+        for (_, path, count, old_content, new_content) in &files {
+            let relative = relative_display(path, workspace);
+            diffs.push_str(
+                &TextDiff::from_lines(old_content, new_content)
+                    .unified_diff()
+                    .context_radius(1)
+                    .header(&relative, &relative)
+                    .to_string(),
+            );
+            file_summaries.push(json!({ "path": relative, "edits": count }));
+        }
+
+        if !params.apply {
+            let mut text = format!(
+                "Rename preview: {} edits in {} files (not applied). Call again with \"apply\": true to write them.\n",
+                edit_count,
+                files.len()
+            );
+            if !outside.is_empty() {
+                text.push_str(&format!(
+                    "Warning: the rename touches files outside the workspace and cannot be applied: {}\n",
+                    outside.join(", ")
+                ));
+            }
+            text.push('\n');
+            text.push_str(&diffs);
+
+            return Ok(vec![ToolResult::Result {
+                data: json!({
+                    "operation": "rename",
+                    "applied": false,
+                    "new_name": new_name,
+                    "files": file_summaries,
+                    "diff": diffs,
+                }),
+                result_for_assistant: Some(text),
+            }]);
+        }
+
+        if !outside.is_empty() {
+            return Err(BitFunError::tool(format!(
+                "Rename touches files outside the workspace: {}",
+                outside.join(", ")
+            )));
+        }
+
+        let snapshot_service = get_global_snapshot_manager().map(|m| m.get_snapshot_service());
+        let turn_index = context
+            .options
+            .as_ref()
+            .and_then(|opts| opts.custom_data.as_ref())
+            .and_then(|data| data.get("turn_index"))
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(0);
+
+        for (index, (uri, path, _, _, new_content)) in files.into_iter().enumerate() {
+            let snapshot = match (&snapshot_service, &context.session_id) {
+                (Some(service), Some(session_id)) => {
+                    // The first file keeps the tool call id so the tool card links to its diff
+                    let operation_id = context.tool_call_id.as_ref().map(|id| {
+                        if index == 0 {
+                            id.clone()
+                        } else {
+                            format!("{}_{}", id, index)
+                        }
+                    });
+                    let operation_id = service
+                        .read()
+                        .await
+                        .intercept_file_modification(
+                            session_id,
+                            turn_index,
+                            self.name(),
+                            input.clone(),
+                            &path,
+                            OperationType::Modify,
+                            operation_id,
+                        )
+                        .await
+                        .map_err(|e| BitFunError::tool(e.to_string()))?;
+                    Some((service, session_id, operation_id))
+                }
+                _ => None,
+            };
+
+            let start_time = std::time::Instant::now();
+            tokio::fs::write(&path, &new_content).await.map_err(|e| {
+                BitFunError::tool(format!("Failed to write {}: {}", path.display(), e))
+            })?;
+
+            if let Some((service, session_id, operation_id)) = snapshot {
+                service
+                    .read()
+                    .await
+                    .complete_file_modification(
+                        session_id,
+                        &operation_id,
+                        start_time.elapsed().as_millis() as u64,
+                    )
+                    .await
+                    .map_err(|e| BitFunError::tool(e.to_string()))?;
+            }
+
+            if manager.is_document_opened(&uri).await {
+                if let Err(e) = manager.change_document(uri, new_content).await {
+                    warn!("Failed to sync renamed document to LSP: {}", e);
+                }
+            }
+        }
+
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "operation": "rename",
+                "applied": true,
+                "new_name": new_name,
+                "files": file_summaries,
+                "diff": diffs,
+            }),
+            result_for_assistant: Some(format!(
+                "Renamed to `{}`: applied {} edits in {} files.\n\n{}",
+                new_name,
+                edit_count,
+                file_summaries.len(),
+                diffs
+            )),
+        }])
+    }
+}
+
+fn relative_display(path: &Path, workspace: &Path) -> String {
+    path.strip_prefix(workspace)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Convert a `file://` URI to a path, decoding percent escapes
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+
+    let bytes = rest.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 3 <= bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    let decoded = String::from_utf8_lossy(&decoded).to_string();
+
+    // Windows URIs look like file:///C:/path
+    let decoded = match decoded.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => decoded[1..].to_string(),
+        _ => decoded,
+    };
+    Some(PathBuf::from(decoded))
+}
+
+/// Byte offset in `line` of a UTF-16 offset, clamped to the line end
+fn utf16_to_byte(line: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (index, ch) in line.char_indices() {
+        if units >= character {
+            return index;
+        }
+        units += ch.len_utf16() as u32;
+    }
+    line.len()
+}
+
+fn byte_to_utf16(line: &str, byte: usize) -> u32 {
+    line[..byte].encode_utf16().count() as u32
+}
+
+/// 1-based character column of a UTF-16 offset
+fn utf16_to_column(line: &str, character: u32) -> u32 {
+    line[..utf16_to_byte(line, character)].chars().count() as u32 + 1
+}
+
+/// UTF-16 offset of a 1-based character column
+fn column_to_utf16(line: &str, column: u32) -> u32 {
+    line.chars()
+        .take(column.saturating_sub(1) as usize)
+        .map(|ch| ch.len_utf16() as u32)
+        .sum()
+}
+
+fn is_ident_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '$'
+}
+
+/// Byte offset of the first whole-word occurrence of `word` at or after `from`
+fn find_word(text: &str, word: &str, from: usize) -> Option<usize> {
+    if word.is_empty() || from > text.len() {
+        return None;
+    }
+    let check_start = word.starts_with(is_ident_char);
+    let check_end = word.ends_with(is_ident_char);
+
+    let mut offset = from;
+    while let Some(found) = text[offset..].find(word) {
+        let start = offset + found;
+        let end = start + word.len();
+        let before_ok = !check_start || !text[..start].ends_with(is_ident_char);
+        let after_ok = !check_end || !text[end..].starts_with(is_ident_char);
+        if before_ok && after_ok {
+            return Some(start);
+        }
+        offset = start + word.chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+/// Split `Type::method` or `Type.method` into its parts
+fn symbol_parts(symbol: &str) -> Vec<&str> {
+    symbol
+        .split(['.', ':'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Find a symbol by (optionally qualified) name in a document outline
+fn find_symbol<'a>(symbols: &'a [SymbolItem], symbol: &str) -> Option<&'a SymbolItem> {
+    let mut parts = symbol_parts(symbol);
+    let name = parts.pop()?;
+    let name_matches = |item: &SymbolItem| {
+        item.name == name
+            || item
+                .name
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.starts_with('('))
+    };
+
+    symbols.iter().find(|item| {
+        name_matches(item)
+            && parts.iter().all(|qualifier| {
+                item.containers
+                    .iter()
+                    .any(|container| find_word(container, qualifier, 0).is_some())
+            })
+    })
+}
+
+fn symbol_kind_name(kind: u64) -> &'static str {
+    match kind {
+        1 => "File",
+        2 => "Module",
+        3 => "Namespace",
+        4 => "Package",
+        5 => "Class",
+        6 => "Method",
+        7 => "Property",
+        8 => "Field",
+        9 => "Constructor",
+        10 => "Enum",
+        11 => "Interface",
+        12 => "Function",
+        13 => "Variable",
+        14 => "Constant",
+        15 => "String",
+        16 => "Number",
+        17 => "Boolean",
+        18 => "Array",
+        19 => "Object",
+        20 => "Key",
+        21 => "Null",
+        22 => "EnumMember",
+        23 => "Struct",
+        24 => "Event",
+        25 => "Operator",
+        26 => "TypeParameter",
+        _ => "Symbol",
+    }
+}
+
+fn range_start(range: Option<&Value>) -> Option<(u32, u32)> {
+    let start = range?.get("start")?;
+    Some((
+        start.get("line")?.as_u64()? as u32,
+        start.get("character")?.as_u64()? as u32,
+    ))
+}
+
+/// Flatten `DocumentSymbol[]` (hierarchical) or `SymbolInformation[]`/`WorkspaceSymbol[]` (flat)
+fn flatten_symbols(raw: &Value) -> Vec<SymbolItem> {
+    fn visit(value: &Value, containers: &mut Vec<String>, out: &mut Vec<SymbolItem>) {
+        let Some(name) = value.get("name").and_then(|n| n.as_str()) else {
+            return;
+        };
+        let kind = symbol_kind_name(value.get("kind").and_then(|k| k.as_u64()).unwrap_or(0));
+
+        let (path, start) = match value.get("location") {
+            // SymbolInformation / WorkspaceSymbol
+            Some(location) => (
+                location.get("uri").and_then(|u| u.as_str()).map(str::to_string),
+                range_start(location.get("range")).unwrap_or((0, 0)),
+            ),
+            // DocumentSymbol
+            None => (
+                None,
+                range_start(value.get("selectionRange"))
+                    .or_else(|| range_start(value.get("range")))
+                    .unwrap_or((0, 0)),
+            ),
+        };
+
+        let mut item_containers = containers.clone();
+        if let Some(container) = value
+            .get("containerName")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+        {
+            item_containers.push(container.to_string());
+        }
+
+        out.push(SymbolItem {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            detail: value
+                .get("detail")
+                .and_then(|d| d.as_str())
+                .map(str::to_string),
+            containers: item_containers,
+            path,
+            line: start.0 + 1,
+            character: start.1,
+        });
+
+        if let Some(children) = value.get("children").and_then(|c| c.as_array()) {
+            containers.push(name.to_string());
+            for child in children {
+                visit(child, containers, out);
+            }
+            containers.pop();
+        }
+    }
+
+    let mut out = Vec::new();
+    for value in raw.as_array().into_iter().flatten() {
+        visit(value, &mut Vec::new(), &mut out);
+    }
+    out
+}
+
+/// Normalize `Location | Location[] | LocationLink[] | null` to `(uri, start)` pairs
+fn collect_locations(raw: &Value) -> Vec<(String, (u32, u32))> {
+    let values: Vec<&Value> = match raw {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![raw],
+        _ => Vec::new(),
+    };
+
+    values
+        .into_iter()
+        .filter_map(|value| {
+            if let Some(uri) = value.get("targetUri").and_then(|u| u.as_str()) {
+                let start = range_start(value.get("targetSelectionRange"))
+                    .or_else(|| range_start(value.get("targetRange")))?;
+                Some((uri.to_string(), start))
+            } else {
+                let uri = value.get("uri").and_then(|u| u.as_str())?;
+                Some((uri.to_string(), range_start(value.get("range"))?))
+            }
+        })
+        .collect()
+}
+
+/// Render hover `contents` (MarkupContent, MarkedString or MarkedString[])
+fn marked_text(contents: &Value) -> String {
+    match contents {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(marked_text)
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        Value::Object(object) => {
+            let value = object.get("value").and_then(|v| v.as_str()).unwrap_or("");
+            match object.get("language").and_then(|l| l.as_str()) {
+                Some(language) => format!("```{}\n{}\n```", language, value),
+                None => value.to_string(),
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+fn parse_text_edit(value: &Value) -> Option<TextEdit> {
+    let range = value.get("range")?;
+    let end = range.get("end")?;
+    Some(TextEdit {
+        start: range_start(Some(range))?,
+        end: (
+            end.get("line")?.as_u64()? as u32,
+            end.get("character")?.as_u64()? as u32,
+        ),
+        new_text: value.get("newText")?.as_str()?.to_string(),
+    })
+}
+
+/// Group the text edits of a `WorkspaceEdit` by document URI
+fn parse_workspace_edit(raw: &Value) -> BitFunResult<BTreeMap<String, Vec<TextEdit>>> {
+    let invalid = || BitFunError::tool("Invalid text edit in WorkspaceEdit".to_string());
+    let mut files: BTreeMap<String, Vec<TextEdit>> = BTreeMap::new();
+
+    if let Some(document_changes) = raw.get("documentChanges").and_then(|c| c.as_array()) {
+        for change in document_changes {
+            if let Some(kind) = change.get("kind").and_then(|k| k.as_str()) {
+                return Err(BitFunError::tool(format!(
+                    "Rename requires a `{}` file operation, which is not supported",
+                    kind
+                )));
+            }
+            let uri = change
+                .get("textDocument")
+                .and_then(|d| d.get("uri"))
+                .and_then(|u| u.as_str())
+                .ok_or_else(invalid)?;
+            let edits = files.entry(uri.to_string()).or_default();
+            for edit in change.get("edits").and_then(|e| e.as_array()).into_iter().flatten() {
+                edits.push(parse_text_edit(edit).ok_or_else(invalid)?);
+            }
+        }
+    } else if let Some(changes) = raw.get("changes").and_then(|c| c.as_object()) {
+        for (uri, edits) in changes {
+            let file_edits = files.entry(uri.clone()).or_default();
+            for edit in edits.as_array().into_iter().flatten() {
+                file_edits.push(parse_text_edit(edit).ok_or_else(invalid)?);
+            }
+        }
+    }
+
+    files.retain(|_, edits| !edits.is_empty());
+    if files.is_empty() {
+        return Err(BitFunError::tool(
+            "The language server returned no edits for this rename".to_string(),
+        ));
+    }
+    Ok(files)
+}
+
+/// Apply LSP text edits (positions in UTF-16 units) to a document
+fn apply_text_edits(content: &str, edits: &[TextEdit]) -> BitFunResult<String> {
+    let mut line_starts = vec![0];
+    line_starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+
+    let offset = |(line, character): (u32, u32)| -> BitFunResult<usize> {
+        let start = *line_starts.get(line as usize).ok_or_else(|| {
+            BitFunError::tool(format!("Edit position is past the end of the file (line {})", line + 1))
+        })?;
+        let end = line_starts
+            .get(line as usize + 1)
+            .map(|next| next - 1)
+            .unwrap_or(content.len());
+        let text = content[start..end].trim_end_matches('\r');
+        Ok(start + utf16_to_byte(text, character))
+    };
+
+    let mut ranges = edits
+        .iter()
+        .map(|edit| Ok((offset(edit.start)?, offset(edit.end)?, edit.new_text.as_str())))
+        .collect::<BitFunResult<Vec<_>>>()?;
+    ranges.sort_by_key(|(start, end, _)| (*start, *end));
+
+    let mut result = String::with_capacity(content.len());
+    let mut cursor = 0;
+    for (start, end, new_text) in ranges {
+        if start < cursor || end < start {
+            return Err(BitFunError::tool("Overlapping text edits in WorkspaceEdit".to_string()));
+        }
+        result.push_str(&content[cursor..start]);
+        result.push_str(new_text);
+        cursor = end;
+    }
+    result.push_str(&content[cursor..]);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_text_edits, find_word, parse_workspace_edit, uri_to_path};
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    #[test]
+    fn applies_rename_edits_from_workspace_edit() {
+        let raw = json!({
+            "documentChanges": [{
+                "textDocument": { "uri": "file:///ws/src/lib.rs", "version": 1 },
+                "edits": [
+                    { "range": { "start": { "line": 1, "character": 10 }, "end": { "line": 1, "character": 13 } }, "newText": "bar" },
+                    { "range": { "start": { "line": 0, "character": 3 }, "end": { "line": 0, "character": 6 } }, "newText": "bar" }
+                ]
+            }]
+        });
+        let files = parse_workspace_edit(&raw).unwrap();
+        let edits = &files["file:///ws/src/lib.rs"];
+
+        // "é" and "😀" take one and two UTF-16 units respectively
+        let content = "fn foo() {}\r\nlet é😀 = foo();\n";
+        assert_eq!(
+            apply_text_edits(content, edits).unwrap(),
+            "fn bar() {}\r\nlet é😀 = bar();\n"
+        );
+    }
+
+    #[test]
+    fn parses_changes_map_and_rejects_file_operations() {
+        let raw = json!({
+            "changes": {
+                "file:///ws/a.ts": [
+                    { "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 1 } }, "newText": "b" }
+                ],
+                "file:///ws/empty.ts": []
+            }
+        });
+        let files = parse_workspace_edit(&raw).unwrap();
+        assert_eq!(files.keys().collect::<Vec<_>>(), vec!["file:///ws/a.ts"]);
+
+        let raw = json!({ "documentChanges": [{ "kind": "rename", "oldUri": "file:///a", "newUri": "file:///b" }] });
+        assert!(parse_workspace_edit(&raw).is_err());
+    }
+
+    #[test]
+    fn finds_whole_words_and_decodes_uris() {
+        assert_eq!(find_word("foo_bar foo(x)", "foo", 0), Some(8));
+        assert_eq!(find_word("foobar", "foo", 0), None);
+        assert_eq!(
+            uri_to_path("file:///ws/my%20dir/a.rs"),
+            Some(PathBuf::from("/ws/my dir/a.rs"))
+        );
+        assert_eq!(
+            uri_to_path("file:///C%3A/ws/a.rs"),
+            Some(PathBuf::from("C:/ws/a.rs"))
+        );
+        assert_eq!(
+            uri_to_path("file:///ws/a%23"),
+            Some(PathBuf::from("/ws/a#"))
+        );
+        assert_eq!(
+            uri_to_path("file:///ws/a%2"),
+            Some(PathBuf::from("/ws/a%2"))
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! Memory tool - lets agents search, create, update and delete memory points
+//!
+//! Memory points live in the user store (shared by all workspaces) or the project store
+//! (`.bitfun/ai_memories.json` in the workspace). The relevant ones are injected into the
+//! system prompt of later turns.
+
+use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::infrastructure::{get_workspace_path, try_get_path_manager_arc};
+use crate::service::ai_memory::recall::bm25_scores;
+use crate::service::ai_memory::{
+    AIMemory, AIMemoryManager, MemoryScope, MemoryStorage, MemoryType,
+};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+/// Maximum number of memory points returned by a search
+const MAX_SEARCH_RESULTS: usize = 20;
+
+/// Prefix of the source recorded on memory points written by an agent
+const AGENT_SOURCE_PREFIX: &str = "Agent ";
+
+/// Memory tool
+pub struct MemoryTool;
+
+impl MemoryTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn action(input: Option<&Value>) -> Option<&str> {
+        input?.get("action")?.as_str()
+    }
+
+    fn parse_scope(input: &Value) -> BitFunResult<Option<MemoryScope>> {
+        match input.get("scope").and_then(|v| v.as_str()) {
+            None => Ok(None),
+            Some("user") => Ok(Some(MemoryScope::User)),
+            Some("project") => Ok(Some(MemoryScope::Project)),
+            Some(other) => Err(BitFunError::tool(format!("Unknown scope: {}", other))),
+        }
+    }
+
+    fn parse_type(input: &Value) -> BitFunResult<Option<MemoryType>> {
+        input
+            .get("type")
+            .filter(|v| !v.is_null())
+            .map(|v| {
+                serde_json::from_value::<MemoryType>(v.clone())
+                    .map_err(|_| BitFunError::tool(format!("Unknown memory type: {}", v)))
+            })
+            .transpose()
+    }
+
+    fn parse_tags(input: &Value) -> Option<Vec<String>> {
+        input.get("tags").and_then(|v| v.as_array()).map(|tags| {
+            tags.iter()
+                .filter_map(|t| t.as_str())
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect()
+        })
+    }
+
+    fn str_field<'a>(input: &'a Value, key: &str) -> Option<&'a str> {
+        input
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
+
+    /// Source recorded on memory points written by an agent
+    fn source(context: &ToolUseContext) -> String {
+        let agent = context.agent_type.as_deref().unwrap_or("unknown");
+        match context.session_id.as_deref() {
+            Some(session_id) => format!(
+                "{}{} (session {})",
+                AGENT_SOURCE_PREFIX, agent, session_id
+            ),
+            None => format!("{}{}", AGENT_SOURCE_PREFIX, agent),
+        }
+    }
+
+    /// Whether the memory point `id` was written by an agent
+    ///
+    /// Permission checks are synchronous, so the stores are read from disk directly.
+    fn is_agent_authored(id: &str) -> bool {
+        let Ok(path_manager) = try_get_path_manager_arc() else {
+            return false;
+        };
+        let mut paths = vec![AIMemoryManager::user_storage_path(&path_manager)];
+        if let Some(workspace) = get_workspace_path() {
+            paths.push(AIMemoryManager::project_storage_path(&workspace));
+        }
+        paths
+            .iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .filter_map(|content| serde_json::from_str::<MemoryStorage>(&content).ok())
+            .flat_map(|storage| storage.memories)
+            .find(|memory| memory.id == id)
+            .is_some_and(|memory| memory.source.starts_with(AGENT_SOURCE_PREFIX))
+    }
+
+    async fn manager(scope: MemoryScope) -> BitFunResult<AIMemoryManager> {
+        let path_manager = try_get_path_manager_arc()?;
+        match scope {
+            MemoryScope::User => AIMemoryManager::new(path_manager).await,
+            MemoryScope::Project => {
+                let workspace = get_workspace_path().ok_or_else(|| {
+                    BitFunError::tool(
+                        "No workspace is open; use the user scope instead".to_string(),
+                    )
+                })?;
+                AIMemoryManager::new_project(path_manager, &workspace.to_string_lossy()).await
+            }
+        }
+    }
+
+    /// Managers for the requested scope, or both stores when no scope is given
+    async fn managers(scope: Option<MemoryScope>) -> BitFunResult<Vec<AIMemoryManager>> {
+        match scope {
+            Some(scope) => Ok(vec![Self::manager(scope).await?]),
+            None => {
+                let mut managers = vec![Self::manager(MemoryScope::User).await?];
+                if get_workspace_path().is_some() {
+                    managers.push(Self::manager(MemoryScope::Project).await?);
+                }
+                Ok(managers)
+            }
+        }
+    }
+
+    /// Find the store containing a memory point
+    async fn find_memory(
+        id: &str,
+        scope: Option<MemoryScope>,
+    ) -> BitFunResult<(AIMemoryManager, AIMemory)> {
+        for manager in Self::managers(scope).await? {
+            if let Some(memory) = manager.get_memory(id).await? {
+                return Ok((manager, memory));
+            }
+        }
+        Err(BitFunError::tool(format!("Memory not found: {}", id)))
+    }
+
+    fn render_memory(scope: MemoryScope, memory: &AIMemory) -> String {
+        format!(
+            "<memory id=\"{}\" scope=\"{}\" type=\"{}\" importance=\"{}\" enabled=\"{}\" tags=\"{}\">\n<title>{}</title>\n<content>{}</content>\n</memory>",
+            memory.id,
+            scope.as_str(),
+            memory.memory_type.label(),
+            memory.importance,
+            memory.enabled,
+            memory.tags.join(", "),
+            memory.title,
+            memory.content
+        )
+    }
+
+    async fn search(&self, input: &Value) -> BitFunResult<(Value, String)> {
+        let mut results = Vec::new();
+        for manager in Self::managers(Self::parse_scope(input)?).await? {
+            let scope = manager.scope();
+            results.extend(
+                manager
+                    .get_all_memories()
+                    .await?
+                    .into_iter()
+                    .map(|memory| (scope, memory)),
+            );
+        }
+
+        match Self::str_field(input, "query") {
+            Some(query) => {
+                let memories: Vec<AIMemory> = results.iter().map(|(_, m)| m.clone()).collect();
+                let scores = bm25_scores(&memories, query);
+                let mut scored: Vec<_> = results
+                    .into_iter()
+                    .zip(scores)
+                    .filter(|(_, score)| *score > 0.0)
+                    .collect();
+                scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+                results = scored.into_iter().map(|(result, _)| result).collect();
+            }
+            None => results.sort_by(|a, b| b.1.importance.cmp(&a.1.importance)),
+        }
+        let total = results.len();
+        results.truncate(MAX_SEARCH_RESULTS);
+
+        let text = if results.is_empty() {
+            "No matching memories.".to_string()
+        } else {
+            let mut text = results
+                .iter()
+                .map(|(scope, memory)| Self::render_memory(*scope, memory))
+                .collect::<Vec<_>>()
+                .join("\n");
+            if total > results.len() {
+                text.push_str(&format!(
+                    "\n({} more matches not shown; refine the query)",
+                    total - results.len()
+                ));
+            }
+            text
+        };
+
+        let memories: Vec<Value> = results
+            .iter()
+            .map(|(scope, memory)| json!({ "scope": scope, "memory": memory }))
+            .collect();
+        Ok((json!({ "memories": memories, "total": total }), text))
+    }
+
+    async fn create(&self, input: &Value, context: &ToolUseContext) -> BitFunResult<(Value, String)> {
+        let title = Self::str_field(input, "title")
+            .ok_or_else(|| BitFunError::tool("title is required".to_string()))?;
+        let content = Self::str_field(input, "content")
+            .ok_or_else(|| BitFunError::tool("content is required".to_string()))?;
+        let scope = Self::parse_scope(input)?.unwrap_or(if get_workspace_path().is_some() {
+            MemoryScope::Project
+        } else {
+            MemoryScope::User
+        });
+        let importance = input
+            .get("importance")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(3)
+            .clamp(1, 5) as u8;
+
+        let mut memory = AIMemory::new(
+            title.to_string(),
+            content.to_string(),
+            Self::parse_type(input)?.unwrap_or_default(),
+            importance,
+        );
+        memory.tags = Self::parse_tags(input).unwrap_or_default();
+        memory.source = Self::source(context);
+
+        let memory = Self::manager(scope).await?.add_memory(memory).await?;
+        let text = format!(
+            "Memory created.\n{}",
+            Self::render_memory(scope, &memory)
+        );
+        Ok((json!({ "scope": scope, "memory": memory }), text))
+    }
+
+    async fn update(&self, input: &Value) -> BitFunResult<(Value, String)> {
+        let id = Self::str_field(input, "id")
+            .ok_or_else(|| BitFunError::tool("id is required".to_string()))?;
+        let (manager, mut memory) = Self::find_memory(id, Self::parse_scope(input)?).await?;
+
+        if let Some(title) = Self::str_field(input, "title") {
+            memory.title = title.to_string();
+        }
+        if let Some(content) = Self::str_field(input, "content") {
+            memory.content = content.to_string();
+        }
+        if let Some(memory_type) = Self::parse_type(input)? {
+            memory.memory_type = memory_type;
+        }
+        if let Some(tags) = Self::parse_tags(input) {
+            memory.tags = tags;
+        }
+        if let Some(importance) = input.get("importance").and_then(|v| v.as_u64()) {
+            memory.importance = importance.clamp(1, 5) as u8;
+        }
+
+        manager.update_memory(memory).await?;
+        let memory = manager
+            .get_memory(id)
+            .await?
+            .ok_or_else(|| BitFunError::tool(format!("Memory not found: {}", id)))?;
+        let text = format!(
+            "Memory updated.\n{}",
+            Self::render_memory(manager.scope(), &memory)
+        );
+        Ok((json!({ "scope": manager.scope(), "memory": memory }), text))
+    }
+
+    async fn delete(&self, input: &Value) -> BitFunResult<(Value, String)> {
+        let id = Self::str_field(input, "id")
+            .ok_or_else(|| BitFunError::tool("id is required".to_string()))?;
+        let (manager, memory) = Self::find_memory(id, Self::parse_scope(input)?).await?;
+        manager.delete_memory(id).await?;
+
+        let text = format!("Memory deleted: {} ({})", memory.title, memory.id);
+        Ok((json!({ "scope": manager.scope(), "memory": memory }), text))
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryTool {
+    fn name(&self) -> &str {
+        "Memory"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Manages long-term memory points that persist across sessions. Memory points relevant to the user's request are automatically included in the system prompt under "Memory Points".
+
+Actions:
+  - "search": Find memory points by keywords (all memories ordered by importance if no query is given)
+  - "create": Save a new memory point
+  - "update": Change a memory point by ID; only the given fields are changed
+  - "delete": Remove a memory point by ID
+
+Scopes:
+  - "project": Facts about the current workspace (build commands, architecture, conventions). Default for create when a workspace is open
+  - "user": Preferences of the user that apply to every project
+
+When to save a memory:
+  - The user states a lasting preference or asks you to remember something
+  - You discover non-obvious project knowledge that future sessions would need (e.g. how to run tests, where generated code comes from)
+  - A shown memory turns out to be wrong or outdated: update or delete it
+
+Do NOT save:
+  - Temporary task state, progress of the current task, or anything only relevant to this conversation
+  - Information that is easy to find in the code or documentation
+  - Secrets such as passwords, tokens or keys
+
+Keep memories short and self-contained, with a descriptive title and a few tags that help find them later."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["search", "create", "update", "delete"],
+                    "description": "The action to perform"
+                },
+                "scope": {
+                    "type": "string",
+                    "enum": ["user", "project"],
+                    "description": "Memory store. For search, update and delete, both stores are used if omitted"
+                },
+                "query": {
+                    "type": "string",
+                    "description": "Keywords to search for (search only)"
+                },
+                "id": {
+                    "type": "string",
+                    "description": "ID of the memory point (update and delete)"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Short descriptive title"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "The information to remember"
+                },
+                "type": {
+                    "type": "string",
+                    "enum": ["tech_preference", "project_context", "user_habit", "code_pattern", "decision", "other"],
+                    "description": "Kind of memory. Default is other"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Keywords that help find the memory"
+                },
+                "importance": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 5,
+                    "description": "Importance from 1 to 5. Default is 3"
+                }
+            },
+            "required": ["action"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        false
+    }
+
+    fn is_concurrency_safe(&self, input: Option<&Value>) -> bool {
+        Self::action(input) == Some("search")
+    }
+
+    /// Deleting, and changing memory points the user wrote, need the user's approval
+    fn needs_permissions(&self, input: Option<&Value>) -> bool {
+        match Self::action(input) {
+            Some("delete") => true,
+            Some("update") => !input
+                .and_then(|input| Self::str_field(input, "id"))
+                .is_some_and(Self::is_agent_authored),
+            _ => false,
+        }
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let (data, result_for_assistant) = match Self::action(Some(input)) {
+            Some("search") => self.search(input).await?,
+            Some("create") => self.create(input, context).await?,
+            Some("update") => self.update(input).await?,
+            Some("delete") => self.delete(input).await?,
+            other => {
+                return Err(BitFunError::tool(format!("Unknown action: {:?}", other)));
+            }
+        };
+
+        Ok(vec![ToolResult::Result {
+            data,
+            result_for_assistant: Some(result_for_assistant),
+        }])
+    }
+}
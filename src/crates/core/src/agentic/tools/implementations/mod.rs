@@ -3,8 +3,11 @@
 pub mod file_read_tool;
 pub mod file_write_tool;
 pub mod file_edit_tool;
+pub mod multi_edit_tool;
 pub mod delete_file_tool;
 pub mod bash_tool;
+pub mod bash_jobs;
+pub mod bash_job_tools;
 pub mod grep_tool;
 pub mod glob_tool;
 pub mod web_tools;
@@ -13,6 +16,8 @@ pub mod ide_control_tool;
 pub mod mermaid_interactive_tool;
 pub mod log_tool;
 pub mod linter_tool;
+pub mod lsp_tool;
+pub mod memory_tool;
 pub mod analyze_image_tool;
 pub mod skill_tool;
 pub mod skills;
@@ -21,6 +26,7 @@ pub mod ls_tool;
 pub mod task_tool;
 pub mod git_tool;
 pub mod create_plan_tool;
+pub mod update_plan_tool;
 pub mod get_file_diff_tool;
 pub mod code_review_tool;
 pub mod util;
@@ -28,8 +34,10 @@ pub mod util;
 pub use file_read_tool::FileReadTool;
 pub use file_write_tool::FileWriteTool;
 pub use file_edit_tool::FileEditTool;
+pub use multi_edit_tool::MultiEditTool;
 pub use delete_file_tool::DeleteFileTool;
 pub use bash_tool::BashTool;
+pub use bash_job_tools::{BashJobsTool, BashOutputTool};
 pub use grep_tool::GrepTool;
 pub use glob_tool::GlobTool;
 pub use web_tools::{WebSearchTool, WebFetchTool};
@@ -38,6 +46,8 @@ pub use ide_control_tool::IdeControlTool;
 pub use mermaid_interactive_tool::MermaidInteractiveTool;
 pub use log_tool::LogTool;
 pub use linter_tool::ReadLintsTool;
+pub use lsp_tool::LspTool;
+pub use memory_tool::MemoryTool;
 pub use analyze_image_tool::AnalyzeImageTool;
 pub use skill_tool::SkillTool;
 pub use ask_user_question_tool::AskUserQuestionTool;
@@ -45,5 +55,6 @@ pub use ls_tool::LSTool;
 pub use task_tool::TaskTool;
 pub use git_tool::GitTool;
 pub use create_plan_tool::CreatePlanTool;
+pub use update_plan_tool::UpdatePlanTool;
 pub use get_file_diff_tool::GetFileDiffTool;
 pub use code_review_tool::CodeReviewTool;
\ No newline at end of file
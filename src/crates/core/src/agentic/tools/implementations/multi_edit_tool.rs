@@ -0,0 +1,395 @@
+use super::util::resolve_path;
+use crate::agentic::tools::framework::{
+    Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
+};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use log::{debug, warn};
+use serde_json::{json, Value};
+use std::path::Path;
+use tool_runtime::fs::multi_edit::{
+    apply_hunks, apply_string_edits, parse_patch, FilePatch, StringEdit,
+};
+
+/// Content of one file before and after a multi-edit; None means the file does not exist
+#[derive(Debug, Clone)]
+pub struct PlannedFileChange {
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Multi-file edit tool
+///
+/// Applies ordered string edits per file or a multi-file patch. Every edit is validated in
+/// memory first and files are only written when all of them apply.
+pub struct MultiEditTool;
+
+impl MultiEditTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Computes the new content of every file touched by the input without writing anything.
+    pub fn plan_changes(input: &Value) -> BitFunResult<Vec<PlannedFileChange>> {
+        let mut plan = ChangePlan::default();
+
+        if let Some(patch) = input.get("patch").and_then(|v| v.as_str()) {
+            let files = parse_patch(patch)
+                .map_err(|e| BitFunError::tool(format!("Invalid patch: {}", e)))?;
+            for file in files {
+                let path = resolve_path(file.path());
+                match file {
+                    FilePatch::Add { content, .. } => {
+                        if plan.current(&path)?.is_some() {
+                            return Err(BitFunError::tool(format!(
+                                "Cannot add {}: file already exists",
+                                path
+                            )));
+                        }
+                        plan.set(&path, Some(content));
+                    }
+                    FilePatch::Delete { .. } => {
+                        if plan.current(&path)?.is_none() {
+                            return Err(BitFunError::tool(format!(
+                                "Cannot delete {}: file does not exist",
+                                path
+                            )));
+                        }
+                        plan.set(&path, None);
+                    }
+                    FilePatch::Update { move_to, hunks, .. } => {
+                        let content = plan.current(&path)?.ok_or_else(|| {
+                            BitFunError::tool(format!(
+                                "Cannot update {}: file does not exist",
+                                path
+                            ))
+                        })?;
+                        let updated = apply_hunks(&content, &hunks)
+                            .map_err(|e| BitFunError::tool(format!("{}: {}", path, e)))?;
+                        match move_to.map(|target| resolve_path(&target)) {
+                            Some(target) if target != path => {
+                                if plan.current(&target)?.is_some() {
+                                    return Err(BitFunError::tool(format!(
+                                        "Cannot move {} to {}: target already exists",
+                                        path, target
+                                    )));
+                                }
+                                plan.set(&path, None);
+                                plan.set(&target, Some(updated));
+                            }
+                            _ => plan.set(&path, Some(updated)),
+                        }
+                    }
+                }
+            }
+        } else if let Some(files) = input.get("files").and_then(|v| v.as_array()) {
+            for file in files {
+                let file_path =
+                    file.get("file_path")
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| {
+                            BitFunError::tool("file_path is required for each file".to_string())
+                        })?;
+                let edits = file
+                    .get("edits")
+                    .and_then(|v| v.as_array())
+                    .ok_or_else(|| {
+                        BitFunError::tool(format!("edits is required for {}", file_path))
+                    })?
+                    .iter()
+                    .map(parse_string_edit)
+                    .collect::<BitFunResult<Vec<_>>>()?;
+
+                let path = resolve_path(file_path);
+                let content = plan.current(&path)?.unwrap_or_default();
+                let updated = apply_string_edits(&content, &edits)
+                    .map_err(|e| BitFunError::tool(format!("{}: {}", path, e)))?;
+                plan.set(&path, Some(updated));
+            }
+        } else {
+            return Err(BitFunError::tool(
+                "Either files or patch is required".to_string(),
+            ));
+        }
+
+        let mut changes = plan.changes;
+        changes.retain(|change| change.before != change.after);
+        Ok(changes)
+    }
+
+    /// Writes planned changes, restoring the files already written if any write fails.
+    async fn write_changes(changes: &[PlannedFileChange]) -> BitFunResult<()> {
+        for (index, change) in changes.iter().enumerate() {
+            if let Err(e) = write_content(&change.path, change.after.as_deref()).await {
+                for written in changes[..index].iter().rev() {
+                    if let Err(restore_err) =
+                        write_content(&written.path, written.before.as_deref()).await
+                    {
+                        warn!(
+                            "Failed to restore file after multi-edit failure: path={} error={}",
+                            written.path, restore_err
+                        );
+                    }
+                }
+                return Err(BitFunError::tool(format!(
+                    "Failed to write {}: {}; no files were changed",
+                    change.path, e
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Tool for MultiEditTool {
+    fn name(&self) -> &str {
+        "MultiEdit"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Makes many edits across one or more files in a single atomic operation. Prefer it over repeated `Edit` calls for refactors.
+
+Provide exactly one of:
+- `files`: for each file, an ordered list of exact string replacements. Each edit applies to the result of the previous one and follows the same rules as `Edit`: `old_string` must match exactly (including indentation) and be unique unless `replace_all` is set. To create a file, give a path that does not exist and a first edit with an empty `old_string`.
+- `patch`: a patch across several files, either in V4A format or as a unified diff.
+
+V4A format:
+```
+*** Begin Patch
+*** Update File: src/app.py
+@@ def handler():
+     value = load()
+-    print(value)
++    log(value)
+*** Add File: src/util.py
++def log(value):
++    print(value)
+*** Delete File: src/old.py
+*** End Patch
+```
+Hunk lines start with ' ' (context), '-' (removed) or '+' (added). Give about 3 lines of context around each change, and use `@@ <line>` with a nearby unique line such as a function signature when the context alone is ambiguous. `*** Move to: <path>` right after an `*** Update File:` line renames the file.
+
+Usage:
+- You must use your `Read` tool at least once in the conversation before editing a file.
+- All edits and hunks are validated before anything is written. If any of them fails, no file is changed and the error names the failing file and edit; fix the input and retry the whole call.
+- Hunks for one file must be in file order."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "files": {
+                    "type": "array",
+                    "description": "Files to edit, each with an ordered list of string replacements",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "file_path": {
+                                "type": "string",
+                                "description": "The absolute path to the file to modify"
+                            },
+                            "edits": {
+                                "type": "array",
+                                "description": "Replacements applied in order",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "old_string": {
+                                            "type": "string",
+                                            "description": "The text to replace"
+                                        },
+                                        "new_string": {
+                                            "type": "string",
+                                            "description": "The text to replace it with"
+                                        },
+                                        "replace_all": {
+                                            "type": "boolean",
+                                            "default": false,
+                                            "description": "Replace all occurences of old_string (default false)"
+                                        }
+                                    },
+                                    "required": ["old_string", "new_string"],
+                                    "additionalProperties": false
+                                }
+                            }
+                        },
+                        "required": ["file_path", "edits"],
+                        "additionalProperties": false
+                    }
+                },
+                "patch": {
+                    "type": "string",
+                    "description": "A V4A patch (*** Begin Patch ... *** End Patch) or unified diff touching one or more files"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        false
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    async fn validate_input(
+        &self,
+        input: &Value,
+        _context: Option<&ToolUseContext>,
+    ) -> ValidationResult {
+        let has_files = input.get("files").is_some_and(|v| !v.is_null());
+        let has_patch = input.get("patch").is_some_and(|v| !v.is_null());
+        let message = match (has_files, has_patch) {
+            (true, true) => Some("Provide either files or patch, not both"),
+            (false, false) => Some("Either files or patch is required"),
+            _ => None,
+        };
+
+        ValidationResult {
+            result: message.is_none(),
+            message: message.map(str::to_string),
+            error_code: message.map(|_| 400),
+            meta: None,
+        }
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        let count = match input.get("files").and_then(|v| v.as_array()) {
+            Some(files) => files.len(),
+            None => input
+                .get("patch")
+                .and_then(|v| v.as_str())
+                .and_then(|patch| parse_patch(patch).ok())
+                .map_or(0, |files| files.len()),
+        };
+        format!("Editing {} file(s)", count)
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        _context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let changes = Self::plan_changes(input)?;
+        Self::write_changes(&changes).await?;
+        debug!("MultiEdit applied: files={}", changes.len());
+
+        let files: Vec<Value> = changes
+            .iter()
+            .map(|change| {
+                let status = match (&change.before, &change.after) {
+                    (None, _) => "created",
+                    (_, None) => "deleted",
+                    _ => "modified",
+                };
+                json!({ "file_path": change.path, "status": status })
+            })
+            .collect();
+        let summary: Vec<String> = files
+            .iter()
+            .map(|f| {
+                format!(
+                    "- {} ({})",
+                    f["file_path"].as_str().unwrap_or_default(),
+                    f["status"].as_str().unwrap_or_default()
+                )
+            })
+            .collect();
+
+        let result = ToolResult::Result {
+            data: json!({
+                "success": true,
+                "files": files,
+            }),
+            result_for_assistant: Some(format!(
+                "Successfully applied edits to {} file(s):\n{}",
+                changes.len(),
+                summary.join("\n")
+            )),
+        };
+
+        Ok(vec![result])
+    }
+}
+
+/// Files touched so far, in first-touch order
+#[derive(Default)]
+struct ChangePlan {
+    changes: Vec<PlannedFileChange>,
+}
+
+impl ChangePlan {
+    /// Content of a file as of the edits planned so far
+    fn current(&mut self, path: &str) -> BitFunResult<Option<String>> {
+        if let Some(change) = self.changes.iter().find(|c| c.path == path) {
+            return Ok(change.after.clone());
+        }
+
+        let before = match std::fs::read_to_string(path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(BitFunError::tool(format!(
+                    "Failed to read file {}: {}",
+                    path, e
+                )))
+            }
+        };
+        self.changes.push(PlannedFileChange {
+            path: path.to_string(),
+            after: before.clone(),
+            before,
+        });
+        Ok(self.changes.last().and_then(|c| c.after.clone()))
+    }
+
+    fn set(&mut self, path: &str, after: Option<String>) {
+        if let Some(change) = self.changes.iter_mut().find(|c| c.path == path) {
+            change.after = after;
+        } else {
+            self.changes.push(PlannedFileChange {
+                path: path.to_string(),
+                before: None,
+                after,
+            });
+        }
+    }
+}
+
+fn parse_string_edit(edit: &Value) -> BitFunResult<StringEdit> {
+    let field = |name: &str| {
+        edit.get(name)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| BitFunError::tool(format!("{} is required for each edit", name)))
+    };
+    Ok(StringEdit {
+        old_string: field("old_string")?,
+        new_string: field("new_string")?,
+        replace_all: edit
+            .get("replace_all")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    })
+}
+
+async fn write_content(path: &str, content: Option<&str>) -> std::io::Result<()> {
+    match content {
+        Some(content) => {
+            if let Some(parent) = Path::new(path).parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(path, content).await
+        }
+        None => match tokio::fs::remove_file(path).await {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        },
+    }
+}
@@ -1,4 +1,4 @@
-use crate::agentic::agents::get_agent_registry;
+use crate::agentic::agents::{get_agent_registry, SubagentIsolation};
 use crate::agentic::coordination::get_global_coordinator;
 use crate::agentic::tools::framework::{
     Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
@@ -26,6 +26,22 @@ impl TaskTool {
             .collect::<Vec<String>>()
     }
 
+    /// Isolation requested in the input, falling back to the subagent's default
+    fn isolation(input: &Value) -> SubagentIsolation {
+        if let Some(isolation) = input
+            .get("isolation")
+            .and_then(|v| v.as_str())
+            .and_then(SubagentIsolation::parse)
+        {
+            return isolation;
+        }
+        input
+            .get("subagent_type")
+            .and_then(|v| v.as_str())
+            .and_then(|id| get_agent_registry().get_subagent_isolation(id))
+            .unwrap_or_default()
+    }
+
     async fn get_agents_descriptions(&self) -> String {
         let registry = get_agent_registry();
         let agent_infos = registry.get_subagents_info().await;
@@ -85,6 +101,7 @@ Usage notes:
 - Clearly tell the agent whether you expect it to write code or just to do research (search, file reads, web fetches, etc.), since it is not aware of the user's intent
 - If the agent description mentions that it should be used proactively, then you should try your best to use it without the user having to ask for it first. Use your judgement.
 - If the user specifies that they want you to run agents "in parallel", you MUST send a single message with multiple Task tool calls. For example, if you need to launch both a code-reviewer agent and a test-runner agent in parallel, send a single message with both tool calls.
+- Set isolation to "worktree" to run an agent that writes code in its own temporary git worktree, so several such agents can run in parallel. The worktree starts from the last commit (uncommitted changes in the workspace are not visible to it). The result gives the branch, worktree path and diff of its changes; they are not applied to the workspace, so review them and apply what you want (e.g. with the MultiEdit tool's patch input or `git apply`). A worktree with no changes is removed automatically.
 
 Example usage:
 
@@ -144,6 +161,11 @@ assistant: "I'm going to use the Task tool to launch the greeting-responder agen
                 "workspace_path": {
                     "type": "string",
                     "description": "The absolute path of the workspace for this task. Required for Explore/FileFinder agent."
+                },
+                "isolation": {
+                    "type": "string",
+                    "enum": ["none", "worktree"],
+                    "description": "Where the agent runs: \"none\" for the current workspace, \"worktree\" for a temporary git worktree. Defaults to the agent's own setting."
                 }
             },
             "required": [
@@ -163,9 +185,12 @@ assistant: "I'm going to use the Task tool to launch the greeting-responder agen
             .and_then(|v| v.get("subagent_type"))
             .and_then(|v| v.as_str());
         match subagent_type {
-            Some(id) => get_agent_registry()
-                .get_subagent_is_readonly(id)
-                .unwrap_or(false),
+            Some(id) => {
+                get_agent_registry()
+                    .get_subagent_is_readonly(id)
+                    .unwrap_or(false)
+                    || input.map(Self::isolation) == Some(SubagentIsolation::Worktree)
+            }
             None => false,
         }
     }
@@ -179,6 +204,20 @@ assistant: "I'm going to use the Task tool to launch the greeting-responder agen
         input: &Value,
         _context: Option<&ToolUseContext>,
     ) -> ValidationResult {
+        if let Some(isolation) = input.get("isolation").and_then(|v| v.as_str()) {
+            if SubagentIsolation::parse(isolation).is_none() {
+                return ValidationResult {
+                    result: false,
+                    message: Some(format!(
+                        "isolation must be \"none\" or \"worktree\", got \"{}\"",
+                        isolation
+                    )),
+                    error_code: Some(400),
+                    meta: None,
+                };
+            }
+        }
+
         InputValidator::new(input)
             .validate_required("prompt")
             .validate_required("subagent_type")
@@ -298,29 +337,91 @@ assistant: "I'm going to use the Task tool to launch the greeting-responder agen
         let coordinator = get_global_coordinator()
             .ok_or_else(|| BitFunError::tool("coordinator not initialized".to_string()))?;
 
+        let parent_info = SubagentParentInfo {
+            tool_call_id,
+            session_id,
+            dialog_turn_id,
+        };
+        let isolation = Self::isolation(input);
+
         // Use coordinator to execute subagent, passing parent tool ID, parent turn_id and cancellation token
-        let result = coordinator
-            .execute_subagent(
-                subagent_type.clone(),
-                prompt,
-                SubagentParentInfo {
-                    tool_call_id,
-                    session_id,
-                    dialog_turn_id,
-                },
-                None,
-                context.cancellation_token.as_ref(),
-            )
-            .await?;
+        let result = match isolation {
+            SubagentIsolation::None => {
+                coordinator
+                    .execute_subagent(
+                        subagent_type.clone(),
+                        prompt,
+                        parent_info,
+                        None,
+                        context.cancellation_token.as_ref(),
+                    )
+                    .await?
+            }
+            SubagentIsolation::Worktree => {
+                coordinator
+                    .execute_isolated_subagent(
+                        subagent_type.clone(),
+                        prompt,
+                        parent_info,
+                        None,
+                        context.cancellation_token.as_ref(),
+                    )
+                    .await?
+            }
+        };
 
         let duration = start_time.elapsed().as_millis();
 
+        let mut result_for_assistant = format!(
+            "Subagent '{}' completed successfully with result:\n<result>\n{}\n</result>",
+            subagent_type, result.text
+        );
+        let worktree = match (&result.worktree, isolation) {
+            (Some(worktree), _) => {
+                let (diff, truncated) = truncate_diff(&worktree.diff);
+                result_for_assistant.push_str(&format!(
+                    "\n\nThe subagent's changes are staged in worktree {} on branch {} and have NOT been applied to the workspace.{}\n<diff>\n{}\n</diff>",
+                    worktree.path,
+                    worktree.branch,
+                    if truncated {
+                        " The diff below is truncated; run `git diff --cached` in the worktree for the rest."
+                    } else {
+                        ""
+                    },
+                    diff
+                ));
+                json!({
+                    "branch": worktree.branch,
+                    "path": worktree.path,
+                    "diff": worktree.diff,
+                })
+            }
+            (None, SubagentIsolation::Worktree) => {
+                result_for_assistant.push_str(
+                    "\n\nThe subagent made no file changes; its worktree has been removed.",
+                );
+                Value::Null
+            }
+            (None, SubagentIsolation::None) => Value::Null,
+        };
+
         Ok(vec![ToolResult::Result {
-            data: json!({"duration": duration}),
-            result_for_assistant: Some(format!(
-                "Subagent '{}' completed successfully with result:\n<result>\n{}\n</result>",
-                subagent_type, result.text
-            )),
+            data: json!({"duration": duration, "worktree": worktree}),
+            result_for_assistant: Some(result_for_assistant),
         }])
     }
 }
+
+/// Longest diff included in the result for the model
+const MAX_DIFF_CHARS: usize = 20_000;
+
+fn truncate_diff(diff: &str) -> (&str, bool) {
+    if diff.len() <= MAX_DIFF_CHARS {
+        return (diff, false);
+    }
+    let mut end = MAX_DIFF_CHARS;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    (&diff[..end], true)
+}
@@ -1,7 +1,11 @@
+use crate::agentic::coordination::get_global_coordinator;
 use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::service::plan::{update_plan_statuses, PlanStepStatus};
 use crate::util::errors::{BitFunError, BitFunResult};
 use async_trait::async_trait;
+use log::{debug, info, warn};
 use serde_json::{json, Value};
+use std::path::Path;
 
 /// TodoWrite tool - record todo items
 pub struct TodoWriteTool;
@@ -260,7 +264,7 @@ When in doubt, use this tool. Being proactive with task management demonstrates
     async fn call_impl(
         &self,
         input: &Value,
-        _context: &ToolUseContext,
+        context: &ToolUseContext,
     ) -> BitFunResult<Vec<ToolResult>> {
         // Parse todos array
         let todos = input
@@ -289,6 +293,10 @@ When in doubt, use this tool. Being proactive with task management demonstrates
             processed_todos.push(todo_obj);
         }
 
+        if let Some(plan_path) = active_plan(context) {
+            sync_plan_steps(&plan_path, &processed_todos, context).await;
+        }
+
         let todo_count = processed_todos.len();
         let mut status_counts = [0; 3];
         processed_todos.iter().for_each(|t| {
@@ -325,3 +333,60 @@ When in doubt, use this tool. Being proactive with task management demonstrates
         }])
     }
 }
+
+/// Plan file the session is executing, if any
+pub(crate) fn active_plan(context: &ToolUseContext) -> Option<String> {
+    context
+        .options
+        .as_ref()
+        .and_then(|opts| opts.custom_data.as_ref())
+        .and_then(|data| data.get("active_plan"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+}
+
+/// Copy todo statuses to the plan steps with the same ids
+///
+/// Clears the session's active plan once every step is completed.
+async fn sync_plan_steps(plan_path: &str, todos: &[Value], context: &ToolUseContext) {
+    let updates: Vec<(&str, PlanStepStatus)> = todos
+        .iter()
+        .filter_map(|todo| {
+            let id = todo.get("id").and_then(|v| v.as_str())?;
+            let status = todo.get("status").and_then(|v| v.as_str())?;
+            Some((id, PlanStepStatus::parse(status)?))
+        })
+        .collect();
+
+    let plan = match update_plan_statuses(Path::new(plan_path), updates).await {
+        Ok(Some(plan)) => plan,
+        Ok(None) => return,
+        Err(e) => {
+            warn!(
+                "Failed to sync todos to plan: path={}, error={}",
+                plan_path, e
+            );
+            return;
+        }
+    };
+    debug!("Plan steps synced from todos: path={}", plan_path);
+
+    if !plan.is_finished() {
+        return;
+    }
+    let (Some(coordinator), Some(session_id)) = (get_global_coordinator(), &context.session_id)
+    else {
+        return;
+    };
+    match coordinator
+        .get_session_manager()
+        .set_active_plan(session_id, None)
+        .await
+    {
+        Ok(()) => info!(
+            "Plan completed: session_id={}, path={}",
+            session_id, plan_path
+        ),
+        Err(e) => warn!("Failed to clear active plan: {}", e),
+    }
+}
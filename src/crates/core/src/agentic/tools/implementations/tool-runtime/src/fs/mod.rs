@@ -1,2 +1,3 @@
 pub mod read_file;
-pub mod edit_file;
\ No newline at end of file
+pub mod edit_file;
+pub mod multi_edit;
\ No newline at end of file
@@ -0,0 +1,655 @@
+//! In-memory multi-edit and patch application
+//!
+//! Nothing here touches the file system: callers compute every new file content first and
+//! only write once all edits and hunks have applied cleanly.
+
+use crate::util::string::normalize_string;
+
+/// One exact string replacement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringEdit {
+    pub old_string: String,
+    pub new_string: String,
+    pub replace_all: bool,
+}
+
+/// One file section of a patch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilePatch {
+    Add {
+        path: String,
+        content: String,
+    },
+    Delete {
+        path: String,
+    },
+    Update {
+        path: String,
+        /// New path when the file is also renamed
+        move_to: Option<String>,
+        hunks: Vec<PatchHunk>,
+    },
+}
+
+impl FilePatch {
+    pub fn path(&self) -> &str {
+        match self {
+            FilePatch::Add { path, .. }
+            | FilePatch::Delete { path }
+            | FilePatch::Update { path, .. } => path,
+        }
+    }
+}
+
+/// Replacement of a run of lines
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatchHunk {
+    /// Line the hunk follows (`@@ fn foo` in V4A, the function context in unified diffs)
+    pub anchor: Option<String>,
+    /// 1-based line of the old text, from a unified diff header
+    pub start_line: Option<usize>,
+    /// Context and removed lines
+    pub old_lines: Vec<String>,
+    /// Context and added lines
+    pub new_lines: Vec<String>,
+    /// The hunk must match at the end of the file
+    pub end_of_file: bool,
+}
+
+/// Applies string edits in order, each on the result of the previous one.
+///
+/// Matching follows `edit_file`: line endings are normalized for matching and the original
+/// style is restored afterwards; `old_string` must be unique unless `replace_all` is set.
+pub fn apply_string_edits(content: &str, edits: &[StringEdit]) -> Result<String, String> {
+    let uses_crlf = content.contains("\r\n");
+    let mut current = normalize_string(content);
+
+    for (index, edit) in edits.iter().enumerate() {
+        let old = normalize_string(&edit.old_string);
+        let new = normalize_string(&edit.new_string);
+
+        if old.is_empty() {
+            if index == 0 && current.is_empty() {
+                current = new;
+                continue;
+            }
+            return Err(format!(
+                "Edit {}: old_string is empty; only the first edit of an empty or new file may omit it.",
+                index + 1
+            ));
+        }
+        if old == new {
+            return Err(format!(
+                "Edit {}: old_string and new_string are identical.",
+                index + 1
+            ));
+        }
+
+        let count = current.matches(&old).count();
+        if count == 0 {
+            return Err(format!(
+                "Edit {}: old_string not found in file (earlier edits in the list are already applied).",
+                index + 1
+            ));
+        }
+        if count > 1 && !edit.replace_all {
+            return Err(format!(
+                "Edit {}: `old_string` appears {} times in file, either provide a larger string with more surrounding context to make it unique or use `replace_all` to change every instance of `old_string`.",
+                index + 1,
+                count
+            ));
+        }
+
+        current = if edit.replace_all {
+            current.replace(&old, &new)
+        } else {
+            current.replacen(&old, &new, 1)
+        };
+    }
+
+    Ok(restore_line_endings(current, uses_crlf))
+}
+
+/// Parses a multi-file patch, either V4A (`*** Begin Patch`) or a unified diff.
+pub fn parse_patch(patch: &str) -> Result<Vec<FilePatch>, String> {
+    let patch = normalize_string(patch);
+    let lines: Vec<&str> = patch.lines().collect();
+
+    let files = if lines
+        .iter()
+        .any(|line| line.trim_end() == "*** Begin Patch" || line.starts_with("*** Update File:"))
+    {
+        parse_v4a(&lines)?
+    } else {
+        parse_unified(&lines)?
+    };
+
+    if files.is_empty() {
+        return Err("Patch contains no file changes.".to_string());
+    }
+    Ok(files)
+}
+
+/// Applies hunks in order to a file content.
+///
+/// Hunks are located after the previous one; when the exact text is not found, matching
+/// retries ignoring trailing and then surrounding whitespace.
+pub fn apply_hunks(content: &str, hunks: &[PatchHunk]) -> Result<String, String> {
+    let uses_crlf = content.contains("\r\n");
+    let normalized = normalize_string(content);
+    let had_trailing_newline = normalized.ends_with('\n');
+    let mut lines: Vec<String> = split_lines(&normalized);
+    let mut cursor = 0;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        if let Some(anchor) = hunk.anchor.as_deref() {
+            let anchor = anchor.trim();
+            if let Some(pos) = (cursor..lines.len()).find(|&i| lines[i].trim() == anchor) {
+                cursor = pos + 1;
+            }
+        }
+
+        let position = if hunk.old_lines.is_empty() {
+            match hunk.start_line {
+                // `-N,0` inserts after line N
+                Some(line) => line.clamp(cursor, lines.len()),
+                None if hunk.anchor.is_some() && !hunk.end_of_file => cursor,
+                None => lines.len(),
+            }
+        } else {
+            find_hunk(&lines, cursor, hunk).ok_or_else(|| {
+                let preview: Vec<&str> =
+                    hunk.old_lines.iter().take(5).map(String::as_str).collect();
+                format!(
+                    "Hunk {}: context not found in file (hunks must be in file order):\n{}",
+                    index + 1,
+                    preview.join("\n")
+                )
+            })?
+        };
+
+        lines.splice(
+            position..position + hunk.old_lines.len(),
+            hunk.new_lines.iter().cloned(),
+        );
+        cursor = position + hunk.new_lines.len();
+    }
+
+    let mut result = lines.join("\n");
+    if !result.is_empty() && (had_trailing_newline || normalized.is_empty()) {
+        result.push('\n');
+    }
+    Ok(restore_line_endings(result, uses_crlf))
+}
+
+fn find_hunk(lines: &[String], cursor: usize, hunk: &PatchHunk) -> Option<usize> {
+    let old = &hunk.old_lines;
+    if old.len() > lines.len() {
+        return None;
+    }
+    let comparisons: [fn(&str, &str) -> bool; 3] = [
+        |a, b| a == b,
+        |a, b| a.trim_end() == b.trim_end(),
+        |a, b| a.trim() == b.trim(),
+    ];
+
+    for eq in comparisons {
+        let matches_at = |start: usize| old.iter().zip(&lines[start..]).all(|(o, l)| eq(o, l));
+        let last_start = lines.len() - old.len();
+
+        if hunk.end_of_file {
+            if last_start >= cursor && matches_at(last_start) {
+                return Some(last_start);
+            }
+            continue;
+        }
+
+        let mut candidates = (cursor..=last_start).filter(|&start| matches_at(start));
+        let found = match hunk.start_line {
+            Some(line) => {
+                let target = line.saturating_sub(1);
+                candidates.min_by_key(|&start| start.abs_diff(target))
+            }
+            None => candidates.next(),
+        };
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+fn parse_v4a(lines: &[&str]) -> Result<Vec<FilePatch>, String> {
+    let mut files = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        if let Some(path) = line.strip_prefix("*** Add File:") {
+            i += 1;
+            let mut content = Vec::new();
+            while i < lines.len() && !lines[i].starts_with("***") {
+                let body = lines[i].strip_prefix('+').ok_or_else(|| {
+                    format!("Line {}: expected '+' in added file: {}", i + 1, lines[i])
+                })?;
+                content.push(body);
+                i += 1;
+            }
+            let mut content = content.join("\n");
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            files.push(FilePatch::Add {
+                path: path.trim().to_string(),
+                content,
+            });
+        } else if let Some(path) = line.strip_prefix("*** Delete File:") {
+            files.push(FilePatch::Delete {
+                path: path.trim().to_string(),
+            });
+            i += 1;
+        } else if let Some(path) = line.strip_prefix("*** Update File:") {
+            i += 1;
+            let mut move_to = None;
+            if let Some(target) = lines.get(i).and_then(|l| l.strip_prefix("*** Move to:")) {
+                move_to = Some(target.trim().to_string());
+                i += 1;
+            }
+
+            let mut hunks: Vec<PatchHunk> = Vec::new();
+            let mut current: Option<PatchHunk> = None;
+            while i < lines.len() {
+                let line = lines[i];
+                if line.trim_end() == "*** End of File" {
+                    current.get_or_insert_with(PatchHunk::default).end_of_file = true;
+                    i += 1;
+                    continue;
+                }
+                if line.starts_with("***") {
+                    break;
+                }
+                if let Some(anchor) = line.strip_prefix("@@") {
+                    hunks.extend(current.take());
+                    let anchor = anchor.trim();
+                    current = Some(PatchHunk {
+                        anchor: (!anchor.is_empty()).then(|| anchor.to_string()),
+                        ..Default::default()
+                    });
+                    i += 1;
+                    continue;
+                }
+
+                let hunk = current.get_or_insert_with(PatchHunk::default);
+                push_hunk_line(hunk, line).map_err(|e| format!("Line {}: {}", i + 1, e))?;
+                i += 1;
+            }
+            hunks.extend(current);
+            hunks.retain(|h| !h.old_lines.is_empty() || !h.new_lines.is_empty());
+
+            if hunks.is_empty() && move_to.is_none() {
+                return Err(format!("Update of {} contains no changes.", path.trim()));
+            }
+            files.push(FilePatch::Update {
+                path: path.trim().to_string(),
+                move_to,
+                hunks,
+            });
+        } else if line.trim().is_empty()
+            || line.trim_end() == "*** Begin Patch"
+            || line.trim_end() == "*** End Patch"
+        {
+            i += 1;
+        } else {
+            return Err(format!("Line {}: unexpected patch line: {}", i + 1, line));
+        }
+    }
+
+    Ok(files)
+}
+
+fn parse_unified(lines: &[&str]) -> Result<Vec<FilePatch>, String> {
+    let mut files = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let (Some(old_header), Some(new_header)) = (
+            lines[i].strip_prefix("--- "),
+            lines.get(i + 1).and_then(|l| l.strip_prefix("+++ ")),
+        ) else {
+            // `diff --git`, `index`, mode lines and any prose around the diff
+            i += 1;
+            continue;
+        };
+        let old_path = diff_header_path(old_header, "a/");
+        let new_path = diff_header_path(new_header, "b/");
+        i += 2;
+
+        let mut hunks = Vec::new();
+        while i < lines.len() && lines[i].starts_with("@@") {
+            let mut hunk = parse_hunk_header(lines[i]);
+            let counts = parse_hunk_counts(lines[i]);
+            let header_line = i + 1;
+            i += 1;
+            match counts {
+                // The header's line counts say exactly where the hunk ends, so body lines
+                // that look like headers (`--- ` for a removed `-- ` line) stay in the hunk
+                Some((mut old_left, mut new_left)) => {
+                    while old_left > 0 || new_left > 0 {
+                        let Some(line) = lines.get(i) else {
+                            return Err(format!(
+                                "Line {}: hunk is shorter than its header says",
+                                header_line
+                            ));
+                        };
+                        if !line.starts_with('\\') {
+                            push_hunk_line(&mut hunk, line)
+                                .map_err(|e| format!("Line {}: {}", i + 1, e))?;
+                            let (old, new) = match line.chars().next() {
+                                Some('+') => (0, 1),
+                                Some('-') => (1, 0),
+                                _ => (1, 1),
+                            };
+                            if old > old_left || new > new_left {
+                                return Err(format!(
+                                    "Line {}: hunk is longer than its header says",
+                                    i + 1
+                                ));
+                            }
+                            old_left -= old;
+                            new_left -= new;
+                        }
+                        i += 1;
+                    }
+                    // `\ No newline at end of file` after the last line
+                    while lines.get(i).is_some_and(|line| line.starts_with('\\')) {
+                        i += 1;
+                    }
+                }
+                None => {
+                    while i < lines.len() && !is_unified_boundary(lines, i) {
+                        if !lines[i].starts_with('\\') {
+                            push_hunk_line(&mut hunk, lines[i])
+                                .map_err(|e| format!("Line {}: {}", i + 1, e))?;
+                        }
+                        i += 1;
+                    }
+                }
+            }
+            hunks.push(hunk);
+        }
+
+        let file = match (old_path, new_path) {
+            (None, Some(path)) => {
+                let mut content = hunks
+                    .iter()
+                    .flat_map(|h| h.new_lines.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if !content.is_empty() {
+                    content.push('\n');
+                }
+                FilePatch::Add { path, content }
+            }
+            (Some(path), None) => FilePatch::Delete { path },
+            (Some(path), Some(new_path)) => {
+                if hunks.is_empty() && path == new_path {
+                    return Err(format!("Diff of {} contains no hunks.", path));
+                }
+                FilePatch::Update {
+                    move_to: (new_path != path).then_some(new_path),
+                    path,
+                    hunks,
+                }
+            }
+            (None, None) => return Err("Diff header has no file path.".to_string()),
+        };
+        files.push(file);
+    }
+
+    Ok(files)
+}
+
+/// Path of a `---`/`+++` header, or None for `/dev/null`
+fn diff_header_path(header: &str, prefix: &str) -> Option<String> {
+    // Headers may carry a tab-separated timestamp
+    let path = header.split('\t').next().unwrap_or(header).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(prefix).unwrap_or(path).to_string())
+}
+
+/// `@@ -12,5 +12,7 @@ fn context`
+fn parse_hunk_header(line: &str) -> PatchHunk {
+    let rest = line.trim_start_matches('@').trim_start();
+    let (ranges, anchor) = match rest.split_once("@@") {
+        Some((ranges, anchor)) => (ranges, anchor.trim()),
+        None => (rest, ""),
+    };
+    let start_line = ranges
+        .split_whitespace()
+        .find_map(|range| range.strip_prefix('-'))
+        .and_then(|range| range.split(',').next())
+        .and_then(|start| start.parse::<usize>().ok());
+
+    PatchHunk {
+        anchor: (!anchor.is_empty()).then(|| anchor.to_string()),
+        start_line,
+        ..Default::default()
+    }
+}
+
+/// Old and new line counts of `@@ -12,5 +12,7 @@`; a missing count means one line
+fn parse_hunk_counts(line: &str) -> Option<(usize, usize)> {
+    let rest = line.trim_start_matches('@').trim_start();
+    let ranges = rest.split_once("@@").map_or(rest, |(ranges, _)| ranges);
+    let count = |prefix: char| {
+        let range = ranges
+            .split_whitespace()
+            .find_map(|range| range.strip_prefix(prefix))?;
+        match range.split_once(',') {
+            Some((_, count)) => count.parse::<usize>().ok(),
+            None => range.parse::<usize>().ok().map(|_| 1),
+        }
+    };
+    Some((count('-')?, count('+')?))
+}
+
+fn is_unified_boundary(lines: &[&str], i: usize) -> bool {
+    let line = lines[i];
+    line.starts_with("@@")
+        || line.starts_with("diff --git ")
+        || (line.starts_with("--- ")
+            && lines
+                .get(i + 1)
+                .is_some_and(|next| next.starts_with("+++ ")))
+}
+
+fn push_hunk_line(hunk: &mut PatchHunk, line: &str) -> Result<(), String> {
+    match line.chars().next() {
+        Some('+') => hunk.new_lines.push(line[1..].to_string()),
+        Some('-') => hunk.old_lines.push(line[1..].to_string()),
+        Some(' ') => {
+            hunk.old_lines.push(line[1..].to_string());
+            hunk.new_lines.push(line[1..].to_string());
+        }
+        // Blank context lines often lose their leading space
+        None => {
+            hunk.old_lines.push(String::new());
+            hunk.new_lines.push(String::new());
+        }
+        Some(_) => {
+            return Err(format!(
+                "expected a line starting with ' ', '-' or '+': {}",
+                line
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn split_lines(content: &str) -> Vec<String> {
+    if content.is_empty() {
+        return Vec::new();
+    }
+    content
+        .strip_suffix('\n')
+        .unwrap_or(content)
+        .split('\n')
+        .map(str::to_string)
+        .collect()
+}
+
+fn restore_line_endings(content: String, uses_crlf: bool) -> String {
+    if uses_crlf {
+        content.replace('\n', "\r\n")
+    } else {
+        content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_edits_apply_in_order() {
+        let edits = vec![
+            StringEdit {
+                old_string: "let a = 1;".to_string(),
+                new_string: "let b = 1;".to_string(),
+                replace_all: false,
+            },
+            StringEdit {
+                old_string: "a".to_string(),
+                new_string: "b".to_string(),
+                replace_all: true,
+            },
+        ];
+        let result = apply_string_edits("let a = 1;\r\nprint(a, a);\r\n", &edits).unwrap();
+        assert_eq!(result, "let b = 1;\r\nprint(b, b);\r\n");
+
+        let err = apply_string_edits("x x", &edits[..1]).unwrap_err();
+        assert!(err.starts_with("Edit 1: old_string not found"));
+    }
+
+    #[test]
+    fn test_v4a_patch() {
+        let patch = "*** Begin Patch
+*** Update File: src/lib.rs
+@@ fn main() {
+-    old();
++    new();
+*** Add File: src/new.rs
++pub fn f() {}
+*** Delete File: src/gone.rs
+*** End Patch";
+        let files = parse_patch(patch).unwrap();
+        assert_eq!(files.len(), 3);
+        assert_eq!(
+            files[1],
+            FilePatch::Add {
+                path: "src/new.rs".to_string(),
+                content: "pub fn f() {}\n".to_string()
+            }
+        );
+
+        let FilePatch::Update { hunks, .. } = &files[0] else {
+            panic!("expected update");
+        };
+        let content = "fn other() {\n    old();\n}\nfn main() {\n    old();\n}\n";
+        assert_eq!(
+            apply_hunks(content, hunks).unwrap(),
+            "fn other() {\n    old();\n}\nfn main() {\n    new();\n}\n"
+        );
+        assert!(apply_hunks("fn main() {}\n", hunks).is_err());
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let patch = "diff --git a/a.txt b/a.txt
+--- a/a.txt
++++ b/a.txt
+@@ -2,3 +2,3 @@
+ two
+-three
++THREE
+ four
+--- /dev/null
++++ b/b.txt
+@@ -0,0 +1,2 @@
++hello
++world
+";
+        let files = parse_patch(patch).unwrap();
+        assert_eq!(
+            files[1],
+            FilePatch::Add {
+                path: "b.txt".to_string(),
+                content: "hello\nworld\n".to_string()
+            }
+        );
+
+        let FilePatch::Update { path, hunks, .. } = &files[0] else {
+            panic!("expected update");
+        };
+        assert_eq!(path, "a.txt");
+        assert_eq!(hunks[0].start_line, Some(2));
+        assert_eq!(
+            apply_hunks("one\ntwo\nthree\nfour\n", hunks).unwrap(),
+            "one\ntwo\nTHREE\nfour\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_with_blank_separators() {
+        let patch = "--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+-one
++ONE
+ two
+
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-three
++THREE
+
+";
+        let files = parse_patch(patch).unwrap();
+        assert_eq!(files.len(), 2);
+        let FilePatch::Update { hunks, .. } = &files[0] else {
+            panic!("expected update");
+        };
+        assert_eq!(apply_hunks("one\ntwo\n", hunks).unwrap(), "ONE\ntwo\n");
+        let FilePatch::Update { hunks, .. } = &files[1] else {
+            panic!("expected update");
+        };
+        assert_eq!(apply_hunks("three\n", hunks).unwrap(), "THREE\n");
+    }
+
+    #[test]
+    fn test_unified_diff_hunk_lines_that_look_like_headers() {
+        let patch = "--- a/query.sql
++++ b/query.sql
+@@ -1,3 +1,3 @@
+ SELECT 1;
+--- old comment
++++ new comment
+ SELECT 2;
+";
+        let files = parse_patch(patch).unwrap();
+        assert_eq!(files.len(), 1);
+        let FilePatch::Update { hunks, .. } = &files[0] else {
+            panic!("expected update");
+        };
+        assert_eq!(
+            apply_hunks("SELECT 1;\n-- old comment\nSELECT 2;\n", hunks).unwrap(),
+            "SELECT 1;\n++ new comment\nSELECT 2;\n"
+        );
+
+        let short = "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n-one\n+ONE\n";
+        assert!(parse_patch(short).is_err());
+    }
+}
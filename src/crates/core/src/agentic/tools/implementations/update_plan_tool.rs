@@ -0,0 +1,174 @@
+//! UpdatePlan tool implementation
+//!
+//! Revises an existing plan file; every revision is saved as a new plan version
+
+use super::create_plan_tool::parse_steps;
+use super::todo_write_tool::active_plan;
+use super::util::resolve_path;
+use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::infrastructure::{get_path_manager_arc, get_workspace_path};
+use crate::service::plan::{load_plan, save_plan};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// UpdatePlan tool - revise a plan file
+pub struct UpdatePlanTool;
+
+impl UpdatePlanTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for UpdatePlanTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for UpdatePlanTool {
+    fn name(&self) -> &str {
+        "UpdatePlan"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r###"Use this tool to revise an existing plan file created by CreatePlan. The previous version is kept in the plan history and the plan's version number increases.
+
+- Only pass the fields that change; omitted fields keep their current value
+- `todos` replaces the whole todo list. Keep the IDs of todos that remain so their progress is preserved; todos with new IDs start as pending
+- While a plan is being executed, `plan_file_path` defaults to the active plan
+- Do not edit plan files with your file editing tools; use this tool so revisions are versioned"###
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "plan_file_path": {
+                    "type": "string",
+                    "description": "Path of the plan file to revise. Defaults to the plan being executed."
+                },
+                "overview": {
+                    "type": "string",
+                    "description": "New 1-2 sentence high-level description of the plan"
+                },
+                "plan": {
+                    "type": "string",
+                    "description": "New plan body in markdown"
+                },
+                "todos": {
+                    "type": "array",
+                    "description": "New list of implementation todos",
+                    "items": {
+                        "type": "object",
+                        "required": ["id", "content"],
+                        "properties": {
+                            "id": {
+                                "type": "string",
+                                "description": "Unique identifier for the todo"
+                            },
+                            "content": {
+                                "type": "string",
+                                "description": "Description of the todo task"
+                            },
+                            "dependencies": {
+                                "type": "array",
+                                "description": "Array of todo IDs that must be completed before this todo can start",
+                                "items": {
+                                    "type": "string"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        // Only writes plan files, doesn't modify code
+        true
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let plan_file_path = input
+            .get("plan_file_path")
+            .and_then(|v| v.as_str())
+            .map(resolve_path)
+            .or_else(|| active_plan(context))
+            .ok_or(BitFunError::validation(
+                "plan_file_path is required when no plan is being executed",
+            ))?;
+        let path = Path::new(&plan_file_path);
+
+        // Only plan files of the current workspace can be revised
+        let workspace_path =
+            get_workspace_path().ok_or(BitFunError::tool("Workspace path not set".to_string()))?;
+        let plans_dir = get_path_manager_arc().project_plans_dir(&workspace_path);
+        if !path.starts_with(&plans_dir) || !plan_file_path.ends_with(".plan.md") {
+            return Err(BitFunError::validation(format!(
+                "Not a plan file of this workspace: {}",
+                plan_file_path
+            )));
+        }
+
+        let mut document = load_plan(path).await?;
+        if let Some(overview) = input.get("overview").and_then(|v| v.as_str()) {
+            document.overview = overview.to_string();
+        }
+        if let Some(plan) = input.get("plan").and_then(|v| v.as_str()) {
+            document.body = plan.to_string();
+        }
+        if let Some(todos) = input.get("todos").and_then(|v| v.as_array()) {
+            let mut steps = parse_steps(Some(todos));
+            for step in &mut steps {
+                if let Some(existing) = document.step(&step.id) {
+                    step.status = existing.status;
+                }
+            }
+            document.steps = steps;
+        }
+
+        let previous_version = document.version;
+        save_plan(path, &mut document).await?;
+
+        let result_for_assistant = if document.version == previous_version {
+            format!(
+                "Plan {} is unchanged (version {}).",
+                plan_file_path, document.version
+            )
+        } else {
+            format!(
+                "Plan {} updated to version {}.",
+                plan_file_path, document.version
+            )
+        };
+
+        let result = json!({
+            "success": true,
+            "plan_file_path": plan_file_path,
+            "name": document.name,
+            "overview": document.overview,
+            "version": document.version,
+            "todos": document.steps
+        });
+
+        Ok(vec![ToolResult::Result {
+            data: result,
+            result_for_assistant: Some(result_for_assistant),
+        }])
+    }
+}
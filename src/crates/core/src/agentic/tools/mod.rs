@@ -14,5 +14,5 @@ pub use input_validator::InputValidator;
 pub use pipeline::*;
 pub use registry::{
     create_tool_registry, get_all_registered_tool_names, get_all_registered_tools, get_all_tools,
-    get_readonly_tools,
+    get_readonly_tools, mcp_tools_revision,
 };
@@ -5,8 +5,10 @@
 pub mod types;
 pub mod state_manager;
 pub mod tool_pipeline;
+pub mod permission_rules;
 
 pub use types::*;
 pub use state_manager::*;
 pub use tool_pipeline::*;
+pub use permission_rules::{PermissionDecision, PermissionMatch, PermissionRules};
 
@@ -0,0 +1,810 @@
+//! Declarative tool permission rules
+//!
+//! Rules are written as `Tool` or `Tool(specifier)` and resolve a tool call to
+//! allow, deny or ask. They are loaded from the user config (`ai.tool_permissions`)
+//! and from the project's `.bitfun/settings.json` (`permissions`). The project settings
+//! come with the checkout, so only their deny and ask rules are honored; and since the
+//! settings can be changed by writing to `.bitfun/`, such writes always ask.
+
+use crate::infrastructure::get_workspace_path;
+use crate::service::config::types::{AIConfig, ToolPermissionRules, ToolPermissionsConfig};
+use crate::service::config::{load_project_settings, GlobalConfigManager};
+use globset::{GlobBuilder, GlobMatcher};
+use log::{debug, warn};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tool_runtime::fs::multi_edit::{parse_patch, FilePatch};
+
+/// Result of evaluating the rules for a tool call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionDecision {
+    Allow,
+    Ask,
+    Deny,
+}
+
+/// Rules that apply regardless of the configuration
+const BUILTIN_ASK_RULES: &[&str] = &[
+    "Write(**/.bitfun/**)",
+    "Edit(**/.bitfun/**)",
+    "MultiEdit(**/.bitfun/**)",
+    "Delete(**/.bitfun/**)",
+];
+
+/// Where a rule was defined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionRuleSource {
+    User,
+    Project,
+    Builtin,
+}
+
+impl fmt::Display for PermissionRuleSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User => write!(f, "user config"),
+            Self::Project => write!(f, "project settings"),
+            Self::Builtin => write!(f, "built-in rule"),
+        }
+    }
+}
+
+/// Rule that decided a tool call
+#[derive(Debug, Clone)]
+pub struct PermissionMatch {
+    pub decision: PermissionDecision,
+    pub rule: String,
+    pub source: PermissionRuleSource,
+}
+
+impl PermissionMatch {
+    /// Message returned to the model when the call is denied
+    pub fn deny_reason(&self, tool_name: &str) -> String {
+        format!(
+            "Permission denied: tool '{}' is blocked by rule '{}' ({}). Do not retry this call; choose a different approach or ask the user.",
+            tool_name, self.rule, self.source
+        )
+    }
+}
+
+/// How the specifier of a tool call is matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpecifierKind {
+    /// Shell-like command, `*` also matches `/`
+    Command,
+    /// File path, `*` stops at `/` and `**` spans directories
+    Path,
+    /// Any other string argument
+    Text,
+}
+
+#[derive(Debug)]
+struct PermissionRule {
+    raw: String,
+    decision: PermissionDecision,
+    source: PermissionRuleSource,
+    tool: GlobMatcher,
+    /// Compiled specifier: (command matcher where `*` matches `/`, path matcher)
+    specifier: Option<(GlobMatcher, GlobMatcher)>,
+}
+
+impl PermissionRule {
+    fn parse(raw: &str, decision: PermissionDecision, source: PermissionRuleSource) -> Option<Self> {
+        let trimmed = raw.trim();
+        let (tool_pattern, specifier) = match trimmed.find('(') {
+            Some(open) if trimmed.ends_with(')') => (
+                trimmed[..open].trim(),
+                Some(trimmed[open + 1..trimmed.len() - 1].trim()),
+            ),
+            _ => (trimmed, None),
+        };
+
+        if tool_pattern.is_empty() {
+            warn!("Ignoring permission rule without tool name: {}", raw);
+            return None;
+        }
+
+        let tool = match compile(&normalize_tool_pattern(tool_pattern), false) {
+            Some(matcher) => matcher,
+            None => {
+                warn!("Ignoring invalid permission rule: {}", raw);
+                return None;
+            }
+        };
+
+        // `Tool()` and `Tool(*)` are the same as `Tool`
+        let specifier = match specifier.filter(|s| !s.is_empty() && *s != "*") {
+            Some(spec) => match (compile(spec, false), compile(spec, true)) {
+                (Some(command), Some(path)) => Some((command, path)),
+                _ => {
+                    warn!("Ignoring invalid permission rule: {}", raw);
+                    return None;
+                }
+            },
+            None => None,
+        };
+
+        Some(Self {
+            raw: trimmed.to_string(),
+            decision,
+            source,
+            tool,
+            specifier,
+        })
+    }
+
+    fn matches_tool(&self, tool_name: &str) -> bool {
+        self.tool.is_match(tool_name)
+    }
+
+    fn matches_value(&self, kind: SpecifierKind, value: &str) -> bool {
+        match &self.specifier {
+            None => true,
+            Some((command, path)) => match kind {
+                SpecifierKind::Path => path.is_match(value),
+                SpecifierKind::Command | SpecifierKind::Text => command.is_match(value),
+            },
+        }
+    }
+}
+
+/// Rule set evaluated by the tool pipeline
+#[derive(Debug, Default)]
+pub struct PermissionRules {
+    rules: Vec<PermissionRule>,
+    workspace: Option<PathBuf>,
+}
+
+impl PermissionRules {
+    /// Build the rules that apply to a mode
+    pub fn from_configs(
+        user: &ToolPermissionsConfig,
+        project: Option<&ToolPermissionsConfig>,
+        mode: &str,
+        workspace: Option<PathBuf>,
+    ) -> Self {
+        let mut rules: Vec<PermissionRule> = BUILTIN_ASK_RULES
+            .iter()
+            .filter_map(|raw| {
+                PermissionRule::parse(raw, PermissionDecision::Ask, PermissionRuleSource::Builtin)
+            })
+            .collect();
+        let mut sources = vec![(user, PermissionRuleSource::User)];
+        if let Some(project) = project {
+            sources.push((project, PermissionRuleSource::Project));
+        }
+
+        for (config, source) in sources {
+            Self::push_rules(&mut rules, &config.rules, source);
+            if let Some(mode_rules) = config.modes.get(mode) {
+                Self::push_rules(&mut rules, mode_rules, source);
+            }
+        }
+
+        Self { rules, workspace }
+    }
+
+    fn push_rules(
+        rules: &mut Vec<PermissionRule>,
+        config: &ToolPermissionRules,
+        source: PermissionRuleSource,
+    ) {
+        let groups = [
+            (&config.deny, PermissionDecision::Deny),
+            (&config.ask, PermissionDecision::Ask),
+            (&config.allow, PermissionDecision::Allow),
+        ];
+        for (patterns, decision) in groups {
+            // A checkout must not pre-approve tool calls
+            if decision == PermissionDecision::Allow && source == PermissionRuleSource::Project {
+                if !patterns.is_empty() {
+                    warn!(
+                        "Ignoring allow rules from project settings: {}",
+                        patterns.join(", ")
+                    );
+                }
+                continue;
+            }
+            rules.extend(
+                patterns
+                    .iter()
+                    .filter_map(|raw| PermissionRule::parse(raw, decision, source)),
+            );
+        }
+    }
+
+    /// Load the rules for a mode from the user config and the current workspace
+    pub async fn load(mode: &str) -> Self {
+        let user = match GlobalConfigManager::get_service().await {
+            Ok(service) => service
+                .get_config::<AIConfig>(Some("ai"))
+                .await
+                .map(|config| config.tool_permissions)
+                .unwrap_or_default(),
+            Err(_) => ToolPermissionsConfig::default(),
+        };
+
+        let workspace = get_workspace_path();
+        let project = workspace
+            .as_deref()
+            .and_then(load_project_settings)
+            .map(|settings| settings.permissions);
+
+        Self::from_configs(&user, project.as_ref(), mode, workspace)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Evaluate a tool call; `None` means no rule applies
+    ///
+    /// Deny rules win over ask rules, which win over allow rules. For shell commands
+    /// chained with `&&`, `||`, `&`, `;`, `|` or newlines, any denied part denies the whole
+    /// command and every part must be allowed for the command to be allowed. Commands with
+    /// substitutions are never allowed by a rule. MultiEdit is checked per target file.
+    pub fn evaluate(&self, tool_name: &str, args: &Value) -> Option<PermissionMatch> {
+        if tool_name == "MultiEdit" {
+            return self.evaluate_multi_edit(args);
+        }
+        self.evaluate_specifier(&[tool_name], extract_specifier(tool_name, args))
+    }
+
+    /// Check every file a MultiEdit call writes against the MultiEdit and Edit rules
+    ///
+    /// A denied target denies the whole call, and every target must be allowed for the
+    /// call to be allowed.
+    fn evaluate_multi_edit(&self, args: &Value) -> Option<PermissionMatch> {
+        let tools = ["MultiEdit", "Edit"];
+        let paths = multi_edit_paths(args);
+        if paths.is_empty() {
+            return self.evaluate_specifier(&tools, None);
+        }
+
+        let matches: Vec<Option<PermissionMatch>> = paths
+            .into_iter()
+            .map(|path| self.evaluate_specifier(&tools, Some((SpecifierKind::Path, path))))
+            .collect();
+        for decision in [PermissionDecision::Deny, PermissionDecision::Ask] {
+            if let Some(matched) = matches.iter().flatten().find(|m| m.decision == decision) {
+                return Some(matched.clone());
+            }
+        }
+        matches.into_iter().collect::<Option<Vec<_>>>()?.into_iter().next()
+    }
+
+    fn evaluate_specifier(
+        &self,
+        tool_names: &[&str],
+        specifier: Option<(SpecifierKind, String)>,
+    ) -> Option<PermissionMatch> {
+        let candidates: Vec<&PermissionRule> = self
+            .rules
+            .iter()
+            .filter(|rule| tool_names.iter().any(|tool| rule.matches_tool(tool)))
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        // Allow rules only see normalized paths that stay inside their base
+        let allowed_paths = match &specifier {
+            Some((SpecifierKind::Path, path)) => self.normalized_path_variants(path),
+            _ => Vec::new(),
+        };
+        let values: Vec<String> = match &specifier {
+            Some((SpecifierKind::Path, path)) => {
+                let mut values = vec![path.clone()];
+                values.extend(allowed_paths.iter().cloned());
+                values
+            }
+            Some((SpecifierKind::Command, command)) => {
+                let mut values = vec![command.trim().to_string()];
+                values.extend(split_command(command).into_iter().filter(|part| part != command.trim()));
+                values
+            }
+            Some((SpecifierKind::Text, text)) => vec![text.clone()],
+            None => Vec::new(),
+        };
+        let kind = specifier.as_ref().map(|(kind, _)| *kind).unwrap_or(SpecifierKind::Text);
+
+        // A rule with a specifier never matches a call without one
+        let matches = |rule: &PermissionRule, value: &str| rule.matches_value(kind, value);
+        let matches_any = |rule: &PermissionRule| {
+            rule.specifier.is_none() || values.iter().any(|value| matches(rule, value))
+        };
+
+        for decision in [PermissionDecision::Deny, PermissionDecision::Ask] {
+            if let Some(rule) = candidates
+                .iter()
+                .find(|rule| rule.decision == decision && matches_any(rule))
+            {
+                return Some(to_match(rule));
+            }
+        }
+
+        let allow_rules: Vec<&&PermissionRule> = candidates
+            .iter()
+            .filter(|rule| rule.decision == PermissionDecision::Allow)
+            .collect();
+
+        if kind == SpecifierKind::Command {
+            if let Some((_, command)) = &specifier {
+                // Substitutions run commands that no allow rule has seen
+                if has_substitution(command) {
+                    return None;
+                }
+                let parts = split_command(command);
+                if parts.len() > 1 {
+                    // Every part must be covered by some allow rule
+                    let mut first = None;
+                    for part in &parts {
+                        let rule = allow_rules.iter().find(|rule| matches(rule, part))?;
+                        first.get_or_insert(**rule);
+                    }
+                    return first.map(to_match);
+                }
+            }
+        }
+
+        if kind == SpecifierKind::Path {
+            return allow_rules
+                .into_iter()
+                .find(|rule| {
+                    rule.specifier.is_none()
+                        || allowed_paths.iter().any(|value| matches(rule, value))
+                })
+                .map(|rule| to_match(rule));
+        }
+
+        allow_rules
+            .into_iter()
+            .find(|rule| matches_any(rule))
+            .map(|rule| to_match(rule))
+    }
+
+    /// Lexically normalized path plus its workspace-relative form
+    ///
+    /// Relative paths are resolved against the workspace. A relative form is only produced
+    /// when the path stays inside the workspace, so `src/../../etc/passwd` never matches
+    /// `src/**`.
+    fn normalized_path_variants(&self, path: &str) -> Vec<String> {
+        let path_ref = Path::new(path);
+        let absolute = match &self.workspace {
+            Some(workspace) if path_ref.is_relative() => workspace.join(path_ref),
+            _ => path_ref.to_path_buf(),
+        };
+        let Some(normalized) = normalize_path(&absolute) else {
+            return Vec::new();
+        };
+
+        let mut variants = Vec::new();
+        if normalized.is_absolute() {
+            variants.push(normalized.to_string_lossy().replace('\\', "/"));
+        }
+        let relative = match &self.workspace {
+            Some(workspace) => normalized.strip_prefix(workspace).ok().map(Path::to_path_buf),
+            None if normalized.is_relative() => Some(normalized.clone()),
+            None => None,
+        };
+        if let Some(relative) = relative.filter(|r| !r.as_os_str().is_empty()) {
+            variants.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+
+        variants
+    }
+}
+
+/// Resolve `.` and `..` without touching the filesystem; None if a relative path
+/// climbs above its start
+fn normalize_path(path: &Path) -> Option<PathBuf> {
+    use std::path::Component;
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() && path.is_relative() {
+                    return None;
+                }
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    Some(normalized)
+}
+
+fn to_match(rule: &PermissionRule) -> PermissionMatch {
+    PermissionMatch {
+        decision: rule.decision,
+        rule: rule.raw.clone(),
+        source: rule.source,
+    }
+}
+
+/// MCP tools are registered as `mcp_{server}_{tool}`; also accept the
+/// `mcp__server__tool` spelling used by other clients
+fn normalize_tool_pattern(pattern: &str) -> String {
+    match pattern.strip_prefix("mcp__") {
+        Some(rest) => format!("mcp_{}", rest.replace("__", "_")),
+        None => pattern.to_string(),
+    }
+}
+
+fn compile(pattern: &str, literal_separator: bool) -> Option<GlobMatcher> {
+    GlobBuilder::new(pattern)
+        .literal_separator(literal_separator)
+        .backslash_escape(true)
+        .build()
+        .map(|glob| glob.compile_matcher())
+        .map_err(|e| debug!("Invalid permission pattern: pattern={}, error={}", pattern, e))
+        .ok()
+}
+
+/// Argument a rule specifier is matched against
+fn extract_specifier(tool_name: &str, args: &Value) -> Option<(SpecifierKind, String)> {
+    let get = |key: &str| args.get(key).and_then(Value::as_str).map(str::to_string);
+
+    match tool_name {
+        "Bash" => get("command").map(|c| (SpecifierKind::Command, c)),
+        "Git" => get("operation").map(|operation| {
+            let command = match get("args") {
+                Some(a) if !a.trim().is_empty() => format!("{} {}", operation, a.trim()),
+                _ => operation,
+            };
+            (SpecifierKind::Command, command)
+        }),
+        "Task" => get("subagent_type").map(|s| (SpecifierKind::Text, s)),
+        _ => {
+            if let Some(path) = get("file_path").or_else(|| get("path")) {
+                return Some((SpecifierKind::Path, path));
+            }
+            ["command", "url", "query", "pattern"]
+                .iter()
+                .find_map(|key| get(key))
+                .map(|value| (SpecifierKind::Text, value))
+        }
+    }
+}
+
+/// Files written by a MultiEdit call: `files[].file_path`, or every source and rename
+/// target of `patch`
+fn multi_edit_paths(args: &Value) -> Vec<String> {
+    if let Some(patch) = args.get("patch").and_then(Value::as_str) {
+        // A patch that does not parse is rejected by the tool before anything is written
+        return parse_patch(patch)
+            .map(|files| {
+                files
+                    .into_iter()
+                    .flat_map(|file| {
+                        let move_to = match &file {
+                            FilePatch::Update { move_to, .. } => move_to.clone(),
+                            _ => None,
+                        };
+                        std::iter::once(file.path().to_string()).chain(move_to)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    args.get("files")
+        .and_then(Value::as_array)
+        .map(|files| {
+            files
+                .iter()
+                .filter_map(|file| file.get("file_path").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a command contains command or process substitution
+///
+/// Checked over the whole string, quotes included, since `$(...)` and backticks
+/// also run inside double quotes.
+fn has_substitution(command: &str) -> bool {
+    ["$(", "`", "<(", ">("]
+        .iter()
+        .any(|token| command.contains(token))
+}
+
+/// Split a shell command on `&&`, `||`, `&`, `;`, `|` and newlines (quotes are respected)
+fn split_command(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) => {
+                current.push(c);
+                if c == q {
+                    quote = None;
+                } else if c == '\\' && q == '"' {
+                    if let Some(next) = chars.next() {
+                        current.push(next);
+                    }
+                }
+            }
+            None => match c {
+                '\'' | '"' => {
+                    quote = Some(c);
+                    current.push(c);
+                }
+                '\\' => {
+                    current.push(c);
+                    if let Some(next) = chars.next() {
+                        current.push(next);
+                    }
+                }
+                ';' | '\n' => parts.push(std::mem::take(&mut current)),
+                '&' => {
+                    if chars.peek() == Some(&'&') {
+                        chars.next();
+                        parts.push(std::mem::take(&mut current));
+                    } else if current.ends_with('>') || chars.peek() == Some(&'>') {
+                        // Redirections such as `2>&1` and `&>file`
+                        current.push(c);
+                    } else {
+                        // Background operator
+                        parts.push(std::mem::take(&mut current));
+                    }
+                }
+                '|' => {
+                    if chars.peek() == Some(&'|') {
+                        chars.next();
+                    }
+                    parts.push(std::mem::take(&mut current));
+                }
+                _ => current.push(c),
+            },
+        }
+    }
+    parts.push(current);
+
+    parts
+        .into_iter()
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(allow: &[&str], deny: &[&str], ask: &[&str]) -> PermissionRules {
+        let to_vec = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        let config = ToolPermissionsConfig {
+            rules: ToolPermissionRules {
+                allow: to_vec(allow),
+                deny: to_vec(deny),
+                ask: to_vec(ask),
+            },
+            modes: Default::default(),
+        };
+        PermissionRules::from_configs(&config, None, "agentic", Some(PathBuf::from("/repo")))
+    }
+
+    fn decision(rules: &PermissionRules, tool: &str, args: Value) -> Option<PermissionDecision> {
+        rules.evaluate(tool, &args).map(|m| m.decision)
+    }
+
+    #[test]
+    fn bash_command_prefix() {
+        let rules = rules(&["Bash(git status*)"], &["Bash(rm -rf *)"], &[]);
+        assert_eq!(
+            decision(&rules, "Bash", json!({"command": "git status --short"})),
+            Some(PermissionDecision::Allow)
+        );
+        assert_eq!(
+            decision(&rules, "Bash", json!({"command": "rm -rf /tmp/x"})),
+            Some(PermissionDecision::Deny)
+        );
+        assert_eq!(decision(&rules, "Bash", json!({"command": "ls"})), None);
+    }
+
+    #[test]
+    fn chained_commands_need_every_part_allowed() {
+        let rules = rules(&["Bash(git status*)", "Bash(git diff*)"], &["Bash(rm *)"], &[]);
+        assert_eq!(
+            decision(&rules, "Bash", json!({"command": "git status && git diff"})),
+            Some(PermissionDecision::Allow)
+        );
+        assert_eq!(
+            decision(&rules, "Bash", json!({"command": "git status && make"})),
+            None
+        );
+        assert_eq!(
+            decision(&rules, "Bash", json!({"command": "git status; rm -f a"})),
+            Some(PermissionDecision::Deny)
+        );
+        assert_eq!(
+            decision(&rules, "Bash", json!({"command": "echo 'a && b'"})),
+            None
+        );
+    }
+
+    #[test]
+    fn background_and_substitutions_are_not_allowed() {
+        let rules = rules(&["Bash(git status*)"], &[], &[]);
+        for command in [
+            "git status & rm -rf ~",
+            "git status\nrm -rf ~",
+            "git status $(rm -rf ~)",
+            "git status `rm -rf ~`",
+            "git status <(rm -rf ~)",
+            "git status \"$(rm -rf ~)\"",
+        ] {
+            assert_eq!(decision(&rules, "Bash", json!({ "command": command })), None);
+        }
+        assert_eq!(
+            decision(&rules, "Bash", json!({"command": "git status 2>&1"})),
+            Some(PermissionDecision::Allow)
+        );
+    }
+
+    #[test]
+    fn path_patterns_use_workspace_relative_paths() {
+        let rules = rules(&["Write(src/**)"], &["Read(**/.env)"], &[]);
+        assert_eq!(
+            decision(&rules, "Write", json!({"file_path": "/repo/src/a/b.rs"})),
+            Some(PermissionDecision::Allow)
+        );
+        assert_eq!(
+            decision(&rules, "Write", json!({"file_path": "/repo/docs/a.md"})),
+            None
+        );
+        assert_eq!(
+            decision(&rules, "Read", json!({"file_path": "/repo/app/.env"})),
+            Some(PermissionDecision::Deny)
+        );
+    }
+
+    #[test]
+    fn allow_rules_do_not_match_paths_escaping_the_workspace() {
+        let rules = rules(&["Write(src/**)"], &[], &[]);
+        for path in [
+            "src/../../etc/passwd",
+            "/repo/src/../../home/u/.ssh/authorized_keys",
+            "./src/../../x",
+        ] {
+            assert_eq!(decision(&rules, "Write", json!({ "file_path": path })), None);
+        }
+        assert_eq!(
+            decision(&rules, "Write", json!({"file_path": "src/a/../b.rs"})),
+            Some(PermissionDecision::Allow)
+        );
+    }
+
+    #[test]
+    fn multi_edit_checks_every_target_against_edit_rules() {
+        let rules = rules(&["Edit(src/**)"], &["Edit(secrets/**)"], &[]);
+        let files = |paths: &[&str]| {
+            let files: Vec<Value> = paths
+                .iter()
+                .map(|path| json!({ "file_path": path, "edits": [] }))
+                .collect();
+            json!({ "files": files })
+        };
+        assert_eq!(
+            decision(&rules, "MultiEdit", files(&["src/a.rs", "src/b.rs"])),
+            Some(PermissionDecision::Allow)
+        );
+        assert_eq!(decision(&rules, "MultiEdit", files(&["src/a.rs", "docs/a.md"])), None);
+        assert_eq!(
+            decision(&rules, "MultiEdit", files(&["src/a.rs", "secrets/key"])),
+            Some(PermissionDecision::Deny)
+        );
+
+        let patch = "--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1 +1 @@\n-a\n+b\n--- a/secrets/key\n+++ b/secrets/key\n@@ -1 +1 @@\n-a\n+b\n";
+        assert_eq!(
+            decision(&rules, "MultiEdit", json!({ "patch": patch })),
+            Some(PermissionDecision::Deny)
+        );
+        let patch = "*** Begin Patch\n*** Update File: src/a.rs\n*** Move to: secrets/a.rs\n@@\n-a\n+b\n*** End Patch\n";
+        assert_eq!(
+            decision(&rules, "MultiEdit", json!({ "patch": patch })),
+            Some(PermissionDecision::Deny)
+        );
+    }
+
+    #[test]
+    fn deny_wins_over_ask_and_allow() {
+        let rules = rules(&["Bash"], &["Bash(sudo *)"], &["Bash(git push*)"]);
+        assert_eq!(
+            decision(&rules, "Bash", json!({"command": "sudo ls"})),
+            Some(PermissionDecision::Deny)
+        );
+        assert_eq!(
+            decision(&rules, "Bash", json!({"command": "git push origin"})),
+            Some(PermissionDecision::Ask)
+        );
+        assert_eq!(
+            decision(&rules, "Bash", json!({"command": "cargo build"})),
+            Some(PermissionDecision::Allow)
+        );
+    }
+
+    #[test]
+    fn mcp_tool_names() {
+        let rules = rules(&[], &["mcp__github__*"], &[]);
+        assert_eq!(
+            decision(&rules, "mcp_github_create_issue", json!({})),
+            Some(PermissionDecision::Deny)
+        );
+        assert_eq!(decision(&rules, "mcp_gitlab_list", json!({})), None);
+    }
+
+    #[test]
+    fn mode_rules_apply_only_to_their_mode() {
+        let mut config = ToolPermissionsConfig::default();
+        config.modes.insert(
+            "debug".to_string(),
+            ToolPermissionRules {
+                deny: vec!["Write".to_string()],
+                ..Default::default()
+            },
+        );
+
+        let debug = PermissionRules::from_configs(&config, None, "debug", None);
+        let agentic = PermissionRules::from_configs(&config, None, "agentic", None);
+        assert!(debug.evaluate("Write", &json!({"file_path": "a"})).is_some());
+        assert!(agentic.evaluate("Write", &json!({"file_path": "a"})).is_none());
+    }
+
+    #[test]
+    fn project_allow_rules_are_ignored_and_bitfun_writes_ask() {
+        let project = ToolPermissionsConfig {
+            rules: ToolPermissionRules {
+                allow: vec!["Bash(*)".to_string(), "Write".to_string()],
+                deny: vec!["Bash(rm *)".to_string()],
+                ask: vec![],
+            },
+            modes: Default::default(),
+        };
+        let project_rules = PermissionRules::from_configs(
+            &ToolPermissionsConfig::default(),
+            Some(&project),
+            "agentic",
+            Some(PathBuf::from("/repo")),
+        );
+        assert_eq!(
+            decision(&project_rules, "Bash", json!({"command": "curl x"})),
+            None
+        );
+        assert_eq!(
+            decision(&project_rules, "Bash", json!({"command": "rm -rf src"})),
+            Some(PermissionDecision::Deny)
+        );
+
+        // Writes to .bitfun ask even when the user allows them
+        let user_rules = rules(&["Write", "Edit", "MultiEdit"], &[], &[]);
+        for path in [".bitfun/settings.json", "/repo/.bitfun/hooks.json", "src/../.bitfun/x"] {
+            assert_eq!(
+                decision(&user_rules, "Write", json!({"file_path": path})),
+                Some(PermissionDecision::Ask),
+                "{}",
+                path
+            );
+        }
+        assert_eq!(
+            decision(
+                &user_rules,
+                "MultiEdit",
+                json!({"files": [{"file_path": "src/a.rs"}, {"file_path": ".bitfun/settings.json"}]})
+            ),
+            Some(PermissionDecision::Ask)
+        );
+        assert_eq!(
+            decision(&user_rules, "Write", json!({"file_path": "src/main.rs"})),
+            Some(PermissionDecision::Allow)
+        );
+    }
+}
@@ -4,13 +4,17 @@
 //! confirmation, execution, caching, retries, etc.
 
 use log::{debug, info, warn, error};
+use super::permission_rules::{PermissionDecision, PermissionRules};
 use super::state_manager::ToolStateManager;
 use super::types::*;
 use crate::agentic::core::{ToolCall, ToolResult as ModelToolResult, ToolExecutionState};
 use crate::agentic::events::types::ToolEventData;
+use crate::agentic::hooks::{format_reminder, HookEvent, HookInput, HookRunner};
 use crate::agentic::tools::registry::ToolRegistry;
 use crate::agentic::tools::framework::{ToolUseContext, ToolOptions, ToolResult as FrameworkToolResult};
 use crate::agentic::tools::image_context::ImageContextProviderRef;
+use crate::infrastructure::get_workspace_path;
+use crate::service::snapshot::{get_global_snapshot_manager, SnapshotManager, WorkspaceScan};
 use crate::util::errors::{BitFunError, BitFunResult};
 use futures::future::join_all;
 use std::collections::HashMap;
@@ -21,6 +25,10 @@ use tokio::sync::{oneshot, RwLock as TokioRwLock};
 use dashmap::DashMap;
 use tokio_util::sync::CancellationToken;
 
+/// Tools never covered by a workspace scan: a subagent's file changes are recorded by its own
+/// wrapped tools, or land in its worktree when it runs isolated
+const SCAN_SKIPPED_TOOLS: &[&str] = &["Task"];
+
 /// Convert framework::ToolResult to core::ToolResult
 /// 
 /// Ensure always has result_for_assistant, avoid tool message content being empty
@@ -398,7 +406,7 @@ impl ToolPipeline {
             .ok_or_else(|| BitFunError::NotFound(format!("Tool task not found: {}", tool_id)))?;
         
         let tool_name = task.tool_call.tool_name.clone();
-        let tool_args = task.tool_call.arguments.clone();
+        let mut tool_args = task.tool_call.arguments.clone();
         let tool_is_error = task.tool_call.is_error;
         
         debug!("Tool task details: tool_name={}, tool_id={}", tool_name, tool_id);
@@ -462,8 +470,66 @@ impl ToolPipeline {
 
         let is_streaming = tool.supports_streaming();
 
-        let needs_confirmation = task.options.confirm_before_run
-            && tool.needs_permissions(Some(&tool_args));
+        // PreToolUse hooks may block the call or rewrite its input
+        let hooks = HookRunner::load().await;
+        let mut hook_context = Vec::new();
+        if hooks.has_hooks(HookEvent::PreToolUse, Some(&tool_name)) {
+            let outcome = hooks.run(self.hook_input(HookEvent::PreToolUse, &task, &tool_args)).await;
+            hook_context.extend(outcome.additional_context);
+
+            if let Some(reason) = outcome.blocked {
+                let error_msg = format!("Tool call blocked by PreToolUse hook: {}", reason);
+                warn!("Tool blocked by hook: tool_name={}, reason={}", tool_name, reason);
+
+                self.state_manager
+                    .update_state(&tool_id, ToolExecutionState::Failed {
+                        error: error_msg.clone(),
+                        is_retryable: false,
+                    })
+                    .await;
+                self.cancellation_tokens.remove(&tool_id);
+
+                return Err(BitFunError::Validation(error_msg));
+            }
+
+            if let Some(updated_input) = outcome.updated_input {
+                self.state_manager.update_task_arguments(&tool_id, updated_input.clone());
+                tool_args = updated_input;
+            }
+        }
+
+        // Declarative permission rules are evaluated before confirmation
+        let permission = PermissionRules::load(&task.context.agent_type)
+            .await
+            .evaluate(&tool_name, &tool_args);
+
+        if let Some(ref matched) = permission {
+            debug!(
+                "Permission rule matched: tool_name={}, rule={}, decision={:?}",
+                tool_name, matched.rule, matched.decision
+            );
+        }
+
+        if let Some(matched) = permission.as_ref().filter(|m| m.decision == PermissionDecision::Deny) {
+            let error_msg = matched.deny_reason(&tool_name);
+            warn!("Tool denied by permission rule: tool_name={}, rule={}", tool_name, matched.rule);
+
+            self.state_manager
+                .update_state(&tool_id, ToolExecutionState::Failed {
+                    error: error_msg.clone(),
+                    is_retryable: false,
+                })
+                .await;
+            self.cancellation_tokens.remove(&tool_id);
+
+            return Err(BitFunError::Validation(error_msg));
+        }
+
+        let needs_confirmation = match permission.map(|m| m.decision) {
+            Some(PermissionDecision::Allow) => false,
+            Some(PermissionDecision::Ask) => true,
+            _ => task.options.confirm_before_run && tool.needs_permissions(Some(&tool_args)),
+        };
 
         if needs_confirmation {
             info!("Tool requires confirmation: tool_name={}", tool_name);
@@ -551,6 +617,9 @@ impl ToolPipeline {
             return Err(BitFunError::Cancelled("Tool was cancelled before execution".to_string()));
         }
         
+        // Pick up arguments rewritten by hooks or by the user during confirmation
+        let task = self.state_manager.get_task(&tool_id).unwrap_or(task);
+
         // Set initial state
         if is_streaming {
             self.state_manager
@@ -573,7 +642,24 @@ impl ToolPipeline {
         self.cancellation_tokens.remove(&tool_id);
         
         match result {
-            Ok(tool_result) => {
+            Ok(mut tool_result) => {
+                if hooks.has_hooks(HookEvent::PostToolUse, Some(&tool_name)) {
+                    let mut input = self.hook_input(HookEvent::PostToolUse, &task, &task.tool_call.arguments);
+                    input.tool_result = Some(tool_result.result.clone());
+                    let outcome = hooks.run(input).await;
+
+                    hook_context.extend(outcome.additional_context);
+                    if let Some(reason) = outcome.blocked {
+                        hook_context.push(format!("PostToolUse hook feedback: {}", reason));
+                    }
+                }
+                if let Some(reminder) = format_reminder(&hook_context) {
+                    tool_result.result_for_assistant = Some(match tool_result.result_for_assistant.take() {
+                        Some(text) if !text.is_empty() => format!("{}\n\n{}", text, reminder),
+                        _ => reminder,
+                    });
+                }
+
                 let duration_ms = start_time.elapsed().as_millis() as u64;
                 
                 self.state_manager
@@ -610,6 +696,13 @@ impl ToolPipeline {
         }
     }
     
+    /// Build the hook input of a tool call
+    fn hook_input(&self, event: HookEvent, task: &ToolTask, tool_args: &serde_json::Value) -> HookInput {
+        HookInput::new(event, task.context.session_id.clone())
+            .with_turn(task.context.dialog_turn_id.clone(), task.context.agent_type.clone())
+            .with_tool(task.tool_call.tool_name.clone(), task.tool_call.tool_id.clone(), tool_args.clone())
+    }
+
     /// Execute with retry
     async fn execute_with_retry(
         &self,
@@ -699,6 +792,9 @@ impl ToolPipeline {
                             map.insert("turn_index".to_string(), serde_json::json!(n));
                         }
                     }
+                    if let Some(active_plan) = task.context.context_vars.get("active_plan") {
+                        map.insert("active_plan".to_string(), serde_json::json!(active_plan));
+                    }
                     
                     map
                 }),
@@ -709,21 +805,42 @@ impl ToolPipeline {
             cancellation_token: Some(cancellation_token),
         };
         
+        let workspace_scan = self.scan_before_unwrapped_tool(task, tool.as_ref()).await;
+        
         let execution_future = tool.call(&task.tool_call.arguments, &tool_context);
         
         let tool_results = match task.options.timeout_secs {
             Some(timeout_secs) => {
                 let timeout_duration = Duration::from_secs(timeout_secs);
-                let result = timeout(timeout_duration, execution_future)
+                timeout(timeout_duration, execution_future)
                     .await
-                    .map_err(|_| BitFunError::Timeout(format!("Tool execution timeout: {}", task.tool_call.tool_name)))?;
-                result?
+                    .map_err(|_| BitFunError::Timeout(format!("Tool execution timeout: {}", task.tool_call.tool_name)))
+                    .and_then(|result| result)
             }
             None => {
-                execution_future.await?
+                execution_future.await
             }
         };
         
+        // Record changes even if the tool failed, since they were made anyway
+        if let Some((snapshot_manager, before)) = workspace_scan {
+            let turn_index = task.context.context_vars.get("turn_index")
+                .and_then(|n| n.parse::<usize>().ok())
+                .unwrap_or(0);
+            if let Err(e) = snapshot_manager.record_workspace_changes(
+                &task.context.session_id,
+                turn_index,
+                &task.tool_call.tool_name,
+                &task.tool_call.arguments,
+                Some(&task.tool_call.tool_id),
+                before,
+            ).await {
+                warn!("Failed to record workspace changes: tool_name={}, error={}", task.tool_call.tool_name, e);
+            }
+        }
+        
+        let tool_results = tool_results?;
+        
         if tool.supports_streaming() && tool_results.len() > 1 {
             self.handle_streaming_results(task, &tool_results).await?;
         }
@@ -733,6 +850,33 @@ impl ToolPipeline {
             .ok_or_else(|| BitFunError::Tool(format!("Tool did not return result: {}", task.tool_call.tool_name)))
     }
     
+    /// Scan the workspace before a tool whose file changes are not recorded by a snapshot wrapper
+    /// 
+    /// Read-only and concurrency-safe tools are skipped, as are subagent calls and calls
+    /// running in another checkout (e.g. a subagent in a git worktree).
+    async fn scan_before_unwrapped_tool(
+        &self,
+        task: &ToolTask,
+        tool: &dyn crate::agentic::tools::framework::Tool,
+    ) -> Option<(Arc<SnapshotManager>, WorkspaceScan)> {
+        if tool.is_readonly()
+            || tool.is_concurrency_safe(Some(&task.tool_call.arguments))
+            || SCAN_SKIPPED_TOOLS.contains(&tool.name())
+        {
+            return None;
+        }
+        let snapshot_manager = get_global_snapshot_manager()?;
+        if snapshot_manager.records_tool(tool.name()) {
+            return None;
+        }
+        if get_workspace_path().as_deref() != Some(snapshot_manager.workspace_dir()) {
+            return None;
+        }
+        
+        let scan = snapshot_manager.scan_workspace().await?;
+        Some((snapshot_manager, scan))
+    }
+    
     /// Handle streaming results
     async fn handle_streaming_results(
         &self,
@@ -5,8 +5,18 @@ use crate::agentic::tools::implementations::*;
 use crate::util::errors::BitFunResult;
 use indexmap::IndexMap;
 use log::{debug, info, trace, warn};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+/// Incremented whenever the set of MCP tools changes, so running dialog turns can
+/// pick up the new tool set on their next round
+static MCP_TOOLS_REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// Current revision of the registered MCP tools
+pub fn mcp_tools_revision() -> u64 {
+    MCP_TOOLS_REVISION.load(Ordering::Acquire)
+}
+
 /// Tool registry - manages all available tools (using IndexMap to maintain registration order)
 pub struct ToolRegistry {
     tools: IndexMap<String, Arc<dyn Tool>>,
@@ -60,6 +70,44 @@ impl ToolRegistry {
             "MCP tools registration completed: before={}, after={}, added={}",
             before_count, after_count, added_count
         );
+        MCP_TOOLS_REVISION.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Replaces the tools of an MCP server with a freshly listed set
+    ///
+    /// Tools that still exist are updated in place; returns the names of the added and
+    /// removed tools.
+    pub fn sync_mcp_server_tools(
+        &mut self,
+        server_id: &str,
+        tools: Vec<Arc<dyn Tool>>,
+    ) -> (Vec<String>, Vec<String>) {
+        let prefix = format!("mcp_{}_", server_id);
+        let new_names: std::collections::HashSet<String> =
+            tools.iter().map(|tool| tool.name().to_string()).collect();
+
+        let removed: Vec<String> = self
+            .tools
+            .keys()
+            .filter(|k| k.starts_with(&prefix) && !new_names.contains(*k))
+            .cloned()
+            .collect();
+        for name in &removed {
+            info!("Unregistering MCP tool: tool_name={}", name);
+            self.tools.shift_remove(name);
+        }
+
+        let mut added = Vec::new();
+        for tool in tools {
+            let name = tool.name().to_string();
+            if self.tools.insert(name.clone(), tool).is_none() {
+                info!("MCP tool registered: tool_name={}", name);
+                added.push(name);
+            }
+        }
+
+        MCP_TOOLS_REVISION.fetch_add(1, Ordering::AcqRel);
+        (added, removed)
     }
 
     /// Remove all tools from the MCP server
@@ -76,6 +124,7 @@ impl ToolRegistry {
             info!("Unregistering MCP tool: tool_name={}", key);
             self.tools.shift_remove(&key);
         }
+        MCP_TOOLS_REVISION.fetch_add(1, Ordering::AcqRel);
     }
 
     /// Register all tools
@@ -87,8 +136,11 @@ impl ToolRegistry {
         self.register_tool(Arc::new(GrepTool::new()));
         self.register_tool(Arc::new(FileWriteTool::new()));
         self.register_tool(Arc::new(FileEditTool::new()));
+        self.register_tool(Arc::new(MultiEditTool::new()));
         self.register_tool(Arc::new(DeleteFileTool::new()));
         self.register_tool(Arc::new(BashTool::new()));
+        self.register_tool(Arc::new(BashOutputTool::new()));
+        self.register_tool(Arc::new(BashJobsTool::new()));
 
         // TodoWrite tool
         self.register_tool(Arc::new(TodoWriteTool::new()));
@@ -120,6 +172,12 @@ impl ToolRegistry {
         // Linter tool (LSP diagnosis)
         self.register_tool(Arc::new(ReadLintsTool::new()));
 
+        // LSP code navigation tool
+        self.register_tool(Arc::new(LspTool::new()));
+
+        // Memory tool
+        self.register_tool(Arc::new(MemoryTool::new()));
+
         // Image analysis tool
         self.register_tool(Arc::new(AnalyzeImageTool::new()));
 
@@ -129,6 +187,9 @@ impl ToolRegistry {
         // CreatePlan tool
         self.register_tool(Arc::new(CreatePlanTool::new()));
 
+        // UpdatePlan tool
+        self.register_tool(Arc::new(UpdatePlanTool::new()));
+
         // Code review submit tool
         self.register_tool(Arc::new(CodeReviewTool::new()));
     }
@@ -0,0 +1,141 @@
+use crate::types::gemini::GeminiSSEData;
+use crate::types::unified::UnifiedResponse;
+use anyhow::{anyhow, Result};
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use log::{error, trace};
+use reqwest::Response;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+fn extract_sse_api_error_message(event_json: &Value) -> Option<String> {
+    let error = event_json.get("error")?;
+    let message = error
+        .get("message")
+        .and_then(|value| value.as_str())
+        .unwrap_or("An error occurred during streaming");
+    match error.get("status").and_then(|value| value.as_str()) {
+        Some(status) => Some(format!("{}: {}", status, message)),
+        None => Some(message.to_string()),
+    }
+}
+
+/// Convert a byte stream into a structured response stream
+///
+/// Gemini has no end-of-stream event: the stream simply closes after the chunk
+/// carrying `finishReason`.
+///
+/// # Arguments
+/// * `response` - HTTP response
+/// * `tx_event` - parsed event sender
+/// * `tx_raw_sse` - optional raw SSE sender (collect raw data for diagnostics)
+pub async fn handle_gemini_stream(
+    response: Response,
+    tx_event: mpsc::UnboundedSender<Result<UnifiedResponse>>,
+    tx_raw_sse: Option<mpsc::UnboundedSender<String>>,
+) {
+    let mut stream = response.bytes_stream().eventsource();
+    let idle_timeout = Duration::from_secs(600);
+    let mut finished = false;
+
+    loop {
+        let sse_event = timeout(idle_timeout, stream.next()).await;
+        let sse = match sse_event {
+            Ok(Some(Ok(sse))) => sse,
+            Ok(None) => {
+                if !finished {
+                    let error_msg = "SSE stream closed before response completed";
+                    error!("{}", error_msg);
+                    let _ = tx_event.send(Err(anyhow!(error_msg)));
+                }
+                return;
+            }
+            Ok(Some(Err(e))) => {
+                let error_msg = format!("SSE stream error: {}", e);
+                error!("{}", error_msg);
+                let _ = tx_event.send(Err(anyhow!(error_msg)));
+                return;
+            }
+            Err(_) => {
+                let error_msg = format!("SSE stream timeout after {}s", idle_timeout.as_secs());
+                error!("{}", error_msg);
+                let _ = tx_event.send(Err(anyhow!(error_msg)));
+                return;
+            }
+        };
+
+        let raw = sse.data;
+        trace!("Gemini SSE: {:?}", raw);
+        if let Some(ref tx) = tx_raw_sse {
+            let _ = tx.send(raw.clone());
+        }
+        if raw.trim().is_empty() {
+            continue;
+        }
+
+        let event_json: Value = match serde_json::from_str(&raw) {
+            Ok(json) => json,
+            Err(e) => {
+                let error_msg = format!("SSE parsing error: {}, data: {}", e, &raw);
+                error!("{}", error_msg);
+                let _ = tx_event.send(Err(anyhow!(error_msg)));
+                return;
+            }
+        };
+
+        if let Some(api_error_message) = extract_sse_api_error_message(&event_json) {
+            let error_msg = format!("SSE API error: {}, data: {}", api_error_message, raw);
+            error!("{}", error_msg);
+            let _ = tx_event.send(Err(anyhow!(error_msg)));
+            return;
+        }
+
+        let sse_data: GeminiSSEData = match serde_json::from_value(event_json) {
+            Ok(event) => event,
+            Err(e) => {
+                let error_msg = format!("SSE data schema error: {}, data: {}", e, &raw);
+                error!("{}", error_msg);
+                let _ = tx_event.send(Err(anyhow!(error_msg)));
+                return;
+            }
+        };
+
+        if let Some(block_reason) = sse_data.block_reason() {
+            let error_msg = format!("Gemini blocked the prompt: {}", block_reason);
+            error!("{}", error_msg);
+            let _ = tx_event.send(Err(anyhow!(error_msg)));
+            return;
+        }
+
+        finished |= sse_data.has_finish_reason();
+        let unified_responses = sse_data
+            .into_unified_responses(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+        trace!("Gemini unified responses: {:?}", unified_responses);
+        for unified_response in unified_responses {
+            let _ = tx_event.send(Ok(unified_response));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract_sse_api_error_message;
+
+    #[test]
+    fn extracts_api_error_with_status() {
+        let event = serde_json::json!({
+            "error": {
+                "code": 503,
+                "message": "The model is overloaded.",
+                "status": "UNAVAILABLE"
+            }
+        });
+        assert_eq!(
+            extract_sse_api_error_message(&event).as_deref(),
+            Some("UNAVAILABLE: The model is overloaded.")
+        );
+        assert!(extract_sse_api_error_message(&serde_json::json!({ "candidates": [] })).is_none());
+    }
+}
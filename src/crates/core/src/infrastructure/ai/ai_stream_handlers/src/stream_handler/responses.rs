@@ -0,0 +1,93 @@
+use crate::types::responses::{ResponsesStreamEvent, ResponsesStreamState, ResponsesStreamStep};
+use crate::types::unified::UnifiedResponse;
+use anyhow::{anyhow, Result};
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use log::{error, trace};
+use reqwest::Response;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+/// Convert a byte stream into a structured response stream
+///
+/// The stream ends with `response.completed` (or `response.incomplete`), whose event
+/// carries usage, the response id and the encrypted reasoning items.
+///
+/// # Arguments
+/// * `response` - HTTP response
+/// * `tx_event` - parsed event sender
+/// * `tx_raw_sse` - optional raw SSE sender (collect raw data for diagnostics)
+pub async fn handle_responses_stream(
+    response: Response,
+    tx_event: mpsc::UnboundedSender<Result<UnifiedResponse>>,
+    tx_raw_sse: Option<mpsc::UnboundedSender<String>>,
+) {
+    let mut stream = response.bytes_stream().eventsource();
+    let idle_timeout = Duration::from_secs(600);
+    let mut state = ResponsesStreamState::default();
+
+    loop {
+        let sse_event = timeout(idle_timeout, stream.next()).await;
+        let sse = match sse_event {
+            Ok(Some(Ok(sse))) => sse,
+            Ok(None) => {
+                let error_msg = "SSE stream closed before response completed";
+                error!("{}", error_msg);
+                let _ = tx_event.send(Err(anyhow!(error_msg)));
+                return;
+            }
+            Ok(Some(Err(e))) => {
+                let error_msg = format!("SSE stream error: {}", e);
+                error!("{}", error_msg);
+                let _ = tx_event.send(Err(anyhow!(error_msg)));
+                return;
+            }
+            Err(_) => {
+                let error_msg = format!("SSE stream timeout after {}s", idle_timeout.as_secs());
+                error!("{}", error_msg);
+                let _ = tx_event.send(Err(anyhow!(error_msg)));
+                return;
+            }
+        };
+
+        let raw = sse.data;
+        trace!("Responses SSE: [{}] {:?}", sse.event, raw);
+        if let Some(ref tx) = tx_raw_sse {
+            let _ = tx.send(format!("[{}] {}", sse.event, raw));
+        }
+        if raw.trim().is_empty() || raw == "[DONE]" {
+            continue;
+        }
+
+        let event: ResponsesStreamEvent = match serde_json::from_str(&raw) {
+            Ok(event) => event,
+            Err(e) => {
+                let error_msg = format!("SSE data schema error: {}, data: {}", e, &raw);
+                error!("{}", error_msg);
+                let _ = tx_event.send(Err(anyhow!(error_msg)));
+                return;
+            }
+        };
+
+        match state.handle_event(event) {
+            ResponsesStreamStep::Continue(unified_responses) => {
+                for unified_response in unified_responses {
+                    trace!("Responses unified response: {:?}", unified_response);
+                    let _ = tx_event.send(Ok(unified_response));
+                }
+            }
+            ResponsesStreamStep::Done(unified_response) => {
+                trace!("Responses unified response: {:?}", unified_response);
+                let _ = tx_event.send(Ok(unified_response));
+                return;
+            }
+            ResponsesStreamStep::Failed(api_error_message) => {
+                let error_msg = format!("SSE API error: {}, data: {}", api_error_message, raw);
+                error!("{}", error_msg);
+                let _ = tx_event.send(Err(anyhow!(error_msg)));
+                return;
+            }
+        }
+    }
+}
@@ -96,6 +96,7 @@ impl From<MessageDelta> for UnifiedResponse {
             tool_call: None,
             usage: value.usage.map(UnifiedTokenUsage::from),
             finish_reason: value.delta.stop_reason,
+            response_id: None,
         }
     }
 }
@@ -0,0 +1,216 @@
+use super::unified::{UnifiedResponse, UnifiedTokenUsage, UnifiedToolCall};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// One `streamGenerateContent?alt=sse` chunk
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiSSEData {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    usage_metadata: Option<GeminiUsageMetadata>,
+    prompt_feedback: Option<PromptFeedback>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    content: Option<Content>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Content {
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Part {
+    text: Option<String>,
+    /// Marks `text` as a thought summary
+    #[serde(default)]
+    thought: bool,
+    thought_signature: Option<String>,
+    function_call: Option<FunctionCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionCall {
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptFeedback {
+    block_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiUsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    #[serde(default)]
+    thoughts_token_count: u32,
+    #[serde(default)]
+    total_token_count: u32,
+    cached_content_token_count: Option<u32>,
+}
+
+impl From<GeminiUsageMetadata> for UnifiedTokenUsage {
+    fn from(usage: GeminiUsageMetadata) -> Self {
+        let candidates_token_count = usage.candidates_token_count + usage.thoughts_token_count;
+        Self {
+            prompt_token_count: usage.prompt_token_count,
+            candidates_token_count,
+            total_token_count: usage
+                .total_token_count
+                .max(usage.prompt_token_count + candidates_token_count),
+            cached_content_token_count: usage.cached_content_token_count,
+        }
+    }
+}
+
+impl GeminiSSEData {
+    /// Reason the prompt was blocked, if it was
+    pub fn block_reason(&self) -> Option<&str> {
+        self.prompt_feedback
+            .as_ref()
+            .and_then(|feedback| feedback.block_reason.as_deref())
+    }
+
+    pub fn has_finish_reason(&self) -> bool {
+        self.candidates
+            .first()
+            .is_some_and(|candidate| candidate.finish_reason.is_some())
+    }
+
+    /// Split the first candidate into unified events, one per part
+    ///
+    /// Function calls arrive complete, so each becomes a single tool call event
+    /// carrying id, name and the full arguments. `new_call_id` supplies ids for
+    /// calls the API did not number.
+    pub fn into_unified_responses(
+        self,
+        mut new_call_id: impl FnMut() -> String,
+    ) -> Vec<UnifiedResponse> {
+        let mut usage = self.usage_metadata.map(UnifiedTokenUsage::from);
+        let (parts, mut finish_reason) = match self.candidates.into_iter().next() {
+            Some(candidate) => (
+                candidate.content.map(|content| content.parts).unwrap_or_default(),
+                candidate.finish_reason,
+            ),
+            None => (Vec::new(), None),
+        };
+
+        let mut responses = Vec::new();
+        for part in parts {
+            let mut response = UnifiedResponse {
+                thinking_signature: part.thought_signature,
+                ..Default::default()
+            };
+
+            if let Some(function_call) = part.function_call {
+                let arguments = if function_call.args.is_null() {
+                    "{}".to_string()
+                } else {
+                    function_call.args.to_string()
+                };
+                response.tool_call = Some(UnifiedToolCall {
+                    id: Some(
+                        function_call
+                            .id
+                            .filter(|id| !id.is_empty())
+                            .unwrap_or_else(&mut new_call_id),
+                    ),
+                    name: Some(function_call.name),
+                    arguments: Some(arguments),
+                });
+            } else if let Some(text) = part.text {
+                if part.thought {
+                    response.reasoning_content = Some(text);
+                } else {
+                    response.text = Some(text);
+                }
+            } else if response.thinking_signature.is_none() {
+                continue;
+            }
+
+            responses.push(response);
+        }
+
+        // Usage and finish reason ride on the last event of the chunk
+        match responses.last_mut() {
+            Some(last) => {
+                last.usage = usage.take();
+                last.finish_reason = finish_reason.take();
+            }
+            None if usage.is_some() || finish_reason.is_some() => {
+                responses.push(UnifiedResponse {
+                    usage,
+                    finish_reason,
+                    ..Default::default()
+                });
+            }
+            None => {}
+        }
+
+        responses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeminiSSEData;
+
+    #[test]
+    fn maps_parts_to_unified_events() {
+        let raw = r#"{
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "Planning the call", "thought": true},
+                        {"text": "Let me check."},
+                        {"functionCall": {"name": "Read", "args": {"path": "a.rs"}}, "thoughtSignature": "sig"},
+                        {"functionCall": {"id": "call_2", "name": "LS", "args": {}}}
+                    ]
+                },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {"promptTokenCount": 10, "candidatesTokenCount": 5, "thoughtsTokenCount": 3, "totalTokenCount": 18}
+        }"#;
+
+        let data: GeminiSSEData = serde_json::from_str(raw).expect("chunk should parse");
+        assert!(data.has_finish_reason());
+
+        let mut next_id = 0;
+        let responses = data.into_unified_responses(|| {
+            next_id += 1;
+            format!("generated_{}", next_id)
+        });
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0].reasoning_content.as_deref(), Some("Planning the call"));
+        assert_eq!(responses[1].text.as_deref(), Some("Let me check."));
+
+        let first_call = responses[2].tool_call.as_ref().expect("tool call");
+        assert_eq!(first_call.id.as_deref(), Some("generated_1"));
+        assert_eq!(first_call.name.as_deref(), Some("Read"));
+        assert_eq!(first_call.arguments.as_deref(), Some(r#"{"path":"a.rs"}"#));
+        assert_eq!(responses[2].thinking_signature.as_deref(), Some("sig"));
+
+        let second_call = responses[3].tool_call.as_ref().expect("tool call");
+        assert_eq!(second_call.id.as_deref(), Some("call_2"));
+        assert_eq!(responses[3].finish_reason.as_deref(), Some("STOP"));
+        let usage = responses[3].usage.as_ref().expect("usage");
+        assert_eq!(usage.candidates_token_count, 8);
+        assert_eq!(usage.total_token_count, 18);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod unified;
 pub mod openai;
-pub mod anthropic;
\ No newline at end of file
+pub mod anthropic;
+pub mod gemini;
+pub mod responses;
\ No newline at end of file
@@ -138,6 +138,7 @@ impl OpenAISSEData {
                 tool_call: None,
                 usage: usage.take(),
                 finish_reason: finish_reason.take(),
+                response_id: None,
             });
         }
 
@@ -155,6 +156,7 @@ impl OpenAISSEData {
                     } else {
                         None
                     },
+                    response_id: None,
                 });
             }
         }
@@ -167,6 +169,7 @@ impl OpenAISSEData {
                 tool_call: None,
                 usage,
                 finish_reason,
+                response_id: None,
             });
         }
 
@@ -0,0 +1,359 @@
+use super::unified::{UnifiedResponse, UnifiedTokenUsage, UnifiedToolCall};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// One OpenAI Responses API stream event (`data.type` carries the event name)
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum ResponsesStreamEvent {
+    #[serde(rename = "response.created")]
+    Created { response: ResponseObject },
+    #[serde(rename = "response.output_item.added")]
+    OutputItemAdded { item: OutputItem },
+    #[serde(rename = "response.output_item.done")]
+    OutputItemDone { item: OutputItem },
+    #[serde(rename = "response.output_text.delta")]
+    OutputTextDelta { delta: String },
+    #[serde(rename = "response.refusal.delta")]
+    RefusalDelta { delta: String },
+    #[serde(rename = "response.reasoning_summary_part.added")]
+    ReasoningSummaryPartAdded {
+        #[serde(default)]
+        summary_index: u32,
+    },
+    #[serde(rename = "response.reasoning_summary_text.delta", alias = "response.reasoning_text.delta")]
+    ReasoningTextDelta { delta: String },
+    #[serde(rename = "response.function_call_arguments.delta")]
+    FunctionCallArgumentsDelta { delta: String },
+    #[serde(rename = "response.completed", alias = "response.incomplete")]
+    Completed { response: ResponseObject },
+    #[serde(rename = "response.failed")]
+    Failed { response: ResponseObject },
+    #[serde(rename = "error")]
+    Error {
+        code: Option<String>,
+        message: Option<String>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResponseObject {
+    pub id: Option<String>,
+    pub status: Option<String>,
+    usage: Option<ResponsesUsage>,
+    incomplete_details: Option<IncompleteDetails>,
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IncompleteDetails {
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+    code: Option<String>,
+    message: Option<String>,
+}
+
+/// Output items; built-in tool calls (web search, code interpreter, ...) run
+/// server-side and fall under `Other`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum OutputItem {
+    #[serde(rename = "function_call")]
+    FunctionCall {
+        call_id: String,
+        name: String,
+        #[serde(default)]
+        arguments: String,
+    },
+    #[serde(rename = "reasoning")]
+    Reasoning(ReasoningItem),
+    #[serde(rename = "message")]
+    Message,
+    #[serde(other)]
+    Other,
+}
+
+/// Reasoning item as passed back in later requests
+///
+/// With `include: ["reasoning.encrypted_content"]` the item carries the encrypted
+/// reasoning, so it can be replayed without server-side storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReasoningItem {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_content: Option<String>,
+    #[serde(default)]
+    pub summary: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponsesUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+    #[serde(default)]
+    total_tokens: u32,
+    input_tokens_details: Option<InputTokensDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InputTokensDetails {
+    cached_tokens: Option<u32>,
+}
+
+impl From<ResponsesUsage> for UnifiedTokenUsage {
+    fn from(usage: ResponsesUsage) -> Self {
+        Self {
+            prompt_token_count: usage.input_tokens,
+            candidates_token_count: usage.output_tokens,
+            total_token_count: usage
+                .total_tokens
+                .max(usage.input_tokens + usage.output_tokens),
+            cached_content_token_count: usage
+                .input_tokens_details
+                .and_then(|details| details.cached_tokens),
+        }
+    }
+}
+
+/// Outcome of feeding one event to [`ResponsesStreamState`]
+#[derive(Debug)]
+pub enum ResponsesStreamStep {
+    Continue(Vec<UnifiedResponse>),
+    Done(UnifiedResponse),
+    Failed(String),
+}
+
+/// Accumulates per-stream state (tool call argument streaming, reasoning items)
+#[derive(Debug, Default)]
+pub struct ResponsesStreamState {
+    /// Whether the current function call's arguments arrived as deltas
+    call_arguments_streamed: bool,
+    has_function_call: bool,
+    reasoning_items: Vec<ReasoningItem>,
+}
+
+impl ResponsesStreamState {
+    pub fn handle_event(&mut self, event: ResponsesStreamEvent) -> ResponsesStreamStep {
+        let response = match event {
+            ResponsesStreamEvent::Created { response } => UnifiedResponse {
+                response_id: response.id,
+                ..Default::default()
+            },
+            ResponsesStreamEvent::OutputTextDelta { delta }
+            | ResponsesStreamEvent::RefusalDelta { delta } => UnifiedResponse {
+                text: Some(delta),
+                ..Default::default()
+            },
+            ResponsesStreamEvent::ReasoningTextDelta { delta } => UnifiedResponse {
+                reasoning_content: Some(delta),
+                ..Default::default()
+            },
+            ResponsesStreamEvent::ReasoningSummaryPartAdded { summary_index } => {
+                if summary_index == 0 {
+                    return ResponsesStreamStep::Continue(Vec::new());
+                }
+                UnifiedResponse {
+                    reasoning_content: Some("\n\n".to_string()),
+                    ..Default::default()
+                }
+            }
+            ResponsesStreamEvent::OutputItemAdded {
+                item: OutputItem::FunctionCall { call_id, name, .. },
+            } => {
+                self.has_function_call = true;
+                self.call_arguments_streamed = false;
+                UnifiedResponse {
+                    tool_call: Some(UnifiedToolCall {
+                        id: Some(call_id),
+                        name: Some(name),
+                        arguments: None,
+                    }),
+                    ..Default::default()
+                }
+            }
+            ResponsesStreamEvent::FunctionCallArgumentsDelta { delta } => {
+                self.call_arguments_streamed = true;
+                UnifiedResponse {
+                    tool_call: Some(UnifiedToolCall {
+                        id: None,
+                        name: None,
+                        arguments: Some(delta),
+                    }),
+                    ..Default::default()
+                }
+            }
+            ResponsesStreamEvent::OutputItemDone { item } => match item {
+                // Some gateways send no argument deltas, only the finished item
+                OutputItem::FunctionCall { arguments, .. }
+                    if !self.call_arguments_streamed && !arguments.is_empty() =>
+                {
+                    UnifiedResponse {
+                        tool_call: Some(UnifiedToolCall {
+                            id: None,
+                            name: None,
+                            arguments: Some(arguments),
+                        }),
+                        ..Default::default()
+                    }
+                }
+                OutputItem::Reasoning(reasoning) => {
+                    self.reasoning_items.push(reasoning);
+                    return ResponsesStreamStep::Continue(Vec::new());
+                }
+                _ => return ResponsesStreamStep::Continue(Vec::new()),
+            },
+            ResponsesStreamEvent::Completed { response } => {
+                return ResponsesStreamStep::Done(self.finish(response));
+            }
+            ResponsesStreamEvent::Failed { response } => {
+                let error = response.error.unwrap_or(ResponseError {
+                    code: None,
+                    message: None,
+                });
+                return ResponsesStreamStep::Failed(Self::error_message(error.code, error.message));
+            }
+            ResponsesStreamEvent::Error { code, message } => {
+                return ResponsesStreamStep::Failed(Self::error_message(code, message));
+            }
+            ResponsesStreamEvent::OutputItemAdded { .. } | ResponsesStreamEvent::Other => {
+                return ResponsesStreamStep::Continue(Vec::new());
+            }
+        };
+        ResponsesStreamStep::Continue(vec![response])
+    }
+
+    fn finish(&mut self, response: ResponseObject) -> UnifiedResponse {
+        let finish_reason = match response.status.as_deref() {
+            Some("incomplete") => match response
+                .incomplete_details
+                .and_then(|details| details.reason)
+                .as_deref()
+            {
+                Some("max_output_tokens") => "length".to_string(),
+                Some(reason) => reason.to_string(),
+                None => "incomplete".to_string(),
+            },
+            _ if self.has_function_call => "tool_calls".to_string(),
+            _ => "stop".to_string(),
+        };
+
+        // Reasoning items are replayed with the assistant message when the
+        // conversation cannot be chained by response id
+        let reasoning_items: Vec<&ReasoningItem> = self
+            .reasoning_items
+            .iter()
+            .filter(|item| item.encrypted_content.is_some())
+            .collect();
+        let thinking_signature = if reasoning_items.is_empty() {
+            None
+        } else {
+            serde_json::to_string(&reasoning_items).ok()
+        };
+
+        UnifiedResponse {
+            thinking_signature,
+            usage: response.usage.map(UnifiedTokenUsage::from),
+            finish_reason: Some(finish_reason),
+            response_id: response.id,
+            ..Default::default()
+        }
+    }
+
+    fn error_message(code: Option<String>, message: Option<String>) -> String {
+        let message = message.unwrap_or_else(|| "An error occurred during streaming".to_string());
+        match code {
+            Some(code) => format!("{}: {}", code, message),
+            None => message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResponsesStreamEvent, ResponsesStreamState, ResponsesStreamStep};
+
+    fn feed(state: &mut ResponsesStreamState, raw: &str) -> ResponsesStreamStep {
+        let event: ResponsesStreamEvent = serde_json::from_str(raw).expect("event should parse");
+        state.handle_event(event)
+    }
+
+    #[test]
+    fn maps_stream_events_to_unified_responses() {
+        let mut state = ResponsesStreamState::default();
+
+        let ResponsesStreamStep::Continue(created) = feed(
+            &mut state,
+            r#"{"type":"response.created","response":{"id":"resp_1","status":"in_progress"}}"#,
+        ) else {
+            panic!("expected continue");
+        };
+        assert_eq!(created[0].response_id.as_deref(), Some("resp_1"));
+
+        feed(
+            &mut state,
+            r#"{"type":"response.output_item.done","item":{"type":"reasoning","id":"rs_1","summary":[],"encrypted_content":"enc"}}"#,
+        );
+
+        let ResponsesStreamStep::Continue(call) = feed(
+            &mut state,
+            r#"{"type":"response.output_item.added","output_index":1,"item":{"type":"function_call","id":"fc_1","call_id":"call_1","name":"Read","arguments":""}}"#,
+        ) else {
+            panic!("expected continue");
+        };
+        let tool_call = call[0].tool_call.as_ref().expect("tool call");
+        assert_eq!(tool_call.id.as_deref(), Some("call_1"));
+        assert_eq!(tool_call.name.as_deref(), Some("Read"));
+
+        feed(
+            &mut state,
+            r#"{"type":"response.function_call_arguments.delta","item_id":"fc_1","delta":"{\"path\":\"a.rs\"}"}"#,
+        );
+        // Arguments already streamed: the finished item adds nothing
+        let ResponsesStreamStep::Continue(done) = feed(
+            &mut state,
+            r#"{"type":"response.output_item.done","item":{"type":"function_call","id":"fc_1","call_id":"call_1","name":"Read","arguments":"{\"path\":\"a.rs\"}"}}"#,
+        ) else {
+            panic!("expected continue");
+        };
+        assert!(done.is_empty());
+
+        let ResponsesStreamStep::Done(last) = feed(
+            &mut state,
+            r#"{"type":"response.completed","response":{"id":"resp_1","status":"completed","usage":{"input_tokens":100,"input_tokens_details":{"cached_tokens":80},"output_tokens":20,"total_tokens":120}}}"#,
+        ) else {
+            panic!("expected done");
+        };
+        assert_eq!(last.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(last.response_id.as_deref(), Some("resp_1"));
+        assert_eq!(
+            last.thinking_signature.as_deref(),
+            Some(r#"[{"id":"rs_1","encrypted_content":"enc","summary":[]}]"#)
+        );
+        let usage = last.usage.expect("usage");
+        assert_eq!(usage.cached_content_token_count, Some(80));
+        assert_eq!(usage.total_token_count, 120);
+    }
+
+    #[test]
+    fn reports_failed_responses() {
+        let mut state = ResponsesStreamState::default();
+        let step = feed(
+            &mut state,
+            r#"{"type":"response.failed","response":{"id":"resp_2","status":"failed","error":{"code":"server_error","message":"The server had an error"}}}"#,
+        );
+        assert!(matches!(
+            step,
+            ResponsesStreamStep::Failed(ref msg) if msg == "server_error: The server had an error"
+        ));
+
+        let step = feed(&mut state, r#"{"type":"response.web_search_call.searching","item_id":"ws_1"}"#);
+        assert!(matches!(step, ResponsesStreamStep::Continue(ref events) if events.is_empty()));
+    }
+}
@@ -18,6 +18,9 @@ pub struct UnifiedResponse {
     pub tool_call: Option<UnifiedToolCall>,
     pub usage: Option<UnifiedTokenUsage>,
     pub finish_reason: Option<String>,
+    /// Id of the response being streamed (OpenAI Responses API, for state chaining)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_id: Option<String>,
 }
 
 impl Default for UnifiedResponse {
@@ -29,6 +32,7 @@ impl Default for UnifiedResponse {
             tool_call: None,
             usage: None,
             finish_reason: None,
+            response_id: None,
         }
     }
 }
@@ -3,11 +3,16 @@
 //! Uses a modular architecture to separate provider-specific logic into the providers module
 
 use crate::infrastructure::ai::providers::anthropic::AnthropicMessageConverter;
-use crate::infrastructure::ai::providers::openai::OpenAIMessageConverter;
+use crate::infrastructure::ai::providers::gemini::GeminiMessageConverter;
+use crate::infrastructure::ai::providers::openai::{OpenAIMessageConverter, OpenAIResponsesConverter};
+use crate::infrastructure::ai::retry::RetryPolicy;
 use crate::service::config::ProxyConfig;
 use crate::util::types::*;
 use crate::util::JsonChecker;
-use ai_stream_handlers::{handle_anthropic_stream, handle_openai_stream, UnifiedResponse};
+use ai_stream_handlers::{
+    handle_anthropic_stream, handle_gemini_stream, handle_openai_stream, handle_responses_stream,
+    UnifiedResponse,
+};
 use anyhow::{anyhow, Result};
 use futures::StreamExt;
 use log::{debug, error, info, warn};
@@ -27,6 +32,7 @@ pub struct StreamResponse {
 pub struct AIClient {
     client: Client,
     pub config: AIConfig,
+    retry_policy: RetryPolicy,
 }
 
 impl AIClient {
@@ -34,14 +40,32 @@ impl AIClient {
     pub fn new(config: AIConfig) -> Self {
         let skip_ssl_verify = config.skip_ssl_verify;
         let client = Self::create_http_client(None, skip_ssl_verify);
-        Self { client, config }
+        Self {
+            client,
+            config,
+            retry_policy: RetryPolicy::default(),
+        }
     }
 
     /// Create an AIClient with proxy configuration
     pub fn new_with_proxy(config: AIConfig, proxy_config: Option<ProxyConfig>) -> Self {
         let skip_ssl_verify = config.skip_ssl_verify;
         let client = Self::create_http_client(proxy_config, skip_ssl_verify);
-        Self { client, config }
+        Self {
+            client,
+            config,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Replace the retry policy used for requests and interrupted streams
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
     }
 
     /// Create an HTTP client (supports proxy config and SSL verification control)
@@ -237,6 +261,55 @@ impl AIClient {
         builder
     }
 
+    /// Apply Gemini-style request headers (merge/replace).
+    fn apply_gemini_headers(
+        &self,
+        mut builder: reqwest::RequestBuilder,
+    ) -> reqwest::RequestBuilder {
+        let has_custom_headers = self
+            .config
+            .custom_headers
+            .as_ref()
+            .map_or(false, |h| !h.is_empty());
+        let is_merge_mode = self.is_merge_headers_mode();
+
+        if has_custom_headers && !is_merge_mode {
+            return self.apply_custom_headers(builder);
+        }
+
+        builder = builder
+            .header("Content-Type", "application/json")
+            .header("x-goog-api-key", &self.config.api_key);
+
+        if has_custom_headers && is_merge_mode {
+            builder = self.apply_custom_headers(builder);
+        }
+
+        builder
+    }
+
+    /// Resolve the Gemini streaming endpoint
+    ///
+    /// `base_url` is either the API root (e.g. `.../v1beta`), in which case the model
+    /// path is appended, or a full `:streamGenerateContent` URL used as-is.
+    fn gemini_stream_url(&self) -> String {
+        let base_url = self.config.base_url.trim_end_matches('/');
+        if base_url.contains(":streamGenerateContent") {
+            if base_url.contains("alt=sse") {
+                base_url.to_string()
+            } else if base_url.contains('?') {
+                format!("{}&alt=sse", base_url)
+            } else {
+                format!("{}?alt=sse", base_url)
+            }
+        } else {
+            format!(
+                "{}/models/{}:streamGenerateContent?alt=sse",
+                base_url, self.config.model
+            )
+        }
+    }
+
     /// Build an OpenAI-format request body
     fn build_openai_request_body(
         &self,
@@ -307,6 +380,88 @@ impl AIClient {
         request_body
     }
 
+    /// Build an OpenAI Responses API request body
+    ///
+    /// With `response_state`, `input` holds only the items after the previous response;
+    /// the server supplies the rest of the conversation.
+    fn build_responses_request_body(
+        &self,
+        instructions: Option<String>,
+        input: Vec<serde_json::Value>,
+        responses_tools: Option<Vec<serde_json::Value>>,
+        response_state: Option<&ResponseState>,
+        extra_body: Option<serde_json::Value>,
+    ) -> serde_json::Value {
+        let mut request_body = serde_json::json!({
+            "model": self.config.model,
+            "input": input,
+            "stream": true
+        });
+
+        if let Some(instructions) = instructions {
+            request_body["instructions"] = serde_json::Value::String(instructions);
+        }
+
+        if let Some(previous_response_id) =
+            response_state.and_then(|state| state.previous_response_id.as_ref())
+        {
+            request_body["previous_response_id"] =
+                serde_json::Value::String(previous_response_id.clone());
+        }
+
+        if let Some(max_tokens) = self.config.max_tokens {
+            request_body["max_output_tokens"] = serde_json::json!(max_tokens);
+        }
+
+        // Encrypted reasoning lets the full history be replayed without server-side state
+        if self.config.enable_thinking_process {
+            request_body["reasoning"] = serde_json::json!({ "summary": "auto" });
+            request_body["include"] = serde_json::json!(["reasoning.encrypted_content"]);
+        }
+
+        let mut builtin_tools = Vec::new();
+        if let Some(extra) = extra_body {
+            if let Some(extra_obj) = extra.as_object() {
+                for (key, value) in extra_obj {
+                    match (key.as_str(), value) {
+                        // Built-in tools (web_search, code_interpreter, ...) join the function tools
+                        ("tools", serde_json::Value::Array(tools)) => {
+                            builtin_tools.extend(tools.iter().cloned());
+                        }
+                        // Merge reasoning so overrides (e.g. effort) keep the summary setting
+                        ("reasoning", serde_json::Value::Object(overrides)) => {
+                            match request_body["reasoning"].as_object_mut() {
+                                Some(reasoning) => reasoning.extend(overrides.clone()),
+                                None => request_body["reasoning"] = value.clone(),
+                            }
+                        }
+                        _ => request_body[key] = value.clone(),
+                    }
+                }
+                debug!(target: "ai::responses_stream_request", "Applied extra_body overrides: {:?}", extra_obj.keys().collect::<Vec<_>>());
+            }
+        }
+
+        debug!(target: "ai::responses_stream_request",
+            "Responses stream request body (excluding tools):\n{}",
+            serde_json::to_string_pretty(&request_body).unwrap_or_else(|_| "serialization failed".to_string())
+        );
+
+        let mut tools = responses_tools.unwrap_or_default();
+        let tool_names = tools
+            .iter()
+            .map(|tool| Self::extract_anthropic_tool_name(tool))
+            .collect::<Vec<_>>();
+        debug!(target: "ai::responses_stream_request", "\ntools: {:?}", tool_names);
+        tools.extend(builtin_tools);
+        if !tools.is_empty() {
+            request_body["tools"] = serde_json::Value::Array(tools);
+            request_body["tool_choice"] = serde_json::Value::String("auto".to_string());
+        }
+
+        request_body
+    }
+
     /// Build an Anthropic-format request body
     fn build_anthropic_request_body(
         &self,
@@ -380,6 +535,87 @@ impl AIClient {
         request_body
     }
 
+    /// Build a Gemini-format request body
+    fn build_gemini_request_body(
+        &self,
+        system_instruction: Option<serde_json::Value>,
+        gemini_contents: Vec<serde_json::Value>,
+        gemini_tools: Option<Vec<serde_json::Value>>,
+        extra_body: Option<serde_json::Value>,
+    ) -> serde_json::Value {
+        let mut request_body = serde_json::json!({
+            "contents": gemini_contents,
+            "generationConfig": {}
+        });
+
+        if let Some(max_tokens) = self.config.max_tokens {
+            request_body["generationConfig"]["maxOutputTokens"] = serde_json::json!(max_tokens);
+        }
+
+        // Thought summaries stream back as `thought` parts; the thinking budget
+        // stays at the model default unless overridden via extra_body
+        if self.config.enable_thinking_process {
+            request_body["generationConfig"]["thinkingConfig"] = serde_json::json!({
+                "includeThoughts": true
+            });
+        }
+
+        if let Some(system) = system_instruction {
+            request_body["systemInstruction"] = system;
+        }
+
+        if let Some(extra) = extra_body {
+            if let Some(extra_obj) = extra.as_object() {
+                for (key, value) in extra_obj {
+                    // Merge generationConfig so overrides (e.g. thinkingBudget) keep the defaults above
+                    match (request_body.get_mut(key.as_str()), value) {
+                        (
+                            Some(serde_json::Value::Object(existing)),
+                            serde_json::Value::Object(overrides),
+                        ) if key == "generationConfig" => {
+                            for (k, v) in overrides {
+                                match (existing.get_mut(k.as_str()), v) {
+                                    (
+                                        Some(serde_json::Value::Object(inner)),
+                                        serde_json::Value::Object(inner_overrides),
+                                    ) => {
+                                        inner.extend(inner_overrides.clone());
+                                    }
+                                    _ => {
+                                        existing.insert(k.clone(), v.clone());
+                                    }
+                                }
+                            }
+                        }
+                        _ => request_body[key] = value.clone(),
+                    }
+                }
+                debug!(target: "ai::gemini_stream_request", "Applied extra_body overrides: {:?}", extra_obj.keys().collect::<Vec<_>>());
+            }
+        }
+
+        debug!(target: "ai::gemini_stream_request",
+            "Gemini stream request body (excluding tools):\n{}",
+            serde_json::to_string_pretty(&request_body).unwrap_or_else(|_| "serialization failed".to_string())
+        );
+
+        if let Some(tools) = gemini_tools {
+            let tool_names = tools
+                .iter()
+                .flat_map(Self::extract_gemini_tool_names)
+                .collect::<Vec<_>>();
+            debug!(target: "ai::gemini_stream_request", "\ntools: {:?}", tool_names);
+            if !tools.is_empty() {
+                request_body["tools"] = serde_json::Value::Array(tools);
+                request_body["toolConfig"] = serde_json::json!({
+                    "functionCallingConfig": { "mode": "AUTO" }
+                });
+            }
+        }
+
+        request_body
+    }
+
     fn extract_openai_tool_name(tool: &serde_json::Value) -> String {
         tool.get("function")
             .and_then(|f| f.get("name"))
@@ -395,6 +631,23 @@ impl AIClient {
             .to_string()
     }
 
+    fn extract_gemini_tool_names(tool: &serde_json::Value) -> Vec<String> {
+        tool.get("functionDeclarations")
+            .and_then(|d| d.as_array())
+            .map(|declarations| {
+                declarations
+                    .iter()
+                    .map(|d| {
+                        d.get("name")
+                            .and_then(|n| n.as_str())
+                            .unwrap_or("unknown")
+                            .to_string()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Send a streaming message request
     ///
     /// Returns `StreamResponse` with:
@@ -421,16 +674,11 @@ impl AIClient {
         tools: Option<Vec<ToolDefinition>>,
         extra_body: Option<serde_json::Value>,
     ) -> Result<StreamResponse> {
-        let max_tries = 3;
         match self.get_api_format().to_lowercase().as_str() {
-            "openai" => {
-                self.send_openai_stream(messages, tools, extra_body, max_tries)
-                    .await
-            }
-            "anthropic" => {
-                self.send_anthropic_stream(messages, tools, extra_body, max_tries)
-                    .await
-            }
+            "openai" => self.send_openai_stream(messages, tools, extra_body).await,
+            "anthropic" => self.send_anthropic_stream(messages, tools, extra_body).await,
+            "gemini" => self.send_gemini_stream(messages, tools, extra_body).await,
+            "responses" => self.send_responses_stream(messages, tools, extra_body).await,
             _ => Err(anyhow!("Unknown API format: {}", self.get_api_format())),
         }
     }
@@ -441,18 +689,16 @@ impl AIClient {
     /// - `messages`: message list
     /// - `tools`: tool definitions
     /// - `extra_body`: extra request body parameters
-    /// - `max_tries`: max attempts (including the first)
     async fn send_openai_stream(
         &self,
         messages: Vec<Message>,
         tools: Option<Vec<ToolDefinition>>,
         extra_body: Option<serde_json::Value>,
-        max_tries: usize,
     ) -> Result<StreamResponse> {
         let url = self.config.base_url.clone();
         debug!(
-            "OpenAI config: model={}, base_url={}, max_tries={}",
-            self.config.model, self.config.base_url, max_tries
+            "OpenAI config: model={}, base_url={}, max_retries={}",
+            self.config.model, self.config.base_url, self.retry_policy.max_retries
         );
 
         // Use OpenAI message converter
@@ -463,109 +709,22 @@ impl AIClient {
         let request_body =
             self.build_openai_request_body(openai_messages, openai_tools, extra_body);
 
-        let mut last_error = None;
-        let base_wait_time_ms = 500;
-
-        for attempt in 0..max_tries {
-            let request_start_time = std::time::Instant::now();
-
-            // Send request - apply request headers
-            let request_builder = self.apply_openai_headers(self.client.post(&url));
-            let response_result = request_builder.json(&request_body).send().await;
-
-            let response = match response_result {
-                Ok(resp) => {
-                    let connect_time = request_start_time.elapsed().as_millis();
-                    let status = resp.status();
-
-                    if status.is_client_error() {
-                        let error_text = resp
-                            .text()
-                            .await
-                            .unwrap_or_else(|e| format!("Failed to read error response: {}", e));
-                        error!(
-                            "OpenAI Streaming API client error {}: {}",
-                            status, error_text
-                        );
-                        return Err(anyhow!(
-                            "OpenAI Streaming API client error {}: {}",
-                            status,
-                            error_text
-                        ));
-                    }
-
-                    if status.is_success() {
-                        debug!(
-                            "Stream request connected: {}ms, status: {}, attempt: {}/{}",
-                            connect_time,
-                            status,
-                            attempt + 1,
-                            max_tries
-                        );
-                        resp
-                    } else {
-                        let error_text = resp
-                            .text()
-                            .await
-                            .unwrap_or_else(|e| format!("Failed to read error response: {}", e));
-                        let error =
-                            anyhow!("OpenAI Streaming API error {}: {}", status, error_text);
-                        warn!(
-                            "Stream request failed (attempt {}/{}): {}",
-                            attempt + 1,
-                            max_tries,
-                            error
-                        );
-                        last_error = Some(error);
-
-                        if attempt < max_tries - 1 {
-                            let delay_ms = base_wait_time_ms * (1 << attempt.min(3));
-                            debug!("Retrying after {}ms (attempt {})", delay_ms, attempt + 2);
-                            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
-                        }
-                        continue;
-                    }
-                }
-                Err(e) => {
-                    let connect_time = request_start_time.elapsed().as_millis();
-                    let error = anyhow!("Stream request connection failed: {}", e);
-                    warn!(
-                        "Stream request connection failed: {}ms, attempt {}/{}, error: {}",
-                        connect_time,
-                        attempt + 1,
-                        max_tries,
-                        e
-                    );
-                    last_error = Some(error);
-
-                    if attempt < max_tries - 1 {
-                        let delay_ms = base_wait_time_ms * (1 << attempt.min(3));
-                        debug!("Retrying after {}ms (attempt {})", delay_ms, attempt + 2);
-                        tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
-                    }
-                    continue;
-                }
-            };
-
-            // Success: create channels and return
-            let (tx, rx) = mpsc::unbounded_channel();
-            let (tx_raw, rx_raw) = mpsc::unbounded_channel();
+        let response = self
+            .send_with_retry("OpenAI", &request_body, || {
+                self.apply_openai_headers(self.client.post(&url))
+            })
+            .await?;
 
-            tokio::spawn(handle_openai_stream(response, tx, Some(tx_raw)));
+        // Success: create channels and return
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (tx_raw, rx_raw) = mpsc::unbounded_channel();
 
-            return Ok(StreamResponse {
-                stream: Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx)),
-                raw_sse_rx: Some(rx_raw),
-            });
-        }
+        tokio::spawn(handle_openai_stream(response, tx, Some(tx_raw)));
 
-        let error_msg = format!(
-            "Stream request failed after {} attempts: {}",
-            max_tries,
-            last_error.unwrap_or_else(|| anyhow!("Unknown error"))
-        );
-        error!("{}", error_msg);
-        Err(anyhow!(error_msg))
+        Ok(StreamResponse {
+            stream: Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx)),
+            raw_sse_rx: Some(rx_raw),
+        })
     }
 
     /// Send an Anthropic streaming request with retries
@@ -574,18 +733,16 @@ impl AIClient {
     /// - `messages`: message list
     /// - `tools`: tool definitions
     /// - `extra_body`: extra request body parameters
-    /// - `max_tries`: max attempts (including the first)
     async fn send_anthropic_stream(
         &self,
         messages: Vec<Message>,
         tools: Option<Vec<ToolDefinition>>,
         extra_body: Option<serde_json::Value>,
-        max_tries: usize,
     ) -> Result<StreamResponse> {
         let url = self.config.base_url.clone();
         debug!(
-            "Anthropic config: model={}, base_url={}, max_tries={}",
-            self.config.model, self.config.base_url, max_tries
+            "Anthropic config: model={}, base_url={}, max_retries={}",
+            self.config.model, self.config.base_url, self.retry_policy.max_retries
         );
 
         // Use Anthropic message converter
@@ -601,37 +758,209 @@ impl AIClient {
             extra_body,
         );
 
+        let response = self
+            .send_with_retry("Anthropic", &request_body, || {
+                self.apply_anthropic_headers(self.client.post(&url), &url)
+            })
+            .await?;
+
+        // Success: create channels and return
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (tx_raw, rx_raw) = mpsc::unbounded_channel();
+
+        tokio::spawn(handle_anthropic_stream(response, tx, Some(tx_raw)));
+
+        Ok(StreamResponse {
+            stream: Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx)),
+            raw_sse_rx: Some(rx_raw),
+        })
+    }
+
+    /// Send a Gemini streaming request with retries
+    ///
+    /// # Parameters
+    /// - `messages`: message list
+    /// - `tools`: tool definitions
+    /// - `extra_body`: extra request body parameters
+    async fn send_gemini_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDefinition>>,
+        extra_body: Option<serde_json::Value>,
+    ) -> Result<StreamResponse> {
+        let url = self.gemini_stream_url();
+        debug!(
+            "Gemini config: model={}, url={}, max_retries={}",
+            self.config.model, url, self.retry_policy.max_retries
+        );
+
+        // Use Gemini message converter
+        let (system_instruction, gemini_contents) =
+            GeminiMessageConverter::convert_messages(messages);
+        let gemini_tools = GeminiMessageConverter::convert_tools(tools);
+
+        // Build request body
+        let request_body = self.build_gemini_request_body(
+            system_instruction,
+            gemini_contents,
+            gemini_tools,
+            extra_body,
+        );
+
+        let response = self
+            .send_with_retry("Gemini", &request_body, || {
+                self.apply_gemini_headers(self.client.post(&url))
+            })
+            .await?;
+
+        // Success: create channels and return
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (tx_raw, rx_raw) = mpsc::unbounded_channel();
+
+        tokio::spawn(handle_gemini_stream(response, tx, Some(tx_raw)));
+
+        Ok(StreamResponse {
+            stream: Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx)),
+            raw_sse_rx: Some(rx_raw),
+        })
+    }
+
+    /// Send an OpenAI Responses API streaming request with retries
+    ///
+    /// Continues from the response behind the last assistant message when there is
+    /// one, sending only newer messages. Falls back to the full history when the
+    /// server no longer has that response, or when `store` is disabled.
+    ///
+    /// # Parameters
+    /// - `messages`: message list
+    /// - `tools`: tool definitions
+    /// - `extra_body`: extra request body parameters
+    async fn send_responses_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDefinition>>,
+        extra_body: Option<serde_json::Value>,
+    ) -> Result<StreamResponse> {
+        let url = self.config.base_url.clone();
+        debug!(
+            "Responses config: model={}, base_url={}, max_retries={}",
+            self.config.model, self.config.base_url, self.retry_policy.max_retries
+        );
+
+        let responses_tools = OpenAIResponsesConverter::convert_tools(tools);
+        let store_disabled = extra_body
+            .as_ref()
+            .and_then(|body| body.get("store"))
+            .and_then(|store| store.as_bool())
+            == Some(false);
+
+        let chain_point = if store_disabled {
+            None
+        } else {
+            OpenAIResponsesConverter::response_state(&messages)
+                .filter(|(_, index)| messages[index + 1..].iter().any(|m| m.role != "system"))
+        };
+
+        let mut response = None;
+        if let Some((response_state, index)) = chain_point {
+            // System messages are sent as instructions on every request
+            let pending_messages: Vec<Message> = messages
+                .iter()
+                .enumerate()
+                .filter(|(i, msg)| *i > index || msg.role == "system")
+                .map(|(_, msg)| msg.clone())
+                .collect();
+            debug!(
+                "Continuing from previous response: id={:?}, sending {} of {} messages",
+                response_state.previous_response_id,
+                pending_messages.len(),
+                messages.len()
+            );
+
+            let (instructions, input) = OpenAIResponsesConverter::convert_messages(pending_messages);
+            let request_body = self.build_responses_request_body(
+                instructions,
+                input,
+                responses_tools.clone(),
+                Some(&response_state),
+                extra_body.clone(),
+            );
+
+            match self
+                .send_with_retry("Responses", &request_body, || {
+                    self.apply_openai_headers(self.client.post(&url))
+                })
+                .await
+            {
+                Ok(chained_response) => response = Some(chained_response),
+                Err(e) if Self::is_previous_response_missing(&e.to_string()) => {
+                    warn!(
+                        "Previous response is no longer available, resending full history: {}",
+                        e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let response = match response {
+            Some(response) => response,
+            None => {
+                let (instructions, input) = OpenAIResponsesConverter::convert_messages(messages);
+                let request_body = self.build_responses_request_body(
+                    instructions,
+                    input,
+                    responses_tools,
+                    None,
+                    extra_body,
+                );
+                self.send_with_retry("Responses", &request_body, || {
+                    self.apply_openai_headers(self.client.post(&url))
+                })
+                .await?
+            }
+        };
+
+        // Success: create channels and return
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (tx_raw, rx_raw) = mpsc::unbounded_channel();
+
+        tokio::spawn(handle_responses_stream(response, tx, Some(tx_raw)));
+
+        Ok(StreamResponse {
+            stream: Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx)),
+            raw_sse_rx: Some(rx_raw),
+        })
+    }
+
+    fn is_previous_response_missing(error_message: &str) -> bool {
+        let msg = error_message.to_lowercase();
+        msg.contains("previous_response_not_found")
+            || (msg.contains("previous response") && msg.contains("not found"))
+    }
+
+    /// Send a streaming request, retrying per the retry policy
+    ///
+    /// Connection failures and retryable statuses (429, 408, 5xx, 529) are retried
+    /// with backoff, honoring `Retry-After`; other client errors fail immediately.
+    async fn send_with_retry(
+        &self,
+        api_name: &str,
+        request_body: &serde_json::Value,
+        build_request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let max_tries = self.retry_policy.max_attempts();
         let mut last_error = None;
-        let base_wait_time_ms = 500;
 
         for attempt in 0..max_tries {
             let request_start_time = std::time::Instant::now();
+            let response_result = build_request().json(request_body).send().await;
 
-            // Send request - apply Anthropic-style request headers
-            let request_builder = self.apply_anthropic_headers(self.client.post(&url), &url);
-            let response_result = request_builder.json(&request_body).send().await;
-
-            let response = match response_result {
+            let retry_after = match response_result {
                 Ok(resp) => {
                     let connect_time = request_start_time.elapsed().as_millis();
                     let status = resp.status();
 
-                    if status.is_client_error() {
-                        let error_text = resp
-                            .text()
-                            .await
-                            .unwrap_or_else(|e| format!("Failed to read error response: {}", e));
-                        error!(
-                            "Anthropic Streaming API client error {}: {}",
-                            status, error_text
-                        );
-                        return Err(anyhow!(
-                            "Anthropic Streaming API client error {}: {}",
-                            status,
-                            error_text
-                        ));
-                    }
-
                     if status.is_success() {
                         debug!(
                             "Stream request connected: {}ms, status: {}, attempt: {}/{}",
@@ -640,29 +969,43 @@ impl AIClient {
                             attempt + 1,
                             max_tries
                         );
-                        resp
-                    } else {
-                        let error_text = resp
-                            .text()
-                            .await
-                            .unwrap_or_else(|e| format!("Failed to read error response: {}", e));
-                        let error =
-                            anyhow!("Anthropic Streaming API error {}: {}", status, error_text);
-                        warn!(
-                            "Stream request failed (attempt {}/{}): {}",
-                            attempt + 1,
-                            max_tries,
-                            error
-                        );
-                        last_error = Some(error);
+                        return Ok(resp);
+                    }
 
-                        if attempt < max_tries - 1 {
-                            let delay_ms = base_wait_time_ms * (1 << attempt.min(3));
-                            debug!("Retrying after {}ms (attempt {})", delay_ms, attempt + 2);
-                            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
-                        }
-                        continue;
+                    let retry_after = RetryPolicy::parse_retry_after(resp.headers());
+                    let error_text = resp
+                        .text()
+                        .await
+                        .unwrap_or_else(|e| format!("Failed to read error response: {}", e));
+
+                    if !RetryPolicy::is_retryable_status(status) {
+                        let kind = if status.is_client_error() {
+                            "client error"
+                        } else {
+                            "error"
+                        };
+                        error!(
+                            "{} Streaming API {} {}: {}",
+                            api_name, kind, status, error_text
+                        );
+                        return Err(anyhow!(
+                            "{} Streaming API {} {}: {}",
+                            api_name,
+                            kind,
+                            status,
+                            error_text
+                        ));
                     }
+
+                    let error = anyhow!("{} Streaming API error {}: {}", api_name, status, error_text);
+                    warn!(
+                        "Stream request failed (attempt {}/{}): {}",
+                        attempt + 1,
+                        max_tries,
+                        error
+                    );
+                    last_error = Some(error);
+                    retry_after
                 }
                 Err(e) => {
                     let connect_time = request_start_time.elapsed().as_millis();
@@ -675,26 +1018,19 @@ impl AIClient {
                         e
                     );
                     last_error = Some(error);
-
-                    if attempt < max_tries - 1 {
-                        let delay_ms = base_wait_time_ms * (1 << attempt.min(3));
-                        debug!("Retrying after {}ms (attempt {})", delay_ms, attempt + 2);
-                        tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
-                    }
-                    continue;
+                    None
                 }
             };
 
-            // Success: create channels and return
-            let (tx, rx) = mpsc::unbounded_channel();
-            let (tx_raw, rx_raw) = mpsc::unbounded_channel();
-
-            tokio::spawn(handle_anthropic_stream(response, tx, Some(tx_raw)));
-
-            return Ok(StreamResponse {
-                stream: Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx)),
-                raw_sse_rx: Some(rx_raw),
-            });
+            if attempt < max_tries - 1 {
+                let delay = self.retry_policy.delay_for(attempt as u32, retry_after);
+                debug!(
+                    "Retrying after {}ms (attempt {})",
+                    delay.as_millis(),
+                    attempt + 2
+                );
+                tokio::time::sleep(delay).await;
+            }
         }
 
         let error_msg = format!(
@@ -818,6 +1154,110 @@ impl AIClient {
         Ok(response)
     }
 
+    /// Compute embedding vectors for the inputs (one vector per input, in order)
+    ///
+    /// Supported for the OpenAI and Gemini formats.
+    pub async fn create_embeddings(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let base_url = self.config.base_url.trim_end_matches('/');
+        let (url, request_body, is_gemini) = match self.get_api_format().to_lowercase().as_str() {
+            "openai" => {
+                let url = if base_url.ends_with("/embeddings") {
+                    base_url.to_string()
+                } else if let Some(root) = base_url.strip_suffix("/chat/completions") {
+                    format!("{}/embeddings", root)
+                } else {
+                    format!("{}/embeddings", base_url)
+                };
+                let body = serde_json::json!({
+                    "model": self.config.model,
+                    "input": inputs,
+                });
+                (url, body, false)
+            }
+            "gemini" => {
+                let root = base_url
+                    .split("/models/")
+                    .next()
+                    .unwrap_or(base_url)
+                    .to_string();
+                let model = format!("models/{}", self.config.model);
+                let url = format!("{}/{}:batchEmbedContents", root, model);
+                let requests: Vec<_> = inputs
+                    .iter()
+                    .map(|text| {
+                        serde_json::json!({
+                            "model": model,
+                            "content": { "parts": [{ "text": text }] }
+                        })
+                    })
+                    .collect();
+                (url, serde_json::json!({ "requests": requests }), true)
+            }
+            other => return Err(anyhow!("Embeddings are not supported for API format: {}", other)),
+        };
+
+        let response = self
+            .send_with_retry("Embeddings", &request_body, || {
+                let builder = self.client.post(&url);
+                if is_gemini {
+                    self.apply_gemini_headers(builder)
+                } else {
+                    self.apply_openai_headers(builder)
+                }
+            })
+            .await?;
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse embeddings response: {}", e))?;
+
+        let parse_vector = |value: &serde_json::Value| -> Option<Vec<f32>> {
+            value
+                .as_array()?
+                .iter()
+                .map(|v| v.as_f64().map(|f| f as f32))
+                .collect()
+        };
+
+        let embeddings: Option<Vec<Vec<f32>>> = if is_gemini {
+            body.get("embeddings")
+                .and_then(|v| v.as_array())
+                .and_then(|items| {
+                    items
+                        .iter()
+                        .map(|item| item.get("values").and_then(parse_vector))
+                        .collect()
+                })
+        } else {
+            body.get("data").and_then(|v| v.as_array()).and_then(|items| {
+                let mut indexed: Vec<(u64, Vec<f32>)> = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let index = item
+                            .get("index")
+                            .and_then(|v| v.as_u64())
+                            .unwrap_or(i as u64);
+                        item.get("embedding")
+                            .and_then(parse_vector)
+                            .map(|vector| (index, vector))
+                    })
+                    .collect::<Option<_>>()?;
+                indexed.sort_by_key(|(index, _)| *index);
+                Some(indexed.into_iter().map(|(_, vector)| vector).collect())
+            })
+        };
+
+        match embeddings {
+            Some(embeddings) if embeddings.len() == inputs.len() => Ok(embeddings),
+            _ => Err(anyhow!("Invalid embeddings response: {}", body)),
+        }
+    }
+
     pub async fn test_connection(&self) -> Result<ConnectionTestResult> {
         let start_time = std::time::Instant::now();
 
@@ -6,7 +6,7 @@
 //! 3. Invalidate cache when configuration changes
 //! 4. Provide global singleton access
 
-use crate::infrastructure::ai::AIClient;
+use crate::infrastructure::ai::{AIClient, RetryPolicy};
 use crate::service::config::{get_global_config_service, ConfigService};
 use crate::util::errors::{BitFunError, BitFunResult};
 use crate::util::types::AIConfig;
@@ -40,6 +40,32 @@ impl AIClientFactory {
         }
     }
 
+    /// Get the fallback clients of an agent, in configured order
+    /// Models that cannot be resolved are skipped
+    pub async fn get_fallback_clients_by_agent(
+        &self,
+        agent_name: &str,
+    ) -> Result<Vec<Arc<AIClient>>> {
+        let global_config: crate::service::config::GlobalConfig =
+            self.config_service.get_config(None).await?;
+
+        let Some(model_ids) = global_config.ai.agent_fallback_models.get(agent_name) else {
+            return Ok(Vec::new());
+        };
+
+        let mut clients = Vec::with_capacity(model_ids.len());
+        for model_id in model_ids {
+            match self.get_client_resolved(model_id).await {
+                Ok(client) => clients.push(client),
+                Err(e) => warn!(
+                    "Skipping fallback model: agent={}, model_id={}, error={}",
+                    agent_name, model_id, e
+                ),
+            }
+        }
+        Ok(clients)
+    }
+
     /// Get a functional agent's AI client
     /// Prefer func_agent_models, fall back to agent_models (legacy), then fast
     pub async fn get_client_by_func_agent(&self, func_agent_name: &str) -> Result<Arc<AIClient>> {
@@ -163,7 +189,10 @@ impl AIClientFactory {
             None
         };
 
-        let client = Arc::new(AIClient::new_with_proxy(ai_config, proxy_config));
+        let client = Arc::new(
+            AIClient::new_with_proxy(ai_config, proxy_config)
+                .with_retry_policy(RetryPolicy::from(&global_config.ai.retry)),
+        );
 
         {
             let mut cache = match self.client_cache.write() {
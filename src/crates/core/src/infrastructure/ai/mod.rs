@@ -5,8 +5,10 @@
 pub mod client;
 pub mod client_factory;
 pub mod providers;
+pub mod retry;
 
 pub use ai_stream_handlers;
 
 pub use client::{AIClient, StreamResponse};
 pub use client_factory::{AIClientFactory, get_global_ai_client_factory, initialize_global_ai_client_factory};
+pub use retry::RetryPolicy;
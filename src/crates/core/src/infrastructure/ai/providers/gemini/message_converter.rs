@@ -0,0 +1,414 @@
+//! Gemini message format converter
+//!
+//! Converts the unified message format to Google Gemini API format (contents/parts)
+
+use log::warn;
+use crate::util::types::{Message, ToolDefinition};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Schema keywords accepted by Gemini function declarations (OpenAPI subset)
+const SUPPORTED_SCHEMA_KEYS: &[&str] = &[
+    "type",
+    "format",
+    "title",
+    "description",
+    "nullable",
+    "enum",
+    "items",
+    "minItems",
+    "maxItems",
+    "properties",
+    "required",
+    "minProperties",
+    "maxProperties",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "minimum",
+    "maximum",
+    "anyOf",
+    "default",
+    "example",
+    "propertyOrdering",
+];
+
+pub struct GeminiMessageConverter;
+
+impl GeminiMessageConverter {
+    /// Convert unified message format to Gemini format
+    ///
+    /// Returns `(systemInstruction, contents)`. Gemini has no system role, and tool
+    /// results are `functionResponse` parts in a user turn, addressed by function name.
+    pub fn convert_messages(messages: Vec<Message>) -> (Option<Value>, Vec<Value>) {
+        let mut system_texts = Vec::new();
+        let mut contents = Vec::new();
+        // tool_call_id -> function name, for tool results that only carry the id
+        let mut call_names: HashMap<String, String> = HashMap::new();
+
+        for msg in messages {
+            match msg.role.as_str() {
+                "system" => {
+                    if let Some(content) = msg.content.filter(|c| !c.is_empty()) {
+                        system_texts.push(content);
+                    }
+                }
+                "user" => {
+                    let parts = Self::convert_user_content(msg.content.unwrap_or_default());
+                    if !parts.is_empty() {
+                        contents.push(json!({ "role": "user", "parts": parts }));
+                    }
+                }
+                "assistant" => {
+                    if let Some(tool_calls) = msg.tool_calls.as_ref() {
+                        for tc in tool_calls {
+                            call_names.insert(tc.id.clone(), tc.name.clone());
+                        }
+                    }
+                    if let Some(converted) = Self::convert_assistant_message(msg) {
+                        contents.push(converted);
+                    }
+                }
+                "tool" => {
+                    contents.push(Self::convert_tool_result_message(msg, &call_names));
+                }
+                _ => {
+                    warn!("Unknown message role: {}", msg.role);
+                }
+            }
+        }
+
+        let system_instruction = if system_texts.is_empty() {
+            None
+        } else {
+            Some(json!({ "parts": [{ "text": system_texts.join("\n\n") }] }))
+        };
+
+        (system_instruction, Self::merge_consecutive_contents(contents))
+    }
+
+    /// Merge consecutive same-role contents; parallel tool results must share one turn
+    fn merge_consecutive_contents(contents: Vec<Value>) -> Vec<Value> {
+        let mut merged: Vec<Value> = Vec::new();
+
+        for content in contents {
+            if let Some(last) = merged.last_mut() {
+                if last.get("role") == content.get("role") {
+                    if let (Some(Value::Array(last_parts)), Some(Value::Array(parts))) =
+                        (last.get_mut("parts"), content.get("parts"))
+                    {
+                        last_parts.extend(parts.iter().cloned());
+                        continue;
+                    }
+                }
+            }
+            merged.push(content);
+        }
+
+        merged
+    }
+
+    /// User content is plain text, or a JSON array of OpenAI/Anthropic/Gemini content blocks
+    fn convert_user_content(content: String) -> Vec<Value> {
+        if let Ok(Value::Array(blocks)) = serde_json::from_str::<Value>(&content) {
+            return blocks.iter().filter_map(Self::convert_content_block).collect();
+        }
+
+        if content.is_empty() {
+            Vec::new()
+        } else {
+            vec![json!({ "text": content })]
+        }
+    }
+
+    fn convert_content_block(block: &Value) -> Option<Value> {
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("text") => block
+                .get("text")
+                .and_then(|t| t.as_str())
+                .map(|text| json!({ "text": text })),
+            Some("image_url") => {
+                let url = block
+                    .get("image_url")
+                    .and_then(|image| image.get("url"))
+                    .and_then(|u| u.as_str())?;
+                match Self::parse_data_url(url) {
+                    Some((mime_type, data)) => Some(json!({
+                        "inlineData": { "mimeType": mime_type, "data": data }
+                    })),
+                    None => {
+                        warn!("Gemini only accepts inline image data, skipping image URL");
+                        Some(json!({ "text": format!("[Image: {}]", url) }))
+                    }
+                }
+            }
+            Some("image") => {
+                let source = block.get("source")?;
+                Some(json!({
+                    "inlineData": {
+                        "mimeType": source.get("media_type").cloned().unwrap_or(json!("image/png")),
+                        "data": source.get("data").cloned().unwrap_or(json!(""))
+                    }
+                }))
+            }
+            // Already a Gemini part
+            None if block.get("text").is_some() || block.get("inlineData").is_some() => {
+                Some(block.clone())
+            }
+            other => {
+                warn!("Unsupported content block for Gemini: {:?}", other);
+                None
+            }
+        }
+    }
+
+    /// Split `data:<mime>;base64,<data>`
+    fn parse_data_url(url: &str) -> Option<(&str, &str)> {
+        let rest = url.strip_prefix("data:")?;
+        let (meta, data) = rest.split_once(',')?;
+        let mime_type = meta.strip_suffix(";base64")?;
+        Some((mime_type, data))
+    }
+
+    /// Convert assistant messages; return None when empty.
+    ///
+    /// Thoughts are not sent back; the thought signature goes on the first function
+    /// call part (or the last text part), as Gemini requires for multi-turn thinking.
+    fn convert_assistant_message(msg: Message) -> Option<Value> {
+        let mut parts = Vec::new();
+
+        if let Some(text) = msg.content {
+            if !text.is_empty() {
+                parts.push(json!({ "text": text }));
+            }
+        }
+
+        let first_call_index = parts.len();
+        if let Some(tool_calls) = msg.tool_calls {
+            for tc in tool_calls {
+                parts.push(json!({
+                    "functionCall": {
+                        "name": tc.name,
+                        "args": tc.arguments
+                    }
+                }));
+            }
+        }
+
+        if parts.is_empty() {
+            return None;
+        }
+
+        if let Some(signature) = msg.thinking_signature.filter(|s| !s.is_empty()) {
+            let index = if first_call_index < parts.len() {
+                first_call_index
+            } else {
+                parts.len() - 1
+            };
+            parts[index]["thoughtSignature"] = json!(signature);
+        }
+
+        Some(json!({
+            "role": "model",
+            "parts": parts
+        }))
+    }
+
+    fn convert_tool_result_message(msg: Message, call_names: &HashMap<String, String>) -> Value {
+        let name = msg
+            .tool_call_id
+            .as_ref()
+            .and_then(|id| call_names.get(id).cloned())
+            .or(msg.name)
+            .unwrap_or_else(|| {
+                warn!("[Gemini] Tool result without a matching function call: id={:?}", msg.tool_call_id);
+                "unknown".to_string()
+            });
+        let content = msg.content.unwrap_or_default();
+
+        json!({
+            "role": "user",
+            "parts": [{
+                "functionResponse": {
+                    "name": name,
+                    "response": { "content": content }
+                }
+            }]
+        })
+    }
+
+    /// Convert tool definitions to Gemini format (one tool holding all function declarations)
+    pub fn convert_tools(tools: Option<Vec<ToolDefinition>>) -> Option<Vec<Value>> {
+        let declarations: Vec<Value> = tools?
+            .into_iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": Self::sanitize_schema(&tool.parameters)
+                })
+            })
+            .collect();
+
+        if declarations.is_empty() {
+            None
+        } else {
+            Some(vec![json!({ "functionDeclarations": declarations })])
+        }
+    }
+
+    /// Reduce a JSON Schema to what Gemini accepts
+    ///
+    /// Drops unsupported keywords (e.g. `additionalProperties`, `$schema`), turns
+    /// `"type": [T, "null"]` into `nullable`, and keeps `required` consistent with
+    /// `properties`.
+    fn sanitize_schema(schema: &Value) -> Value {
+        let Some(object) = schema.as_object() else {
+            return schema.clone();
+        };
+
+        let mut result = Map::new();
+        for (key, value) in object {
+            if !SUPPORTED_SCHEMA_KEYS.contains(&key.as_str()) {
+                continue;
+            }
+            let value = match key.as_str() {
+                "type" => match value {
+                    Value::Array(types) => {
+                        let mut non_null = types.iter().filter(|t| t.as_str() != Some("null"));
+                        if types.len() > 1 && types.iter().any(|t| t.as_str() == Some("null")) {
+                            result.insert("nullable".to_string(), json!(true));
+                        }
+                        non_null.next().cloned().unwrap_or(json!("string"))
+                    }
+                    other => other.clone(),
+                },
+                "properties" => match value.as_object() {
+                    Some(properties) => Value::Object(
+                        properties
+                            .iter()
+                            .map(|(name, prop)| (name.clone(), Self::sanitize_schema(prop)))
+                            .collect(),
+                    ),
+                    None => continue,
+                },
+                "items" => Self::sanitize_schema(value),
+                "anyOf" => match value.as_array() {
+                    Some(variants) => {
+                        Value::Array(variants.iter().map(Self::sanitize_schema).collect())
+                    }
+                    None => continue,
+                },
+                _ => value.clone(),
+            };
+            result.insert(key.clone(), value);
+        }
+
+        let property_names: Option<Vec<String>> = result
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .map(|p| p.keys().cloned().collect());
+        if let Some(Value::Array(required)) = result.get_mut("required") {
+            required.retain(|name| {
+                name.as_str().is_some_and(|name| {
+                    property_names
+                        .as_ref()
+                        .is_some_and(|names| names.iter().any(|n| n == name))
+                })
+            });
+            if required.is_empty() {
+                result.remove("required");
+            }
+        }
+
+        Value::Object(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeminiMessageConverter;
+    use crate::util::types::{Message, ToolCall, ToolDefinition};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn converts_conversation_with_tool_round_trip() {
+        let mut arguments = HashMap::new();
+        arguments.insert("path".to_string(), json!("a.rs"));
+        let mut assistant = Message::assistant_with_tools(vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "Read".to_string(),
+            arguments,
+        }]);
+        assistant.content = Some("Reading it.".to_string());
+        assistant.thinking_signature = Some("sig".to_string());
+
+        let tool_result = Message {
+            role: "tool".to_string(),
+            content: Some("fn main() {}".to_string()),
+            reasoning_content: None,
+            thinking_signature: None,
+            tool_calls: None,
+            tool_call_id: Some("call_1".to_string()),
+            name: None,
+            response_id: None,
+        };
+
+        let image = json!([
+            { "type": "image_url", "image_url": { "url": "data:image/png;base64,AAAA" } },
+            { "type": "text", "text": "What is this?" }
+        ]);
+
+        let (system, contents) = GeminiMessageConverter::convert_messages(vec![
+            Message::system("Be brief.".to_string()),
+            Message::user("Show a.rs".to_string()),
+            assistant,
+            tool_result,
+            Message::user(image.to_string()),
+        ]);
+
+        assert_eq!(system, Some(json!({ "parts": [{ "text": "Be brief." }] })));
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[1]["role"], "model");
+        assert_eq!(contents[1]["parts"][1]["functionCall"]["name"], "Read");
+        assert_eq!(contents[1]["parts"][1]["thoughtSignature"], "sig");
+
+        // Tool result and the following user message share one user turn
+        let parts = contents[2]["parts"].as_array().unwrap();
+        assert_eq!(parts[0]["functionResponse"]["name"], "Read");
+        assert_eq!(parts[1]["inlineData"]["mimeType"], "image/png");
+        assert_eq!(parts[2]["text"], "What is this?");
+    }
+
+    #[test]
+    fn sanitizes_tool_schema() {
+        let tools = GeminiMessageConverter::convert_tools(Some(vec![ToolDefinition {
+            name: "Edit".to_string(),
+            description: "Edit a file".to_string(),
+            parameters: json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "limit": { "type": ["integer", "null"] },
+                    "options": {
+                        "type": "object",
+                        "properties": {},
+                        "additionalProperties": false
+                    }
+                },
+                "required": ["path", "missing"],
+                "additionalProperties": false
+            }),
+        }]))
+        .unwrap();
+
+        let parameters = &tools[0]["functionDeclarations"][0]["parameters"];
+        assert!(parameters.get("$schema").is_none());
+        assert!(parameters.get("additionalProperties").is_none());
+        assert_eq!(parameters["required"], json!(["path"]));
+        assert_eq!(parameters["properties"]["limit"], json!({ "type": "integer", "nullable": true }));
+        assert!(parameters["properties"]["options"].get("additionalProperties").is_none());
+    }
+}
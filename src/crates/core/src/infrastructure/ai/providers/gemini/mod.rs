@@ -0,0 +1,7 @@
+//! Google Gemini API provider
+//!
+//! Implements interaction with Google Gemini models
+
+pub mod message_converter;
+
+pub use message_converter::GeminiMessageConverter;
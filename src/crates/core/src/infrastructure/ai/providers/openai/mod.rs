@@ -1,6 +1,8 @@
 //! OpenAI provider module
 
 pub mod message_converter;
+pub mod responses_converter;
 
 pub use message_converter::OpenAIMessageConverter;
+pub use responses_converter::OpenAIResponsesConverter;
 
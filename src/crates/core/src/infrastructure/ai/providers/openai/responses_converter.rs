@@ -0,0 +1,232 @@
+//! OpenAI Responses API format converter
+//!
+//! Converts the unified message format to Responses API input items
+
+use log::warn;
+use crate::util::types::{Message, ResponseState, ToolDefinition};
+use serde_json::{json, Value};
+
+pub struct OpenAIResponsesConverter;
+
+impl OpenAIResponsesConverter {
+    /// Find where a request can continue from server-side state
+    ///
+    /// Returns the id of the response behind the last assistant message and the index
+    /// of that message; only the messages after it need to be sent.
+    pub fn response_state(messages: &[Message]) -> Option<(ResponseState, usize)> {
+        let (index, message) = messages
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, msg)| msg.role == "assistant")?;
+        let response_id = message.response_id.clone().filter(|id| !id.is_empty())?;
+
+        Some((
+            ResponseState {
+                previous_response_id: Some(response_id),
+                conversation_id: None,
+            },
+            index,
+        ))
+    }
+
+    /// Convert unified message format to Responses API format
+    ///
+    /// Returns `(instructions, input)`. System messages become `instructions`, which
+    /// the API does not carry over between chained responses.
+    pub fn convert_messages(messages: Vec<Message>) -> (Option<String>, Vec<Value>) {
+        let mut instructions = Vec::new();
+        let mut input = Vec::new();
+
+        for msg in messages {
+            match msg.role.as_str() {
+                "system" => {
+                    if let Some(content) = msg.content.filter(|c| !c.is_empty()) {
+                        instructions.push(content);
+                    }
+                }
+                "user" => {
+                    input.push(json!({
+                        "role": "user",
+                        "content": Self::convert_user_content(msg.content.unwrap_or_default())
+                    }));
+                }
+                "assistant" => Self::convert_assistant_message(msg, &mut input),
+                "tool" => {
+                    let output = msg
+                        .content
+                        .filter(|c| !c.is_empty())
+                        .unwrap_or_else(|| "Tool execution completed".to_string());
+                    input.push(json!({
+                        "type": "function_call_output",
+                        "call_id": msg.tool_call_id.unwrap_or_default(),
+                        "output": output
+                    }));
+                }
+                _ => {
+                    warn!("Unknown message role: {}", msg.role);
+                }
+            }
+        }
+
+        let instructions = if instructions.is_empty() {
+            None
+        } else {
+            Some(instructions.join("\n\n"))
+        };
+
+        (instructions, input)
+    }
+
+    /// User content is plain text, or a JSON array of OpenAI/Anthropic content blocks
+    fn convert_user_content(content: String) -> Value {
+        if let Ok(Value::Array(blocks)) = serde_json::from_str::<Value>(&content) {
+            let parts: Vec<Value> = blocks.iter().filter_map(Self::convert_content_block).collect();
+            if !parts.is_empty() {
+                return Value::Array(parts);
+            }
+        }
+
+        if content.trim().is_empty() {
+            Value::String(" ".to_string())
+        } else {
+            Value::String(content)
+        }
+    }
+
+    fn convert_content_block(block: &Value) -> Option<Value> {
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("text") | Some("input_text") => block
+                .get("text")
+                .and_then(|t| t.as_str())
+                .map(|text| json!({ "type": "input_text", "text": text })),
+            Some("image_url") => {
+                let image_url = block.get("image_url")?;
+                let url = image_url
+                    .get("url")
+                    .and_then(|u| u.as_str())
+                    .or_else(|| image_url.as_str())?;
+                Some(json!({ "type": "input_image", "image_url": url }))
+            }
+            Some("image") => {
+                let source = block.get("source")?;
+                let mime_type = source
+                    .get("media_type")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("image/png");
+                let data = source.get("data").and_then(|d| d.as_str())?;
+                Some(json!({
+                    "type": "input_image",
+                    "image_url": format!("data:{};base64,{}", mime_type, data)
+                }))
+            }
+            Some("input_image") => Some(block.clone()),
+            other => {
+                warn!("Unsupported content block for Responses API: {:?}", other);
+                None
+            }
+        }
+    }
+
+    /// Assistant turns expand to reasoning items, a message and function calls
+    ///
+    /// Encrypted reasoning items (kept in `thinking_signature`) are replayed ahead of
+    /// the output they belong to, as the API requires.
+    fn convert_assistant_message(msg: Message, input: &mut Vec<Value>) {
+        if let Some(signature) = msg.thinking_signature.as_deref() {
+            match serde_json::from_str::<Vec<Value>>(signature) {
+                Ok(items) => {
+                    for mut item in items {
+                        item["type"] = json!("reasoning");
+                        input.push(item);
+                    }
+                }
+                Err(_) => {
+                    warn!("[Responses] Ignoring thinking signature that is not a reasoning item list");
+                }
+            }
+        }
+
+        if let Some(text) = msg.content.filter(|t| !t.trim().is_empty()) {
+            input.push(json!({
+                "role": "assistant",
+                "content": text
+            }));
+        }
+
+        for tc in msg.tool_calls.unwrap_or_default() {
+            input.push(json!({
+                "type": "function_call",
+                "call_id": tc.id,
+                "name": tc.name,
+                "arguments": serde_json::to_string(&tc.arguments).unwrap_or_default()
+            }));
+        }
+    }
+
+    pub fn convert_tools(tools: Option<Vec<ToolDefinition>>) -> Option<Vec<Value>> {
+        tools.map(|tool_defs| {
+            tool_defs
+                .into_iter()
+                .map(|tool| {
+                    // Strict mode is the API default but needs closed, all-required schemas
+                    json!({
+                        "type": "function",
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.parameters,
+                        "strict": false
+                    })
+                })
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OpenAIResponsesConverter;
+    use crate::util::types::{Message, ToolCall};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn converts_messages_and_finds_chain_point() {
+        let mut assistant = Message::assistant_with_tools(vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "LS".to_string(),
+            arguments: HashMap::new(),
+        }]);
+        assistant.thinking_signature =
+            Some(r#"[{"id":"rs_1","encrypted_content":"enc","summary":[]}]"#.to_string());
+        assistant.response_id = Some("resp_1".to_string());
+
+        let mut tool_result = Message::user("a.rs\nb.rs".to_string());
+        tool_result.role = "tool".to_string();
+        tool_result.tool_call_id = Some("call_1".to_string());
+
+        let messages = vec![
+            Message::system("Be brief.".to_string()),
+            Message::user("List files".to_string()),
+            assistant,
+            tool_result,
+        ];
+
+        let (state, index) =
+            OpenAIResponsesConverter::response_state(&messages).expect("chain point");
+        assert_eq!(state.previous_response_id.as_deref(), Some("resp_1"));
+        assert_eq!(index, 2);
+
+        let (instructions, input) = OpenAIResponsesConverter::convert_messages(messages);
+        assert_eq!(instructions.as_deref(), Some("Be brief."));
+        assert_eq!(
+            input,
+            vec![
+                json!({ "role": "user", "content": "List files" }),
+                json!({ "type": "reasoning", "id": "rs_1", "encrypted_content": "enc", "summary": [] }),
+                json!({ "type": "function_call", "call_id": "call_1", "name": "LS", "arguments": "{}" }),
+                json!({ "type": "function_call_output", "call_id": "call_1", "output": "a.rs\nb.rs" }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Retry policy for model requests
+//!
+//! Exponential backoff with jitter, honoring the provider's `Retry-After`
+
+use crate::service::config::RetryConfig;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::from(&RetryConfig::default())
+    }
+}
+
+impl From<&RetryConfig> for RetryPolicy {
+    fn from(config: &RetryConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            initial_delay: Duration::from_millis(config.initial_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms.max(config.initial_delay_ms)),
+            jitter: config.jitter,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Total attempts including the first
+    pub fn max_attempts(&self) -> usize {
+        self.max_retries as usize + 1
+    }
+
+    /// Delay before retry number `retry_index` (0-based)
+    ///
+    /// A server-provided `Retry-After` wins over the computed backoff; both are
+    /// capped at `max_delay`.
+    pub fn delay_for(&self, retry_index: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(self.max_delay);
+        }
+
+        let backoff = self
+            .initial_delay
+            .saturating_mul(1u32 << retry_index.min(16))
+            .min(self.max_delay);
+        if !self.jitter {
+            return backoff;
+        }
+
+        // Equal jitter: keep half of the backoff, randomize the other half
+        let half = backoff / 2;
+        let spread = half.as_millis() as u64;
+        if spread == 0 {
+            return backoff;
+        }
+        half + Duration::from_millis(random_u64() % (spread + 1))
+    }
+
+    /// Whether a response status is worth retrying (rate limit, timeout, server side)
+    pub fn is_retryable_status(status: StatusCode) -> bool {
+        status == StatusCode::TOO_MANY_REQUESTS
+            || status == StatusCode::REQUEST_TIMEOUT
+            || status.is_server_error()
+            // Anthropic "overloaded"
+            || status.as_u16() == 529
+    }
+
+    /// Read the retry delay requested by the provider
+    ///
+    /// Supports `retry-after-ms` (OpenAI/Anthropic) and `Retry-After` as seconds
+    /// or an HTTP date.
+    pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+        if let Some(ms) = headers
+            .get("retry-after-ms")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<f64>().ok())
+        {
+            if ms.is_finite() && ms >= 0.0 {
+                return Some(Duration::from_millis(ms as u64));
+            }
+        }
+
+        let value = headers
+            .get(reqwest::header::RETRY_AFTER)?
+            .to_str()
+            .ok()?
+            .trim();
+        if let Ok(secs) = value.parse::<u64>() {
+            return Some(Duration::from_secs(secs));
+        }
+
+        let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+        let date: SystemTime = date.into();
+        Some(
+            date.duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO),
+        )
+    }
+
+    /// Whether an error message describes a transient failure
+    /// (network, rate limit, overloaded or interrupted stream)
+    pub fn is_transient_error(error_message: &str) -> bool {
+        let msg = error_message.to_lowercase();
+
+        let non_retryable_keywords = [
+            "invalid api key",
+            "unauthorized",
+            "forbidden",
+            "model not found",
+            "unsupported model",
+            "invalid request",
+            "bad request",
+            "prompt is too long",
+            "content policy",
+            "proxy authentication required",
+            "client error 400",
+            "client error 401",
+            "client error 403",
+            "client error 404",
+            "client error 422",
+            "sse parsing error",
+            "schema error",
+            "unknown api format",
+        ];
+
+        let transient_keywords = [
+            "transport error",
+            "error decoding response body",
+            "stream closed before response completed",
+            "stream processing error",
+            "sse stream error",
+            "sse error",
+            "sse timeout",
+            "stream data timeout",
+            "timeout",
+            "connection reset",
+            "broken pipe",
+            "unexpected eof",
+            "connection refused",
+            "temporarily unavailable",
+            "service unavailable",
+            "bad gateway",
+            "internal server error",
+            "gateway timeout",
+            "overloaded",
+            "proxy",
+            "tunnel",
+            "dns",
+            "network",
+            "econnreset",
+            "econnrefused",
+            "etimedout",
+            "rate limit",
+            "too many requests",
+            "429",
+            "529",
+        ];
+
+        if non_retryable_keywords.iter().any(|k| msg.contains(k)) {
+            return false;
+        }
+
+        transient_keywords.iter().any(|k| msg.contains(k))
+    }
+}
+
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use reqwest::StatusCode;
+    use std::time::Duration;
+
+    fn policy(jitter: bool) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 5,
+            initial_delay: Duration::from_millis(1000),
+            max_delay: Duration::from_millis(5000),
+            jitter,
+        }
+    }
+
+    #[test]
+    fn backs_off_exponentially_within_bounds() {
+        let fixed = policy(false);
+        assert_eq!(fixed.delay_for(0, None), Duration::from_millis(1000));
+        assert_eq!(fixed.delay_for(2, None), Duration::from_millis(4000));
+        assert_eq!(fixed.delay_for(10, None), Duration::from_millis(5000));
+        assert_eq!(
+            fixed.delay_for(0, Some(Duration::from_secs(60))),
+            Duration::from_millis(5000)
+        );
+
+        let jittered = policy(true);
+        for _ in 0..20 {
+            let delay = jittered.delay_for(1, None);
+            assert!(delay >= Duration::from_millis(1000) && delay <= Duration::from_millis(2000));
+        }
+    }
+
+    #[test]
+    fn parses_retry_after_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(
+            RetryPolicy::parse_retry_after(&headers),
+            Some(Duration::from_secs(7))
+        );
+
+        headers.insert("retry-after-ms", HeaderValue::from_static("1500"));
+        assert_eq!(
+            RetryPolicy::parse_retry_after(&headers),
+            Some(Duration::from_millis(1500))
+        );
+
+        let mut past = HeaderMap::new();
+        past.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(RetryPolicy::parse_retry_after(&past), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn classifies_statuses_and_errors() {
+        assert!(RetryPolicy::is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(RetryPolicy::is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(RetryPolicy::is_retryable_status(
+            StatusCode::from_u16(529).unwrap()
+        ));
+        assert!(!RetryPolicy::is_retryable_status(StatusCode::UNAUTHORIZED));
+
+        assert!(RetryPolicy::is_transient_error(
+            "Error: Stream processing error: SSE Error: Transport Error: Error decoding response body"
+        ));
+        assert!(RetryPolicy::is_transient_error(
+            "Stream processing error: overloaded_error: Overloaded"
+        ));
+        assert!(!RetryPolicy::is_transient_error(
+            "OpenAI Streaming API client error 401: unauthorized"
+        ));
+        assert!(!RetryPolicy::is_transient_error(
+            "Stream processing error: SSE data schema error: missing field choices"
+        ));
+    }
+}
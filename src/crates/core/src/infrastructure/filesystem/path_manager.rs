@@ -73,6 +73,11 @@ impl PathManager {
         self.user_root.join("config")
     }
 
+    /// Get MCP OAuth credentials directory: ~/.config/bitfun/config/mcp-oauth/
+    pub fn mcp_oauth_dir(&self) -> PathBuf {
+        self.user_config_dir().join("mcp-oauth")
+    }
+
     /// Get app config file path: ~/.config/bitfun/config/app.json
     pub fn app_config_file(&self) -> PathBuf {
         self.user_config_dir().join("app.json")
@@ -184,6 +189,11 @@ impl PathManager {
         self.project_root(workspace_path).join("config.json")
     }
 
+    /// Get project settings file: {project}/.bitfun/settings.json
+    pub fn project_settings_file(&self, workspace_path: &Path) -> PathBuf {
+        self.project_root(workspace_path).join("settings.json")
+    }
+
     /// Get project .gitignore file: {project}/.bitfun/.gitignore
     pub fn project_gitignore_file(&self, workspace_path: &Path) -> PathBuf {
         self.project_root(workspace_path).join(".gitignore")
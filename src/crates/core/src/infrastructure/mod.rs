@@ -18,4 +18,4 @@ pub use filesystem::{
     PathManager, SearchMatchType,
 };
 // pub use storage::{};
-pub use workspace_path::{get_workspace_path, set_workspace_path};
+pub use workspace_path::{get_workspace_path, set_workspace_path, with_workspace_path};
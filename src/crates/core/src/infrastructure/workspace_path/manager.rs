@@ -1,12 +1,18 @@
 //! Workspace path management
 //!
-//! Provides global workspace path set/get
+//! Provides global workspace path set/get, with a per-task override for work that runs in
+//! another checkout (e.g. subagents in a git worktree)
 
+use std::future::Future;
 use std::path::PathBuf;
 use std::sync::RwLock;
 
 static GLOBAL_WORKSPACE_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
 
+tokio::task_local! {
+    static WORKSPACE_PATH_OVERRIDE: PathBuf;
+}
+
 pub fn set_workspace_path(workspace_path: Option<PathBuf>) {
     if let Ok(mut path) = GLOBAL_WORKSPACE_PATH.write() {
         *path = workspace_path;
@@ -14,8 +20,20 @@ pub fn set_workspace_path(workspace_path: Option<PathBuf>) {
 }
 
 pub fn get_workspace_path() -> Option<PathBuf> {
+    if let Ok(path) = WORKSPACE_PATH_OVERRIDE.try_with(|path| path.clone()) {
+        return Some(path);
+    }
+
     GLOBAL_WORKSPACE_PATH
         .read()
         .ok()
         .and_then(|path| path.clone())
 }
+
+/// Runs `future` with `get_workspace_path` returning `workspace_path` inside it
+///
+/// The override follows the future, not the process: the global path is unchanged for
+/// everything else, but tasks spawned from inside the future do not inherit it.
+pub async fn with_workspace_path<F: Future>(workspace_path: PathBuf, future: F) -> F::Output {
+    WORKSPACE_PATH_OVERRIDE.scope(workspace_path, future).await
+}
@@ -2,4 +2,4 @@
 
 pub mod manager;
 
-pub use manager::{get_workspace_path, set_workspace_path};
+pub use manager::{get_workspace_path, set_workspace_path, with_workspace_path};
@@ -1,10 +1,11 @@
 //! AI memory point manager
 
-use super::types::{AIMemory, MemoryStorage, MemoryType};
+use super::recall::{format_memories_prompt, recall_memories};
+use super::types::{AIMemory, MemoryScope, MemoryStorage};
 use crate::infrastructure::PathManager;
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::debug;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
 use tokio::sync::RwLock;
@@ -18,12 +19,14 @@ pub struct AIMemoryManager {
     storage: Arc<RwLock<MemoryStorage>>,
     /// Storage file path
     storage_path: PathBuf,
+    /// Store this manager operates on
+    scope: MemoryScope,
 }
 
 impl AIMemoryManager {
     /// Creates a new memory manager (user-level).
     pub async fn new(path_manager: Arc<PathManager>) -> BitFunResult<Self> {
-        let storage_path = path_manager.user_data_dir().join("ai_memories.json");
+        let storage_path = Self::user_storage_path(&path_manager);
 
         if let Some(parent) = storage_path.parent() {
             fs::create_dir_all(parent)
@@ -41,6 +44,7 @@ impl AIMemoryManager {
             path_manager,
             storage: Arc::new(RwLock::new(storage)),
             storage_path,
+            scope: MemoryScope::User,
         })
     }
 
@@ -49,8 +53,7 @@ impl AIMemoryManager {
         path_manager: Arc<PathManager>,
         workspace_path: &str,
     ) -> BitFunResult<Self> {
-        let workspace_path = PathBuf::from(workspace_path);
-        let storage_path = workspace_path.join(".bitfun").join("ai_memories.json");
+        let storage_path = Self::project_storage_path(Path::new(workspace_path));
 
         if let Some(parent) = storage_path.parent() {
             fs::create_dir_all(parent)
@@ -68,9 +71,20 @@ impl AIMemoryManager {
             path_manager,
             storage: Arc::new(RwLock::new(storage)),
             storage_path,
+            scope: MemoryScope::Project,
         })
     }
 
+    /// Storage file of the user store.
+    pub fn user_storage_path(path_manager: &PathManager) -> PathBuf {
+        path_manager.user_data_dir().join("ai_memories.json")
+    }
+
+    /// Storage file of a workspace's project store.
+    pub fn project_storage_path(workspace_path: &Path) -> PathBuf {
+        workspace_path.join(".bitfun").join("ai_memories.json")
+    }
+
     /// Loads storage from disk.
     async fn load_storage(path: &PathBuf) -> BitFunResult<MemoryStorage> {
         let content = fs::read_to_string(path)
@@ -101,6 +115,11 @@ impl AIMemoryManager {
         Ok(())
     }
 
+    /// Returns the store this manager operates on.
+    pub fn scope(&self) -> MemoryScope {
+        self.scope
+    }
+
     /// Returns the storage path (for debugging and logging).
     pub fn get_storage_path(&self) -> &PathBuf {
         &self.storage_path
@@ -157,40 +176,25 @@ impl AIMemoryManager {
             .collect())
     }
 
+    /// Returns a memory point by ID.
+    pub async fn get_memory(&self, id: &str) -> BitFunResult<Option<AIMemory>> {
+        let storage = self.storage.read().await;
+        Ok(storage.memories.iter().find(|m| m.id == id).cloned())
+    }
+
     /// Gets memory points for prompt assembly.
+    /// Only the memory points most relevant to `query` are included (see `recall`).
     /// Returns a formatted string that can be appended to the prompt directly.
-    pub async fn get_memories_for_prompt(&self) -> BitFunResult<Option<String>> {
-        let memories = self.get_enabled_memories().await?;
-
-        if memories.is_empty() {
-            return Ok(None);
-        }
-
-        let mut sorted_memories = memories;
-        sorted_memories.sort_by(|a, b| b.importance.cmp(&a.importance));
-
-        let mut prompt = String::from("# Memory Points\n");
-        prompt.push_str("The following are important memory points set by the user, consider these information in the conversation\n\n");
-
-        for memory in sorted_memories.iter() {
-            let type_label = match memory.memory_type {
-                MemoryType::TechPreference => "Technology Preference",
-                MemoryType::ProjectContext => "Project Context",
-                MemoryType::UserHabit => "User Habit",
-                MemoryType::CodePattern => "Code Pattern",
-                MemoryType::Decision => "Architecture Decision",
-                MemoryType::Other => "Others",
-            };
-
-            prompt.push_str(&format!(
-                "## {} [{}] (Importance: {}/5)\n{}\n",
-                memory.title, type_label, memory.importance, memory.content
-            ));
-            prompt.push_str("\n");
-        }
-        prompt.push_str("\n");
+    pub async fn get_memories_for_prompt(&self, query: Option<&str>) -> BitFunResult<Option<String>> {
+        let candidates = self
+            .get_enabled_memories()
+            .await?
+            .into_iter()
+            .map(|memory| (self.scope, memory))
+            .collect();
 
-        Ok(Some(prompt))
+        let recalled = recall_memories(candidates, query).await;
+        Ok(format_memories_prompt(&recalled))
     }
 
     /// Toggles whether a memory point is enabled.
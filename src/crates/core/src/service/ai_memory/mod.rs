@@ -1,7 +1,9 @@
 //! AI memory point management module
 
 pub mod manager;
+pub mod recall;
 pub mod types;
 
 pub use manager::AIMemoryManager;
-pub use types::{AIMemory, MemoryStorage, MemoryType};
+pub use recall::{format_memories_prompt, recall_memories, RecalledMemory};
+pub use types::{AIMemory, MemoryScope, MemoryStorage, MemoryType};
@@ -0,0 +1,448 @@
+//! Memory recall - select the memory points relevant to the current turn
+//!
+//! Memories are ranked by BM25 over title, content and tags. When an embedding model
+//! is available, cosine similarity of embeddings is blended in. The top-ranked
+//! memories are injected until the count limit or token budget is reached.
+
+use super::types::{AIMemory, MemoryScope};
+use crate::infrastructure::ai::get_global_ai_client_factory;
+use crate::service::config::global::get_global_config_service;
+use crate::service::config::types::{AIConfig, MemoryRecallConfig, ModelCapability};
+use crate::util::token_counter::TokenCounter;
+use dashmap::DashMap;
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// BM25 term frequency saturation
+const BM25_K1: f32 = 1.2;
+/// BM25 document length normalization
+const BM25_B: f32 = 0.75;
+/// Weight of embedding similarity when blended with the lexical score
+const EMBEDDING_WEIGHT: f32 = 0.6;
+/// Weight of importance (1-5), used to break ties and order unrelated memories
+const IMPORTANCE_WEIGHT: f32 = 0.02;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "can", "do", "for", "from", "how", "i",
+    "in", "is", "it", "me", "my", "of", "on", "or", "please", "that", "the", "this", "to",
+    "we", "what", "with", "you",
+];
+
+/// Embeddings of memory points, keyed by model, memory ID and update time
+static EMBEDDING_CACHE: OnceLock<DashMap<String, Vec<f32>>> = OnceLock::new();
+
+/// Memory point selected for the prompt
+#[derive(Debug, Clone)]
+pub struct RecalledMemory {
+    pub scope: MemoryScope,
+    pub memory: AIMemory,
+    pub score: f32,
+}
+
+/// Split text into lowercase search terms
+///
+/// Latin words are split on non-alphanumeric characters; CJK text, which has no
+/// word boundaries, is indexed as single characters and character bigrams.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut word = String::new();
+    let mut prev_cjk: Option<char> = None;
+
+    let flush_word = |word: &mut String, terms: &mut Vec<String>| {
+        if word.chars().count() > 1 && !STOP_WORDS.contains(&word.as_str()) {
+            terms.push(std::mem::take(word));
+        } else {
+            word.clear();
+        }
+    };
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut terms);
+            terms.push(c.to_string());
+            if let Some(prev) = prev_cjk {
+                terms.push(format!("{}{}", prev, c));
+            }
+            prev_cjk = Some(c);
+        } else if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+            prev_cjk = None;
+        } else {
+            flush_word(&mut word, &mut terms);
+            prev_cjk = None;
+        }
+    }
+    flush_word(&mut word, &mut terms);
+
+    terms
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // Hiragana, Katakana
+        | 0x3400..=0x4DBF   // CJK Extension A
+        | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF   // Hangul
+        | 0xF900..=0xFAFF)  // CJK Compatibility Ideographs
+}
+
+/// Searchable text of a memory point; the title and tags count twice
+fn document_terms(memory: &AIMemory) -> Vec<String> {
+    let mut terms = tokenize(&memory.title);
+    terms.extend(terms.clone());
+    for tag in &memory.tags {
+        let tag_terms = tokenize(tag);
+        terms.extend(tag_terms.iter().cloned());
+        terms.extend(tag_terms);
+    }
+    terms.extend(tokenize(&memory.content));
+    terms
+}
+
+/// BM25 score of each memory for the query
+pub fn bm25_scores(memories: &[AIMemory], query: &str) -> Vec<f32> {
+    let mut query_terms = tokenize(query);
+    query_terms.sort();
+    query_terms.dedup();
+
+    let documents: Vec<HashMap<String, usize>> = memories
+        .iter()
+        .map(|memory| {
+            let mut frequencies = HashMap::new();
+            for term in document_terms(memory) {
+                *frequencies.entry(term).or_insert(0) += 1;
+            }
+            frequencies
+        })
+        .collect();
+    let lengths: Vec<f32> = documents
+        .iter()
+        .map(|doc| doc.values().sum::<usize>() as f32)
+        .collect();
+
+    if query_terms.is_empty() || documents.is_empty() {
+        return vec![0.0; memories.len()];
+    }
+
+    let doc_count = documents.len() as f32;
+    let avg_length = (lengths.iter().sum::<f32>() / doc_count).max(1.0);
+
+    let idf: Vec<f32> = query_terms
+        .iter()
+        .map(|term| {
+            let containing = documents.iter().filter(|doc| doc.contains_key(term)).count() as f32;
+            ((doc_count - containing + 0.5) / (containing + 0.5) + 1.0).ln()
+        })
+        .collect();
+
+    documents
+        .iter()
+        .zip(&lengths)
+        .map(|(doc, length)| {
+            query_terms
+                .iter()
+                .zip(&idf)
+                .map(|(term, idf)| {
+                    let tf = *doc.get(term).unwrap_or(&0) as f32;
+                    if tf == 0.0 {
+                        return 0.0;
+                    }
+                    let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * length / avg_length);
+                    idf * tf * (BM25_K1 + 1.0) / (tf + norm)
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Cosine similarity of two vectors (0 if either is empty or zero)
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Combine lexical and (optional) semantic scores with importance
+///
+/// Lexical scores are normalized by the best match so both signals are in 0..=1.
+fn combine_scores(memories: &[AIMemory], lexical: &[f32], semantic: Option<&[f32]>) -> Vec<f32> {
+    let max_lexical = lexical.iter().cloned().fold(0.0_f32, f32::max);
+
+    memories
+        .iter()
+        .enumerate()
+        .map(|(i, memory)| {
+            let lexical = if max_lexical > 0.0 {
+                lexical[i] / max_lexical
+            } else {
+                0.0
+            };
+            let relevance = match semantic {
+                Some(semantic) => {
+                    (1.0 - EMBEDDING_WEIGHT) * lexical + EMBEDDING_WEIGHT * semantic[i].max(0.0)
+                }
+                None => lexical,
+            };
+            relevance + IMPORTANCE_WEIGHT * memory.importance as f32
+        })
+        .collect()
+}
+
+/// Render memory points as a prompt section
+pub fn format_memories_prompt(memories: &[RecalledMemory]) -> Option<String> {
+    if memories.is_empty() {
+        return None;
+    }
+
+    let mut prompt = String::from("# Memory Points\n");
+    prompt.push_str("The following are memory points relevant to the current request, saved by the user or by agents. Consider these information in the conversation. Use the Memory tool to search for more, or to correct memories that are outdated\n\n");
+    for recalled in memories {
+        prompt.push_str(&format_memory(recalled));
+        prompt.push('\n');
+    }
+    prompt.push('\n');
+
+    Some(prompt)
+}
+
+fn format_memory(recalled: &RecalledMemory) -> String {
+    let memory = &recalled.memory;
+    format!(
+        "## {} [{}] (Importance: {}/5, scope: {}, id: {})\n{}\n",
+        memory.title,
+        memory.memory_type.label(),
+        memory.importance,
+        recalled.scope.as_str(),
+        memory.id,
+        memory.content
+    )
+}
+
+/// Rank memory points for the query and keep the best within the limits
+///
+/// Without a query, memories are ordered by importance.
+pub async fn recall_memories(
+    candidates: Vec<(MemoryScope, AIMemory)>,
+    query: Option<&str>,
+) -> Vec<RecalledMemory> {
+    let ai_config = match get_global_config_service().await {
+        Ok(service) => service.get_config::<AIConfig>(Some("ai")).await.ok(),
+        Err(_) => None,
+    };
+    let recall_config = ai_config
+        .as_ref()
+        .map(|config| config.memory_recall.clone())
+        .unwrap_or_default();
+
+    let query = query.map(str::trim).filter(|q| !q.is_empty());
+    let memories: Vec<AIMemory> = candidates.iter().map(|(_, m)| m.clone()).collect();
+
+    let scores = match query {
+        Some(query) => {
+            let lexical = bm25_scores(&memories, query);
+            let semantic = match ai_config.as_ref() {
+                Some(config) if recall_config.use_embeddings && !memories.is_empty() => {
+                    embedding_scores(config, &memories, query).await
+                }
+                _ => None,
+            };
+            combine_scores(&memories, &lexical, semantic.as_deref())
+        }
+        None => combine_scores(&memories, &vec![0.0; memories.len()], None),
+    };
+
+    let ranked = candidates
+        .into_iter()
+        .zip(scores)
+        .map(|((scope, memory), score)| RecalledMemory {
+            scope,
+            memory,
+            score,
+        })
+        .collect();
+
+    select_within_budget(ranked, &recall_config)
+}
+
+/// Keep the highest-scoring memories within the count limit and token budget
+fn select_within_budget(
+    mut ranked: Vec<RecalledMemory>,
+    config: &MemoryRecallConfig,
+) -> Vec<RecalledMemory> {
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let mut selected = Vec::new();
+    let mut used_tokens = 0;
+    for recalled in ranked {
+        if selected.len() >= config.max_memories {
+            break;
+        }
+        let tokens = TokenCounter::estimate_tokens(&format_memory(&recalled));
+        if used_tokens + tokens > config.token_budget {
+            continue;
+        }
+        used_tokens += tokens;
+        selected.push(recalled);
+    }
+
+    debug!(
+        "Recalled {} memory points, estimated tokens: {}",
+        selected.len(),
+        used_tokens
+    );
+    selected
+}
+
+/// Cosine similarity of each memory to the query, using the embedding model
+///
+/// Returns None when no embedding model is configured or the request fails, in which
+/// case ranking falls back to BM25 alone.
+async fn embedding_scores(
+    ai_config: &AIConfig,
+    memories: &[AIMemory],
+    query: &str,
+) -> Option<Vec<f32>> {
+    let model_id = ai_config
+        .default_models
+        .embedding
+        .clone()
+        .filter(|id| !id.is_empty())
+        .or_else(|| {
+            ai_config
+                .models
+                .iter()
+                .find(|m| m.enabled && m.capabilities.contains(&ModelCapability::Embedding))
+                .map(|m| m.id.clone())
+        })?;
+
+    let factory = get_global_ai_client_factory().await.ok()?;
+    let client = match factory.get_client_by_id(&model_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Failed to get embedding model client: model_id={}, error={}", model_id, e);
+            return None;
+        }
+    };
+
+    let cache = EMBEDDING_CACHE.get_or_init(DashMap::new);
+    let cache_key = |memory: &AIMemory| format!("{}:{}:{}", model_id, memory.id, memory.updated_at);
+
+    let missing: Vec<&AIMemory> = memories
+        .iter()
+        .filter(|memory| !cache.contains_key(&cache_key(memory)))
+        .collect();
+    let mut inputs: Vec<String> = missing
+        .iter()
+        .map(|memory| {
+            format!(
+                "{}\n{}\n{}",
+                memory.title,
+                memory.tags.join(", "),
+                memory.content
+            )
+        })
+        .collect();
+    inputs.push(query.to_string());
+
+    let mut embeddings = match client.create_embeddings(&inputs).await {
+        Ok(embeddings) => embeddings,
+        Err(e) => {
+            warn!("Failed to compute memory embeddings: {}", e);
+            return None;
+        }
+    };
+    let query_embedding = embeddings.pop()?;
+    for (memory, embedding) in missing.iter().zip(embeddings) {
+        cache.insert(cache_key(memory), embedding);
+    }
+
+    Some(
+        memories
+            .iter()
+            .map(|memory| {
+                cache
+                    .get(&cache_key(memory))
+                    .map(|embedding| cosine_similarity(&embedding, &query_embedding))
+                    .unwrap_or(0.0)
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::ai_memory::MemoryType;
+
+    fn memory(title: &str, content: &str, tags: &[&str], importance: u8) -> AIMemory {
+        let mut memory = AIMemory::new(
+            title.to_string(),
+            content.to_string(),
+            MemoryType::Other,
+            importance,
+        );
+        memory.tags = tags.iter().map(|t| t.to_string()).collect();
+        memory
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("How to run the E2E tests? Use `pnpm test:e2e`."),
+            vec!["run", "e2e", "tests", "use", "pnpm", "test", "e2e"]
+        );
+        assert_eq!(tokenize("测试"), vec!["测", "试", "测试"]);
+    }
+
+    #[test]
+    fn test_ranking_and_budget() {
+        let memories = vec![
+            memory("Code style", "Prefer early returns over nested ifs", &["style"], 5),
+            memory("Database", "Migrations live in db/migrations and run with sqlx", &["postgres"], 2),
+            memory("Testing", "Run integration tests with cargo nextest", &["tests"], 3),
+        ];
+
+        let lexical = bm25_scores(&memories, "how do I add a postgres migration?");
+        assert!(lexical[1] > 0.0);
+        assert_eq!(lexical[0], 0.0);
+        assert_eq!(lexical[2], 0.0);
+
+        let scores = combine_scores(&memories, &lexical, None);
+        let ranked: Vec<RecalledMemory> = memories
+            .into_iter()
+            .zip(scores)
+            .map(|(memory, score)| RecalledMemory {
+                scope: MemoryScope::Project,
+                memory,
+                score,
+            })
+            .collect();
+
+        let config = MemoryRecallConfig {
+            max_memories: 2,
+            token_budget: 10_000,
+            use_embeddings: false,
+        };
+        let selected = select_within_budget(ranked.clone(), &config);
+        let titles: Vec<&str> = selected.iter().map(|r| r.memory.title.as_str()).collect();
+        assert_eq!(titles, vec!["Database", "Code style"]);
+
+        let config = MemoryRecallConfig {
+            token_budget: 0,
+            ..config
+        };
+        assert!(select_within_budget(ranked, &config).is_empty());
+
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+    }
+}
@@ -27,6 +27,39 @@ impl Default for MemoryType {
     }
 }
 
+/// Memory store a memory point belongs to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryScope {
+    /// User-level store, shared by all workspaces
+    User,
+    /// Project-level store in the workspace's `.bitfun` directory
+    Project,
+}
+
+impl MemoryScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemoryScope::User => "user",
+            MemoryScope::Project => "project",
+        }
+    }
+}
+
+impl MemoryType {
+    /// Label used in prompts
+    pub fn label(&self) -> &'static str {
+        match self {
+            MemoryType::TechPreference => "Technology Preference",
+            MemoryType::ProjectContext => "Project Context",
+            MemoryType::UserHabit => "User Habit",
+            MemoryType::CodePattern => "Code Pattern",
+            MemoryType::Decision => "Architecture Decision",
+            MemoryType::Other => "Others",
+        }
+    }
+}
+
 /// AI memory point
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AIMemory {
@@ -5,6 +5,7 @@
 pub mod factory;
 pub mod global;
 pub mod manager;
+pub mod project_settings;
 pub mod providers;
 pub mod service;
 pub mod tool_config_sync;
@@ -17,6 +18,7 @@ pub use global::{
     subscribe_config_updates, ConfigUpdateEvent, GlobalConfigManager,
 };
 pub use manager::{ConfigManager, ConfigManagerSettings, ConfigStatistics};
+pub use project_settings::{load_project_settings, project_settings_path, ProjectSettings};
 pub use providers::ConfigProviderRegistry;
 pub use service::{ConfigExport, ConfigHealthStatus, ConfigImportResult, ConfigService};
 pub use tool_config_sync::{sync_tool_configs, ModeSyncInfo, SyncReport};
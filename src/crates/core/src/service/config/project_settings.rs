@@ -0,0 +1,45 @@
+//! Project settings
+//!
+//! Per-project agent settings stored in `{project}/.bitfun/settings.json`.
+
+use super::types::{HooksConfig, ToolPermissionsConfig};
+use crate::infrastructure::try_get_path_manager_arc;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Contents of `.bitfun/settings.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectSettings {
+    /// Tool permission rules
+    pub permissions: ToolPermissionsConfig,
+    /// Lifecycle hooks
+    pub hooks: HooksConfig,
+}
+
+/// Path of the settings file of a workspace
+pub fn project_settings_path(workspace: &Path) -> PathBuf {
+    match try_get_path_manager_arc() {
+        Ok(path_manager) => path_manager.project_settings_file(workspace),
+        Err(_) => workspace.join(".bitfun").join("settings.json"),
+    }
+}
+
+/// Load the settings of a workspace; `None` if the file is missing or invalid
+pub fn load_project_settings(workspace: &Path) -> Option<ProjectSettings> {
+    let settings_file = project_settings_path(workspace);
+    let content = std::fs::read_to_string(&settings_file).ok()?;
+
+    match serde_json::from_str::<ProjectSettings>(&content) {
+        Ok(settings) => Some(settings),
+        Err(e) => {
+            warn!(
+                "Failed to parse project settings, ignoring: path={}, error={}",
+                settings_file.display(),
+                e
+            );
+            None
+        }
+    }
+}
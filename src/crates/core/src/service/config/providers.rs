@@ -355,6 +355,10 @@ impl ConfigProvider for TerminalConfigProvider {
             if terminal_config.scrollback > 100000 {
                 warnings.push("Large scrollback buffer may impact performance".to_string());
             }
+
+            if terminal_config.sandbox.enabled && !cfg!(target_os = "linux") {
+                warnings.push("Command sandbox is only supported on Linux".to_string());
+            }
         } else {
             return Err(BitFunError::validation(
                 "Invalid terminal config format".to_string(),
@@ -230,6 +230,23 @@ pub struct TerminalConfig {
     pub cursor_style: String,
     pub scrollback: u32,
     pub theme: TerminalThemeConfig,
+    /// Sandbox for commands run by agents.
+    pub sandbox: TerminalSandboxConfig,
+}
+
+/// Sandbox for agent commands (Linux only, requires bubblewrap).
+///
+/// Commands can write only to the workspace, the BitFun temp directories and
+/// `writable_paths`; credential directories such as `~/.ssh` are hidden.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalSandboxConfig {
+    pub enabled: bool,
+    pub allow_network: bool,
+    /// Extra writable paths, e.g. package manager caches.
+    pub writable_paths: Vec<String>,
+    /// Extra paths hidden in addition to the default credential locations.
+    pub hidden_paths: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -336,6 +353,8 @@ pub struct DefaultModelsConfig {
     pub image_generation: Option<String>,
     /// Speech recognition model.
     pub speech_recognition: Option<String>,
+    /// Embedding model (memory recall).
+    pub embedding: Option<String>,
 }
 
 impl Default for DefaultModelsConfig {
@@ -347,6 +366,7 @@ impl Default for DefaultModelsConfig {
             image_understanding: None,
             image_generation: None,
             speech_recognition: None,
+            embedding: None,
         }
     }
 }
@@ -362,6 +382,12 @@ pub struct AIConfig {
     /// agent_type -> model_id
     pub agent_models: HashMap<String, String>,
 
+    /// Ordered fallback models per agent, tried in turn when the model from
+    /// `agent_models` is unavailable (rate limited, overloaded, down).
+    /// agent_type -> [model_id]
+    #[serde(default)]
+    pub agent_fallback_models: HashMap<String, Vec<String>>,
+
     /// Model mapping for functional agents (e.g. startchat-func-agent, git-func-agent).
     /// func_agent_name -> model_id
     #[serde(default)]
@@ -384,6 +410,10 @@ pub struct AIConfig {
     /// Global proxy configuration.
     pub proxy: ProxyConfig,
 
+    /// Retry policy for model requests.
+    #[serde(default)]
+    pub retry: RetryConfig,
+
     /// Tool execution timeout in seconds; `None` means wait indefinitely.
     #[serde(default = "default_tool_execution_timeout")]
     pub tool_execution_timeout_secs: Option<u64>,
@@ -404,6 +434,120 @@ pub struct AIConfig {
     /// Used to detect added and removed tools.
     #[serde(default)]
     pub known_tools: Vec<String>,
+
+    /// Declarative tool permission rules (allow/deny/ask).
+    #[serde(default)]
+    pub tool_permissions: ToolPermissionsConfig,
+
+    /// Lifecycle hooks (shell commands run at defined points of a dialog turn).
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Approved project hooks: workspace path -> hash of the approved `hooks`
+    /// of its `.bitfun/settings.json`. Project hooks only run while the hash matches.
+    #[serde(default)]
+    pub approved_project_hooks: HashMap<String, String>,
+
+    /// Selection of memories injected into the system prompt.
+    #[serde(default)]
+    pub memory_recall: MemoryRecallConfig,
+}
+
+/// Memory recall configuration.
+///
+/// Memories are ranked by relevance to the current user message (BM25 over title, content
+/// and tags, combined with embedding similarity when an embedding model is available).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryRecallConfig {
+    /// Maximum number of memories injected per turn.
+    pub max_memories: usize,
+    /// Token budget for injected memories.
+    pub token_budget: usize,
+    /// Use the embedding model (`default_models.embedding`, or the first enabled model
+    /// tagged `embedding`) to rank memories.
+    pub use_embeddings: bool,
+}
+
+impl Default for MemoryRecallConfig {
+    fn default() -> Self {
+        Self {
+            max_memories: 10,
+            token_budget: 2000,
+            use_embeddings: true,
+        }
+    }
+}
+
+/// Tool permission rules.
+///
+/// Each rule is `Tool` or `Tool(specifier)`, e.g. `Bash(git status*)`, `Write(src/**)`
+/// or `mcp__github__*`. Deny rules take precedence over ask rules, which take precedence
+/// over allow rules.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolPermissionRules {
+    /// Run without confirmation.
+    pub allow: Vec<String>,
+    /// Refuse to run; the reason is returned to the model as a tool error.
+    pub deny: Vec<String>,
+    /// Always ask for confirmation, even when confirmation is skipped globally.
+    pub ask: Vec<String>,
+}
+
+/// Tool permission configuration (user config `ai.tool_permissions`, or `permissions`
+/// in the project's `.bitfun/settings.json`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolPermissionsConfig {
+    /// Rules applied in every mode.
+    #[serde(flatten)]
+    pub rules: ToolPermissionRules,
+
+    /// Additional rules per mode (mode_id -> rules).
+    pub modes: HashMap<String, ToolPermissionRules>,
+}
+
+/// Lifecycle hook configuration (user config `ai.hooks`, or `hooks` in the
+/// project's `.bitfun/settings.json`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Before a tool runs; may block the call or rewrite its input.
+    #[serde(rename = "PreToolUse")]
+    pub pre_tool_use: Vec<HookCommandConfig>,
+    /// After a tool completed successfully.
+    #[serde(rename = "PostToolUse")]
+    pub post_tool_use: Vec<HookCommandConfig>,
+    /// When the model finishes a dialog turn; blocking makes the model continue.
+    #[serde(rename = "TurnEnd")]
+    pub turn_end: Vec<HookCommandConfig>,
+    /// Before the first dialog turn of a session.
+    #[serde(rename = "SessionStart")]
+    pub session_start: Vec<HookCommandConfig>,
+}
+
+impl HooksConfig {
+    /// Whether no hook command is configured.
+    pub fn is_empty(&self) -> bool {
+        self.pre_tool_use.is_empty()
+            && self.post_tool_use.is_empty()
+            && self.turn_end.is_empty()
+            && self.session_start.is_empty()
+    }
+}
+
+/// Single hook command.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookCommandConfig {
+    /// Tool name patterns separated by `|` (e.g. `Write|Edit`, `mcp_github_*`).
+    /// Only used by tool hooks; empty matches every tool.
+    pub matcher: Option<String>,
+    /// Shell command; receives the hook input as JSON on stdin.
+    pub command: String,
+    /// Kill the command after this many seconds (default 60).
+    pub timeout_secs: Option<u64>,
 }
 
 /// Mode configuration (tool configuration per mode).
@@ -742,6 +886,11 @@ pub struct AIModelConfig {
     /// Custom request body (JSON string, used to override default request body fields).
     #[serde(default)]
     pub custom_request_body: Option<String>,
+
+    /// Tokenizer used for context accounting: "o200k", "cl100k", "anthropic" or "estimate".
+    /// Detected from the provider and model name when unset.
+    #[serde(default)]
+    pub tokenizer: Option<String>,
 }
 
 /// Proxy configuration.
@@ -761,6 +910,26 @@ pub struct ProxyConfig {
     pub password: Option<String>,
 }
 
+/// Retry policy for model requests.
+///
+/// Applies to rate limits (429), server errors (5xx), overloaded responses and
+/// streams interrupted before any output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Retries after the first attempt; 0 disables retrying.
+    pub max_retries: u32,
+
+    /// Delay before the first retry, doubled on each further retry.
+    pub initial_delay_ms: u64,
+
+    /// Upper bound for a single delay, also applied to `Retry-After`.
+    pub max_delay_ms: u64,
+
+    /// Randomize delays so concurrent sessions do not retry in lockstep.
+    pub jitter: bool,
+}
+
 /// Configuration provider interface.
 #[async_trait]
 pub trait ConfigProvider: Send + Sync {
@@ -1014,6 +1183,18 @@ impl Default for TerminalConfig {
             cursor_style: "block".to_string(),
             scrollback: 1000,
             theme: TerminalThemeConfig::default(),
+            sandbox: TerminalSandboxConfig::default(),
+        }
+    }
+}
+
+impl Default for TerminalSandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allow_network: false,
+            writable_paths: Vec::new(),
+            hidden_paths: Vec::new(),
         }
     }
 }
@@ -1075,16 +1256,22 @@ impl Default for AIConfig {
         Self {
             models: vec![],
             agent_models: std::collections::HashMap::new(),
+            agent_fallback_models: std::collections::HashMap::new(),
             func_agent_models: std::collections::HashMap::new(),
             default_models: DefaultModelsConfig::default(),
             mode_configs: std::collections::HashMap::new(),
             subagent_configs: std::collections::HashMap::new(),
             proxy: ProxyConfig::default(),
+            retry: RetryConfig::default(),
             tool_execution_timeout_secs: default_tool_execution_timeout(),
             tool_confirmation_timeout_secs: default_tool_confirmation_timeout(),
             skip_tool_confirmation: false,
             debug_mode_config: DebugModeConfig::default(),
             known_tools: Vec::new(),
+            tool_permissions: ToolPermissionsConfig::default(),
+            hooks: HooksConfig::default(),
+            approved_project_hooks: std::collections::HashMap::new(),
+            memory_recall: MemoryRecallConfig::default(),
         }
     }
 }
@@ -1100,6 +1287,17 @@ impl Default for ProxyConfig {
     }
 }
 
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay_ms: 1000,
+            max_delay_ms: 30_000,
+            jitter: true,
+        }
+    }
+}
+
 impl Default for AIModelConfig {
     fn default() -> Self {
         Self {
@@ -1126,6 +1324,7 @@ impl Default for AIModelConfig {
             custom_headers_mode: None,
             skip_ssl_verify: false,
             custom_request_body: None,
+            tokenizer: None,
         }
     }
 }
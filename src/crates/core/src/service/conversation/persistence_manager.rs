@@ -330,6 +330,54 @@ impl ConversationPersistenceManager {
     }
 
 
+    /// Copies a session's metadata and turns up to `turn_index` (inclusive) into a new session.
+    ///
+    /// Returns `false` if the source session has no conversation history.
+    pub async fn fork_session(
+        &self,
+        source_session_id: &str,
+        target_session_id: &str,
+        target_session_name: &str,
+        turn_index: usize,
+    ) -> BitFunResult<bool> {
+        let Some(source) = self.load_session_metadata(source_session_id).await? else {
+            return Ok(false);
+        };
+
+        let mut metadata = SessionMetadata::new(
+            target_session_id.to_string(),
+            target_session_name.to_string(),
+            source.agent_type.clone(),
+            source.model_name.clone(),
+        );
+        metadata.snapshot_session_id = source
+            .snapshot_session_id
+            .as_ref()
+            .map(|_| target_session_id.to_string());
+        metadata.tags = source.tags.clone();
+        metadata.todos = source.todos.clone();
+
+        let mut custom_metadata = source
+            .custom_metadata
+            .clone()
+            .filter(|value| value.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+        custom_metadata["parentSessionId"] = serde_json::json!(source_session_id);
+        custom_metadata["forkTurnIndex"] = serde_json::json!(turn_index);
+        metadata.custom_metadata = Some(custom_metadata);
+
+        // Turn saves update the counters of the new metadata
+        self.save_session_metadata(&metadata).await?;
+        for index in 0..=turn_index.min(source.turn_count.saturating_sub(1)) {
+            if let Some(mut turn) = self.load_dialog_turn(source_session_id, index).await? {
+                turn.session_id = target_session_id.to_string();
+                self.save_dialog_turn(&turn).await?;
+            }
+        }
+
+        Ok(true)
+    }
+
     /// Updates the session's last active time.
     pub async fn touch_session(&self, session_id: &str) -> BitFunResult<()> {
         if let Some(mut metadata) = self.load_session_metadata(session_id).await? {
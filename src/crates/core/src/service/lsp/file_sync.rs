@@ -416,7 +416,7 @@ impl LspFileSync {
 }
 
 /// Detects a file language.
-fn detect_language(path: &Path) -> String {
+pub(crate) fn detect_language(path: &Path) -> String {
     if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
         match ext {
             "rs" => "rust",
@@ -636,6 +636,24 @@ impl LspManager {
             .await
     }
 
+    /// Searches workspace symbols (Workspace Symbols).
+    /// Used for project-wide symbol lookup by name.
+    pub async fn workspace_symbols(
+        &self,
+        language: &str,
+        query: &str,
+    ) -> Result<serde_json::Value> {
+        let process = self.get_process(language).await?;
+
+        let params = serde_json::json!({
+            "query": query
+        });
+
+        process
+            .send_request("workspace/symbol", Some(params))
+            .await
+    }
+
     /// Gets semantic tokens (Semantic Tokens).
     /// Used for semantic-level syntax highlighting.
     pub async fn get_semantic_tokens(
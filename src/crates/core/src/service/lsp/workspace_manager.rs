@@ -471,8 +471,7 @@ impl WorkspaceLspManager {
 
     /// Ensures the server is running (prevents duplicate starts).
     /// Returns the actual server language key in use (may differ from the requested one, e.g. c -> cpp).
-    #[allow(dead_code)]
-    async fn ensure_server_running(&self, language: &str) -> Result<String> {
+    pub async fn ensure_server_running(&self, language: &str) -> Result<String> {
         let status = {
             let states = self.server_states.read().await;
 
@@ -1366,6 +1365,20 @@ impl WorkspaceLspManager {
         lsp.get_document_symbols(&server_language, uri).await
     }
 
+    /// Searches symbols across the workspace.
+    pub async fn workspace_symbols(
+        &self,
+        language: &str,
+        query: &str,
+    ) -> Result<serde_json::Value> {
+        let server_language = self
+            .get_running_server_for_language(language)
+            .await
+            .ok_or_else(|| anyhow!("LSP server not running for language: {}", language))?;
+        let lsp = self.lsp_manager.read().await;
+        lsp.workspace_symbols(&server_language, query).await
+    }
+
     /// Gets diagnostics for a file (used by the `ReadLints` tool).
     /// Returns cached diagnostics without triggering new LSP requests.
     pub async fn get_diagnostics(&self, uri: &str) -> Result<Vec<serde_json::Value>> {
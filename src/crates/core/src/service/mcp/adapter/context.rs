@@ -178,18 +178,26 @@ impl MCPContextProvider {
             BitFunError::NotFound(format!("MCP server connection not found: {}", server_id))
         })?;
 
-        let result = connection.list_resources(None).await?;
+        let resources = manager.list_resources(server_id).await?;
 
         let relevant = ResourceAdapter::filter_and_rank(
-            result.resources,
-            query,
-            0.1, // Lower threshold; we do additional filtering later
+            resources, query, 0.1, // Lower threshold; we do additional filtering later
             50,  // Up to 50 per server
         );
 
         let mut resources_with_content = Vec::new();
 
         for (resource, _score) in relevant {
+            if let Some(contents) = manager
+                .get_subscribed_resource(server_id, &resource.uri)
+                .await
+            {
+                if let Some(content) = contents.first() {
+                    resources_with_content.push((resource, content.clone()));
+                }
+                continue;
+            }
+
             match connection.read_resource(&resource.uri).await {
                 Ok(read_result) => {
                     if let Some(content) = read_result.contents.first() {
@@ -246,8 +254,8 @@ impl MCPContextProvider {
 
         for server_id in server_ids {
             if let Some(connection) = self.server_manager.get_connection(&server_id).await {
-                if let Ok(result) = connection.list_prompts(None).await {
-                    for prompt in result.prompts {
+                if let Ok(prompts) = self.server_manager.list_prompts(&server_id).await {
+                    for prompt in prompts {
                         if prompt_names.contains(&prompt.name) {
                             if let Ok(content) = connection
                                 .get_prompt(&prompt.name, Some(arguments.clone()))
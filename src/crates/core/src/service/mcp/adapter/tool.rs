@@ -2,16 +2,19 @@
 //!
 //! Wraps MCP tools as implementations of BitFun's `Tool` trait.
 
+use crate::agentic::coordination::get_global_coordinator;
+use crate::agentic::events::ToolEventData;
 use crate::agentic::tools::framework::{
     Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
 };
-use crate::service::mcp::protocol::{MCPTool, MCPToolResult};
-use crate::service::mcp::server::connection::MCPConnection;
+use crate::service::mcp::protocol::{MCPTool, MCPToolResult, ProgressParams};
+use crate::service::mcp::server::connection::{MCPCallOptions, MCPConnection};
 use crate::util::errors::BitFunResult;
 use async_trait::async_trait;
 use log::{debug, error, info, warn};
 use serde_json::Value;
 use std::sync::Arc;
+use tokio::sync::mpsc;
 
 /// MCP tool wrapper that adapts an MCP tool to BitFun's `Tool`.
 pub struct MCPToolWrapper {
@@ -162,7 +165,7 @@ impl Tool for MCPToolWrapper {
     async fn call_impl(
         &self,
         input: &Value,
-        _context: &ToolUseContext,
+        context: &ToolUseContext,
     ) -> BitFunResult<Vec<ToolResult>> {
         info!(
             "Calling MCP tool: {} from server: {}",
@@ -175,10 +178,22 @@ impl Tool for MCPToolWrapper {
 
         let start = std::time::Instant::now();
 
+        let (progress_tx, progress_rx) = mpsc::unbounded_channel();
+        let forwarder = self.forward_progress(context, progress_rx);
+        let options = MCPCallOptions {
+            timeout: None,
+            progress: Some(progress_tx),
+            cancellation_token: context.cancellation_token.clone(),
+            session_id: context.session_id.clone(),
+        };
         let result = self
             .connection
-            .call_tool(&self.mcp_tool.name, Some(input.clone()))
-            .await?;
+            .call_tool_with_options(&self.mcp_tool.name, Some(input.clone()), options)
+            .await;
+        if let Some(forwarder) = forwarder {
+            forwarder.abort();
+        }
+        let result = result?;
 
         let elapsed = start.elapsed();
         debug!("MCP tool returned after {:?}", elapsed);
@@ -193,6 +208,60 @@ impl Tool for MCPToolWrapper {
     }
 }
 
+impl MCPToolWrapper {
+    /// Relays progress reported by the server as tool progress events.
+    fn forward_progress(
+        &self,
+        context: &ToolUseContext,
+        mut progress_rx: mpsc::UnboundedReceiver<ProgressParams>,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        let coordinator = get_global_coordinator()?;
+        let tool_id = context.tool_call_id.clone()?;
+        let session_id = context.session_id.clone()?;
+        let turn_id = context.dialog_turn_id.clone()?;
+        let subagent_parent_info = context.subagent_parent_info.clone();
+        let tool_name = self.full_name.clone();
+
+        Some(tokio::spawn(async move {
+            while let Some(progress) = progress_rx.recv().await {
+                let tool_event = ToolEventData::Progress {
+                    tool_id: tool_id.clone(),
+                    tool_name: tool_name.clone(),
+                    message: progress_message(&progress),
+                    percentage: progress_percentage(&progress),
+                };
+                coordinator
+                    .emit_tool_event(
+                        session_id.clone(),
+                        turn_id.clone(),
+                        tool_event,
+                        subagent_parent_info.clone(),
+                    )
+                    .await;
+            }
+        }))
+    }
+}
+
+/// Progress message, falling back to the raw counts
+fn progress_message(progress: &ProgressParams) -> String {
+    match (&progress.message, progress.total) {
+        (Some(message), _) => message.clone(),
+        (None, Some(total)) => format!("{}/{}", progress.progress, total),
+        (None, None) => format!("{}", progress.progress),
+    }
+}
+
+/// Percentage (0-100) when the total is known, otherwise 0
+fn progress_percentage(progress: &ProgressParams) -> f32 {
+    match progress.total {
+        Some(total) if total > 0.0 => {
+            ((progress.progress / total) * 100.0).clamp(0.0, 100.0) as f32
+        }
+        _ => 0.0,
+    }
+}
+
 /// MCP tool adapter that manages multiple MCP tool wrappers.
 pub struct MCPToolAdapter {
     tools: Vec<Arc<dyn Tool>>,
@@ -0,0 +1,148 @@
+//! Loopback redirect listener
+//!
+//! Receives the authorization response on `http://127.0.0.1:{port}/callback`.
+
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::debug;
+use reqwest::Url;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const CALLBACK_PATH: &str = "/callback";
+const MAX_REQUEST_HEAD: usize = 16 * 1024;
+
+const SUCCESS_PAGE: &str = "<!DOCTYPE html><html><body><h3>Authorization complete</h3>\
+<p>You can close this window and return to BitFun.</p></body></html>";
+const FAILURE_PAGE: &str = "<!DOCTYPE html><html><body><h3>Authorization failed</h3>\
+<p>Return to BitFun for details.</p></body></html>";
+
+/// Listener for the OAuth redirect.
+pub struct LoopbackListener {
+    listener: TcpListener,
+    port: u16,
+}
+
+impl LoopbackListener {
+    /// Binds to the given port on 127.0.0.1; 0 picks a free port.
+    pub async fn bind(port: u16) -> BitFunResult<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).await.map_err(|e| {
+            BitFunError::MCPError(format!(
+                "Failed to listen for OAuth redirect on port {}: {}",
+                port, e
+            ))
+        })?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| BitFunError::MCPError(format!("Failed to read listener address: {}", e)))?
+            .port();
+        Ok(Self { listener, port })
+    }
+
+    /// Port the listener is bound to.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Redirect URI to register with the authorization server.
+    pub fn redirect_uri(&self) -> String {
+        format!("http://127.0.0.1:{}{}", self.port, CALLBACK_PATH)
+    }
+
+    /// Waits for the redirect and returns the authorization code.
+    ///
+    /// Requests to other paths (e.g. a browser asking for a favicon) are ignored.
+    pub async fn wait_for_code(
+        self,
+        expected_state: &str,
+        timeout: Duration,
+    ) -> BitFunResult<String> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let (mut stream, _) = self.listener.accept().await.map_err(|e| {
+                    BitFunError::MCPError(format!("Failed to accept OAuth redirect: {}", e))
+                })?;
+
+                let Some(target) = read_request_target(&mut stream).await else {
+                    continue;
+                };
+                let Ok(url) = Url::parse(&format!("http://127.0.0.1{}", target)) else {
+                    respond(&mut stream, "400 Bad Request", FAILURE_PAGE).await;
+                    continue;
+                };
+                if url.path() != CALLBACK_PATH {
+                    respond(&mut stream, "404 Not Found", "").await;
+                    continue;
+                }
+
+                let param = |name: &str| {
+                    url.query_pairs()
+                        .find(|(key, _)| key == name)
+                        .map(|(_, value)| value.into_owned())
+                };
+                debug!("Received OAuth redirect");
+
+                let result = if let Some(error) = param("error") {
+                    Err(BitFunError::MCPError(format!(
+                        "Authorization denied: {}{}",
+                        error,
+                        param("error_description")
+                            .map(|d| format!(" ({})", d))
+                            .unwrap_or_default()
+                    )))
+                } else if param("state").as_deref() != Some(expected_state) {
+                    Err(BitFunError::MCPError(
+                        "OAuth redirect state mismatch".to_string(),
+                    ))
+                } else {
+                    param("code").ok_or_else(|| {
+                        BitFunError::MCPError("OAuth redirect is missing the code".to_string())
+                    })
+                };
+
+                let page = if result.is_ok() {
+                    SUCCESS_PAGE
+                } else {
+                    FAILURE_PAGE
+                };
+                respond(&mut stream, "200 OK", page).await;
+                return result;
+            }
+        })
+        .await
+        .map_err(|_| {
+            BitFunError::Timeout("Timed out waiting for OAuth authorization".to_string())
+        })?
+    }
+}
+
+/// Reads the request head and returns the request target of the request line
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 || buffer.len() > MAX_REQUEST_HEAD {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&buffer);
+    let mut parts = head.lines().next()?.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => Some(target.to_string()),
+        _ => None,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
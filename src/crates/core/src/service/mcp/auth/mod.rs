@@ -0,0 +1,13 @@
+//! MCP authorization
+//!
+//! OAuth 2.1 support for remote MCP servers, following the MCP authorization spec:
+//! protected-resource metadata discovery, dynamic client registration, the PKCE
+//! authorization-code flow with a loopback redirect, and token refresh.
+
+pub mod callback;
+pub mod oauth;
+pub mod store;
+
+pub use callback::LoopbackListener;
+pub use oauth::{BrowserOpener, MCPOAuthClient, OAuthSettings};
+pub use store::{OAuthClientRegistration, OAuthCredentialStore, OAuthTokens, StoredCredentials};
@@ -0,0 +1,844 @@
+//! OAuth 2.1 client for remote MCP servers
+//!
+//! Authorization runs when the server answers 401:
+//! 1. Discover the protected-resource metadata (RFC 9728) to find the authorization server,
+//!    falling back to the MCP server's origin
+//! 2. Fetch the authorization server metadata (RFC 8414 / OpenID discovery)
+//! 3. Register a client dynamically (RFC 7591) unless a client ID is configured
+//! 4. Run the authorization-code flow with PKCE (S256) and a loopback redirect
+//! 5. Store the tokens; expired tokens are refreshed with the refresh token
+
+use super::callback::LoopbackListener;
+use super::store::{OAuthClientRegistration, OAuthCredentialStore, OAuthTokens, StoredCredentials};
+use crate::infrastructure::events::event_system::{get_global_event_system, BackendEvent};
+use crate::util::errors::{BitFunError, BitFunResult};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use log::{debug, info, warn};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// How long to wait for the user to finish authorizing in the browser
+const AUTHORIZATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Event emitted with the authorization URL, so the UI can show it
+pub const MCP_OAUTH_AUTHORIZATION_EVENT: &str = "mcp-oauth-authorization";
+
+/// Opens the authorization URL for the user.
+pub type BrowserOpener = Arc<dyn Fn(&str) -> BitFunResult<()> + Send + Sync>;
+
+/// OAuth settings of a server (`settings.oauth` in the server config).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OAuthSettings {
+    /// Authorize with OAuth when the server requires it.
+    pub enabled: bool,
+    /// Pre-registered client; dynamic registration is used when missing.
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    /// Requested scopes; defaults to the scopes the server advertises.
+    pub scopes: Vec<String>,
+    /// Fixed loopback port, for clients registered with a specific redirect URI.
+    pub redirect_port: Option<u16>,
+}
+
+impl Default for OAuthSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            client_id: None,
+            client_secret: None,
+            scopes: Vec::new(),
+            redirect_port: None,
+        }
+    }
+}
+
+/// Protected-resource metadata (RFC 9728)
+#[derive(Debug, Clone, Deserialize)]
+struct ProtectedResourceMetadata {
+    #[serde(default)]
+    resource: Option<String>,
+    #[serde(default)]
+    authorization_servers: Vec<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+}
+
+/// Authorization server metadata (RFC 8414)
+#[derive(Debug, Clone, Deserialize)]
+struct AuthorizationServerMetadata {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    #[serde(default)]
+    registration_endpoint: Option<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+    #[serde(default)]
+    code_challenge_methods_supported: Option<Vec<String>>,
+}
+
+/// Token endpoint response
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+impl TokenResponse {
+    fn into_tokens(self, previous_refresh_token: Option<String>) -> OAuthTokens {
+        OAuthTokens {
+            access_token: self.access_token,
+            refresh_token: self.refresh_token.or(previous_refresh_token),
+            expires_at: self
+                .expires_in
+                .map(|secs| chrono::Utc::now().timestamp() + secs),
+            scope: self.scope,
+        }
+    }
+}
+
+/// OAuth client for one MCP server.
+pub struct MCPOAuthClient {
+    server_id: String,
+    server_url: String,
+    settings: OAuthSettings,
+    store: OAuthCredentialStore,
+    http: Client,
+    opener: BrowserOpener,
+    /// Loaded lazily; the lock also serializes refreshes and authorization flows
+    credentials: Mutex<Option<StoredCredentials>>,
+}
+
+impl MCPOAuthClient {
+    /// Creates a client that stores its credentials in `store`.
+    pub fn new(
+        server_id: String,
+        server_url: String,
+        settings: OAuthSettings,
+        store: OAuthCredentialStore,
+    ) -> Self {
+        let http = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .use_rustls_tls()
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            server_id,
+            server_url,
+            settings,
+            store,
+            http,
+            opener: Arc::new(open_in_browser),
+            credentials: Mutex::new(None),
+        }
+    }
+
+    /// Replaces how the authorization URL is opened (the system browser by default).
+    pub fn with_browser_opener(mut self, opener: BrowserOpener) -> Self {
+        self.opener = opener;
+        self
+    }
+
+    /// Returns a usable access token, refreshing an expired one if possible.
+    ///
+    /// Never starts an interactive authorization.
+    pub async fn access_token(&self) -> Option<String> {
+        let mut guard = self.credentials.lock().await;
+        let credentials = self.load(&mut guard).await;
+        let tokens = credentials.tokens.clone()?;
+        if !tokens.is_expired() {
+            return Some(tokens.access_token);
+        }
+
+        match self.refresh(credentials).await {
+            Ok(token) => Some(token),
+            Err(e) => {
+                warn!(
+                    "Failed to refresh MCP OAuth token: server_id={} error={}",
+                    self.server_id, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Handles a 401 response and returns a new access token.
+    ///
+    /// Uses a token obtained concurrently by another request if there is one, otherwise
+    /// refreshes the token, and falls back to a full authorization.
+    pub async fn handle_unauthorized(
+        &self,
+        www_authenticate: Option<&str>,
+        rejected_token: Option<&str>,
+    ) -> BitFunResult<String> {
+        let mut guard = self.credentials.lock().await;
+        let credentials = self.load(&mut guard).await;
+
+        if let Some(tokens) = credentials.tokens.clone() {
+            if Some(tokens.access_token.as_str()) != rejected_token && !tokens.is_expired() {
+                return Ok(tokens.access_token);
+            }
+            if tokens.refresh_token.is_some() {
+                match self.refresh(credentials).await {
+                    Ok(token) => return Ok(token),
+                    Err(e) => warn!(
+                        "MCP OAuth refresh failed, authorizing again: server_id={} error={}",
+                        self.server_id, e
+                    ),
+                }
+            }
+        }
+
+        let metadata_url = www_authenticate.and_then(parse_resource_metadata_url);
+        self.authorize(credentials, metadata_url).await
+    }
+
+    /// Deletes the stored credentials.
+    pub async fn logout(&self) -> BitFunResult<()> {
+        *self.credentials.lock().await = Some(StoredCredentials::default());
+        self.store.remove(&self.server_id).await
+    }
+
+    /// Loads the stored credentials on first use; credentials of another URL are ignored.
+    async fn load<'a>(
+        &self,
+        guard: &'a mut Option<StoredCredentials>,
+    ) -> &'a mut StoredCredentials {
+        if guard.is_none() {
+            let stored = self
+                .store
+                .load(&self.server_id)
+                .await
+                .filter(|c| c.server_url == self.server_url)
+                .unwrap_or_else(|| StoredCredentials {
+                    server_url: self.server_url.clone(),
+                    ..Default::default()
+                });
+            *guard = Some(stored);
+        }
+        guard.get_or_insert_with(StoredCredentials::default)
+    }
+
+    async fn save(&self, credentials: &StoredCredentials) {
+        if let Err(e) = self.store.save(&self.server_id, credentials).await {
+            warn!(
+                "Failed to save MCP OAuth credentials: server_id={} error={}",
+                self.server_id, e
+            );
+        }
+    }
+
+    async fn refresh(&self, credentials: &mut StoredCredentials) -> BitFunResult<String> {
+        let refresh_token = credentials
+            .tokens
+            .as_ref()
+            .and_then(|t| t.refresh_token.clone())
+            .ok_or_else(|| BitFunError::MCPError("No refresh token".to_string()))?;
+        let token_endpoint = credentials
+            .token_endpoint
+            .clone()
+            .ok_or_else(|| BitFunError::MCPError("Token endpoint unknown".to_string()))?;
+        let client = credentials
+            .client
+            .clone()
+            .ok_or_else(|| BitFunError::MCPError("OAuth client unknown".to_string()))?;
+
+        debug!("Refreshing MCP OAuth token: server_id={}", self.server_id);
+        let mut form = vec![
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token.clone()),
+            ("resource", self.server_url.clone()),
+        ];
+        push_client_auth(&mut form, &client);
+        let response = self.request_token(&token_endpoint, &form).await?;
+
+        let tokens = response.into_tokens(Some(refresh_token));
+        let access_token = tokens.access_token.clone();
+        credentials.tokens = Some(tokens);
+        self.save(credentials).await;
+        info!("Refreshed MCP OAuth token: server_id={}", self.server_id);
+        Ok(access_token)
+    }
+
+    async fn authorize(
+        &self,
+        credentials: &mut StoredCredentials,
+        metadata_url: Option<String>,
+    ) -> BitFunResult<String> {
+        if !self.settings.enabled {
+            return Err(BitFunError::MCPError(format!(
+                "MCP server '{}' requires authorization, but OAuth is disabled",
+                self.server_id
+            )));
+        }
+        info!(
+            "Starting MCP OAuth authorization: server_id={}",
+            self.server_id
+        );
+
+        let resource_metadata = self.discover_resource_metadata(metadata_url).await;
+        let authorization_server = resource_metadata
+            .as_ref()
+            .and_then(|m| m.authorization_servers.first().cloned())
+            .unwrap_or_else(|| origin(&self.server_url));
+        let resource = resource_metadata
+            .as_ref()
+            .and_then(|m| m.resource.clone())
+            .unwrap_or_else(|| self.server_url.clone());
+        let metadata = self
+            .discover_authorization_server(&authorization_server)
+            .await?;
+
+        if let Some(methods) = &metadata.code_challenge_methods_supported {
+            if !methods.iter().any(|m| m == "S256") {
+                return Err(BitFunError::MCPError(
+                    "Authorization server does not support PKCE (S256)".to_string(),
+                ));
+            }
+        }
+
+        // Reuse the port of a stored registration so its redirect URI stays valid
+        let stored_client = credentials
+            .client
+            .clone()
+            .filter(|_| credentials.authorization_server.as_deref() == Some(&authorization_server));
+        let port = self
+            .settings
+            .redirect_port
+            .or_else(|| {
+                stored_client
+                    .as_ref()
+                    .and_then(|c| Url::parse(&c.redirect_uri).ok())
+                    .and_then(|u| u.port())
+            })
+            .unwrap_or(0);
+        let listener = match LoopbackListener::bind(port).await {
+            Ok(listener) => listener,
+            Err(e) if port != 0 && self.settings.redirect_port.is_none() => {
+                debug!("Stored redirect port unavailable, using a new one: {}", e);
+                LoopbackListener::bind(0).await?
+            }
+            Err(e) => return Err(e),
+        };
+        let redirect_uri = listener.redirect_uri();
+
+        let client = match (&self.settings.client_id, stored_client) {
+            (Some(client_id), _) => OAuthClientRegistration {
+                client_id: client_id.clone(),
+                client_secret: self.settings.client_secret.clone(),
+                redirect_uri: redirect_uri.clone(),
+            },
+            (None, Some(client)) if client.redirect_uri == redirect_uri => client,
+            (None, _) => self.register_client(&metadata, &redirect_uri).await?,
+        };
+        credentials.authorization_server = Some(authorization_server);
+        credentials.token_endpoint = Some(metadata.token_endpoint.clone());
+        credentials.client = Some(client.clone());
+
+        let verifier = random_token();
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        let state = random_token();
+        let scopes = if !self.settings.scopes.is_empty() {
+            self.settings.scopes.clone()
+        } else if let Some(m) = resource_metadata.filter(|m| !m.scopes_supported.is_empty()) {
+            m.scopes_supported
+        } else {
+            metadata.scopes_supported.clone()
+        };
+
+        let mut url = Url::parse(&metadata.authorization_endpoint)
+            .map_err(|e| BitFunError::MCPError(format!("Invalid authorization endpoint: {}", e)))?;
+        check_browser_url(&url)?;
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("response_type", "code")
+                .append_pair("client_id", &client.client_id)
+                .append_pair("redirect_uri", &redirect_uri)
+                .append_pair("code_challenge", &challenge)
+                .append_pair("code_challenge_method", "S256")
+                .append_pair("state", &state)
+                .append_pair("resource", &resource);
+            if !scopes.is_empty() {
+                query.append_pair("scope", &scopes.join(" "));
+            }
+        }
+
+        let event = BackendEvent::Custom {
+            event_name: MCP_OAUTH_AUTHORIZATION_EVENT.to_string(),
+            payload: json!({ "serverId": self.server_id, "url": url.as_str() }),
+        };
+        let _ = get_global_event_system().emit(event).await;
+        if let Err(e) = (self.opener)(url.as_str()) {
+            warn!(
+                "Failed to open browser, open this URL to authorize: {} ({})",
+                url, e
+            );
+        }
+
+        let code = listener
+            .wait_for_code(&state, AUTHORIZATION_TIMEOUT)
+            .await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code".to_string()),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+            ("code_verifier", verifier),
+            ("resource", resource),
+        ];
+        push_client_auth(&mut form, &client);
+        let response = self.request_token(&metadata.token_endpoint, &form).await?;
+
+        let tokens = response.into_tokens(None);
+        let access_token = tokens.access_token.clone();
+        credentials.tokens = Some(tokens);
+        self.save(credentials).await;
+        info!(
+            "MCP OAuth authorization completed: server_id={}",
+            self.server_id
+        );
+        Ok(access_token)
+    }
+
+    async fn discover_resource_metadata(
+        &self,
+        metadata_url: Option<String>,
+    ) -> Option<ProtectedResourceMetadata> {
+        let candidates = match metadata_url {
+            Some(url) => vec![url],
+            None => well_known_urls(&self.server_url, "oauth-protected-resource"),
+        };
+        for url in candidates {
+            if let Some(metadata) = self.get_json::<ProtectedResourceMetadata>(&url).await {
+                debug!("Found protected resource metadata: {}", url);
+                return Some(metadata);
+            }
+        }
+        None
+    }
+
+    async fn discover_authorization_server(
+        &self,
+        issuer: &str,
+    ) -> BitFunResult<AuthorizationServerMetadata> {
+        let mut candidates = well_known_urls(issuer, "oauth-authorization-server");
+        candidates.extend(well_known_urls(issuer, "openid-configuration"));
+        candidates.push(format!(
+            "{}/.well-known/openid-configuration",
+            issuer.trim_end_matches('/')
+        ));
+
+        for url in candidates {
+            if let Some(metadata) = self.get_json::<AuthorizationServerMetadata>(&url).await {
+                debug!("Found authorization server metadata: {}", url);
+                return Ok(metadata);
+            }
+        }
+
+        // Servers without metadata use the default endpoints on their origin
+        let base = origin(issuer);
+        debug!("No authorization server metadata, using defaults: {}", base);
+        Ok(AuthorizationServerMetadata {
+            authorization_endpoint: format!("{}/authorize", base),
+            token_endpoint: format!("{}/token", base),
+            registration_endpoint: Some(format!("{}/register", base)),
+            scopes_supported: Vec::new(),
+            code_challenge_methods_supported: None,
+        })
+    }
+
+    async fn register_client(
+        &self,
+        metadata: &AuthorizationServerMetadata,
+        redirect_uri: &str,
+    ) -> BitFunResult<OAuthClientRegistration> {
+        let endpoint = metadata.registration_endpoint.as_ref().ok_or_else(|| {
+            BitFunError::MCPError(
+                "Authorization server does not support dynamic client registration; \
+                 configure settings.oauth.clientId for this server"
+                    .to_string(),
+            )
+        })?;
+
+        let response = self
+            .http
+            .post(endpoint)
+            .json(&json!({
+                "client_name": "BitFun",
+                "redirect_uris": [redirect_uri],
+                "grant_types": ["authorization_code", "refresh_token"],
+                "response_types": ["code"],
+                "token_endpoint_auth_method": "none",
+            }))
+            .send()
+            .await
+            .map_err(|e| BitFunError::MCPError(format!("Client registration failed: {}", e)))?;
+        let body = read_json(response, "Client registration").await?;
+
+        let client_id = body
+            .get("client_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                BitFunError::MCPError("Client registration returned no client_id".to_string())
+            })?;
+        info!(
+            "Registered MCP OAuth client: server_id={} client_id={}",
+            self.server_id, client_id
+        );
+        Ok(OAuthClientRegistration {
+            client_id: client_id.to_string(),
+            client_secret: body
+                .get("client_secret")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            redirect_uri: redirect_uri.to_string(),
+        })
+    }
+
+    async fn request_token(
+        &self,
+        token_endpoint: &str,
+        form: &[(&str, String)],
+    ) -> BitFunResult<TokenResponse> {
+        let response = self
+            .http
+            .post(token_endpoint)
+            .header("Accept", "application/json")
+            .form(form)
+            .send()
+            .await
+            .map_err(|e| BitFunError::MCPError(format!("Token request failed: {}", e)))?;
+        let body = read_json(response, "Token request").await?;
+        serde_json::from_value(body)
+            .map_err(|e| BitFunError::MCPError(format!("Invalid token response: {}", e)))
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Option<T> {
+        let response = self
+            .http
+            .get(url)
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        response.json().await.ok()
+    }
+}
+
+/// Extracts `resource_metadata` from a `WWW-Authenticate: Bearer ...` header
+fn parse_resource_metadata_url(header: &str) -> Option<String> {
+    let start = header.find("resource_metadata=")? + "resource_metadata=".len();
+    let rest = &header[start..];
+    let value = match rest.strip_prefix('"') {
+        Some(quoted) => &quoted[..quoted.find('"')?],
+        None => rest.split([',', ' ']).next()?,
+    };
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Well-known metadata URLs for a URL: path-aware first, then at the root
+fn well_known_urls(url: &str, suffix: &str) -> Vec<String> {
+    let base = origin(url);
+    let path = Url::parse(url)
+        .map(|u| u.path().trim_end_matches('/').to_string())
+        .unwrap_or_default();
+
+    let mut urls = Vec::new();
+    if !path.is_empty() {
+        urls.push(format!("{}/.well-known/{}{}", base, suffix, path));
+    }
+    urls.push(format!("{}/.well-known/{}", base, suffix));
+    urls
+}
+
+/// `scheme://host[:port]` of a URL
+fn origin(url: &str) -> String {
+    Url::parse(url)
+        .map(|u| u.origin().ascii_serialization())
+        .unwrap_or_else(|_| url.trim_end_matches('/').to_string())
+}
+
+fn push_client_auth(form: &mut Vec<(&str, String)>, client: &OAuthClientRegistration) {
+    form.push(("client_id", client.client_id.clone()));
+    if let Some(secret) = &client.client_secret {
+        form.push(("client_secret", secret.clone()));
+    }
+}
+
+/// 256 bits of randomness, base64url-encoded (used for the PKCE verifier and state)
+fn random_token() -> String {
+    let mut bytes = Vec::with_capacity(32);
+    bytes.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    bytes.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+async fn read_json(response: reqwest::Response, what: &str) -> BitFunResult<Value> {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(BitFunError::MCPError(format!(
+            "{} failed with {}: {}",
+            what, status, text
+        )));
+    }
+    serde_json::from_str(&text)
+        .map_err(|e| BitFunError::MCPError(format!("{} returned invalid JSON: {}", what, e)))
+}
+
+/// Rejects URLs that are unsafe to hand to the system opener
+///
+/// The authorization endpoint comes from server-provided metadata. `file:` and custom-scheme
+/// URLs would make the opener launch local programs, so only `https`, and `http` on
+/// loopback, are accepted.
+fn check_browser_url(url: &Url) -> BitFunResult<()> {
+    let host = url.host_str().unwrap_or_default();
+    let loopback = host.eq_ignore_ascii_case("localhost")
+        || host == "[::1]"
+        || host
+            .parse::<std::net::Ipv4Addr>()
+            .is_ok_and(|ip| ip.is_loopback());
+    match url.scheme() {
+        "https" if !host.is_empty() => Ok(()),
+        "http" if loopback => Ok(()),
+        _ => Err(BitFunError::MCPError(format!(
+            "Refusing to open authorization URL with unsupported scheme or host: {}",
+            url
+        ))),
+    }
+}
+
+/// Opens a URL in the system browser
+fn open_in_browser(url: &str) -> BitFunResult<()> {
+    let parsed = Url::parse(url)
+        .map_err(|e| BitFunError::MCPError(format!("Invalid URL {}: {}", url, e)))?;
+    check_browser_url(&parsed)?;
+
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = crate::util::process_manager::create_command("rundll32");
+        command.args(["url.dll,FileProtocolHandler", url]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = crate::util::process_manager::create_command("open");
+        command.arg(url);
+        command
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = {
+        let mut command = crate::util::process_manager::create_command("xdg-open");
+        command.arg(url);
+        command
+    };
+
+    command
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| BitFunError::ProcessError(format!("Failed to open browser: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Form, Query, State};
+    use axum::response::Redirect;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MockState {
+        base: String,
+        challenge: Option<String>,
+    }
+
+    type Shared = Arc<std::sync::Mutex<MockState>>;
+
+    async fn mock_authorization_server() -> String {
+        let state: Shared = Arc::default();
+        let app = Router::new()
+            .route(
+                "/.well-known/oauth-protected-resource/mcp",
+                get(|State(s): State<Shared>| async move {
+                    let base = s.lock().unwrap().base.clone();
+                    Json(json!({
+                        "resource": format!("{}/mcp", base),
+                        "authorization_servers": [format!("{}/auth", base)],
+                        "scopes_supported": ["mcp"]
+                    }))
+                }),
+            )
+            .route(
+                "/.well-known/oauth-authorization-server/auth",
+                get(|State(s): State<Shared>| async move {
+                    let base = s.lock().unwrap().base.clone();
+                    Json(json!({
+                        "issuer": format!("{}/auth", base),
+                        "authorization_endpoint": format!("{}/auth/authorize", base),
+                        "token_endpoint": format!("{}/auth/token", base),
+                        "registration_endpoint": format!("{}/auth/register", base),
+                        "code_challenge_methods_supported": ["S256"]
+                    }))
+                }),
+            )
+            .route(
+                "/auth/register",
+                post(|Json(body): Json<Value>| async move {
+                    Json(json!({ "client_id": "client-1", "redirect_uris": body["redirect_uris"] }))
+                }),
+            )
+            .route(
+                "/auth/authorize",
+                get(
+                    |State(s): State<Shared>, Query(q): Query<HashMap<String, String>>| async move {
+                        assert_eq!(q["client_id"], "client-1");
+                        assert_eq!(q["code_challenge_method"], "S256");
+                        s.lock().unwrap().challenge = Some(q["code_challenge"].clone());
+                        Redirect::to(&format!(
+                            "{}?code=code-1&state={}",
+                            q["redirect_uri"], q["state"]
+                        ))
+                    },
+                ),
+            )
+            .route(
+                "/auth/token",
+                post(
+                    |State(s): State<Shared>, Form(f): Form<HashMap<String, String>>| async move {
+                        let (access_token, refresh_token) = match f["grant_type"].as_str() {
+                            "authorization_code" => {
+                                let challenge = URL_SAFE_NO_PAD
+                                    .encode(Sha256::digest(f["code_verifier"].as_bytes()));
+                                assert_eq!(Some(challenge), s.lock().unwrap().challenge.clone());
+                                assert_eq!(f["code"], "code-1");
+                                ("access-1", "refresh-1")
+                            }
+                            "refresh_token" => {
+                                assert_eq!(f["refresh_token"], "refresh-1");
+                                ("access-2", "refresh-1")
+                            }
+                            other => panic!("unexpected grant type {}", other),
+                        };
+                        Json(json!({
+                            "access_token": access_token,
+                            "token_type": "Bearer",
+                            "refresh_token": refresh_token,
+                            "expires_in": 3600
+                        }))
+                    },
+                ),
+            )
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        state.lock().unwrap().base = base.clone();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        base
+    }
+
+    #[test]
+    fn test_parse_resource_metadata_url() {
+        assert_eq!(
+            parse_resource_metadata_url(
+                r#"Bearer error="invalid_token", resource_metadata="https://a.b/.well-known/oauth-protected-resource""#
+            )
+            .as_deref(),
+            Some("https://a.b/.well-known/oauth-protected-resource")
+        );
+        assert_eq!(parse_resource_metadata_url("Bearer realm=\"x\""), None);
+    }
+
+    #[test]
+    fn test_check_browser_url() {
+        for url in [
+            "https://auth.example.com/authorize",
+            "http://127.0.0.1:8080/authorize",
+            "http://localhost/authorize",
+            "http://[::1]:9000/authorize",
+        ] {
+            assert!(
+                check_browser_url(&Url::parse(url).unwrap()).is_ok(),
+                "{}",
+                url
+            );
+        }
+        for url in [
+            "file:///etc/passwd",
+            "http://auth.example.com/authorize",
+            "ms-settings:privacy",
+            "javascript:alert(1)",
+            "smb://host/share/app.exe",
+        ] {
+            assert!(
+                check_browser_url(&Url::parse(url).unwrap()).is_err(),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authorization_flow_against_mock_server() {
+        let base = mock_authorization_server().await;
+        let store_dir = std::env::temp_dir().join(format!("bitfun-oauth-{}", uuid::Uuid::new_v4()));
+        let store = OAuthCredentialStore::new(store_dir.clone());
+
+        // Stands in for the browser: follows the redirect to the loopback listener
+        let opener: BrowserOpener = Arc::new(|url: &str| {
+            let url = url.to_string();
+            tokio::spawn(async move {
+                let _ = reqwest::get(url).await;
+            });
+            Ok(())
+        });
+        let client = MCPOAuthClient::new(
+            "mock".to_string(),
+            format!("{}/mcp", base),
+            OAuthSettings::default(),
+            store.clone(),
+        )
+        .with_browser_opener(opener);
+
+        assert_eq!(client.access_token().await, None);
+        let token = client.handle_unauthorized(None, None).await.unwrap();
+        assert_eq!(token, "access-1");
+        assert_eq!(client.access_token().await.as_deref(), Some("access-1"));
+
+        let stored = store.load("mock").await.unwrap();
+        assert_eq!(stored.client.unwrap().client_id, "client-1");
+        assert_eq!(
+            stored.tokens.unwrap().refresh_token.as_deref(),
+            Some("refresh-1")
+        );
+
+        // A rejected token is refreshed without another authorization
+        let token = client
+            .handle_unauthorized(None, Some("access-1"))
+            .await
+            .unwrap();
+        assert_eq!(token, "access-2");
+
+        let _ = std::fs::remove_dir_all(store_dir);
+    }
+}
@@ -0,0 +1,153 @@
+//! OAuth credential storage
+//!
+//! Credentials are stored per server as JSON files in the user config directory
+//! (`~/.config/bitfun/config/mcp-oauth/{server_id}.json`).
+
+use crate::infrastructure::try_get_path_manager_arc;
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+
+/// Seconds before expiry at which a token is treated as expired
+const EXPIRY_SKEW_SECS: i64 = 60;
+
+/// Client registered with an authorization server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthClientRegistration {
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+}
+
+/// Tokens issued for a server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthTokens {
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Unix timestamp (seconds) at which the access token expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+impl OAuthTokens {
+    /// Whether the access token has expired (or is about to).
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|at| chrono::Utc::now().timestamp() + EXPIRY_SKEW_SECS >= at)
+    }
+}
+
+/// Everything stored for one server.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredCredentials {
+    /// URL of the MCP server the credentials belong to
+    #[serde(default)]
+    pub server_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization_server: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<OAuthClientRegistration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<OAuthTokens>,
+}
+
+/// Per-server credential files.
+#[derive(Debug, Clone)]
+pub struct OAuthCredentialStore {
+    dir: PathBuf,
+}
+
+impl OAuthCredentialStore {
+    /// Creates a store in the given directory.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Creates a store in the user config directory.
+    pub fn user_default() -> BitFunResult<Self> {
+        Ok(Self::new(try_get_path_manager_arc()?.mcp_oauth_dir()))
+    }
+
+    /// Loads the credentials of a server; missing or unreadable files yield None.
+    pub async fn load(&self, server_id: &str) -> Option<StoredCredentials> {
+        let path = self.path(server_id);
+        let content = tokio::fs::read_to_string(&path).await.ok()?;
+        serde_json::from_str(&content)
+            .map_err(|e| warn!("Invalid MCP OAuth credentials file {:?}: {}", path, e))
+            .ok()
+    }
+
+    /// Saves the credentials of a server.
+    pub async fn save(&self, server_id: &str, credentials: &StoredCredentials) -> BitFunResult<()> {
+        tokio::fs::create_dir_all(&self.dir).await.map_err(|e| {
+            BitFunError::io(format!("Failed to create directory {:?}: {}", self.dir, e))
+        })?;
+
+        let path = self.path(server_id);
+        let content = serde_json::to_string_pretty(credentials)?;
+
+        // Tokens are secrets: write a file that is private to the user from the start, then
+        // move it into place so an existing, more permissive file is never written to
+        let tmp_path = self
+            .dir
+            .join(format!(".{}.tmp", uuid::Uuid::new_v4().simple()));
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let result = async {
+            let mut file = options.open(&tmp_path).await?;
+            file.write_all(content.as_bytes()).await?;
+            file.sync_all().await?;
+            drop(file);
+            tokio::fs::rename(&tmp_path, &path).await
+        }
+        .await;
+        if let Err(e) = result {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(BitFunError::io(format!(
+                "Failed to write {:?}: {}",
+                path, e
+            )));
+        }
+        Ok(())
+    }
+
+    /// Deletes the credentials of a server.
+    pub async fn remove(&self, server_id: &str) -> BitFunResult<()> {
+        let path = self.path(server_id);
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(BitFunError::io(format!(
+                "Failed to delete {:?}: {}",
+                path, e
+            ))),
+        }
+    }
+
+    fn path(&self, server_id: &str) -> PathBuf {
+        let file_name: String = server_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{}.json", file_name))
+    }
+}
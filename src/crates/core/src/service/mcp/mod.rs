@@ -8,10 +8,14 @@
 //! - `server`: MCP server management (processes, connections, registry)
 //! - `adapter`: Adapter layer (Resource/Prompt/Tool adapters)
 //! - `config`: MCP configuration management
+//! - `auth`: OAuth authorization for remote servers
+//! - `serve`: Server mode publishing BitFun tools to other MCP clients
 
 pub mod adapter;
+pub mod auth;
 pub mod config;
 pub mod protocol;
+pub mod serve;
 pub mod server;
 
 // Re-export main components.
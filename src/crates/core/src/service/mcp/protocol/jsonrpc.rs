@@ -27,7 +27,7 @@ pub fn create_initialize_request(
 ) -> MCPRequest {
     let params = InitializeParams {
         protocol_version: super::types::default_protocol_version(),
-        capabilities: MCPCapability::default(),
+        capabilities: MCPCapability::client(),
         client_info: MCPServerInfo {
             name: client_name.into(),
             version: client_version.into(),
@@ -68,6 +68,26 @@ pub fn create_resources_read_request(id: u64, uri: impl Into<String>) -> MCPRequ
     )
 }
 
+/// Creates a `resources/subscribe` request.
+pub fn create_resources_subscribe_request(id: u64, uri: impl Into<String>) -> MCPRequest {
+    let params = ResourcesReadParams { uri: uri.into() };
+    MCPRequest::new(
+        Value::Number(id.into()),
+        "resources/subscribe".to_string(),
+        serialize_params("resources/subscribe", params),
+    )
+}
+
+/// Creates a `resources/unsubscribe` request.
+pub fn create_resources_unsubscribe_request(id: u64, uri: impl Into<String>) -> MCPRequest {
+    let params = ResourcesReadParams { uri: uri.into() };
+    MCPRequest::new(
+        Value::Number(id.into()),
+        "resources/unsubscribe".to_string(),
+        serialize_params("resources/unsubscribe", params),
+    )
+}
+
 /// Creates a `prompts/list` request.
 pub fn create_prompts_list_request(id: u64, cursor: Option<String>) -> MCPRequest {
     let params = if cursor.is_some() {
@@ -124,6 +144,20 @@ pub fn create_tools_call_request(
     )
 }
 
+/// Creates the `notifications/initialized` notification (sent after `initialize`).
+pub fn create_initialized_notification() -> MCPNotification {
+    MCPNotification::new("notifications/initialized".to_string(), None)
+}
+
+/// Creates a `notifications/cancelled` notification for an in-flight request.
+pub fn create_cancelled_notification(request_id: Value, reason: Option<String>) -> MCPNotification {
+    let params = CancelledParams { request_id, reason };
+    MCPNotification::new(
+        "notifications/cancelled".to_string(),
+        serialize_params("notifications/cancelled", params),
+    )
+}
+
 /// Creates a `ping` request (heartbeat).
 pub fn create_ping_request(id: u64) -> MCPRequest {
     MCPRequest::new(
@@ -3,6 +3,7 @@
 //! Handles communication with remote MCP servers over HTTP and SSE.
 
 use super::{MCPMessage, MCPNotification, MCPRequest, MCPResponse};
+use crate::service::mcp::auth::MCPOAuthClient;
 use crate::util::errors::{BitFunError, BitFunResult};
 use eventsource_stream::Eventsource;
 use futures_util::StreamExt;
@@ -10,6 +11,8 @@ use log::{debug, error, info, warn};
 use reqwest::Client;
 use serde_json::Value;
 use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 /// Remote MCP transport.
@@ -18,6 +21,9 @@ pub struct RemoteMCPTransport {
     client: Client,
     session_id: tokio::sync::RwLock<Option<String>>,
     auth_token: Option<String>,
+    oauth: Option<Arc<MCPOAuthClient>>,
+    /// Receives messages other than the response that arrive in a streamed response
+    message_tx: Option<mpsc::UnboundedSender<MCPMessage>>,
 }
 
 impl RemoteMCPTransport {
@@ -43,13 +49,80 @@ impl RemoteMCPTransport {
             client,
             session_id: tokio::sync::RwLock::new(None),
             auth_token,
+            oauth: None,
+            message_tx: None,
         }
     }
 
-    /// Sends a JSON-RPC request to the remote server.
-    pub async fn send_request(&self, request: &MCPRequest) -> BitFunResult<Value> {
-        debug!("Sending request to {}: method={}", self.url, request.method);
+    /// Authorizes requests with OAuth; used when no static token is configured.
+    pub fn with_oauth(mut self, oauth: Arc<MCPOAuthClient>) -> Self {
+        self.oauth = Some(oauth);
+        self
+    }
+
+    /// Forwards notifications and requests streamed alongside a response (e.g. progress
+    /// notifications of a tool call) to the connection.
+    pub fn with_message_sender(mut self, message_tx: mpsc::UnboundedSender<MCPMessage>) -> Self {
+        self.message_tx = Some(message_tx);
+        self
+    }
+
+    /// Returns the `Authorization` header value for the next request.
+    pub async fn authorization_header(&self) -> Option<String> {
+        if let Some(token) = &self.auth_token {
+            return Some(token.clone());
+        }
+        let oauth = self.oauth.as_ref()?;
+        oauth
+            .access_token()
+            .await
+            .map(|token| format!("Bearer {}", token))
+    }
+
+    /// POSTs a JSON-RPC message; on 401 the OAuth client obtains a new token and the
+    /// request is sent once more.
+    async fn post<T: serde::Serialize + ?Sized>(
+        &self,
+        body: &T,
+        timeout: Option<Duration>,
+    ) -> BitFunResult<reqwest::Response> {
+        let authorization = self.authorization_header().await;
+        let response = self
+            .post_with(body, authorization.as_deref(), timeout)
+            .await?;
+
+        let Some(oauth) = self
+            .oauth
+            .as_ref()
+            .filter(|_| self.auth_token.is_none())
+            .filter(|_| response.status() == reqwest::StatusCode::UNAUTHORIZED)
+        else {
+            return Ok(response);
+        };
 
+        let www_authenticate = response
+            .headers()
+            .get(reqwest::header::WWW_AUTHENTICATE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let rejected_token = authorization
+            .as_deref()
+            .and_then(|h| h.strip_prefix("Bearer "));
+        info!("Remote MCP server requires authorization: url={}", self.url);
+
+        let token = oauth
+            .handle_unauthorized(www_authenticate.as_deref(), rejected_token)
+            .await?;
+        self.post_with(body, Some(&format!("Bearer {}", token)), timeout)
+            .await
+    }
+
+    async fn post_with<T: serde::Serialize + ?Sized>(
+        &self,
+        body: &T,
+        authorization: Option<&str>,
+        timeout: Option<Duration>,
+    ) -> BitFunResult<reqwest::Response> {
         let mut request_builder = self
             .client
             .post(&self.url)
@@ -57,11 +130,14 @@ impl RemoteMCPTransport {
             .header("Content-Type", "application/json")
             .header("User-Agent", "BitFun-MCP-Client/1.0");
 
-        if let Some(ref token) = self.auth_token {
+        if let Some(token) = authorization {
             request_builder = request_builder.header("Authorization", token);
         }
+        if let Some(timeout) = timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
 
-        let response = request_builder.json(request).send().await.map_err(|e| {
+        request_builder.json(body).send().await.map_err(|e| {
             let error_detail = if e.is_timeout() {
                 "Request timed out, please check network connection"
             } else if e.is_connect() {
@@ -83,7 +159,20 @@ impl RemoteMCPTransport {
             }
 
             BitFunError::MCPError(format!("HTTP request failed ({}): {}", error_detail, e))
-        })?;
+        })
+    }
+
+    /// Sends a JSON-RPC request to the remote server.
+    ///
+    /// `timeout` overrides the client default for requests that may run long (tool calls).
+    pub async fn send_request(
+        &self,
+        request: &MCPRequest,
+        timeout: Option<Duration>,
+    ) -> BitFunResult<Value> {
+        debug!("Sending request to {}: method={}", self.url, request.method);
+
+        let response = self.post(request, timeout).await?;
 
         let status = response.status();
 
@@ -112,6 +201,15 @@ impl RemoteMCPTransport {
             )));
         }
 
+        let is_event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        if is_event_stream {
+            return self.read_streamed_response(response, &request.id).await;
+        }
+
         let response_text = response.text().await.map_err(|e| {
             error!("Failed to read response body: {}", e);
             BitFunError::MCPError(format!("Failed to read response body: {}", e))
@@ -133,6 +231,26 @@ impl RemoteMCPTransport {
         Ok(json_response)
     }
 
+    /// Sends a message that has no response (notification, or response to a server
+    /// request).
+    pub async fn send_message(&self, message: &MCPMessage) -> BitFunResult<()> {
+        let response = self.post(message, None).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(BitFunError::MCPError(format!(
+                "Server error {}: {}",
+                status, error_text
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Returns the current session ID.
     pub async fn get_session_id(&self) -> Option<String> {
         self.session_id.read().await.clone()
@@ -143,6 +261,50 @@ impl RemoteMCPTransport {
         self.auth_token.clone()
     }
 
+    /// Reads an SSE response until the response to the request arrives; other messages
+    /// (progress notifications, server requests) are forwarded as they come in.
+    async fn read_streamed_response(
+        &self,
+        response: reqwest::Response,
+        request_id: &Value,
+    ) -> BitFunResult<Value> {
+        let mut stream = response.bytes_stream().eventsource();
+        while let Some(event) = stream.next().await {
+            let event = event.map_err(|e| {
+                BitFunError::MCPError(format!("Failed to read streamed response: {}", e))
+            })?;
+            if event.data.trim().is_empty() {
+                continue;
+            }
+
+            let value: Value = match serde_json::from_str(&event.data) {
+                Ok(value) => value,
+                Err(e) => {
+                    warn!(
+                        "Failed to parse JSON from SSE event: {} (data: {})",
+                        e, event.data
+                    );
+                    continue;
+                }
+            };
+            match Self::parse_message(&value) {
+                Some(MCPMessage::Response(response)) if &response.id == request_id => {
+                    return Ok(value);
+                }
+                Some(message) => {
+                    if let Some(tx) = &self.message_tx {
+                        let _ = tx.send(message);
+                    }
+                }
+                None => {}
+            }
+        }
+
+        Err(BitFunError::MCPError(
+            "Stream ended before the response arrived".to_string(),
+        ))
+    }
+
     /// Parses an SSE-formatted response and extracts JSON from the `data` field.
     fn parse_sse_response(sse_text: &str) -> BitFunResult<Value> {
         // SSE format example:
@@ -0,0 +1,10 @@
+//! MCP server mode
+//!
+//! Lets BitFun act as an MCP server so other agents and editors can use its workspace tools
+//! (file search, Git, lints, LSP navigation) over stdio or streamable HTTP.
+
+pub mod server;
+pub mod transport;
+
+pub use server::{MCPToolServer, DEFAULT_SERVED_TOOLS};
+pub use transport::{serve_http, serve_stdio, MCP_HTTP_PATH};
@@ -0,0 +1,342 @@
+//! MCP tool server
+//!
+//! Answers MCP requests from other clients by publishing a selection of the tools in the
+//! global `ToolRegistry`. Tool calls go through the same declarative permission rules as the
+//! tool pipeline; since there is nobody to confirm a call, anything that would need
+//! confirmation is refused.
+
+use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::agentic::tools::pipeline::{PermissionDecision, PermissionRules};
+use crate::agentic::tools::registry::get_global_tool_registry;
+use crate::service::mcp::protocol::{
+    default_protocol_version, InitializeResult, MCPCapability, MCPError, MCPResponse,
+    MCPServerInfo, MCPTool, MCPToolResult, MCPToolResultContent, ToolsCallParams, ToolsCapability,
+    ToolsListResult,
+};
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Tools published when no selection is given.
+pub const DEFAULT_SERVED_TOOLS: &[&str] = &[
+    "Read",
+    "Grep",
+    "Glob",
+    "LS",
+    "ReadLints",
+    "Git",
+    "GetFileDiff",
+    "LSP",
+];
+
+/// Protocol versions the server can speak; the first is used when the client asks for an
+/// unknown one.
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+/// Serves registry tools to MCP clients.
+pub struct MCPToolServer {
+    tools: Vec<String>,
+    /// Mode whose permission rules apply to tool calls
+    mode: String,
+}
+
+impl MCPToolServer {
+    /// Creates a server publishing the given tools.
+    pub fn new(tools: Vec<String>) -> Self {
+        Self {
+            tools,
+            mode: "agentic".to_string(),
+        }
+    }
+
+    /// Sets the mode whose permission rules apply to tool calls.
+    pub fn with_mode(mut self, mode: impl Into<String>) -> Self {
+        self.mode = mode.into();
+        self
+    }
+
+    /// Names of the published tools.
+    pub fn tool_names(&self) -> &[String] {
+        &self.tools
+    }
+
+    /// Handles one JSON-RPC message (or batch) and returns the reply, if any.
+    ///
+    /// Notifications and responses produce no reply.
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        match message {
+            Value::Array(messages) => {
+                let mut replies = Vec::new();
+                for message in messages {
+                    if let Some(reply) = Box::pin(self.handle_message(message)).await {
+                        replies.push(reply);
+                    }
+                }
+                (!replies.is_empty()).then(|| Value::Array(replies))
+            }
+            Value::Object(ref object) => {
+                let Some(method) = object.get("method").and_then(|m| m.as_str()) else {
+                    // Responses to requests we never send
+                    return None;
+                };
+                let id = object.get("id").cloned().filter(|id| !id.is_null());
+                let params = object.get("params").cloned();
+
+                let Some(id) = id else {
+                    debug!("MCP serve notification: method={}", method);
+                    return None;
+                };
+
+                let response = match self.dispatch(method, params).await {
+                    Ok(result) => MCPResponse::success(id, result),
+                    Err(error) => MCPResponse::error(id, error),
+                };
+                serde_json::to_value(response).ok()
+            }
+            _ => serde_json::to_value(MCPResponse::error(
+                Value::Null,
+                MCPError::invalid_request("Expected a JSON-RPC message"),
+            ))
+            .ok(),
+        }
+    }
+
+    async fn dispatch(&self, method: &str, params: Option<Value>) -> Result<Value, MCPError> {
+        match method {
+            "initialize" => Ok(self.initialize(params.as_ref())),
+            "ping" => Ok(json!({})),
+            "tools/list" => {
+                let result = ToolsListResult {
+                    tools: self.list_tools().await,
+                    next_cursor: None,
+                };
+                serde_json::to_value(result).map_err(|e| MCPError::internal_error(e.to_string()))
+            }
+            "tools/call" => {
+                let params: ToolsCallParams = serde_json::from_value(params.unwrap_or(Value::Null))
+                    .map_err(|e| MCPError::invalid_params(e.to_string()))?;
+                let result = self.call_tool(params).await?;
+                serde_json::to_value(result).map_err(|e| MCPError::internal_error(e.to_string()))
+            }
+            other => Err(MCPError::method_not_found(other)),
+        }
+    }
+
+    fn initialize(&self, params: Option<&Value>) -> Value {
+        let requested = params
+            .and_then(|p| p.get("protocolVersion"))
+            .and_then(|v| v.as_str());
+        let protocol_version = match requested {
+            Some(version) if SUPPORTED_PROTOCOL_VERSIONS.contains(&version) => version.to_string(),
+            _ => default_protocol_version(),
+        };
+        let client = params
+            .and_then(|p| p.pointer("/clientInfo/name"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        info!(
+            "MCP client connected: client={} protocol_version={}",
+            client, protocol_version
+        );
+
+        let result = InitializeResult {
+            protocol_version,
+            capabilities: MCPCapability {
+                resources: None,
+                prompts: None,
+                tools: Some(ToolsCapability::default()),
+                logging: None,
+                roots: None,
+                sampling: None,
+                elicitation: None,
+            },
+            server_info: MCPServerInfo {
+                name: "bitfun".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                description: Some("BitFun workspace tools".to_string()),
+                vendor: None,
+            },
+        };
+        serde_json::to_value(result).unwrap_or_default()
+    }
+
+    /// Published tools that are registered and enabled.
+    async fn served_tools(&self) -> Vec<Arc<dyn Tool>> {
+        let registry = get_global_tool_registry();
+        let registry = registry.read().await;
+        let mut tools = Vec::with_capacity(self.tools.len());
+        for name in &self.tools {
+            match registry.get_tool(name) {
+                Some(tool) if tool.is_enabled().await => tools.push(tool),
+                Some(_) => debug!("Skipping disabled tool: {}", name),
+                None => warn!("Tool to serve over MCP is not registered: {}", name),
+            }
+        }
+        tools
+    }
+
+    async fn list_tools(&self) -> Vec<MCPTool> {
+        let mut list = Vec::new();
+        for tool in self.served_tools().await {
+            list.push(MCPTool {
+                name: tool.name().to_string(),
+                description: tool.description().await.ok(),
+                input_schema: tool
+                    .input_json_schema()
+                    .unwrap_or_else(|| tool.input_schema()),
+            });
+        }
+        list
+    }
+
+    async fn call_tool(&self, params: ToolsCallParams) -> Result<MCPToolResult, MCPError> {
+        let name = params.name;
+        let tool = self
+            .served_tools()
+            .await
+            .into_iter()
+            .find(|tool| tool.name() == name)
+            .ok_or_else(|| MCPError::invalid_params(format!("Unknown tool: {}", name)))?;
+        let input = params.arguments.unwrap_or_else(|| json!({}));
+
+        let permission = PermissionRules::load(&self.mode)
+            .await
+            .evaluate(&name, &input);
+        match permission {
+            Some(matched) if matched.decision == PermissionDecision::Deny => {
+                warn!(
+                    "MCP tool call denied by permission rule: tool_name={}, rule={}",
+                    name, matched.rule
+                );
+                return Ok(error_result(matched.deny_reason(&name)));
+            }
+            Some(matched) if matched.decision == PermissionDecision::Ask => {
+                return Ok(error_result(format!(
+                    "Tool call requires confirmation (rule '{}'), which is not available over MCP",
+                    matched.rule
+                )));
+            }
+            Some(_) => {}
+            None if tool.needs_permissions(Some(&input)) => {
+                return Ok(error_result(format!(
+                    "Tool call to {} requires confirmation, which is not available over MCP; \
+                     add an allow rule to permit it",
+                    name
+                )));
+            }
+            None => {}
+        }
+
+        let context = build_context(&self.mode);
+        let validation = tool.validate_input(&input, Some(&context)).await;
+        if !validation.result {
+            return Ok(error_result(format!(
+                "Invalid input for {}: {}",
+                name,
+                validation
+                    .message
+                    .unwrap_or_else(|| "validation failed".to_string())
+            )));
+        }
+
+        debug!("MCP tool call: tool_name={}", name);
+        match tool.call(&input, &context).await {
+            Ok(results) => Ok(MCPToolResult {
+                content: Some(vec![MCPToolResultContent::Text {
+                    text: results_to_text(&results),
+                }]),
+                is_error: false,
+            }),
+            Err(e) => Ok(error_result(format!("Tool {} failed: {}", name, e))),
+        }
+    }
+}
+
+fn error_result(message: String) -> MCPToolResult {
+    MCPToolResult {
+        content: Some(vec![MCPToolResultContent::Text { text: message }]),
+        is_error: true,
+    }
+}
+
+fn build_context(mode: &str) -> ToolUseContext {
+    let id = uuid::Uuid::new_v4().to_string();
+    ToolUseContext {
+        tool_call_id: Some(format!("mcp-serve-{}", id)),
+        message_id: None,
+        agent_type: Some(mode.to_string()),
+        session_id: Some(format!("mcp-serve-session-{}", id)),
+        dialog_turn_id: None,
+        safe_mode: None,
+        abort_controller: None,
+        read_file_timestamps: Default::default(),
+        options: None,
+        response_state: None,
+        image_context_provider: None,
+        subagent_parent_info: None,
+        cancellation_token: Some(CancellationToken::new()),
+    }
+}
+
+/// Final results as text, preferring what the tool renders for the model
+fn results_to_text(results: &[ToolResult]) -> String {
+    let texts: Vec<String> = results
+        .iter()
+        .filter_map(|result| match result {
+            ToolResult::Result {
+                result_for_assistant: Some(text),
+                ..
+            } if !text.is_empty() => Some(text.clone()),
+            ToolResult::Result { data, .. } => Some(match data {
+                Value::String(s) => s.clone(),
+                other => serde_json::to_string_pretty(other).unwrap_or_default(),
+            }),
+            _ => None,
+        })
+        .collect();
+    texts.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_handle_message_replies_to_requests_only() {
+        let server = Arc::new(MCPToolServer::new(Vec::new()));
+
+        let reply = server
+            .handle_message(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": "2025-03-26", "clientInfo": { "name": "test", "version": "1" } }
+            }))
+            .await
+            .unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["result"]["protocolVersion"], "2025-03-26");
+        assert!(reply["result"]["capabilities"]["tools"].is_object());
+
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(server.handle_message(notification).await.is_none());
+
+        let reply = server
+            .handle_message(json!({ "jsonrpc": "2.0", "id": "x", "method": "resources/list" }))
+            .await
+            .unwrap();
+        assert_eq!(reply["error"]["code"], MCPError::METHOD_NOT_FOUND);
+
+        let reply = server
+            .handle_message(json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": { "name": "Bash", "arguments": { "command": "ls" } }
+            }))
+            .await
+            .unwrap();
+        assert_eq!(reply["error"]["code"], MCPError::INVALID_PARAMS);
+    }
+}
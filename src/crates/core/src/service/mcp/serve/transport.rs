@@ -0,0 +1,158 @@
+//! Transports for the MCP tool server
+//!
+//! - stdio: newline-delimited JSON-RPC on stdin/stdout
+//! - streamable HTTP: JSON-RPC posted to `/mcp`, answered with a JSON body
+
+use super::server::MCPToolServer;
+use crate::service::mcp::protocol::{MCPError, MCPResponse};
+use crate::util::errors::{BitFunError, BitFunResult};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use log::{debug, error, info, warn};
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+/// Path of the streamable HTTP endpoint.
+pub const MCP_HTTP_PATH: &str = "/mcp";
+
+/// Serves over stdin/stdout until stdin closes.
+///
+/// Requests are handled concurrently so a slow tool call does not block pings; replies are
+/// written one per line in completion order.
+pub async fn serve_stdio(server: Arc<MCPToolServer>) -> BitFunResult<()> {
+    let (reply_tx, mut reply_rx) = mpsc::unbounded_channel::<Value>();
+
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(reply) = reply_rx.recv().await {
+            let mut line = match serde_json::to_string(&reply) {
+                Ok(line) => line,
+                Err(e) => {
+                    error!("Failed to serialize MCP reply: {}", e);
+                    continue;
+                }
+            };
+            line.push('\n');
+            if stdout.write_all(line.as_bytes()).await.is_err() || stdout.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    info!("MCP server listening on stdio");
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| BitFunError::io(format!("Failed to read stdin: {}", e)))?
+    {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Invalid MCP message on stdin: {}", e);
+                let _ = reply_tx.send(parse_error_reply(e));
+                continue;
+            }
+        };
+
+        let server = server.clone();
+        let reply_tx = reply_tx.clone();
+        tokio::spawn(async move {
+            if let Some(reply) = server.handle_message(message).await {
+                let _ = reply_tx.send(reply);
+            }
+        });
+    }
+
+    debug!("stdin closed, stopping MCP server");
+    drop(reply_tx);
+    let _ = writer.await;
+    Ok(())
+}
+
+/// Serves streamable HTTP on the given address until the process exits.
+///
+/// Only plain JSON responses are produced, so `GET` (the server-initiated SSE stream) is
+/// answered with 405 as the spec allows. Requests from browser origins other than
+/// localhost are rejected to guard against DNS rebinding.
+pub async fn serve_http(server: Arc<MCPToolServer>, addr: SocketAddr) -> BitFunResult<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| BitFunError::service(format!("Failed to bind {}: {}", addr, e)))?;
+    let local_addr = listener
+        .local_addr()
+        .map_err(|e| BitFunError::service(format!("Failed to read listener address: {}", e)))?;
+
+    let app = Router::new()
+        .route(MCP_HTTP_PATH, post(http_handler).get(method_not_allowed))
+        .with_state(server);
+
+    info!(
+        "MCP server listening on http://{}{}",
+        local_addr, MCP_HTTP_PATH
+    );
+    axum::serve(listener, app)
+        .await
+        .map_err(|e| BitFunError::service(format!("MCP HTTP server error: {}", e)))
+}
+
+async fn http_handler(
+    State(server): State<Arc<MCPToolServer>>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    if let Some(origin) = headers.get(header::ORIGIN).and_then(|v| v.to_str().ok()) {
+        if !is_local_origin(origin) {
+            warn!("Rejected MCP request from origin: {}", origin);
+            return StatusCode::FORBIDDEN.into_response();
+        }
+    }
+
+    let message: Value = match serde_json::from_str(&body) {
+        Ok(message) => message,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(parse_error_reply(e))).into_response();
+        }
+    };
+
+    match server.handle_message(message).await {
+        Some(reply) => Json(reply).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+async fn method_not_allowed() -> StatusCode {
+    StatusCode::METHOD_NOT_ALLOWED
+}
+
+fn parse_error_reply(error: serde_json::Error) -> Value {
+    serde_json::to_value(MCPResponse::error(
+        Value::Null,
+        MCPError::parse_error(error.to_string()),
+    ))
+    .unwrap_or_default()
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let host = origin
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(origin);
+    if host.starts_with("[::1]") {
+        return true;
+    }
+    let host = host.split(':').next().unwrap_or(host);
+    matches!(host, "localhost" | "127.0.0.1")
+}
@@ -0,0 +1,359 @@
+//! MCP server catalog
+//!
+//! Caches the prompts, resources and subscribed resource contents of running servers and
+//! keeps them, and the registered tools, in sync with `list_changed` / `updated`
+//! notifications.
+
+use super::connection::MCPConnection;
+use crate::agentic::tools::registry::get_global_tool_registry;
+use crate::infrastructure::events::event_system::{get_global_event_system, BackendEvent};
+use crate::service::mcp::adapter::tool::MCPToolAdapter;
+use crate::service::mcp::protocol::{MCPNotification, MCPPrompt, MCPResource, MCPResourceContent};
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::{debug, info, warn};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+
+/// Event emitted when the tools, prompts or resources of a server change.
+pub const MCP_LIST_CHANGED_EVENT: &str = "mcp-list-changed";
+
+/// Event emitted when a subscribed resource changes.
+pub const MCP_RESOURCE_UPDATED_EVENT: &str = "mcp-resource-updated";
+
+/// Cached lists of one server.
+#[derive(Debug, Default)]
+struct ServerCatalog {
+    prompts: Option<Vec<MCPPrompt>>,
+    resources: Option<Vec<MCPResource>>,
+    /// Subscribed resources and their latest contents
+    subscriptions: HashMap<String, Vec<MCPResourceContent>>,
+}
+
+/// Catalog of all running servers.
+#[derive(Default)]
+pub struct MCPCatalog {
+    servers: RwLock<HashMap<String, ServerCatalog>>,
+}
+
+impl MCPCatalog {
+    /// Creates an empty catalog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the prompts of a server, listing them on first use.
+    pub async fn prompts(
+        &self,
+        server_id: &str,
+        connection: &MCPConnection,
+    ) -> BitFunResult<Vec<MCPPrompt>> {
+        if let Some(prompts) = self.cached(server_id, |c| c.prompts.clone()).await {
+            return Ok(prompts);
+        }
+        let prompts = connection.list_prompts(None).await?.prompts;
+        self.servers
+            .write()
+            .await
+            .entry(server_id.to_string())
+            .or_default()
+            .prompts = Some(prompts.clone());
+        Ok(prompts)
+    }
+
+    /// Returns the resources of a server, listing them on first use.
+    pub async fn resources(
+        &self,
+        server_id: &str,
+        connection: &MCPConnection,
+    ) -> BitFunResult<Vec<MCPResource>> {
+        if let Some(resources) = self.cached(server_id, |c| c.resources.clone()).await {
+            return Ok(resources);
+        }
+        let resources = connection.list_resources(None).await?.resources;
+        self.servers
+            .write()
+            .await
+            .entry(server_id.to_string())
+            .or_default()
+            .resources = Some(resources.clone());
+        Ok(resources)
+    }
+
+    /// Returns the latest contents of a subscribed resource.
+    pub async fn subscribed_contents(
+        &self,
+        server_id: &str,
+        uri: &str,
+    ) -> Option<Vec<MCPResourceContent>> {
+        self.cached(server_id, |c| c.subscriptions.get(uri).cloned())
+            .await
+    }
+
+    /// Subscribes to a resource and caches its current contents.
+    pub async fn subscribe(
+        &self,
+        server_id: &str,
+        uri: &str,
+        connection: &MCPConnection,
+    ) -> BitFunResult<Vec<MCPResourceContent>> {
+        connection.subscribe_resource(uri).await?;
+        let contents = connection.read_resource(uri).await?.contents;
+        self.servers
+            .write()
+            .await
+            .entry(server_id.to_string())
+            .or_default()
+            .subscriptions
+            .insert(uri.to_string(), contents.clone());
+        info!(
+            "Subscribed to MCP resource: server_id={} uri={}",
+            server_id, uri
+        );
+        Ok(contents)
+    }
+
+    /// Cancels a resource subscription.
+    pub async fn unsubscribe(
+        &self,
+        server_id: &str,
+        uri: &str,
+        connection: &MCPConnection,
+    ) -> BitFunResult<()> {
+        let removed = self
+            .servers
+            .write()
+            .await
+            .get_mut(server_id)
+            .and_then(|c| c.subscriptions.remove(uri));
+        if removed.is_none() {
+            return Err(BitFunError::NotFound(format!(
+                "Not subscribed to MCP resource: {}",
+                uri
+            )));
+        }
+        connection.unsubscribe_resource(uri).await
+    }
+
+    /// Drops everything cached for a server.
+    pub async fn remove_server(&self, server_id: &str) {
+        self.servers.write().await.remove(server_id);
+    }
+
+    /// Handles the notifications of a server until its connection closes.
+    pub fn start_listener(
+        self: &Arc<Self>,
+        server_id: String,
+        server_name: String,
+        connection: Arc<MCPConnection>,
+        mut notifications: mpsc::UnboundedReceiver<MCPNotification>,
+    ) {
+        let catalog = self.clone();
+        tokio::spawn(async move {
+            while let Some(notification) = notifications.recv().await {
+                let result = match notification.method.as_str() {
+                    "notifications/tools/list_changed" => {
+                        refresh_tools(&server_id, &server_name, connection.clone()).await
+                    }
+                    "notifications/prompts/list_changed" => {
+                        catalog.refresh_prompts(&server_id, &connection).await
+                    }
+                    "notifications/resources/list_changed" => {
+                        catalog.refresh_resources(&server_id, &connection).await
+                    }
+                    "notifications/resources/updated" => {
+                        let uri = notification
+                            .params
+                            .as_ref()
+                            .and_then(|p| p.get("uri"))
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default();
+                        catalog
+                            .refresh_subscription(&server_id, uri, &connection)
+                            .await
+                    }
+                    other => {
+                        debug!(
+                            "Ignoring MCP notification: server_id={} method={}",
+                            server_id, other
+                        );
+                        Ok(())
+                    }
+                };
+                if let Err(e) = result {
+                    warn!(
+                        "Failed to handle MCP notification: server_id={} method={} error={}",
+                        server_id, notification.method, e
+                    );
+                }
+            }
+            debug!("MCP notification listener stopped: server_id={}", server_id);
+        });
+    }
+
+    async fn cached<T>(
+        &self,
+        server_id: &str,
+        f: impl FnOnce(&ServerCatalog) -> Option<T>,
+    ) -> Option<T> {
+        self.servers.read().await.get(server_id).and_then(f)
+    }
+
+    async fn refresh_prompts(
+        &self,
+        server_id: &str,
+        connection: &MCPConnection,
+    ) -> BitFunResult<()> {
+        let prompts = connection.list_prompts(None).await?.prompts;
+        let old = self
+            .servers
+            .write()
+            .await
+            .entry(server_id.to_string())
+            .or_default()
+            .prompts
+            .replace(prompts.clone())
+            .unwrap_or_default();
+
+        let (added, removed) = diff_names(
+            old.iter().map(|p| p.name.as_str()),
+            prompts.iter().map(|p| p.name.as_str()),
+        );
+        emit_list_changed(server_id, "prompts", added, removed).await;
+        Ok(())
+    }
+
+    async fn refresh_resources(
+        &self,
+        server_id: &str,
+        connection: &MCPConnection,
+    ) -> BitFunResult<()> {
+        let resources = connection.list_resources(None).await?.resources;
+        let old = self
+            .servers
+            .write()
+            .await
+            .entry(server_id.to_string())
+            .or_default()
+            .resources
+            .replace(resources.clone())
+            .unwrap_or_default();
+
+        let (added, removed) = diff_names(
+            old.iter().map(|r| r.uri.as_str()),
+            resources.iter().map(|r| r.uri.as_str()),
+        );
+        emit_list_changed(server_id, "resources", added, removed).await;
+        Ok(())
+    }
+
+    async fn refresh_subscription(
+        &self,
+        server_id: &str,
+        uri: &str,
+        connection: &MCPConnection,
+    ) -> BitFunResult<()> {
+        let subscribed = self
+            .cached(server_id, |c| {
+                c.subscriptions.contains_key(uri).then_some(())
+            })
+            .await
+            .is_some();
+        if !subscribed {
+            debug!(
+                "Ignoring update of unsubscribed MCP resource: server_id={} uri={}",
+                server_id, uri
+            );
+            return Ok(());
+        }
+
+        let contents = connection.read_resource(uri).await?.contents;
+        if let Some(catalog) = self.servers.write().await.get_mut(server_id) {
+            // Skip if unsubscribed while reading
+            if let Some(entry) = catalog.subscriptions.get_mut(uri) {
+                *entry = contents;
+            }
+        }
+
+        debug!("MCP resource updated: server_id={} uri={}", server_id, uri);
+        let event = BackendEvent::Custom {
+            event_name: MCP_RESOURCE_UPDATED_EVENT.to_string(),
+            payload: json!({ "serverId": server_id, "uri": uri }),
+        };
+        let _ = get_global_event_system().emit(event).await;
+        Ok(())
+    }
+}
+
+/// Re-lists the tools of a server and updates the global tool registry.
+async fn refresh_tools(
+    server_id: &str,
+    server_name: &str,
+    connection: Arc<MCPConnection>,
+) -> BitFunResult<()> {
+    let mut adapter = MCPToolAdapter::new();
+    adapter
+        .load_tools_from_server(server_id, server_name, connection)
+        .await?;
+
+    let (added, removed) = {
+        let registry = get_global_tool_registry();
+        let mut registry_lock = registry.write().await;
+        registry_lock.sync_mcp_server_tools(server_id, adapter.get_tools().to_vec())
+    };
+    info!(
+        "Refreshed MCP tools: server_id={} added={:?} removed={:?}",
+        server_id, added, removed
+    );
+    emit_list_changed(server_id, "tools", added, removed).await;
+    Ok(())
+}
+
+async fn emit_list_changed(server_id: &str, kind: &str, added: Vec<String>, removed: Vec<String>) {
+    let event = BackendEvent::Custom {
+        event_name: MCP_LIST_CHANGED_EVENT.to_string(),
+        payload: json!({
+            "serverId": server_id,
+            "kind": kind,
+            "added": added,
+            "removed": removed,
+        }),
+    };
+    let _ = get_global_event_system().emit(event).await;
+}
+
+/// Names present only in `new` (added) and only in `old` (removed)
+fn diff_names<'a>(
+    old: impl Iterator<Item = &'a str>,
+    new: impl Iterator<Item = &'a str>,
+) -> (Vec<String>, Vec<String>) {
+    let old: HashSet<&str> = old.collect();
+    let new: Vec<&str> = new.collect();
+    let new_set: HashSet<&str> = new.iter().copied().collect();
+
+    let added = new
+        .iter()
+        .filter(|name| !old.contains(*name))
+        .map(|name| name.to_string())
+        .collect();
+    let mut removed: Vec<String> = old
+        .into_iter()
+        .filter(|name| !new_set.contains(name))
+        .map(str::to_string)
+        .collect();
+    removed.sort();
+    (added, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_names() {
+        let (added, removed) = diff_names(["a", "b", "c"].into_iter(), ["b", "d", "a"].into_iter());
+        assert_eq!(added, vec!["d".to_string()]);
+        assert_eq!(removed, vec!["c".to_string()]);
+    }
+}
@@ -2,27 +2,62 @@
 //!
 //! Handles communication connections to MCP servers and request/response management.
 
+use super::request_handler::MCPRequestHandler;
+use crate::service::mcp::auth::MCPOAuthClient;
 use crate::service::mcp::protocol::{
-    create_initialize_request, create_ping_request, create_prompts_get_request,
-    create_prompts_list_request, create_resources_list_request, create_resources_read_request,
+    create_cancelled_notification, create_initialize_request, create_initialized_notification,
+    create_ping_request, create_prompts_get_request, create_prompts_list_request,
+    create_resources_list_request, create_resources_read_request,
+    create_resources_subscribe_request, create_resources_unsubscribe_request,
     create_tools_call_request, create_tools_list_request, parse_response_result,
-    transport::MCPTransport, transport_remote::RemoteMCPTransport, InitializeResult, MCPMessage,
-    MCPRequest, MCPResponse, MCPToolResult, PromptsGetResult, PromptsListResult,
-    ResourcesListResult, ResourcesReadResult, ToolsListResult,
+    transport::MCPTransport, transport_remote::RemoteMCPTransport, InitializeResult, MCPError,
+    MCPMessage, MCPNotification, MCPRequest, MCPResponse, MCPToolResult, ProgressParams,
+    PromptsGetResult, PromptsListResult, ResourcesListResult, ResourcesReadResult, ToolsListResult,
 };
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, warn};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::process::ChildStdin;
 use tokio::sync::{mpsc, oneshot, RwLock};
+use tokio_util::sync::CancellationToken;
 
 /// Request/response waiter.
 type ResponseWaiter = oneshot::Sender<MCPResponse>;
 
+/// Handler slot for requests sent by the server.
+type RequestHandlerSlot = Arc<RwLock<Option<Arc<MCPRequestHandler>>>>;
+
+/// Listener slot for notifications sent by the server.
+type NotificationSlot = Arc<RwLock<Option<mpsc::UnboundedSender<MCPNotification>>>>;
+
+/// Listeners for `notifications/progress`, keyed by progress token.
+type ProgressListeners = Arc<RwLock<HashMap<String, mpsc::UnboundedSender<ProgressParams>>>>;
+
+/// Longest a request may run, even while the server keeps reporting progress.
+const MAX_REQUEST_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// Options of a single request.
+#[derive(Debug, Clone, Default)]
+pub struct MCPCallOptions {
+    /// Time allowed without a response or progress notification (connection default when
+    /// unset)
+    pub timeout: Option<Duration>,
+    /// Receives progress reported by the server; a progress token is attached when set
+    pub progress: Option<mpsc::UnboundedSender<ProgressParams>>,
+    /// Cancels the request and notifies the server
+    pub cancellation_token: Option<CancellationToken>,
+    /// Chat session the request is made for; requests the server sends while handling it
+    /// are shown there. A progress token is attached when set
+    pub session_id: Option<String>,
+}
+
 /// Transport type.
+#[derive(Clone)]
 enum TransportType {
     Local(Arc<MCPTransport>),
     Remote(Arc<RemoteMCPTransport>),
@@ -32,52 +67,81 @@ enum TransportType {
 pub struct MCPConnection {
     transport: TransportType,
     pending_requests: Arc<RwLock<HashMap<u64, ResponseWaiter>>>,
+    request_handler: RequestHandlerSlot,
+    notification_listener: NotificationSlot,
+    progress_listeners: ProgressListeners,
+    next_progress_token: AtomicU64,
     request_timeout: Duration,
 }
 
 impl MCPConnection {
     /// Creates a new local connection instance (stdin/stdout).
     pub fn new_local(stdin: ChildStdin, message_rx: mpsc::UnboundedReceiver<MCPMessage>) -> Self {
-        let transport = Arc::new(MCPTransport::new(stdin));
-        let pending_requests = Arc::new(RwLock::new(HashMap::new()));
-
-        let pending = pending_requests.clone();
-        tokio::spawn(async move {
-            Self::handle_messages(message_rx, pending).await;
-        });
-
-        Self {
-            transport: TransportType::Local(transport),
-            pending_requests,
-            request_timeout: Duration::from_secs(180),
-        }
+        let transport = TransportType::Local(Arc::new(MCPTransport::new(stdin)));
+        Self::with_transport(transport, message_rx)
     }
 
     /// Creates a new remote connection instance (HTTP/SSE).
+    ///
+    /// `message_tx` is the sender side of `message_rx`; messages streamed alongside a
+    /// response are fed back through it.
     pub fn new_remote(
         url: String,
         auth_token: Option<String>,
+        oauth: Option<Arc<MCPOAuthClient>>,
+        message_tx: mpsc::UnboundedSender<MCPMessage>,
+        message_rx: mpsc::UnboundedReceiver<MCPMessage>,
+    ) -> Self {
+        let mut transport =
+            RemoteMCPTransport::new(url, auth_token).with_message_sender(message_tx);
+        if let Some(oauth) = oauth {
+            transport = transport.with_oauth(oauth);
+        }
+        let transport = TransportType::Remote(Arc::new(transport));
+        Self::with_transport(transport, message_rx)
+    }
+
+    fn with_transport(
+        transport: TransportType,
         message_rx: mpsc::UnboundedReceiver<MCPMessage>,
     ) -> Self {
-        let transport = Arc::new(RemoteMCPTransport::new(url, auth_token));
         let pending_requests = Arc::new(RwLock::new(HashMap::new()));
+        let request_handler: RequestHandlerSlot = Arc::new(RwLock::new(None));
+        let notification_listener: NotificationSlot = Arc::new(RwLock::new(None));
+        let progress_listeners: ProgressListeners = Arc::new(RwLock::new(HashMap::new()));
 
         let pending = pending_requests.clone();
+        let handler = request_handler.clone();
+        let listener = notification_listener.clone();
+        let progress = progress_listeners.clone();
+        let reply_transport = transport.clone();
         tokio::spawn(async move {
-            Self::handle_messages(message_rx, pending).await;
+            Self::handle_messages(
+                message_rx,
+                pending,
+                handler,
+                listener,
+                progress,
+                reply_transport,
+            )
+            .await;
         });
 
         Self {
-            transport: TransportType::Remote(transport),
+            transport,
             pending_requests,
+            request_handler,
+            notification_listener,
+            progress_listeners,
+            next_progress_token: AtomicU64::new(1),
             request_timeout: Duration::from_secs(180),
         }
     }
 
-    /// Returns the auth token for a remote connection.
+    /// Returns the auth token (`Authorization` header value) for a remote connection.
     pub async fn get_auth_token(&self) -> Option<String> {
         match &self.transport {
-            TransportType::Remote(transport) => transport.get_auth_token(),
+            TransportType::Remote(transport) => transport.authorization_header().await,
             TransportType::Local(_) => None,
         }
     }
@@ -95,10 +159,27 @@ impl MCPConnection {
         Self::new_local(stdin, message_rx)
     }
 
+    /// Sets the handler for requests sent by the server (sampling, roots, elicitation).
+    pub async fn set_request_handler(&self, handler: Arc<MCPRequestHandler>) {
+        *self.request_handler.write().await = Some(handler);
+    }
+
+    /// Returns a receiver for notifications sent by the server (list changes,
+    /// resource updates). Replaces any previous receiver.
+    pub async fn subscribe_notifications(&self) -> mpsc::UnboundedReceiver<MCPNotification> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        *self.notification_listener.write().await = Some(tx);
+        rx
+    }
+
     /// Handles received messages.
     async fn handle_messages(
         mut rx: mpsc::UnboundedReceiver<MCPMessage>,
         pending_requests: Arc<RwLock<HashMap<u64, ResponseWaiter>>>,
+        request_handler: RequestHandlerSlot,
+        notification_listener: NotificationSlot,
+        progress_listeners: ProgressListeners,
+        transport: TransportType,
     ) {
         while let Some(message) = rx.recv().await {
             match message {
@@ -113,12 +194,112 @@ impl MCPConnection {
                     }
                 }
                 MCPMessage::Notification(notification) => {
-                    debug!("Received MCP notification: method={}", notification.method);
+                    Self::dispatch_notification(
+                        &notification_listener,
+                        &progress_listeners,
+                        notification,
+                    )
+                    .await;
+                }
+                // Notifications without an id may be deserialized as requests
+                MCPMessage::Request(request) if request.id.is_null() => {
+                    let notification = MCPNotification::new(request.method, request.params);
+                    Self::dispatch_notification(
+                        &notification_listener,
+                        &progress_listeners,
+                        notification,
+                    )
+                    .await;
                 }
-                MCPMessage::Request(_request) => {
-                    warn!("Received unexpected request from MCP server");
+                MCPMessage::Request(request) => {
+                    let handler = request_handler.read().await.clone();
+                    let transport = transport.clone();
+                    // Handled concurrently: sampling and elicitation wait for the user
+                    tokio::spawn(async move {
+                        let result = match handler {
+                            Some(handler) => handler.handle(&request).await,
+                            None => Err(MCPError::method_not_found(&request.method)),
+                        };
+                        if let Err(e) = Self::send_reply(&transport, request.id, result).await {
+                            warn!(
+                                "Failed to reply to MCP server request: method={} error={}",
+                                request.method, e
+                            );
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    /// Forwards a notification to the listener, if any. Progress goes to the request that
+    /// carries the token.
+    async fn dispatch_notification(
+        listener: &NotificationSlot,
+        progress_listeners: &ProgressListeners,
+        notification: MCPNotification,
+    ) {
+        debug!("Received MCP notification: method={}", notification.method);
+        if notification.method == "notifications/progress" {
+            let params = notification
+                .params
+                .and_then(|p| serde_json::from_value::<ProgressParams>(p).ok());
+            if let Some(params) = params {
+                let key = progress_key(&params.progress_token);
+                if let Some(tx) = progress_listeners.read().await.get(&key) {
+                    let _ = tx.send(params);
                 }
             }
+            return;
+        }
+        if let Some(tx) = listener.read().await.as_ref() {
+            let _ = tx.send(notification);
+        }
+    }
+
+    /// Sends the reply to a request from the server.
+    async fn send_reply(
+        transport: &TransportType,
+        id: Value,
+        result: Result<Value, MCPError>,
+    ) -> BitFunResult<()> {
+        match transport {
+            TransportType::Local(transport) => match result {
+                Ok(result) => transport.send_response(id, result).await,
+                Err(error) => transport.send_error(id, error).await,
+            },
+            TransportType::Remote(transport) => {
+                let response = match result {
+                    Ok(result) => MCPResponse::success(id, result),
+                    Err(error) => MCPResponse::error(id, error),
+                };
+                transport
+                    .send_message(&MCPMessage::Response(response))
+                    .await
+            }
+        }
+    }
+
+    /// Sends a notification.
+    async fn send_notification(&self, notification: MCPNotification) -> BitFunResult<()> {
+        Self::send_notification_via(&self.transport, notification).await
+    }
+
+    async fn send_notification_via(
+        transport: &TransportType,
+        notification: MCPNotification,
+    ) -> BitFunResult<()> {
+        match transport {
+            TransportType::Local(transport) => {
+                transport
+                    .send_notification(notification.method, notification.params)
+                    .await
+            }
+            TransportType::Remote(transport) => {
+                transport
+                    .send_message(&MCPMessage::Notification(notification))
+                    .await
+            }
         }
     }
 
@@ -128,9 +309,70 @@ impl MCPConnection {
         method: String,
         params: Option<Value>,
     ) -> BitFunResult<MCPResponse> {
+        self.send_request_with(method, params, &MCPCallOptions::default())
+            .await
+    }
+
+    /// Sends a request and waits for the response, reporting progress and honouring the
+    /// timeout and cancellation of `options`.
+    ///
+    /// Progress notifications restart the timeout. If the request does not complete (timed
+    /// out, cancelled, or the future is dropped) the server is sent `notifications/cancelled`.
+    async fn send_request_with(
+        &self,
+        method: String,
+        mut params: Option<Value>,
+        options: &MCPCallOptions,
+    ) -> BitFunResult<MCPResponse> {
+        let timeout = options.timeout.unwrap_or(self.request_timeout);
+
+        let mut progress_rx = None;
+        let mut progress_key_registered = None;
+        let mut session_call = None;
+        if options.progress.is_some() || options.session_id.is_some() {
+            let token = format!(
+                "bitfun-{}",
+                self.next_progress_token.fetch_add(1, Ordering::Relaxed)
+            );
+            let params = params.get_or_insert_with(|| Value::Object(Default::default()));
+            if let Some(object) = params.as_object_mut() {
+                object.insert(
+                    "_meta".to_string(),
+                    serde_json::json!({ "progressToken": token }),
+                );
+                if options.progress.is_some() {
+                    let (tx, rx) = mpsc::unbounded_channel();
+                    self.progress_listeners
+                        .write()
+                        .await
+                        .insert(token.clone(), tx);
+                    progress_rx = Some(rx);
+                    progress_key_registered = Some(token.clone());
+                }
+                if let Some(session_id) = options.session_id.clone() {
+                    if let Some(handler) = self.request_handler.read().await.clone() {
+                        handler.begin_call(&token, session_id);
+                        session_call = Some((handler, token));
+                    }
+                }
+            }
+        }
+
+        let mut in_flight = InFlightRequest {
+            request_id: Value::Null,
+            transport: self.transport.clone(),
+            pending_requests: self.pending_requests.clone(),
+            progress_listeners: self.progress_listeners.clone(),
+            progress_key: progress_key_registered,
+            session_call,
+            completed: false,
+            reason: None,
+        };
+
         match &self.transport {
             TransportType::Local(transport) => {
                 let request_id = transport.send_request(method.clone(), params).await?;
+                in_flight.request_id = Value::from(request_id);
 
                 let (tx, rx) = oneshot::channel();
                 {
@@ -138,17 +380,23 @@ impl MCPConnection {
                     pending.insert(request_id, tx);
                 }
 
-                match tokio::time::timeout(self.request_timeout, rx).await {
-                    Ok(Ok(response)) => Ok(response),
-                    Ok(Err(_)) => Err(BitFunError::MCPError(format!(
-                        "Request channel closed for method: {}",
-                        method
-                    ))),
-                    Err(_) => Err(BitFunError::Timeout(format!(
-                        "Request timeout for method: {}",
-                        method
-                    ))),
-                }
+                let response = async {
+                    rx.await.map_err(|_| {
+                        BitFunError::MCPError(format!(
+                            "Request channel closed for method: {}",
+                            method
+                        ))
+                    })
+                };
+                wait_for_response(
+                    &method,
+                    response,
+                    timeout,
+                    progress_rx,
+                    options,
+                    &mut in_flight,
+                )
+                .await
             }
             TransportType::Remote(transport) => {
                 let request_id = SystemTime::now()
@@ -166,18 +414,33 @@ impl MCPConnection {
                     method: method.clone(),
                     params,
                 };
+                in_flight.request_id = request.id.clone();
 
-                let response_value = transport.send_request(&request).await?;
-
-                let response: MCPResponse =
-                    serde_json::from_value(response_value).map_err(|e| {
+                // The HTTP timeout only caps the request; idle time is checked below
+                let http_timeout = if progress_rx.is_some() {
+                    MAX_REQUEST_DURATION
+                } else {
+                    timeout
+                };
+                let response = async {
+                    let response_value =
+                        transport.send_request(&request, Some(http_timeout)).await?;
+                    serde_json::from_value::<MCPResponse>(response_value).map_err(|e| {
                         BitFunError::MCPError(format!(
                             "Failed to parse response for method {}: {}",
                             method, e
                         ))
-                    })?;
-
-                Ok(response)
+                    })
+                };
+                wait_for_response(
+                    &method,
+                    response,
+                    timeout,
+                    progress_rx,
+                    options,
+                    &mut in_flight,
+                )
+                .await
             }
         }
     }
@@ -192,7 +455,10 @@ impl MCPConnection {
         let response = self
             .send_request_and_wait(request.method.clone(), request.params)
             .await?;
-        parse_response_result(&response)
+        let result = parse_response_result(&response)?;
+        self.send_notification(create_initialized_notification())
+            .await?;
+        Ok(result)
     }
 
     /// Lists resources.
@@ -216,6 +482,24 @@ impl MCPConnection {
         parse_response_result(&response)
     }
 
+    /// Subscribes to updates of a resource (`notifications/resources/updated`).
+    pub async fn subscribe_resource(&self, uri: &str) -> BitFunResult<()> {
+        let request = create_resources_subscribe_request(0, uri);
+        let response = self
+            .send_request_and_wait(request.method.clone(), request.params)
+            .await?;
+        parse_response_result::<Value>(&response).map(|_| ())
+    }
+
+    /// Cancels a resource subscription.
+    pub async fn unsubscribe_resource(&self, uri: &str) -> BitFunResult<()> {
+        let request = create_resources_unsubscribe_request(0, uri);
+        let response = self
+            .send_request_and_wait(request.method.clone(), request.params)
+            .await?;
+        parse_response_result::<Value>(&response).map(|_| ())
+    }
+
     /// Lists prompts.
     pub async fn list_prompts(&self, cursor: Option<String>) -> BitFunResult<PromptsListResult> {
         let request = create_prompts_list_request(0, cursor);
@@ -252,12 +536,23 @@ impl MCPConnection {
         &self,
         name: &str,
         arguments: Option<Value>,
+    ) -> BitFunResult<MCPToolResult> {
+        self.call_tool_with_options(name, arguments, MCPCallOptions::default())
+            .await
+    }
+
+    /// Calls a tool with progress reporting, a per-call timeout and cancellation.
+    pub async fn call_tool_with_options(
+        &self,
+        name: &str,
+        arguments: Option<Value>,
+        options: MCPCallOptions,
     ) -> BitFunResult<MCPToolResult> {
         debug!("Calling MCP tool: name={}", name);
         let request = create_tools_call_request(0, name, arguments);
 
         let response = self
-            .send_request_and_wait(request.method.clone(), request.params)
+            .send_request_with(request.method.clone(), request.params, &options)
             .await?;
 
         parse_response_result(&response)
@@ -273,6 +568,125 @@ impl MCPConnection {
     }
 }
 
+/// Waits for a response while forwarding progress, restarting the timeout on each progress
+/// notification.
+async fn wait_for_response(
+    method: &str,
+    response: impl Future<Output = BitFunResult<MCPResponse>>,
+    timeout: Duration,
+    mut progress_rx: Option<mpsc::UnboundedReceiver<ProgressParams>>,
+    options: &MCPCallOptions,
+    in_flight: &mut InFlightRequest,
+) -> BitFunResult<MCPResponse> {
+    tokio::pin!(response);
+    let started = Instant::now();
+
+    loop {
+        let remaining = MAX_REQUEST_DURATION.saturating_sub(started.elapsed());
+        let progress = async {
+            match progress_rx.as_mut() {
+                Some(rx) => rx.recv().await,
+                None => std::future::pending().await,
+            }
+        };
+        let cancelled = async {
+            match &options.cancellation_token {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            result = &mut response => {
+                in_flight.completed = true;
+                return result;
+            }
+            Some(update) = progress => {
+                debug!(
+                    "MCP progress: method={} progress={} total={:?}",
+                    method, update.progress, update.total
+                );
+                if let Some(tx) = &options.progress {
+                    let _ = tx.send(update);
+                }
+            }
+            _ = tokio::time::sleep(timeout.min(remaining)) => {
+                in_flight.reason = Some("Request timed out".to_string());
+                return Err(BitFunError::Timeout(format!(
+                    "Request timeout for method: {}",
+                    method
+                )));
+            }
+            _ = cancelled => {
+                in_flight.reason = Some("Cancelled by the user".to_string());
+                return Err(BitFunError::Cancelled(format!(
+                    "Request cancelled for method: {}",
+                    method
+                )));
+            }
+        }
+    }
+}
+
+/// Cleans up after a request. If the request did not complete, the server is told to stop
+/// working on it.
+struct InFlightRequest {
+    request_id: Value,
+    transport: TransportType,
+    pending_requests: Arc<RwLock<HashMap<u64, ResponseWaiter>>>,
+    progress_listeners: ProgressListeners,
+    progress_key: Option<String>,
+    /// Request handler the call was recorded with, and its progress token
+    session_call: Option<(Arc<MCPRequestHandler>, String)>,
+    completed: bool,
+    reason: Option<String>,
+}
+
+impl Drop for InFlightRequest {
+    fn drop(&mut self) {
+        if let Some((handler, token)) = self.session_call.take() {
+            handler.end_call(&token);
+        }
+        if self.completed && self.progress_key.is_none() {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let request_id = self.request_id.clone();
+        let transport = self.transport.clone();
+        let pending_requests = self.pending_requests.clone();
+        let progress_listeners = self.progress_listeners.clone();
+        let progress_key = self.progress_key.take();
+        let cancel = !self.completed && !request_id.is_null();
+        let reason = self.reason.take();
+        handle.spawn(async move {
+            if let Some(key) = progress_key {
+                progress_listeners.write().await.remove(&key);
+            }
+            if !cancel {
+                return;
+            }
+            if let Some(id) = request_id.as_u64() {
+                pending_requests.write().await.remove(&id);
+            }
+            debug!("Cancelling MCP request: id={}", request_id);
+            let notification = create_cancelled_notification(request_id, reason);
+            if let Err(e) = MCPConnection::send_notification_via(&transport, notification).await {
+                warn!("Failed to send MCP cancellation: {}", e);
+            }
+        });
+    }
+}
+
+pub(super) fn progress_key(token: &Value) -> String {
+    match token {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
 /// MCP connection pool.
 pub struct MCPConnectionPool {
     connections: Arc<RwLock<HashMap<String, Arc<MCPConnection>>>>,
@@ -316,3 +730,39 @@ impl Default for MCPConnectionPool {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_progress_is_routed_by_token() {
+        let listener: NotificationSlot = Arc::new(RwLock::new(None));
+        let progress_listeners: ProgressListeners = Arc::new(RwLock::new(HashMap::new()));
+        let (notification_tx, mut notification_rx) = mpsc::unbounded_channel();
+        *listener.write().await = Some(notification_tx);
+        let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+        progress_listeners
+            .write()
+            .await
+            .insert("bitfun-1".to_string(), progress_tx);
+
+        let progress = MCPNotification::new(
+            "notifications/progress".to_string(),
+            Some(json!({ "progressToken": "bitfun-1", "progress": 2, "total": 4 })),
+        );
+        MCPConnection::dispatch_notification(&listener, &progress_listeners, progress).await;
+        let other = MCPNotification::new("notifications/tools/list_changed".to_string(), None);
+        MCPConnection::dispatch_notification(&listener, &progress_listeners, other).await;
+
+        let update = progress_rx.try_recv().unwrap();
+        assert_eq!(update.progress, 2.0);
+        assert_eq!(update.total, Some(4.0));
+        assert_eq!(
+            notification_rx.try_recv().unwrap().method,
+            "notifications/tools/list_changed"
+        );
+        assert!(notification_rx.try_recv().is_err());
+    }
+}
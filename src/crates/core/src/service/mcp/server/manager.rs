@@ -2,10 +2,14 @@
 //!
 //! Manages the lifecycle of all MCP servers.
 
+use super::catalog::MCPCatalog;
 use super::connection::{MCPConnection, MCPConnectionPool};
+use super::request_handler::MCPRequestHandler;
 use super::{MCPServerConfig, MCPServerRegistry, MCPServerStatus};
 use crate::service::mcp::adapter::tool::MCPToolAdapter;
+use crate::service::mcp::auth::{MCPOAuthClient, OAuthCredentialStore, OAuthSettings};
 use crate::service::mcp::config::MCPConfigService;
+use crate::service::mcp::protocol::{MCPPrompt, MCPResource, MCPResourceContent};
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, error, info, warn};
 use std::sync::Arc;
@@ -15,6 +19,7 @@ pub struct MCPServerManager {
     registry: Arc<MCPServerRegistry>,
     connection_pool: Arc<MCPConnectionPool>,
     config_service: Arc<MCPConfigService>,
+    catalog: Arc<MCPCatalog>,
 }
 
 impl MCPServerManager {
@@ -24,6 +29,7 @@ impl MCPServerManager {
             registry: Arc::new(MCPServerRegistry::new()),
             connection_pool: Arc::new(MCPConnectionPool::new()),
             config_service,
+            catalog: Arc::new(MCPCatalog::new()),
         }
     }
 
@@ -139,6 +145,8 @@ impl MCPServerManager {
             return Ok(());
         }
 
+        proc.set_request_handler(Arc::new(MCPRequestHandler::new(&config)));
+
         match config.server_type {
             super::MCPServerType::Local => {
                 let command = config.command.as_ref().ok_or_else(|| {
@@ -172,6 +180,8 @@ impl MCPServerManager {
                     url, server_id
                 );
 
+                proc.set_oauth_client(Self::build_oauth_client(&config, url));
+
                 proc.start_remote(url, &config.env).await.map_err(|e| {
                     error!(
                         "Failed to connect to remote MCP server: url={} id={} error={}",
@@ -193,6 +203,14 @@ impl MCPServerManager {
                 .add_connection(server_id.to_string(), connection.clone())
                 .await;
 
+            let notifications = connection.subscribe_notifications().await;
+            self.catalog.start_listener(
+                server_id.to_string(),
+                config.name.clone(),
+                connection.clone(),
+                notifications,
+            );
+
             match Self::register_mcp_tools(server_id, &config.name, connection).await {
                 Ok(count) => {
                     info!(
@@ -231,6 +249,7 @@ impl MCPServerManager {
         let stop_result = proc.stop().await;
 
         self.connection_pool.remove_connection(server_id).await;
+        self.catalog.remove_server(server_id).await;
 
         Self::unregister_mcp_tools(server_id).await;
 
@@ -305,6 +324,49 @@ impl MCPServerManager {
         self.connection_pool.get_connection(server_id).await
     }
 
+    /// Lists the prompts of a server (cached until the server reports a change).
+    pub async fn list_prompts(&self, server_id: &str) -> BitFunResult<Vec<MCPPrompt>> {
+        let connection = self.require_connection(server_id).await?;
+        self.catalog.prompts(server_id, &connection).await
+    }
+
+    /// Lists the resources of a server (cached until the server reports a change).
+    pub async fn list_resources(&self, server_id: &str) -> BitFunResult<Vec<MCPResource>> {
+        let connection = self.require_connection(server_id).await?;
+        self.catalog.resources(server_id, &connection).await
+    }
+
+    /// Subscribes to a resource; its contents are refreshed on every update.
+    pub async fn subscribe_resource(
+        &self,
+        server_id: &str,
+        uri: &str,
+    ) -> BitFunResult<Vec<MCPResourceContent>> {
+        let connection = self.require_connection(server_id).await?;
+        self.catalog.subscribe(server_id, uri, &connection).await
+    }
+
+    /// Cancels a resource subscription.
+    pub async fn unsubscribe_resource(&self, server_id: &str, uri: &str) -> BitFunResult<()> {
+        let connection = self.require_connection(server_id).await?;
+        self.catalog.unsubscribe(server_id, uri, &connection).await
+    }
+
+    /// Returns the latest contents of a subscribed resource.
+    pub async fn get_subscribed_resource(
+        &self,
+        server_id: &str,
+        uri: &str,
+    ) -> Option<Vec<MCPResourceContent>> {
+        self.catalog.subscribed_contents(server_id, uri).await
+    }
+
+    async fn require_connection(&self, server_id: &str) -> BitFunResult<Arc<MCPConnection>> {
+        self.get_connection(server_id).await.ok_or_else(|| {
+            BitFunError::NotFound(format!("MCP server connection not found: {}", server_id))
+        })
+    }
+
     /// Returns all server IDs.
     pub async fn get_all_server_ids(&self) -> Vec<String> {
         self.registry.get_all_server_ids().await
@@ -385,6 +447,50 @@ impl MCPServerManager {
         Ok(())
     }
 
+    /// Deletes the stored OAuth credentials of a server; it authorizes again on next start.
+    pub async fn clear_oauth_credentials(&self, server_id: &str) -> BitFunResult<()> {
+        OAuthCredentialStore::user_default()?
+            .remove(server_id)
+            .await
+    }
+
+    /// Builds the OAuth client of a remote server, unless it uses a static token.
+    fn build_oauth_client(config: &MCPServerConfig, url: &str) -> Option<Arc<MCPOAuthClient>> {
+        let has_static_token =
+            config.env.contains_key("Authorization") || config.env.contains_key("AUTHORIZATION");
+        if has_static_token {
+            return None;
+        }
+
+        let settings: OAuthSettings = config
+            .settings
+            .get("oauth")
+            .and_then(|value| {
+                serde_json::from_value(value.clone())
+                    .map_err(|e| {
+                        warn!(
+                            "Invalid OAuth settings for MCP server: id={} error={}",
+                            config.id, e
+                        )
+                    })
+                    .ok()
+            })
+            .unwrap_or_default();
+        if !settings.enabled {
+            return None;
+        }
+
+        let store = OAuthCredentialStore::user_default()
+            .map_err(|e| warn!("MCP OAuth credentials unavailable: {}", e))
+            .ok()?;
+        Some(Arc::new(MCPOAuthClient::new(
+            config.id.clone(),
+            url.to_string(),
+            settings,
+            store,
+        )))
+    }
+
     /// Registers MCP tools into the global tool registry.
     async fn register_mcp_tools(
         server_id: &str,
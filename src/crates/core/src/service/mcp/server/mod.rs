@@ -2,15 +2,19 @@
 //!
 //! Manages MCP server process lifecycles, connections, and registration.
 
+pub mod catalog;
 pub mod connection;
 pub mod manager;
 pub mod process;
 pub mod registry;
+pub mod request_handler;
 
-pub use connection::{MCPConnection, MCPConnectionPool};
+pub use catalog::{MCPCatalog, MCP_LIST_CHANGED_EVENT, MCP_RESOURCE_UPDATED_EVENT};
+pub use connection::{MCPCallOptions, MCPConnection, MCPConnectionPool};
 pub use manager::MCPServerManager;
 pub use process::{MCPServerProcess, MCPServerStatus, MCPServerType};
 pub use registry::MCPServerRegistry;
+pub use request_handler::{MCPRequestHandler, MCPSamplingSettings};
 
 /// MCP server configuration.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -3,8 +3,10 @@
 //! Handles starting, stopping, monitoring, and restarting MCP server processes.
 
 use super::connection::MCPConnection;
+use super::request_handler::MCPRequestHandler;
+use crate::service::mcp::auth::MCPOAuthClient;
 use crate::service::mcp::protocol::{
-    InitializeResult, MCPMessage, MCPServerInfo, RemoteMCPTransport,
+    InitializeResult, MCPCapability, MCPMessage, MCPServerInfo, RemoteMCPTransport,
 };
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, error, info, warn};
@@ -45,12 +47,15 @@ pub struct MCPServerProcess {
     child: Option<Child>,
     connection: Option<Arc<MCPConnection>>,
     server_info: Option<MCPServerInfo>,
+    capabilities: Option<MCPCapability>,
     start_time: Option<Instant>,
     restart_count: u32,
     max_restarts: u32,
     health_check_interval: Duration,
     last_ping_time: Arc<RwLock<Option<Instant>>>,
     message_rx: Option<mpsc::UnboundedReceiver<MCPMessage>>,
+    request_handler: Option<Arc<MCPRequestHandler>>,
+    oauth: Option<Arc<MCPOAuthClient>>,
 }
 
 impl MCPServerProcess {
@@ -64,15 +69,28 @@ impl MCPServerProcess {
             child: None,
             connection: None,
             server_info: None,
+            capabilities: None,
             start_time: None,
             restart_count: 0,
             max_restarts: 3,
             health_check_interval: Duration::from_secs(30),
             last_ping_time: Arc::new(RwLock::new(None)),
             message_rx: None,
+            request_handler: None,
+            oauth: None,
         }
     }
 
+    /// Sets the OAuth client used by remote servers that require authorization.
+    pub fn set_oauth_client(&mut self, oauth: Option<Arc<MCPOAuthClient>>) {
+        self.oauth = oauth;
+    }
+
+    /// Sets the handler for requests sent by the server; applied on the next start.
+    pub fn set_request_handler(&mut self, handler: Arc<MCPRequestHandler>) {
+        self.request_handler = Some(handler);
+    }
+
     /// Starts the server process.
     pub async fn start(
         &mut self,
@@ -175,7 +193,9 @@ impl MCPServerProcess {
 
         let connection = Arc::new(MCPConnection::new_remote(
             url.to_string(),
-            auth_token.clone(),
+            auth_token,
+            self.oauth.clone(),
+            tx.clone(),
             rx,
         ));
         self.connection = Some(connection.clone());
@@ -184,6 +204,7 @@ impl MCPServerProcess {
         self.handshake().await?;
 
         let session_id = connection.get_session_id().await;
+        let auth_token = connection.get_auth_token().await;
         RemoteMCPTransport::start_sse_loop(url.to_string(), session_id, auth_token, tx);
 
         self.set_status(MCPServerStatus::Connected).await;
@@ -204,6 +225,10 @@ impl MCPServerProcess {
             .as_ref()
             .ok_or_else(|| BitFunError::MCPError("Connection not established".to_string()))?;
 
+        if let Some(handler) = &self.request_handler {
+            connection.set_request_handler(handler.clone()).await;
+        }
+
         debug!(
             "Initiating handshake with MCP server: name={} id={}",
             self.name, self.id
@@ -223,6 +248,7 @@ impl MCPServerProcess {
         );
 
         self.server_info = Some(result.server_info);
+        self.capabilities = Some(result.capabilities);
         Ok(())
     }
 
@@ -299,6 +325,11 @@ impl MCPServerProcess {
         self.server_info.as_ref()
     }
 
+    /// Returns the capabilities announced by the server.
+    pub fn capabilities(&self) -> Option<&MCPCapability> {
+        self.capabilities.as_ref()
+    }
+
     /// Starts health checks.
     fn start_health_check(&self) {
         let status = self.status.clone();
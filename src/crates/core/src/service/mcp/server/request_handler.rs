@@ -0,0 +1,647 @@
+//! Handling of requests sent by MCP servers
+//!
+//! Servers may call back into the client while a tool call is running. Prompts are shown in the
+//! chat session of the call the request belongs to, found by the progress token in the
+//! request's `_meta` or, without one, by the only session with calls in progress:
+//! - `sampling/createMessage`: a model completion, run through `AIClient` after the user
+//!   approved it
+//! - `roots/list`: the workspace folders the server may operate on
+//! - `elicitation/create`: structured input from the user, asked through the same
+//!   channel as the AskUserQuestion tool
+//! - `ping`
+
+use super::connection::progress_key;
+use super::MCPServerConfig;
+use crate::agentic::tools::user_input_manager::get_user_input_manager;
+use crate::infrastructure::ai::get_global_ai_client_factory;
+use crate::infrastructure::events::event_system::{get_global_event_system, BackendEvent};
+use crate::infrastructure::get_workspace_path;
+use crate::service::mcp::protocol::{
+    CreateMessageParams, CreateMessageResult, ElicitParams, ElicitResult, MCPError, MCPRequest,
+    MCPRoot, RootsListResult, SamplingContent,
+};
+use crate::util::types::Message as AIMessage;
+use log::{debug, info, warn};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long to wait for the user to answer an approval or elicitation
+const USER_INPUT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Answer label for skipping an optional elicitation field
+const SKIP_OPTION: &str = "Skip";
+
+/// Sampling settings of a server (`settings.sampling` in the server config).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MCPSamplingSettings {
+    /// Whether the server may request completions.
+    pub enabled: bool,
+    /// Model ID used for completions; "primary" or "fast" select the default models.
+    /// Defaults to the fast model when the server prefers speed, otherwise the primary model.
+    pub model: Option<String>,
+    /// Run completions without asking the user.
+    pub auto_approve: bool,
+}
+
+impl Default for MCPSamplingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            model: None,
+            auto_approve: false,
+        }
+    }
+}
+
+/// Handler for requests sent by one MCP server.
+pub struct MCPRequestHandler {
+    server_id: String,
+    server_name: String,
+    sampling: MCPSamplingSettings,
+    /// Chat sessions of the tool calls in progress, by progress token
+    active_calls: Mutex<HashMap<String, String>>,
+}
+
+impl MCPRequestHandler {
+    /// Creates a handler for a server.
+    pub fn new(config: &MCPServerConfig) -> Self {
+        let sampling = config
+            .settings
+            .get("sampling")
+            .and_then(|value| {
+                serde_json::from_value(value.clone())
+                    .map_err(|e| {
+                        warn!(
+                            "Invalid sampling settings for MCP server: id={} error={}",
+                            config.id, e
+                        )
+                    })
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        Self {
+            server_id: config.id.clone(),
+            server_name: config.name.clone(),
+            sampling,
+            active_calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records a tool call of `session_id` sent with `progress_token`.
+    pub fn begin_call(&self, progress_token: &str, session_id: String) {
+        if let Ok(mut calls) = self.active_calls.lock() {
+            calls.insert(progress_token.to_string(), session_id);
+        }
+    }
+
+    /// Forgets a tool call recorded by `begin_call`.
+    pub fn end_call(&self, progress_token: &str) {
+        if let Ok(mut calls) = self.active_calls.lock() {
+            calls.remove(progress_token);
+        }
+    }
+
+    /// Chat session a server request belongs to
+    ///
+    /// Requests without a known progress token are only attributed when every call in
+    /// progress belongs to the same session.
+    fn session_for(&self, params: &Value) -> Option<String> {
+        let calls = self.active_calls.lock().ok()?;
+        let token = params
+            .get("_meta")
+            .and_then(|meta| meta.get("progressToken"));
+        if let Some(session_id) = token.and_then(|token| calls.get(&progress_key(token))) {
+            return Some(session_id.clone());
+        }
+        let mut sessions = calls.values();
+        let first = sessions.next()?;
+        sessions
+            .all(|session| session == first)
+            .then(|| first.clone())
+    }
+
+    /// Session to show a server request in; rejects requests outside of a tool call
+    fn require_session(&self, params: &Value, method: &str) -> Result<String, MCPError> {
+        self.session_for(params).ok_or_else(|| {
+            warn!(
+                "Rejected MCP server request outside of a tool call: server_id={} method={}",
+                self.server_id, method
+            );
+            MCPError::invalid_request(format!(
+                "{} is only accepted while a tool call of this client is in progress",
+                method
+            ))
+        })
+    }
+
+    /// Handles a request and returns its result.
+    pub async fn handle(self: Arc<Self>, request: &MCPRequest) -> Result<Value, MCPError> {
+        debug!(
+            "Handling MCP server request: server_id={} method={}",
+            self.server_id, request.method
+        );
+        let params = request.params.clone().unwrap_or(Value::Null);
+
+        match request.method.as_str() {
+            "ping" => Ok(json!({})),
+            "roots/list" => Ok(to_value(&Self::list_roots())?),
+            "sampling/createMessage" => {
+                let session_id = self.require_session(&params, &request.method)?;
+                let params: CreateMessageParams = serde_json::from_value(params)
+                    .map_err(|e| MCPError::invalid_params(e.to_string()))?;
+                Ok(to_value(&self.create_message(params, &session_id).await?)?)
+            }
+            "elicitation/create" => {
+                let session_id = self.require_session(&params, &request.method)?;
+                let params: ElicitParams = serde_json::from_value(params)
+                    .map_err(|e| MCPError::invalid_params(e.to_string()))?;
+                Ok(to_value(&self.elicit(params, &session_id).await)?)
+            }
+            other => Err(MCPError::method_not_found(other)),
+        }
+    }
+
+    /// Workspace folders exposed as roots.
+    fn list_roots() -> RootsListResult {
+        let roots = get_workspace_path()
+            .map(|path| {
+                vec![MCPRoot {
+                    uri: path_to_file_uri(&path),
+                    name: path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string()),
+                }]
+            })
+            .unwrap_or_default();
+        RootsListResult { roots }
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageParams,
+        session_id: &str,
+    ) -> Result<CreateMessageResult, MCPError> {
+        if !self.sampling.enabled {
+            return Err(MCPError::invalid_request(format!(
+                "Sampling is disabled for MCP server '{}'",
+                self.server_name
+            )));
+        }
+
+        let model_id = self.sampling.model.clone().unwrap_or_else(|| {
+            let prefers_speed = params.model_preferences.as_ref().is_some_and(|prefs| {
+                prefs.speed_priority.unwrap_or(0.0) > prefs.intelligence_priority.unwrap_or(0.0)
+            });
+            if prefers_speed { "fast" } else { "primary" }.to_string()
+        });
+
+        let factory = get_global_ai_client_factory()
+            .await
+            .map_err(|e| MCPError::internal_error(e.to_string()))?;
+        let client = factory
+            .get_client_resolved(&model_id)
+            .await
+            .map_err(|e| MCPError::internal_error(format!("Sampling model unavailable: {}", e)))?;
+
+        let mut messages = Vec::new();
+        if let Some(system_prompt) = params.system_prompt.as_ref().filter(|s| !s.is_empty()) {
+            messages.push(AIMessage::system(system_prompt.clone()));
+        }
+        for message in &params.messages {
+            let text = match &message.content {
+                SamplingContent::Text { text } => text.clone(),
+                SamplingContent::Image { mime_type, .. }
+                | SamplingContent::Audio { mime_type, .. } => {
+                    return Err(MCPError::invalid_params(format!(
+                        "Unsupported sampling content: {}",
+                        mime_type
+                    )));
+                }
+            };
+            messages.push(match message.role.as_str() {
+                "assistant" => AIMessage::assistant(text),
+                _ => AIMessage::user(text),
+            });
+        }
+
+        if !self.sampling.auto_approve {
+            let preview = params
+                .messages
+                .iter()
+                .rev()
+                .find_map(|m| match &m.content {
+                    SamplingContent::Text { text } if m.role == "user" => Some(text.as_str()),
+                    _ => None,
+                })
+                .unwrap_or("");
+            let question = format!(
+                "MCP server '{}' requests a completion from model '{}' (up to {} tokens). Last message: \"{}\"",
+                self.server_name,
+                client.config.model,
+                params.max_tokens,
+                truncate(preview, 500)
+            );
+            let answers = self
+                .ask_user(
+                    session_id,
+                    "sampling",
+                    json!([{
+                        "question": question,
+                        "header": "MCP sampling",
+                        "options": [
+                            { "label": "Allow", "description": "Send the request to the model and return the result to the server" },
+                            { "label": "Deny", "description": "Reject the request" }
+                        ],
+                        "multiSelect": false
+                    }]),
+                )
+                .await;
+            if answer_text(answers.as_ref(), 0).as_deref() != Some("Allow") {
+                info!(
+                    "MCP sampling request rejected: server_id={}",
+                    self.server_id
+                );
+                return Err(MCPError::invalid_request(
+                    "User rejected the sampling request",
+                ));
+            }
+        }
+
+        let mut extra_body = client
+            .config
+            .custom_request_body
+            .clone()
+            .filter(|body| body.is_object())
+            .unwrap_or_else(|| json!({}));
+        if matches!(
+            client.config.format.to_lowercase().as_str(),
+            "openai" | "anthropic"
+        ) {
+            extra_body["max_tokens"] = json!(params.max_tokens);
+            if let Some(temperature) = params.temperature {
+                extra_body["temperature"] = json!(temperature);
+            }
+            if !params.stop_sequences.is_empty() {
+                let key = if client.config.format.eq_ignore_ascii_case("anthropic") {
+                    "stop_sequences"
+                } else {
+                    "stop"
+                };
+                extra_body[key] = json!(params.stop_sequences);
+            }
+        }
+
+        info!(
+            "Running MCP sampling request: server_id={} model={}",
+            self.server_id, client.config.model
+        );
+        let response = client
+            .send_message_with_extra_body(messages, None, Some(extra_body))
+            .await
+            .map_err(|e| MCPError::internal_error(format!("Sampling failed: {}", e)))?;
+
+        let stop_reason = match response.finish_reason.as_deref() {
+            Some("length" | "max_tokens" | "MAX_TOKENS") => "maxTokens",
+            Some("stop_sequence") => "stopSequence",
+            _ => "endTurn",
+        };
+        Ok(CreateMessageResult {
+            role: "assistant".to_string(),
+            content: SamplingContent::Text {
+                text: response.text,
+            },
+            model: client.config.model.clone(),
+            stop_reason: Some(stop_reason.to_string()),
+        })
+    }
+
+    /// Asks the user for the fields of the requested schema.
+    ///
+    /// Each property becomes a question; a last question lets the user decline.
+    async fn elicit(&self, params: ElicitParams, session_id: &str) -> ElicitResult {
+        let fields = ElicitField::from_schema(&params.requested_schema);
+
+        let mut questions: Vec<Value> = fields.iter().map(ElicitField::question).collect();
+        questions.push(json!({
+            "question": format!("MCP server '{}' asks: {}", self.server_name, params.message),
+            "header": "MCP request",
+            "options": [
+                { "label": "Accept", "description": "Send the answers above to the server" },
+                { "label": "Decline", "description": "Do not share this information" }
+            ],
+            "multiSelect": false
+        }));
+
+        let Some(answers) = self
+            .ask_user(session_id, "elicitation", Value::Array(questions))
+            .await
+        else {
+            return ElicitResult {
+                action: "cancel".to_string(),
+                content: None,
+            };
+        };
+        if answer_text(Some(&answers), fields.len()).as_deref() != Some("Accept") {
+            return ElicitResult {
+                action: "decline".to_string(),
+                content: None,
+            };
+        }
+
+        let mut content = Map::new();
+        for (index, field) in fields.iter().enumerate() {
+            if let Some(value) = answer_text(Some(&answers), index).and_then(|a| field.parse(&a)) {
+                content.insert(field.name.clone(), value);
+            }
+        }
+        ElicitResult {
+            action: "accept".to_string(),
+            content: Some(content),
+        }
+    }
+
+    /// Shows questions to the user and waits for the answers.
+    ///
+    /// Returns None if the user did not answer in time.
+    async fn ask_user(&self, session_id: &str, kind: &str, questions: Value) -> Option<Value> {
+        let request_id = format!("mcp_{}_{}_{}", kind, self.server_id, uuid::Uuid::new_v4());
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let manager = get_user_input_manager();
+        manager.register_channel(request_id.clone(), tx);
+
+        let event = BackendEvent::ToolAwaitingUserInput {
+            tool_id: request_id.clone(),
+            session_id: session_id.to_string(),
+            questions: json!({ "questions": questions }),
+        };
+        let _ = get_global_event_system().emit(event).await;
+
+        match tokio::time::timeout(USER_INPUT_TIMEOUT, rx).await {
+            Ok(Ok(response)) => Some(response.answers),
+            Ok(Err(_)) => None,
+            Err(_) => {
+                warn!(
+                    "MCP {} request timed out waiting for user: server_id={}",
+                    kind, self.server_id
+                );
+                manager.cancel(&request_id);
+                None
+            }
+        }
+    }
+}
+
+/// Field of an elicitation schema
+#[derive(Debug, Clone)]
+struct ElicitField {
+    name: String,
+    title: String,
+    description: Option<String>,
+    kind: String,
+    required: bool,
+    /// Enum values and their labels
+    choices: Vec<(Value, String)>,
+    default: Option<Value>,
+}
+
+impl ElicitField {
+    fn from_schema(schema: &Value) -> Vec<Self> {
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+
+        let Some(properties) = schema.get("properties").and_then(|v| v.as_object()) else {
+            return Vec::new();
+        };
+
+        properties
+            .iter()
+            .map(|(name, property)| {
+                let values = property
+                    .get("enum")
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                let names = property.get("enumNames").and_then(|v| v.as_array());
+                let choices = values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        let label = names
+                            .and_then(|names| names.get(i))
+                            .and_then(|v| v.as_str())
+                            .map(str::to_string)
+                            .unwrap_or_else(|| value_label(value));
+                        (value.clone(), label)
+                    })
+                    .collect();
+
+                Self {
+                    name: name.clone(),
+                    title: property
+                        .get("title")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(name)
+                        .to_string(),
+                    description: property
+                        .get("description")
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string),
+                    kind: property
+                        .get("type")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("string")
+                        .to_string(),
+                    required: required.contains(&name.as_str()),
+                    choices,
+                    default: property.get("default").cloned(),
+                }
+            })
+            .collect()
+    }
+
+    /// Question for the AskUserQuestion card; free-form values are entered via "Other"
+    fn question(&self) -> Value {
+        let mut options: Vec<Value> = if !self.choices.is_empty() {
+            self.choices
+                .iter()
+                .map(|(_, label)| json!({ "label": label, "description": label }))
+                .collect()
+        } else if self.kind == "boolean" {
+            vec![
+                json!({ "label": "Yes", "description": "true" }),
+                json!({ "label": "No", "description": "false" }),
+            ]
+        } else {
+            self.default
+                .iter()
+                .map(|default| {
+                    json!({ "label": value_label(default), "description": "Default value" })
+                })
+                .collect()
+        };
+        if !self.required {
+            options.push(json!({ "label": SKIP_OPTION, "description": "Leave this field empty" }));
+        }
+
+        let mut question = self.title.clone();
+        if let Some(description) = &self.description {
+            question.push_str(&format!(" ({})", description));
+        }
+        if options.is_empty() || (self.choices.is_empty() && self.kind != "boolean") {
+            question.push_str(" Enter a value with \"Other\".");
+        }
+
+        json!({
+            "question": question,
+            "header": truncate(&self.title, 20),
+            "options": options,
+            "multiSelect": false
+        })
+    }
+
+    /// Converts an answer to a value of the field's type
+    fn parse(&self, answer: &str) -> Option<Value> {
+        let answer = answer.trim();
+        if answer.is_empty() || (!self.required && answer == SKIP_OPTION) {
+            return None;
+        }
+        if let Some((value, _)) = self.choices.iter().find(|(_, label)| label == answer) {
+            return Some(value.clone());
+        }
+
+        match self.kind.as_str() {
+            "boolean" => match answer.to_lowercase().as_str() {
+                "yes" | "true" => Some(Value::Bool(true)),
+                "no" | "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            "integer" => answer.parse::<i64>().ok().map(Value::from),
+            "number" => answer.parse::<f64>().ok().map(Value::from),
+            _ => Some(Value::String(answer.to_string())),
+        }
+    }
+}
+
+/// Answer to the question at `index` (answers are keyed by question index)
+fn answer_text(answers: Option<&Value>, index: usize) -> Option<String> {
+    let answers = answers?;
+    let answers = answers
+        .get("answers")
+        .filter(|v| v.is_object())
+        .unwrap_or(answers);
+    match answers.get(index.to_string())? {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) => items.first().and_then(|v| v.as_str()).map(str::to_string),
+        other => Some(value_label(other)),
+    }
+}
+
+fn value_label(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let truncated: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+        format!("{}...", truncated)
+    }
+}
+
+fn to_value<T: serde::Serialize>(value: &T) -> Result<Value, MCPError> {
+    serde_json::to_value(value).map_err(|e| MCPError::internal_error(e.to_string()))
+}
+
+/// `file://` URI of a local path
+fn path_to_file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        format!("file:///{}", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elicit_fields() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "confirm": { "type": "boolean", "title": "Confirm" },
+                "count": { "type": "integer", "default": 3 },
+                "env": { "type": "string", "enum": ["prod", "dev"], "enumNames": ["Production", "Development"] }
+            },
+            "required": ["env", "confirm"]
+        });
+        let fields = ElicitField::from_schema(&schema);
+        let field = |name: &str| fields.iter().find(|f| f.name == name).unwrap();
+
+        assert_eq!(field("confirm").parse("Yes"), Some(Value::Bool(true)));
+        assert_eq!(field("count").parse("42"), Some(json!(42)));
+        assert_eq!(field("count").parse(SKIP_OPTION), None);
+        assert_eq!(field("env").parse("Production"), Some(json!("prod")));
+
+        let question = field("count").question();
+        assert_eq!(question["options"][0]["label"], "3");
+        assert_eq!(question["options"][1]["label"], SKIP_OPTION);
+
+        let answers = json!({ "0": "Development", "1": ["Accept"] });
+        assert_eq!(
+            answer_text(Some(&answers), 0).as_deref(),
+            Some("Development")
+        );
+        assert_eq!(answer_text(Some(&answers), 1).as_deref(), Some("Accept"));
+        assert_eq!(answer_text(Some(&answers), 2), None);
+    }
+
+    #[test]
+    fn test_requests_are_routed_to_the_session_of_their_call() {
+        let handler = MCPRequestHandler {
+            server_id: "server".to_string(),
+            server_name: "Server".to_string(),
+            sampling: MCPSamplingSettings::default(),
+            active_calls: Mutex::new(HashMap::new()),
+        };
+        let with_token = |token: &str| json!({ "_meta": { "progressToken": token } });
+
+        assert_eq!(handler.session_for(&json!({})), None);
+        assert!(handler
+            .require_session(&json!({}), "elicitation/create")
+            .is_err());
+
+        handler.begin_call("bitfun-1", "session-a".to_string());
+        assert_eq!(
+            handler.session_for(&json!({})).as_deref(),
+            Some("session-a")
+        );
+
+        handler.begin_call("bitfun-2", "session-b".to_string());
+        assert_eq!(
+            handler.session_for(&with_token("bitfun-2")).as_deref(),
+            Some("session-b")
+        );
+        assert_eq!(handler.session_for(&json!({})), None);
+
+        handler.end_call("bitfun-1");
+        handler.end_call("bitfun-2");
+        assert_eq!(handler.session_for(&with_token("bitfun-2")), None);
+    }
+}
@@ -12,6 +12,7 @@ pub mod git; // Git service
 pub mod i18n; // I18n service
 pub mod lsp; // LSP (Language Server Protocol) system
 pub mod mcp; // MCP (Model Context Protocol) system
+pub mod plan; // Plan documents
 pub mod project_context; // Project context management
 pub mod snapshot; // Snapshot-based change tracking
 pub mod system; // System command detection and execution
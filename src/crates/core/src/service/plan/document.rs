@@ -0,0 +1,367 @@
+//! Plan documents
+//!
+//! A plan is a markdown file under `.bitfun/plans/` with YAML front-matter holding its name,
+//! overview, version and steps. Each step has a stable id that todos refer to while the plan
+//! is executed. Saving a plan whose content changed bumps its version; every version is kept
+//! under `plans/history/`, and status-only updates are written in place.
+
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Status of a plan step, matching the todo statuses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStepStatus {
+    #[default]
+    Pending,
+    InProgress,
+    Completed,
+}
+
+impl PlanStepStatus {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "in_progress" => Some(Self::InProgress),
+            "completed" => Some(Self::Completed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+        }
+    }
+}
+
+/// One step of a plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStep {
+    pub id: String,
+    pub content: String,
+    #[serde(default)]
+    pub status: PlanStepStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+}
+
+/// A plan document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanDocument {
+    pub name: String,
+    pub overview: String,
+    /// Starts at 1 and increases each time the plan's content changes
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default, rename = "todos", skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<PlanStep>,
+    /// Markdown after the front-matter
+    #[serde(skip)]
+    pub body: String,
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+impl PlanDocument {
+    pub fn new(name: &str, overview: &str, body: &str, steps: Vec<PlanStep>) -> BitFunResult<Self> {
+        let document = Self {
+            name: name.to_string(),
+            overview: overview.to_string(),
+            version: 1,
+            steps,
+            body: body.to_string(),
+        };
+        document.validate()?;
+        Ok(document)
+    }
+
+    /// Parses a plan file's content.
+    pub fn parse(content: &str) -> BitFunResult<Self> {
+        let content = content.trim_start_matches('\u{feff}');
+        let rest = content
+            .strip_prefix("---\n")
+            .or_else(|| content.strip_prefix("---\r\n"))
+            .ok_or_else(|| BitFunError::validation("Plan file has no front-matter"))?;
+        let (yaml, body) = match rest.find("\n---") {
+            Some(end) => {
+                let body = &rest[end + 4..];
+                let body = body.strip_prefix('\r').unwrap_or(body);
+                (&rest[..end], body.strip_prefix('\n').unwrap_or(body))
+            }
+            None => return Err(BitFunError::validation("Plan front-matter is not closed")),
+        };
+
+        let mut document: PlanDocument = serde_yaml::from_str(yaml)
+            .map_err(|e| BitFunError::validation(format!("Invalid plan front-matter: {}", e)))?;
+        document.body = body.trim_start_matches(['\r', '\n']).to_string();
+        document.validate()?;
+        Ok(document)
+    }
+
+    /// Renders the plan as a markdown file.
+    pub fn render(&self) -> String {
+        let yaml = serde_yaml::to_string(self).unwrap_or_default();
+        format!("---\n{}---\n\n{}", yaml, self.body)
+    }
+
+    pub fn step(&self, id: &str) -> Option<&PlanStep> {
+        self.steps.iter().find(|step| step.id == id)
+    }
+
+    /// Whether every step is completed
+    pub fn is_finished(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| step.status == PlanStepStatus::Completed)
+    }
+
+    /// Sets the status of the steps whose ids appear in `updates`; returns whether any changed.
+    pub fn apply_statuses<'a>(
+        &mut self,
+        updates: impl IntoIterator<Item = (&'a str, PlanStepStatus)>,
+    ) -> bool {
+        let mut changed = false;
+        for (id, status) in updates {
+            if let Some(step) = self.steps.iter_mut().find(|step| step.id == id) {
+                if step.status != status {
+                    step.status = status;
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Whether two documents differ in anything but step statuses and version
+    fn same_content(&self, other: &PlanDocument) -> bool {
+        let strip = |document: &PlanDocument| {
+            let mut document = document.clone();
+            document.version = 0;
+            for step in &mut document.steps {
+                step.status = PlanStepStatus::Pending;
+            }
+            document.body = document.body.trim_end().to_string();
+            document
+        };
+        strip(self) == strip(other)
+    }
+
+    fn validate(&self) -> BitFunResult<()> {
+        let mut seen = std::collections::HashSet::new();
+        for step in &self.steps {
+            if step.id.trim().is_empty() {
+                return Err(BitFunError::validation("Plan step id cannot be empty"));
+            }
+            if !seen.insert(step.id.as_str()) {
+                return Err(BitFunError::validation(format!(
+                    "Duplicate plan step id: {}",
+                    step.id
+                )));
+            }
+        }
+        for step in &self.steps {
+            if let Some(missing) = step
+                .dependencies
+                .iter()
+                .find(|dep| !seen.contains(dep.as_str()))
+            {
+                return Err(BitFunError::validation(format!(
+                    "Plan step {} depends on unknown step {}",
+                    step.id, missing
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads and parses a plan file.
+pub async fn load_plan(path: &Path) -> BitFunResult<PlanDocument> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| BitFunError::io(format!("Failed to read plan {}: {}", path.display(), e)))?;
+    PlanDocument::parse(&content)
+}
+
+/// Writes a plan file, versioning content changes.
+///
+/// Every version is also kept as `history/<name>.v<version>.plan.md` next to the plan. Since
+/// the plan file may have been edited directly, `document` is compared with the latest
+/// recorded version rather than the file: if its content (ignoring step statuses) differs, it
+/// gets the next version and is recorded; otherwise it keeps the recorded version.
+pub async fn save_plan(path: &Path, document: &mut PlanDocument) -> BitFunResult<()> {
+    document.validate()?;
+
+    let new_version = match latest_recorded_version(path).await {
+        Some(recorded) if recorded.same_content(document) => {
+            document.version = recorded.version;
+            false
+        }
+        Some(recorded) => {
+            document.version = recorded.version + 1;
+            true
+        }
+        None => true,
+    };
+
+    let content = document.render();
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to create plans directory: {}", e)))?;
+    }
+    if tokio::fs::read_to_string(path).await.ok().as_deref() != Some(content.as_str()) {
+        tokio::fs::write(path, &content).await.map_err(|e| {
+            BitFunError::io(format!("Failed to write plan {}: {}", path.display(), e))
+        })?;
+    }
+
+    if new_version {
+        let history_path = history_path(path, document.version);
+        if let Some(parent) = history_path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                BitFunError::io(format!("Failed to create plan history directory: {}", e))
+            })?;
+        }
+        tokio::fs::write(&history_path, &content)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to record plan version: {}", e)))?;
+        debug!(
+            "Recorded plan version: path={}, version={}",
+            history_path.display(),
+            document.version
+        );
+    }
+    Ok(())
+}
+
+/// Reads a plan file, recording direct edits made since it was last saved as a new version.
+pub async fn load_current_plan(path: &Path) -> BitFunResult<PlanDocument> {
+    let mut document = load_plan(path).await?;
+    save_plan(path, &mut document).await?;
+    Ok(document)
+}
+
+/// Updates step statuses in a plan file; returns the plan if anything changed.
+pub async fn update_plan_statuses<'a>(
+    path: &Path,
+    updates: impl IntoIterator<Item = (&'a str, PlanStepStatus)>,
+) -> BitFunResult<Option<PlanDocument>> {
+    let mut document = load_plan(path).await?;
+    if !document.apply_statuses(updates) {
+        return Ok(None);
+    }
+    save_plan(path, &mut document).await?;
+    Ok(Some(document))
+}
+
+/// Latest version of a plan kept in its history
+async fn latest_recorded_version(path: &Path) -> Option<PlanDocument> {
+    let (history_dir, stem) = history_location(path);
+    let prefix = format!("{}.v", stem);
+    let mut entries = tokio::fs::read_dir(&history_dir).await.ok()?;
+    let mut latest = None;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let version = file_name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(".plan.md"))
+            .and_then(|version| version.parse::<u32>().ok());
+        latest = latest.max(version);
+    }
+    load_plan(&history_path(path, latest?)).await.ok()
+}
+
+fn history_path(path: &Path, version: u32) -> PathBuf {
+    let (history_dir, stem) = history_location(path);
+    history_dir.join(format!("{}.v{}.plan.md", stem, version))
+}
+
+/// History directory of a plan and the file name stem its versions use
+fn history_location(path: &Path) -> (PathBuf, String) {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = file_name
+        .strip_suffix(".plan.md")
+        .or_else(|| file_name.strip_suffix(".md"))
+        .unwrap_or(&file_name)
+        .to_string();
+    let history_dir = path.parent().unwrap_or(Path::new(".")).join("history");
+    (history_dir, stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(id: &str, dependencies: &[&str]) -> PlanStep {
+        PlanStep {
+            id: id.to_string(),
+            content: format!("Do {}", id),
+            status: PlanStepStatus::Pending,
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_versions_content_changes_only() {
+        let dir = std::env::temp_dir().join(format!("bitfun-plan-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("feature_1234.plan.md");
+
+        let mut plan = PlanDocument::new(
+            "Feature",
+            "Add a feature",
+            "# Feature\n\nDetails",
+            vec![step("setup", &[]), step("build", &["setup"])],
+        )
+        .unwrap();
+        save_plan(&path, &mut plan).await.unwrap();
+        assert_eq!(load_plan(&path).await.unwrap(), plan);
+
+        let updated = update_plan_statuses(&path, [("setup", PlanStepStatus::Completed)])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.version, 1);
+        assert_eq!(
+            updated.step("setup").unwrap().status,
+            PlanStepStatus::Completed
+        );
+
+        let mut revised = updated.clone();
+        revised.body.push_str("\n\nMore details");
+        save_plan(&path, &mut revised).await.unwrap();
+        assert_eq!(load_plan(&path).await.unwrap().version, 2);
+        let archived = load_plan(&dir.join("history").join("feature_1234.v1.plan.md"))
+            .await
+            .unwrap();
+        assert_eq!(archived.version, 1);
+
+        // A direct edit of the file is recorded as a new version when the plan is next loaded
+        let edited = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("More details", "Edited details");
+        std::fs::write(&path, edited).unwrap();
+        let current = load_current_plan(&path).await.unwrap();
+        assert_eq!(current.version, 3);
+        assert_eq!(load_plan(&path).await.unwrap().version, 3);
+        let recorded = load_plan(&dir.join("history").join("feature_1234.v2.plan.md"))
+            .await
+            .unwrap();
+        assert!(recorded.body.contains("More details"));
+        assert_eq!(load_current_plan(&path).await.unwrap().version, 3);
+
+        assert!(PlanDocument::new("x", "y", "", vec![step("a", &["missing"])]).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
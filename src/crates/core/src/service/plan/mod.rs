@@ -0,0 +1,10 @@
+//! Plan document service
+//!
+//! Reads and writes the versioned plan files created in plan mode and executed in agentic mode.
+
+pub mod document;
+
+pub use document::{
+    load_current_plan, load_plan, save_plan, update_plan_statuses, PlanDocument, PlanStep,
+    PlanStepStatus,
+};
@@ -1,15 +1,17 @@
 use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::agentic::tools::implementations::MultiEditTool;
 use crate::agentic::tools::registry::{get_global_tool_registry, ToolRegistry};
 use crate::infrastructure::get_workspace_path;
 use crate::service::snapshot::service::SnapshotService;
 use crate::service::snapshot::types::{
     OperationType, SnapshotConfig, SnapshotError, SnapshotResult,
 };
+use crate::service::snapshot::workspace_scanner::{WorkspaceScan, WorkspaceScanner};
 use async_trait::async_trait;
 use log::{debug, error, info, warn};
 use serde_json::Value;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -20,6 +22,8 @@ pub struct SnapshotManager {
     snapshot_service: Arc<RwLock<SnapshotService>>,
     original_tools: Vec<Arc<dyn Tool>>,
     file_modification_tools: HashSet<String>,
+    workspace_dir: PathBuf,
+    workspace_scanner: Arc<std::sync::Mutex<WorkspaceScanner>>,
     initialized: bool,
 }
 
@@ -34,7 +38,10 @@ impl SnapshotManager {
             workspace_dir.display()
         );
 
-        let mut snapshot_service = SnapshotService::new(workspace_dir, config);
+        let workspace_scanner = Arc::new(std::sync::Mutex::new(WorkspaceScanner::new(
+            workspace_dir.clone(),
+        )));
+        let mut snapshot_service = SnapshotService::new(workspace_dir.clone(), config);
         snapshot_service.initialize().await?;
         let snapshot_service = Arc::new(RwLock::new(snapshot_service));
 
@@ -43,6 +50,7 @@ impl SnapshotManager {
         let file_modification_tools = [
             "Write",
             "Edit",
+            "MultiEdit",
             "Delete",
             "write_file",
             "edit_file",
@@ -59,6 +67,8 @@ impl SnapshotManager {
             snapshot_service,
             original_tools,
             file_modification_tools,
+            workspace_dir,
+            workspace_scanner,
             initialized: true,
         })
     }
@@ -68,6 +78,16 @@ impl SnapshotManager {
         self.file_modification_tools.contains(tool_name)
     }
 
+    /// Returns the workspace directory whose changes are tracked.
+    pub fn workspace_dir(&self) -> &Path {
+        &self.workspace_dir
+    }
+
+    /// Returns whether the tool's file changes are recorded by its snapshot wrapper.
+    pub fn records_tool(&self, tool_name: &str) -> bool {
+        self.is_file_modification_tool(tool_name)
+    }
+
     /// Returns wrapped tool list.
     pub fn get_wrapped_tools(&self) -> Vec<Arc<dyn Tool>> {
         if !self.initialized {
@@ -107,21 +127,137 @@ impl SnapshotManager {
             .await
     }
 
+    /// Scans the workspace so that changes made by a tool call can be detected afterwards.
+    ///
+    /// Returns None when the workspace is too large to scan.
+    pub async fn scan_workspace(&self) -> Option<WorkspaceScan> {
+        let scanner = self.workspace_scanner.clone();
+        tokio::task::spawn_blocking(move || scanner.lock().ok()?.scan())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Records every workspace change since `before` as an operation of one tool call.
+    ///
+    /// Used for tools that are not wrapped (e.g. Bash), so that rollback and review also cover
+    /// files they changed. Files that a wrapped tool recorded during the scan window (e.g. in a
+    /// concurrent session) are left to that record. The operations are marked as detected,
+    /// since the scan cannot tell the tool's changes from other edits made meanwhile.
+    /// Returns the number of recorded operations.
+    pub async fn record_workspace_changes(
+        &self,
+        session_id: &str,
+        turn_index: usize,
+        tool_name: &str,
+        tool_input: &Value,
+        tool_call_id: Option<&str>,
+        before: WorkspaceScan,
+    ) -> SnapshotResult<usize> {
+        let Some(after) = self.scan_workspace().await else {
+            return Ok(0);
+        };
+        let mut changes = before.changes_to(&after);
+        if changes.is_empty() {
+            return Ok(0);
+        }
+
+        let snapshot_service = self.snapshot_service.read().await;
+        let recorded_elsewhere = snapshot_service
+            .paths_recorded_since(before.started_at())
+            .await;
+        changes.retain(|change| {
+            let keep = !recorded_elsewhere.contains(&change.path);
+            if !keep {
+                debug!(
+                    "Skipping change already recorded by a wrapped tool: path={}",
+                    change.path.display()
+                );
+            }
+            keep
+        });
+        let mut recorded = 0;
+        for (index, change) in changes.iter().enumerate() {
+            // Operation ids must be unique, so only the first file uses the tool call id
+            let operation_id = tool_call_id.map(|id| {
+                if index == 0 {
+                    id.to_string()
+                } else {
+                    format!("{}#{}", id, index)
+                }
+            });
+            let before = change
+                .before
+                .as_ref()
+                .map(|(content, modified)| (content.as_slice(), *modified));
+            match snapshot_service
+                .record_detected_modification(
+                    session_id,
+                    turn_index,
+                    tool_name,
+                    tool_input.clone(),
+                    &change.path,
+                    before,
+                    operation_id,
+                )
+                .await
+            {
+                Ok(_) => recorded += 1,
+                Err(e) => warn!(
+                    "Failed to record detected file change: tool_name={} path={} error={}",
+                    tool_name,
+                    change.path.display(),
+                    e
+                ),
+            }
+        }
+
+        debug!(
+            "Recorded workspace changes: tool_name={} session_id={} turn_index={} files={}",
+            tool_name, session_id, turn_index, recorded
+        );
+        Ok(recorded)
+    }
+
     /// Rolls back a session.
-    pub async fn rollback_session(&self, session_id: &str) -> SnapshotResult<Vec<PathBuf>> {
+    ///
+    /// Operations attributed by a workspace scan are only reverted with `confirm_detected`.
+    pub async fn rollback_session(
+        &self,
+        session_id: &str,
+        confirm_detected: bool,
+    ) -> SnapshotResult<Vec<PathBuf>> {
         let snapshot_service = self.snapshot_service.read().await;
-        snapshot_service.rollback_session(session_id).await
+        snapshot_service
+            .rollback_session(session_id, confirm_detected)
+            .await
     }
 
     /// Rolls back to a specific turn.
+    ///
+    /// Operations attributed by a workspace scan are only reverted with `confirm_detected`.
     pub async fn rollback_to_turn(
         &self,
         session_id: &str,
         turn_index: usize,
+        confirm_detected: bool,
     ) -> SnapshotResult<Vec<PathBuf>> {
         let snapshot_service = self.snapshot_service.read().await;
         snapshot_service
-            .rollback_to_turn(session_id, turn_index)
+            .rollback_to_turn(session_id, turn_index, confirm_detected)
+            .await
+    }
+
+    /// Copies a session's file operations up to a turn into a forked session.
+    pub async fn fork_session(
+        &self,
+        source_session_id: &str,
+        target_session_id: &str,
+        up_to_turn: usize,
+    ) -> SnapshotResult<usize> {
+        let snapshot_service = self.snapshot_service.read().await;
+        snapshot_service
+            .fork_session(source_session_id, target_session_id, up_to_turn)
             .await
     }
 
@@ -422,6 +558,7 @@ impl Tool for WrappedTool {
         let file_modification_tools = [
             "Write",
             "Edit",
+            "MultiEdit",
             "Delete",
             "write_file",
             "edit_file",
@@ -438,7 +575,13 @@ impl Tool for WrappedTool {
                 self.name()
             );
 
-            match self.handle_file_modification_internal(input, context).await {
+            let handled = if self.name() == "MultiEdit" {
+                self.handle_multi_file_modification(input, context).await
+            } else {
+                self.handle_file_modification_internal(input, context).await
+            };
+
+            match handled {
                 Ok(results) => {
                     return Ok(results);
                 }
@@ -559,6 +702,74 @@ impl WrappedTool {
         Ok(results)
     }
 
+    /// Handles a tool that changes several files in one call.
+    ///
+    /// The changes are planned (and validated) up front so that each touched file gets exactly
+    /// one snapshot operation, and nothing is recorded when the edits would not apply.
+    async fn handle_multi_file_modification(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> crate::util::errors::BitFunResult<Vec<ToolResult>> {
+        let session_id = context.session_id.clone().ok_or_else(|| {
+            crate::util::errors::BitFunError::Tool(
+                "session_id is required in ToolUseContext".to_string(),
+            )
+        })?;
+        let changes = MultiEditTool::plan_changes(input)?;
+        let turn_index = self.extract_turn_index(context);
+
+        let snapshot_service = self.snapshot_service.read().await;
+        let mut operation_ids = Vec::with_capacity(changes.len());
+        for (index, change) in changes.iter().enumerate() {
+            let operation_type = match (&change.before, &change.after) {
+                (None, _) => OperationType::Create,
+                (_, None) => OperationType::Delete,
+                _ => OperationType::Modify,
+            };
+            // Operation ids must be unique, so only the first file uses the tool call id
+            let operation_id = context.tool_call_id.as_ref().map(|id| {
+                if index == 0 {
+                    id.clone()
+                } else {
+                    format!("{}#{}", id, index)
+                }
+            });
+            let operation_id = snapshot_service
+                .intercept_file_modification(
+                    &session_id,
+                    turn_index,
+                    self.name(),
+                    input.clone(),
+                    Path::new(&change.path),
+                    operation_type,
+                    operation_id,
+                )
+                .await
+                .map_err(|e| crate::util::errors::BitFunError::Tool(e.to_string()))?;
+            operation_ids.push(operation_id);
+        }
+
+        debug!(
+            "Recorded multi-file modification: tool_name={} files={}",
+            self.name(),
+            operation_ids.len()
+        );
+
+        let start_time = std::time::Instant::now();
+        let results = self.original_tool.call(input, context).await?;
+        let duration_ms = start_time.elapsed().as_millis() as u64;
+
+        for operation_id in &operation_ids {
+            snapshot_service
+                .complete_file_modification(&session_id, operation_id, duration_ms)
+                .await
+                .map_err(|e| crate::util::errors::BitFunError::Tool(e.to_string()))?;
+        }
+
+        Ok(results)
+    }
+
     /// Extracts the turn index.
     fn extract_turn_index(&self, context: &ToolUseContext) -> usize {
         context
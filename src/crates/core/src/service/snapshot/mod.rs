@@ -6,6 +6,7 @@ pub mod service;
 pub mod snapshot_core;
 pub mod snapshot_system;
 pub mod types;
+pub mod workspace_scanner;
 
 pub use events::{
     emit_snapshot_event, emit_snapshot_session_event, initialize_snapshot_event_emitter,
@@ -18,3 +19,4 @@ pub use manager::{
 pub use service::{SnapshotService, SystemStats};
 pub use snapshot_core::{FileChangeEntry, FileChangeQueue, SessionStats, SnapshotCore};
 pub use types::*;
+pub use workspace_scanner::{DetectedChange, WorkspaceScan, WorkspaceScanner};
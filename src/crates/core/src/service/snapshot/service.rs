@@ -7,8 +7,10 @@ use crate::service::snapshot::types::{
     OperationType, SessionInfo, SnapshotConfig, SnapshotError, SnapshotResult,
 };
 use log::info;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::sync::RwLock;
 
 /// Snapshot-based change tracking service (operation-history + file snapshots, git-isolated).
@@ -145,6 +147,62 @@ impl SnapshotService {
         Ok(operation_id)
     }
 
+    /// Record a change that a tool already made without going through the snapshot wrapper.
+    ///
+    /// `before` is the file's content and modification time before the change, or None if the
+    /// file did not exist. Returns operation_id.
+    pub async fn record_detected_modification(
+        &self,
+        session_id: &str,
+        turn_index: usize,
+        tool_name: &str,
+        tool_input: serde_json::Value,
+        file_path: &Path,
+        before: Option<(&[u8], SystemTime)>,
+        operation_id_override: Option<String>,
+    ) -> SnapshotResult<String> {
+        self.ensure_initialized().await?;
+        self.validate_file_path(file_path).await?;
+
+        let operation_type = match before {
+            None => OperationType::Create,
+            Some(_) if !file_path.exists() => OperationType::Delete,
+            Some(_) => OperationType::Modify,
+        };
+
+        let operation_id = {
+            let mut snapshot_core = self.snapshot_core.write().await;
+            snapshot_core
+                .start_detected_file_operation(
+                    session_id,
+                    turn_index,
+                    file_path.to_path_buf(),
+                    operation_type.clone(),
+                    tool_name.to_string(),
+                    tool_input,
+                    operation_id_override,
+                    before,
+                )
+                .await?
+        };
+
+        emit_snapshot_session_event(
+            session_id,
+            SnapshotEvent::file_modification_started(
+                session_id.to_string(),
+                operation_id.clone(),
+                file_path.to_path_buf(),
+                format!("{:?}", operation_type),
+            ),
+        )
+        .await;
+
+        self.complete_file_modification(session_id, &operation_id, 0)
+            .await?;
+
+        Ok(operation_id)
+    }
+
     pub async fn get_file_diff_with_anchor(
         &self,
         session_id: &str,
@@ -223,12 +281,18 @@ impl SnapshotService {
         Ok(())
     }
 
-    pub async fn rollback_session(&self, session_id: &str) -> SnapshotResult<Vec<PathBuf>> {
+    pub async fn rollback_session(
+        &self,
+        session_id: &str,
+        confirm_detected: bool,
+    ) -> SnapshotResult<Vec<PathBuf>> {
         self.ensure_initialized().await?;
         info!("Rolling back session: session_id={}", session_id);
 
         let mut snapshot_core = self.snapshot_core.write().await;
-        let restored_files = snapshot_core.rollback_session(session_id).await?;
+        let restored_files = snapshot_core
+            .rollback_session(session_id, confirm_detected)
+            .await?;
 
         let released_count = self
             .file_lock_manager
@@ -248,6 +312,7 @@ impl SnapshotService {
         &self,
         session_id: &str,
         turn_index: usize,
+        confirm_detected: bool,
     ) -> SnapshotResult<Vec<PathBuf>> {
         self.ensure_initialized().await?;
         info!(
@@ -256,7 +321,29 @@ impl SnapshotService {
         );
 
         let mut snapshot_core = self.snapshot_core.write().await;
-        snapshot_core.rollback_to_turn(session_id, turn_index).await
+        snapshot_core
+            .rollback_to_turn(session_id, turn_index, confirm_detected)
+            .await
+    }
+
+    /// Files recorded by the tools themselves (not by a workspace scan) since `since`.
+    pub async fn paths_recorded_since(&self, since: SystemTime) -> HashSet<PathBuf> {
+        let snapshot_core = self.snapshot_core.read().await;
+        snapshot_core.paths_recorded_since(since)
+    }
+
+    pub async fn fork_session(
+        &self,
+        source_session_id: &str,
+        target_session_id: &str,
+        up_to_turn: usize,
+    ) -> SnapshotResult<usize> {
+        self.ensure_initialized().await?;
+
+        let mut snapshot_core = self.snapshot_core.write().await;
+        snapshot_core
+            .fork_session(source_session_id, target_session_id, up_to_turn)
+            .await
     }
 
     pub async fn accept_session(&self, session_id: &str) -> SnapshotResult<()> {
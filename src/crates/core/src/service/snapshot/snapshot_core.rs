@@ -130,6 +130,80 @@ impl SnapshotCore {
             None
         };
 
+        self.push_operation(
+            session_id,
+            turn_index,
+            file_path,
+            operation_type,
+            tool_name,
+            tool_input,
+            operation_id_override,
+            before_snapshot_id,
+            false,
+        )
+        .await
+    }
+
+    /// Start an operation for a change that has already happened, returns operation_id.
+    ///
+    /// `before` is the content the file had before the change (None if it did not exist) with
+    /// its modification time; complete the operation as usual to snapshot the current state.
+    pub async fn start_detected_file_operation(
+        &mut self,
+        session_id: &str,
+        turn_index: usize,
+        file_path: PathBuf,
+        operation_type: OperationType,
+        tool_name: String,
+        tool_input: serde_json::Value,
+        operation_id_override: Option<String>,
+        before: Option<(&[u8], SystemTime)>,
+    ) -> SnapshotResult<String> {
+        let before_snapshot_id = match before {
+            Some((content, last_modified)) => Some(
+                self.snapshot_system
+                    .create_snapshot_from_content(&file_path, content, last_modified)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        self.push_operation(
+            session_id,
+            turn_index,
+            file_path,
+            operation_type,
+            tool_name,
+            tool_input,
+            operation_id_override,
+            before_snapshot_id,
+            true,
+        )
+        .await
+    }
+
+    /// Files recorded by the tools themselves (not by a workspace scan) since `since`
+    pub fn paths_recorded_since(&self, since: SystemTime) -> HashSet<PathBuf> {
+        self.sessions
+            .values()
+            .flat_map(|session| session.all_operations_iter())
+            .filter(|op| !op.detected && op.timestamp >= since)
+            .flat_map(|op| std::iter::once(op.file_path.clone()).chain(op.path_after.clone()))
+            .collect()
+    }
+
+    async fn push_operation(
+        &mut self,
+        session_id: &str,
+        turn_index: usize,
+        file_path: PathBuf,
+        operation_type: OperationType,
+        tool_name: String,
+        tool_input: serde_json::Value,
+        operation_id_override: Option<String>,
+        before_snapshot_id: Option<String>,
+        detected: bool,
+    ) -> SnapshotResult<String> {
         if let Some(before_id) = &before_snapshot_id {
             if !self.snapshot_system.has_baseline(&file_path).await {
                 match self
@@ -192,6 +266,7 @@ impl SnapshotCore {
             diff_summary: DiffSummary::default(),
             path_before: None,
             path_after: None,
+            detected,
         });
 
         session.last_updated = SystemTime::now();
@@ -554,13 +629,20 @@ impl SnapshotCore {
         entries
     }
 
-    pub async fn rollback_session(&mut self, session_id: &str) -> SnapshotResult<Vec<PathBuf>> {
+    ///
+    /// Operations found by a workspace scan are only reverted with `confirm_detected`.
+    pub async fn rollback_session(
+        &mut self,
+        session_id: &str,
+        confirm_detected: bool,
+    ) -> SnapshotResult<Vec<PathBuf>> {
         info!("Rolling back session: session_id={}", session_id);
         let Some(session) = self.sessions.get(session_id) else {
             return Ok(Vec::new());
         };
 
         let mut to_rollback: Vec<FileOperation> = session.all_operations_iter().cloned().collect();
+        check_detected_confirmed(&to_rollback, confirm_detected)?;
         to_rollback.sort_by_key(|op| (op.turn_index, op.seq_in_turn));
         to_rollback.reverse();
 
@@ -573,10 +655,13 @@ impl SnapshotCore {
     }
 
     /// Rollback to the start of `target_turn` (undo target_turn and later turns).
+    ///
+    /// Operations found by a workspace scan are only reverted with `confirm_detected`.
     pub async fn rollback_to_turn(
         &mut self,
         session_id: &str,
         target_turn: usize,
+        confirm_detected: bool,
     ) -> SnapshotResult<Vec<PathBuf>> {
         info!(
             "Rolling back to turn: session_id={} turn_index={}",
@@ -591,6 +676,7 @@ impl SnapshotCore {
             .filter(|op| op.turn_index >= target_turn)
             .cloned()
             .collect();
+        check_detected_confirmed(&to_rollback, confirm_detected)?;
         to_rollback.sort_by_key(|op| (op.turn_index, op.seq_in_turn));
         to_rollback.reverse();
 
@@ -610,6 +696,56 @@ impl SnapshotCore {
         Ok(restored)
     }
 
+    /// Copy the operation history of `source_session_id` up to `up_to_turn` (inclusive) to a
+    /// new session, so the fork can be rolled back independently. Snapshot files are shared.
+    pub async fn fork_session(
+        &mut self,
+        source_session_id: &str,
+        target_session_id: &str,
+        up_to_turn: usize,
+    ) -> SnapshotResult<usize> {
+        let Some(source) = self.sessions.get(source_session_id) else {
+            return Ok(0);
+        };
+
+        let mut target = SessionHistory::new(target_session_id.to_string());
+        let mut copied = 0usize;
+        for (turn_index, turn) in source.turns.range(..=up_to_turn) {
+            let operations = turn
+                .operations
+                .iter()
+                .map(|op| {
+                    let mut op = op.clone();
+                    op.operation_id = Uuid::new_v4().to_string();
+                    op.session_id = target_session_id.to_string();
+                    op
+                })
+                .collect::<Vec<_>>();
+            copied += operations.len();
+            target.turns.insert(
+                *turn_index,
+                TurnHistory {
+                    turn_index: *turn_index,
+                    operations,
+                },
+            );
+        }
+
+        if copied == 0 {
+            return Ok(0);
+        }
+
+        self.sessions.insert(target_session_id.to_string(), target);
+        self.persist_session(target_session_id).await?;
+        self.rebuild_operation_index();
+
+        info!(
+            "Forked session operations: source={} target={} up_to_turn={} operations={}",
+            source_session_id, target_session_id, up_to_turn, copied
+        );
+        Ok(copied)
+    }
+
     pub async fn cleanup_session(&mut self, session_id: &str) -> SnapshotResult<()> {
         let snapshot_ids_to_delete: Vec<String> =
             if let Some(session) = self.sessions.get(session_id) {
@@ -634,6 +770,20 @@ impl SnapshotCore {
                 Vec::new()
             };
 
+        // Forked sessions share snapshot files; keep the ones still referenced elsewhere
+        let referenced_elsewhere: HashSet<&str> = self
+            .sessions
+            .iter()
+            .filter(|(id, _)| id.as_str() != session_id)
+            .flat_map(|(_, session)| session.all_operations_iter())
+            .flat_map(|op| [op.before_snapshot_id.as_deref(), op.after_snapshot_id.as_deref()])
+            .flatten()
+            .collect();
+        let snapshot_ids_to_delete: Vec<String> = snapshot_ids_to_delete
+            .into_iter()
+            .filter(|id| !referenced_elsewhere.contains(id.as_str()))
+            .collect();
+
         for snapshot_id in &snapshot_ids_to_delete {
             if let Err(e) = self.snapshot_system.delete_snapshot(snapshot_id).await {
                 warn!(
@@ -836,6 +986,24 @@ impl SnapshotCore {
     }
 }
 
+/// Refuses to revert operations found by a workspace scan unless the user confirmed it
+fn check_detected_confirmed(ops: &[FileOperation], confirm_detected: bool) -> SnapshotResult<()> {
+    if confirm_detected {
+        return Ok(());
+    }
+    let mut paths: Vec<String> = ops
+        .iter()
+        .filter(|op| op.detected)
+        .map(|op| op.file_path.display().to_string())
+        .collect();
+    if paths.is_empty() {
+        return Ok(());
+    }
+    paths.sort();
+    paths.dedup();
+    Err(SnapshotError::DetectedChangesNeedConfirmation(paths))
+}
+
 fn sanitize_id(id: &str) -> String {
     id.chars()
         .map(|c| {
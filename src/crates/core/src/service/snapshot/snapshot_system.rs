@@ -299,7 +299,41 @@ impl FileSnapshotSystem {
 
         let metadata = self.extract_file_metadata(file_path).await?;
 
-        let content_hash = self.calculate_content_hash(&content);
+        self.store_content(file_path, &content, metadata).await
+    }
+
+    /// Creates a snapshot of content the file held earlier but may no longer hold.
+    ///
+    /// Used for changes detected after the fact, where the previous content was captured by a
+    /// workspace scan rather than read just before the change.
+    pub async fn create_snapshot_from_content(
+        &mut self,
+        file_path: &Path,
+        content: &[u8],
+        last_modified: SystemTime,
+    ) -> SnapshotResult<String> {
+        debug!(
+            "Creating snapshot from content: file_path={}",
+            file_path.display()
+        );
+
+        let metadata = FileMetadata {
+            size: content.len() as u64,
+            permissions: None,
+            last_modified,
+            encoding: detect_content_encoding(content).to_string(),
+        };
+
+        self.store_content(file_path, content, metadata).await
+    }
+
+    async fn store_content(
+        &mut self,
+        file_path: &Path,
+        content: &[u8],
+        metadata: FileMetadata,
+    ) -> SnapshotResult<String> {
+        let content_hash = self.calculate_content_hash(content);
 
         if self.dedup_enabled && self.hash_to_path.contains_key(&content_hash) {
             debug!(
@@ -309,7 +343,7 @@ impl FileSnapshotSystem {
             return Ok(self.find_snapshot_by_hash(&content_hash)?);
         }
 
-        let optimized_content = self.optimize_content(&content);
+        let optimized_content = self.optimize_content(content);
 
         let snapshot = FileSnapshot {
             snapshot_id: Uuid::new_v4().to_string(),
@@ -367,18 +401,9 @@ impl FileSnapshotSystem {
 
     /// Detects file encoding.
     async fn detect_file_encoding(&self, file_path: &Path) -> Option<String> {
-        match fs::read(file_path) {
-            Ok(bytes) => {
-                if bytes.is_ascii() {
-                    Some("ascii".to_string())
-                } else if String::from_utf8(bytes).is_ok() {
-                    Some("utf-8".to_string())
-                } else {
-                    Some("binary".to_string())
-                }
-            }
-            Err(_) => None,
-        }
+        fs::read(file_path)
+            .ok()
+            .map(|bytes| detect_content_encoding(&bytes).to_string())
     }
 
     /// Computes content hash.
@@ -782,3 +807,13 @@ impl FileSnapshotSystem {
         self.get_baseline_snapshot_id(file_path).await.is_some()
     }
 }
+
+fn detect_content_encoding(bytes: &[u8]) -> &'static str {
+    if bytes.is_ascii() {
+        "ascii"
+    } else if std::str::from_utf8(bytes).is_ok() {
+        "utf-8"
+    } else {
+        "binary"
+    }
+}
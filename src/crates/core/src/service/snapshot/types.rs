@@ -52,6 +52,10 @@ pub struct FileOperation {
     pub diff_summary: DiffSummary,
     pub path_before: Option<PathBuf>,
     pub path_after: Option<PathBuf>,
+    /// Attributed by a workspace scan rather than recorded by the tool; may include edits
+    /// made outside the agent, so reverting it needs confirmation
+    #[serde(default)]
+    pub detected: bool,
 }
 
 /// Diff summary
@@ -174,6 +178,12 @@ pub enum SnapshotError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error(
+        "Rollback would revert changes found by a workspace scan, which may include edits made outside the agent: {}",
+        .0.join(", ")
+    )]
+    DetectedChangesNeedConfirmation(Vec<String>),
+
     #[error("Tool execution error: {0}")]
     ToolExecution(#[from] crate::util::errors::BitFunError),
 }
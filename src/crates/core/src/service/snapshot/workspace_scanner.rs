@@ -0,0 +1,270 @@
+//! Workspace content scanning
+//!
+//! Detects file changes made outside the snapshot-wrapped tools (shell commands, code
+//! generators, formatters, ...) by comparing content hashes of the workspace's files before
+//! and after a tool call. Gitignored files are skipped. Content is cached between scans and
+//! only re-read when a file's size or modification time changes.
+
+use ignore::WalkBuilder;
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Files larger than this are tracked by size and modification time only
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Workspaces with more files than this are not scanned
+const MAX_FILES: usize = 20_000;
+/// Workspaces whose tracked content exceeds this are not scanned
+const MAX_TOTAL_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Directories never scanned, wherever they appear
+const SKIPPED_DIRS: &[&str] = &[".git", ".bitfun", ".worktrees"];
+
+/// State of one file at scan time
+#[derive(Debug, Clone)]
+pub struct ScannedFile {
+    pub size: u64,
+    pub modified: SystemTime,
+    /// Content hash; None for files too large to keep
+    pub hash: Option<String>,
+    pub content: Option<Arc<Vec<u8>>>,
+}
+
+/// Files in the workspace at one point in time
+#[derive(Debug, Clone)]
+pub struct WorkspaceScan {
+    files: HashMap<PathBuf, ScannedFile>,
+    /// When the scan started; changes recorded elsewhere after this belong to the window
+    started_at: SystemTime,
+}
+
+impl Default for WorkspaceScan {
+    fn default() -> Self {
+        Self {
+            files: HashMap::new(),
+            started_at: SystemTime::UNIX_EPOCH,
+        }
+    }
+}
+
+/// A file that changed between two scans
+#[derive(Debug, Clone)]
+pub struct DetectedChange {
+    pub path: PathBuf,
+    /// Content and modification time before the change; None if the file did not exist
+    pub before: Option<(Arc<Vec<u8>>, SystemTime)>,
+}
+
+impl WorkspaceScan {
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    /// Files created, modified or deleted between this scan and `after`, sorted by path.
+    ///
+    /// Changes to files too large to keep are skipped since they could not be restored.
+    pub fn changes_to(&self, after: &WorkspaceScan) -> Vec<DetectedChange> {
+        let mut changes = Vec::new();
+
+        for (path, before) in &self.files {
+            let changed = match after.files.get(path) {
+                Some(current) => !same_content(before, current),
+                None => true,
+            };
+            if !changed {
+                continue;
+            }
+            match &before.content {
+                Some(content) => changes.push(DetectedChange {
+                    path: path.clone(),
+                    before: Some((content.clone(), before.modified)),
+                }),
+                None => debug!(
+                    "Skipping change to untracked large file: path={}",
+                    path.display()
+                ),
+            }
+        }
+
+        for (path, current) in &after.files {
+            if self.files.contains_key(path) {
+                continue;
+            }
+            if current.content.is_some() {
+                changes.push(DetectedChange {
+                    path: path.clone(),
+                    before: None,
+                });
+            } else {
+                debug!(
+                    "Skipping creation of untracked large file: path={}",
+                    path.display()
+                );
+            }
+        }
+
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes
+    }
+}
+
+fn same_content(a: &ScannedFile, b: &ScannedFile) -> bool {
+    match (&a.hash, &b.hash) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.size == b.size && a.modified == b.modified,
+    }
+}
+
+/// Scans a workspace, reusing the content of files unchanged since the previous scan
+pub struct WorkspaceScanner {
+    root: PathBuf,
+    previous: WorkspaceScan,
+    warned_too_large: bool,
+}
+
+impl WorkspaceScanner {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            previous: WorkspaceScan::default(),
+            warned_too_large: false,
+        }
+    }
+
+    /// Scans the workspace; None when it is too large to track.
+    ///
+    /// Blocking: run it off the async runtime.
+    pub fn scan(&mut self) -> Option<WorkspaceScan> {
+        let started_at = SystemTime::now();
+        let mut files = HashMap::new();
+        let mut total_bytes = 0u64;
+
+        let walker = WalkBuilder::new(&self.root)
+            .hidden(false)
+            .git_ignore(true)
+            .git_exclude(true)
+            .git_global(false)
+            .require_git(false)
+            .filter_entry(|entry| {
+                !(entry.file_type().is_some_and(|t| t.is_dir())
+                    && SKIPPED_DIRS.iter().any(|name| entry.file_name() == *name))
+            })
+            .build();
+
+        for entry in walker {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let size = metadata.len();
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            let path = entry.into_path();
+
+            if files.len() >= MAX_FILES || total_bytes > MAX_TOTAL_BYTES {
+                if !self.warned_too_large {
+                    warn!(
+                        "Workspace too large to track changes from shell commands: root={}",
+                        self.root.display()
+                    );
+                    self.warned_too_large = true;
+                }
+                self.previous = WorkspaceScan::default();
+                return None;
+            }
+
+            let reusable = self
+                .previous
+                .files
+                .get(&path)
+                .filter(|previous| previous.size == size && previous.modified == modified);
+            let scanned = match reusable {
+                Some(previous) => previous.clone(),
+                None if size <= MAX_FILE_BYTES => match std::fs::read(&path) {
+                    Ok(content) => ScannedFile {
+                        size,
+                        modified,
+                        hash: Some(format!("{:x}", md5::compute(&content))),
+                        content: Some(Arc::new(content)),
+                    },
+                    Err(_) => continue,
+                },
+                None => ScannedFile {
+                    size,
+                    modified,
+                    hash: None,
+                    content: None,
+                },
+            };
+            if scanned.content.is_some() {
+                total_bytes += size;
+            }
+            files.insert(path, scanned);
+        }
+
+        let scan = WorkspaceScan { files, started_at };
+        self.previous = scan.clone();
+        Some(scan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_between_scans() {
+        let root = std::env::temp_dir().join(format!("bitfun-scan-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join(".bitfun")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(root.join("keep.txt"), "keep").unwrap();
+        std::fs::write(root.join("edit.txt"), "old").unwrap();
+        std::fs::write(root.join("remove.txt"), "gone").unwrap();
+
+        let mut scanner = WorkspaceScanner::new(root.clone());
+        let before = scanner.scan().unwrap();
+
+        std::fs::write(root.join("edit.txt"), "new content").unwrap();
+        std::fs::remove_file(root.join("remove.txt")).unwrap();
+        std::fs::write(root.join("added.txt"), "added").unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join("target/out.bin"), "ignored").unwrap();
+        std::fs::write(root.join(".bitfun/state.json"), "{}").unwrap();
+
+        let after = scanner.scan().unwrap();
+        let changes = before.changes_to(&after);
+        let summary: Vec<(String, Option<String>)> = changes
+            .iter()
+            .map(|change| {
+                (
+                    change
+                        .path
+                        .file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string(),
+                    change
+                        .before
+                        .as_ref()
+                        .map(|(content, _)| String::from_utf8_lossy(content).to_string()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("added.txt".to_string(), None),
+                ("edit.txt".to_string(), Some("old".to_string())),
+                ("remove.txt".to_string(), Some("gone".to_string())),
+            ]
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -12,11 +12,13 @@
 //! - `config`: Configuration types and defaults
 //! - `events`: Event definitions for frontend communication
 //! - `api`: Public API for external consumers
+//! - `sandbox`: Opt-in confinement of commands on Linux
 
 pub mod api;
 pub mod config;
 pub mod events;
 pub mod pty;
+pub mod sandbox;
 pub mod session;
 pub mod shell;
 
@@ -45,6 +47,7 @@ pub use pty::{
     PtyWriter,
     SpawnResult,
 };
+pub use sandbox::{SandboxPolicy, SandboxViolation};
 pub use session::{
     CommandExecuteResult, CommandStream, CommandStreamEvent, ExecuteOptions, SessionManager,
     SessionStatus, TerminalBindingOptions, TerminalSession, TerminalSessionBinding,
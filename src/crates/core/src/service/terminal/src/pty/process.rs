@@ -559,11 +559,17 @@ pub fn spawn_pty(
                     }
                 }
                 InternalCommand::Signal(signal) => {
-                    // For now, we only support SIGINT via Ctrl+C
-                    if signal == "SIGINT" || signal == "INT" {
+                    // Signals are delivered through the terminal's control characters
+                    let control_char = match signal.as_str() {
+                        // Ctrl+C (ASCII 0x03)
+                        "SIGINT" | "INT" => Some(0x03),
+                        // Ctrl+\ (ASCII 0x1C)
+                        "SIGQUIT" | "QUIT" => Some(0x1c),
+                        _ => None,
+                    };
+                    if let Some(control_char) = control_char {
                         if let Ok(mut writer_guard) = writer.lock() {
-                            // Send Ctrl+C (ASCII 0x03)
-                            let _ = writer_guard.write_all(&[0x03]);
+                            let _ = writer_guard.write_all(&[control_char]);
                             let _ = writer_guard.flush();
                         }
                     }
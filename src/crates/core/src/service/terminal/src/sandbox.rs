@@ -0,0 +1,372 @@
+//! Sandbox - Confine commands with Linux namespaces
+//!
+//! A sandboxed terminal session runs its shell inside bubblewrap (`bwrap`), in private
+//! mount, PID, IPC and (unless allowed) network namespaces. Every command of the session
+//! runs in that one shell, so `cd` and exported variables carry over between commands:
+//! - The host file system is mounted read-only, except for the writable paths
+//! - `.bitfun` and `.git` inside writable paths stay read-only, since hooks stored there
+//!   run outside the sandbox; when they do not exist yet an empty read-only directory
+//!   takes their place, so they cannot be created either
+//! - `/tmp` is a private tmpfs
+//! - Hidden paths (credentials such as `~/.ssh`) are masked with empty mounts
+//!
+//! The shell still runs in the session's PTY, so output streaming and Ctrl+C keep working.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::ShellConfig;
+use crate::shell::{ShellDetector, ShellType};
+use crate::{TerminalError, TerminalResult};
+
+/// Credential locations under the home directory hidden by default
+const DEFAULT_HIDDEN_HOME_PATHS: &[&str] = &[
+    ".ssh",
+    ".gnupg",
+    ".aws",
+    ".azure",
+    ".kube",
+    ".docker",
+    ".config/gcloud",
+    ".config/gh",
+    ".netrc",
+    ".git-credentials",
+    ".npmrc",
+    ".pypirc",
+];
+
+/// Directories kept read-only inside each writable path: their hooks run unsandboxed
+const PROTECTED_DIR_NAMES: &[&str] = &[".bitfun", ".git"];
+
+/// Output fragments that indicate a blocked operation
+const WRITE_DENIED_MARKERS: &[&str] = &["Read-only file system"];
+const NETWORK_DENIED_MARKERS: &[&str] = &[
+    "Network is unreachable",
+    "Could not resolve host",
+    "Temporary failure in name resolution",
+    "Name or service not known",
+    "getaddrinfo ENOTFOUND",
+    "getaddrinfo EAI_AGAIN",
+];
+
+/// Sandbox policy for commands
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SandboxPolicy {
+    /// Paths the command may write to
+    pub writable_paths: Vec<PathBuf>,
+    /// Paths kept read-only even though they are inside a writable path
+    pub read_only_paths: Vec<PathBuf>,
+    /// Paths that appear empty inside the sandbox
+    pub hidden_paths: Vec<PathBuf>,
+    /// Whether the command may use the network
+    pub allow_network: bool,
+}
+
+/// Operation blocked by the sandbox, detected from command output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SandboxViolation {
+    /// Write outside the writable paths
+    FileWrite,
+    /// Access to a hidden path
+    FileRead(PathBuf),
+    /// Network access
+    Network,
+    /// The sandbox itself could not be set up
+    Setup(String),
+}
+
+impl SandboxPolicy {
+    /// Create a policy with the default hidden paths, protecting `.bitfun` and `.git`
+    /// in every writable path
+    pub fn new(writable_paths: Vec<PathBuf>, allow_network: bool) -> Self {
+        let hidden_paths = dirs::home_dir()
+            .map(|home| {
+                DEFAULT_HIDDEN_HOME_PATHS
+                    .iter()
+                    .map(|path| home.join(path))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let read_only_paths = writable_paths
+            .iter()
+            .flat_map(|path| PROTECTED_DIR_NAMES.iter().map(move |name| path.join(name)))
+            .collect();
+
+        Self {
+            writable_paths,
+            read_only_paths,
+            hidden_paths,
+            allow_network,
+        }
+    }
+
+    /// Whether commands can be sandboxed on this system
+    pub fn is_supported() -> bool {
+        cfg!(target_os = "linux") && ShellDetector::find_in_path("bwrap").is_some()
+    }
+
+    /// Make a session's shell start inside the sandbox
+    ///
+    /// The shell becomes the command run by `bwrap`; its environment passes through.
+    /// `integration_dir` holds the shell integration scripts and stays readable even
+    /// when it lies in `/tmp`.
+    pub fn wrap_shell(
+        &self,
+        shell_config: &mut ShellConfig,
+        shell_type: &ShellType,
+        integration_dir: Option<&Path>,
+    ) -> TerminalResult<()> {
+        if !matches!(
+            shell_type,
+            ShellType::Bash | ShellType::Zsh | ShellType::Fish | ShellType::Sh | ShellType::Ksh
+        ) {
+            return Err(TerminalError::Shell(format!(
+                "Sandboxed sessions are not supported in {}",
+                shell_type.name()
+            )));
+        }
+
+        let working_directory = shell_config.cwd.clone().unwrap_or_else(|| "/".to_string());
+        let mut args = self.bwrap_args(&working_directory, integration_dir);
+        args.push("--".to_string());
+        args.push(std::mem::replace(
+            &mut shell_config.executable,
+            "bwrap".to_string(),
+        ));
+        args.append(&mut shell_config.args);
+        shell_config.args = args;
+        Ok(())
+    }
+
+    /// Arguments of `bwrap` up to (excluding) the `--` before the sandboxed program
+    fn bwrap_args(&self, working_directory: &str, integration_dir: Option<&Path>) -> Vec<String> {
+        let mut args: Vec<String> = [
+            "--ro-bind", "/", "/", "--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let bind = |args: &mut Vec<String>, flag: &str, path: &Path| {
+            let path = path.to_string_lossy().to_string();
+            args.extend([flag.to_string(), path.clone(), path]);
+        };
+        if let Some(dir) = integration_dir.filter(|dir| dir.starts_with("/tmp") && dir.exists()) {
+            bind(&mut args, "--ro-bind", dir);
+        }
+
+        // Later mounts win: writable paths, then the read-only paths inside them, then
+        // writable paths nested in a read-only path (such as `.bitfun/local/temp`)
+        let existing_writable: Vec<&PathBuf> =
+            self.writable_paths.iter().filter(|p| p.exists()).collect();
+        let (nested, outer): (Vec<&PathBuf>, Vec<&PathBuf>) =
+            existing_writable.iter().copied().partition(|path| {
+                self.read_only_paths
+                    .iter()
+                    .any(|read_only| path.starts_with(read_only))
+            });
+        for path in outer {
+            bind(&mut args, "--bind", path);
+        }
+        for path in &self.read_only_paths {
+            if path.exists() {
+                bind(&mut args, "--ro-bind", path);
+            } else if existing_writable.iter().any(|w| path.starts_with(w))
+                && path.parent().is_some_and(Path::exists)
+            {
+                // Without a mount the path could be created through the writable parent;
+                // bwrap leaves the empty mount point behind on the host
+                let path = path.to_string_lossy().to_string();
+                args.extend([
+                    "--tmpfs".to_string(),
+                    path.clone(),
+                    "--remount-ro".to_string(),
+                    path,
+                ]);
+            }
+        }
+        for path in nested {
+            bind(&mut args, "--bind", path);
+        }
+        for path in &self.hidden_paths {
+            let display = path.to_string_lossy().to_string();
+            if path.is_dir() {
+                args.extend(["--tmpfs".to_string(), display]);
+            } else if path.exists() {
+                args.extend(["--ro-bind".to_string(), "/dev/null".to_string(), display]);
+            }
+        }
+
+        args.extend(
+            ["--unshare-pid", "--unshare-ipc", "--die-with-parent"]
+                .iter()
+                .map(|s| s.to_string()),
+        );
+        if !self.allow_network {
+            args.push("--unshare-net".to_string());
+        }
+        args.extend([
+            "--setenv".to_string(),
+            "BITFUN_SANDBOX".to_string(),
+            "1".to_string(),
+            "--chdir".to_string(),
+            working_directory.to_string(),
+        ]);
+        args
+    }
+
+    /// Detect blocked operations from the output of a sandboxed command
+    pub fn detect_violations(&self, output: &str) -> Vec<SandboxViolation> {
+        let mut violations = Vec::new();
+
+        if let Some(line) = output.lines().find(|line| line.starts_with("bwrap:")) {
+            violations.push(SandboxViolation::Setup(line.trim().to_string()));
+            return violations;
+        }
+
+        if WRITE_DENIED_MARKERS.iter().any(|m| output.contains(m)) {
+            violations.push(SandboxViolation::FileWrite);
+        }
+
+        let home = dirs::home_dir();
+        for path in &self.hidden_paths {
+            let tilde_form = home
+                .as_deref()
+                .and_then(|home| path.strip_prefix(home).ok())
+                .map(|rest| format!("~/{}", rest.display()));
+            let mentioned = output.contains(&*path.to_string_lossy())
+                || tilde_form.is_some_and(|p| output.contains(&p));
+            if mentioned {
+                violations.push(SandboxViolation::FileRead(path.clone()));
+            }
+        }
+
+        if !self.allow_network && NETWORK_DENIED_MARKERS.iter().any(|m| output.contains(m)) {
+            violations.push(SandboxViolation::Network);
+        }
+
+        violations
+    }
+}
+
+impl SandboxViolation {
+    /// Explanation for the model
+    pub fn describe(&self, policy: &SandboxPolicy) -> String {
+        match self {
+            SandboxViolation::FileWrite => format!(
+                "A write outside the writable paths was blocked by the sandbox. Writable paths: {}, /tmp (private). Read-only: {}.",
+                display_paths(&policy.writable_paths),
+                display_paths(&policy.read_only_paths)
+            ),
+            SandboxViolation::FileRead(path) => format!(
+                "{} is hidden by the sandbox and appears empty.",
+                path.display()
+            ),
+            SandboxViolation::Network => {
+                "Network access is disabled by the sandbox.".to_string()
+            }
+            SandboxViolation::Setup(message) => {
+                format!("The sandbox could not be set up: {}", message)
+            }
+        }
+    }
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn bash_config(cwd: &str) -> ShellConfig {
+        ShellConfig {
+            executable: "bash".to_string(),
+            args: vec![
+                "--init-file".to_string(),
+                "/scripts/bash.sh".to_string(),
+                "-i".to_string(),
+            ],
+            env: HashMap::new(),
+            cwd: Some(cwd.to_string()),
+            login: false,
+        }
+    }
+
+    #[test]
+    fn test_wrap_shell_and_detect_violations() {
+        let workspace = std::env::temp_dir();
+        let policy = SandboxPolicy {
+            writable_paths: vec![workspace.clone()],
+            read_only_paths: vec![],
+            hidden_paths: vec![PathBuf::from("/nonexistent/.ssh")],
+            allow_network: false,
+        };
+
+        let mut config = bash_config("/work dir");
+        policy
+            .wrap_shell(&mut config, &ShellType::Bash, None)
+            .unwrap();
+        let args = config.args.join(" ");
+        assert_eq!(config.executable, "bwrap");
+        assert!(args.starts_with("--ro-bind / / --dev /dev --proc /proc --tmpfs /tmp"));
+        assert!(args.contains(&format!("--bind {0} {0}", workspace.display())));
+        assert!(args.contains("--unshare-net"));
+        assert!(!args.contains("/nonexistent/.ssh"));
+        assert!(args.ends_with("--chdir /work dir -- bash --init-file /scripts/bash.sh -i"));
+        assert!(policy
+            .wrap_shell(&mut bash_config("C:\\"), &ShellType::PowerShell, None)
+            .is_err());
+
+        assert_eq!(
+            policy.detect_violations("touch: cannot touch '/etc/x': Read-only file system"),
+            vec![SandboxViolation::FileWrite]
+        );
+        assert_eq!(
+            policy.detect_violations("curl: (6) Could not resolve host: example.com"),
+            vec![SandboxViolation::Network]
+        );
+        assert_eq!(
+            policy.detect_violations("cat: /nonexistent/.ssh/id_rsa: No such file or directory"),
+            vec![SandboxViolation::FileRead(PathBuf::from("/nonexistent/.ssh"))]
+        );
+        assert!(policy.detect_violations("ok").is_empty());
+    }
+
+    #[test]
+    fn test_wrap_shell_protects_hook_dirs() {
+        let workspace = std::env::temp_dir().join(format!("bitfun-sandbox-{}", std::process::id()));
+        let temp = workspace.join(".bitfun/local/temp");
+        let config = workspace.join("user-config");
+        std::fs::create_dir_all(&temp).unwrap();
+        std::fs::create_dir_all(&config).unwrap();
+
+        let mut policy = SandboxPolicy::new(vec![temp.clone(), workspace.clone()], false);
+        policy.hidden_paths.push(config.clone());
+        let mut shell = bash_config("/");
+        policy
+            .wrap_shell(&mut shell, &ShellType::Bash, None)
+            .unwrap();
+        let args = shell.args.join(" ");
+
+        let ws = workspace.display();
+        let position = |needle: String| {
+            args.find(&needle)
+                .unwrap_or_else(|| panic!("missing {} in {}", needle, args))
+        };
+        let workspace_bind = position(format!("--bind {0} {0}", ws));
+        let bitfun_ro = position(format!("--ro-bind {0}/.bitfun {0}/.bitfun", ws));
+        // `.git` does not exist yet, so an empty read-only directory stands in for it
+        let git_placeholder = position(format!("--tmpfs {0}/.git --remount-ro {0}/.git", ws));
+        let temp_bind = position(format!("--bind {0} {0}", temp.display()));
+        position(format!("--tmpfs {}", config.display()));
+        assert!(workspace_bind < bitfun_ro && workspace_bind < git_placeholder);
+        assert!(bitfun_ro < temp_bind);
+
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+}
@@ -16,6 +16,7 @@ use std::sync::Arc;
 use dashmap::DashMap;
 use log::warn;
 
+use crate::sandbox::SandboxPolicy;
 use crate::session::get_session_manager;
 use crate::shell::ShellType;
 use crate::{TerminalError, TerminalResult};
@@ -37,6 +38,8 @@ pub struct TerminalBindingOptions {
     pub cols: Option<u16>,
     /// Terminal rows (default: 30)
     pub rows: Option<u16>,
+    /// Sandbox to run the terminal's shell in
+    pub sandbox: Option<SandboxPolicy>,
 }
 
 /// Terminal Session Binding Manager
@@ -66,6 +69,8 @@ impl TerminalSessionBinding {
     ///
     /// If a binding already exists, returns the existing terminal session ID.
     /// If no binding exists, creates a new terminal session and establishes the binding.
+    /// A bound session whose sandbox differs from `options.sandbox` is closed and
+    /// replaced, since the sandbox of a running shell cannot change.
     ///
     /// # Arguments
     /// * `owner_id` - The external entity ID (e.g., chat_session_id, workflow_id)
@@ -78,14 +83,30 @@ impl TerminalSessionBinding {
         owner_id: &str,
         options: TerminalBindingOptions,
     ) -> TerminalResult<String> {
+        let session_manager = get_session_manager()
+            .ok_or_else(|| TerminalError::Session("SessionManager not initialized".to_string()))?;
+
         // Check if binding already exists
         if let Some(terminal_session_id) = self.get(owner_id) {
-            return Ok(terminal_session_id);
-        }
+            let sandbox_changed = match session_manager.get_session(&terminal_session_id).await {
+                Some(session) => session.sandbox != options.sandbox,
+                None => false,
+            };
+            if !sandbox_changed {
+                return Ok(terminal_session_id);
+            }
 
-        // Create a new terminal session
-        let session_manager = get_session_manager()
-            .ok_or_else(|| TerminalError::Session("SessionManager not initialized".to_string()))?;
+            self.unbind(owner_id);
+            if let Err(e) = session_manager
+                .close_session(&terminal_session_id, true)
+                .await
+            {
+                warn!(
+                    "Failed to close terminal session {} after sandbox change: {}",
+                    terminal_session_id, e
+                );
+            }
+        }
 
         // Generate a session ID based on owner_id for easier debugging
         let terminal_session_id = options.session_id.unwrap_or_else(|| {
@@ -101,17 +122,35 @@ impl TerminalSessionBinding {
             .unwrap_or_else(|| format!("Terminal-{}", &owner_id[..8.min(owner_id.len())]));
 
         // Create the session
-        let _session = session_manager
-            .create_session(
-                Some(terminal_session_id.clone()),
-                Some(session_name),
-                options.shell_type,
-                options.working_directory,
-                options.env,
-                options.cols,
-                options.rows,
-            )
-            .await?;
+        let _session = match options.sandbox {
+            Some(sandbox) => {
+                session_manager
+                    .create_sandboxed_session(
+                        Some(terminal_session_id.clone()),
+                        Some(session_name),
+                        options.shell_type,
+                        options.working_directory,
+                        options.env,
+                        options.cols,
+                        options.rows,
+                        sandbox,
+                    )
+                    .await?
+            }
+            None => {
+                session_manager
+                    .create_session(
+                        Some(terminal_session_id.clone()),
+                        Some(session_name),
+                        options.shell_type,
+                        options.working_directory,
+                        options.env,
+                        options.cols,
+                        options.rows,
+                    )
+                    .await?
+            }
+        };
 
         // Establish the binding
         self.bindings
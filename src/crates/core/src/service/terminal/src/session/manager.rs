@@ -13,6 +13,7 @@ use tokio::time::timeout;
 use crate::config::{ShellConfig, TerminalConfig};
 use crate::events::{TerminalEvent, TerminalEventEmitter};
 use crate::pty::{ProcessProperty, PtyService, PtyServiceEvent};
+use crate::sandbox::SandboxPolicy;
 use crate::shell::{
     CommandState, ScriptsManager, ShellDetector, ShellIntegration, ShellIntegrationEvent,
     ShellIntegrationManager, ShellType,
@@ -329,6 +330,61 @@ impl SessionManager {
         cols: Option<u16>,
         rows: Option<u16>,
         enable_integration: bool,
+    ) -> TerminalResult<TerminalSession> {
+        self.create_session_inner(
+            session_id,
+            name,
+            shell_type,
+            cwd,
+            env,
+            cols,
+            rows,
+            enable_integration,
+            None,
+        )
+        .await
+    }
+
+    /// Create a new terminal session whose shell runs inside the sandbox
+    ///
+    /// All commands of the session run in that shell, so its state (current directory,
+    /// exported variables) carries over between commands like in an unsandboxed session.
+    pub async fn create_sandboxed_session(
+        &self,
+        session_id: Option<String>,
+        name: Option<String>,
+        shell_type: Option<ShellType>,
+        cwd: Option<String>,
+        env: Option<HashMap<String, String>>,
+        cols: Option<u16>,
+        rows: Option<u16>,
+        sandbox: SandboxPolicy,
+    ) -> TerminalResult<TerminalSession> {
+        self.create_session_inner(
+            session_id,
+            name,
+            shell_type,
+            cwd,
+            env,
+            cols,
+            rows,
+            true,
+            Some(sandbox),
+        )
+        .await
+    }
+
+    async fn create_session_inner(
+        &self,
+        session_id: Option<String>,
+        name: Option<String>,
+        shell_type: Option<ShellType>,
+        cwd: Option<String>,
+        env: Option<HashMap<String, String>>,
+        cols: Option<u16>,
+        rows: Option<u16>,
+        enable_integration: bool,
+        sandbox: Option<SandboxPolicy>,
     ) -> TerminalResult<TerminalSession> {
         // Use provided session ID or generate a new one
         let session_id = session_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
@@ -420,12 +476,21 @@ impl SessionManager {
             self.inject_shell_integration(&mut shell_config, &shell_type, &nonce);
         }
 
+        // The sandbox wraps the shell including its integration arguments
+        if let Some(ref policy) = sandbox {
+            policy.wrap_shell(
+                &mut shell_config,
+                &shell_type,
+                Some(self.scripts_manager.scripts_dir()),
+            )?;
+        }
+
         // Use provided dimensions or fall back to config defaults
         let cols = cols.unwrap_or(self.config.default_cols);
         let rows = rows.unwrap_or(self.config.default_rows);
 
         // Create the session record
-        let session = TerminalSession::new(
+        let mut session = TerminalSession::new(
             session_id.clone(),
             name,
             shell_type.clone(),
@@ -433,6 +498,7 @@ impl SessionManager {
             cols,
             rows,
         );
+        session.sandbox = sandbox;
 
         // Store the session
         {
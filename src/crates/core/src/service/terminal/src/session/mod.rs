@@ -24,6 +24,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::sandbox::SandboxPolicy;
 use crate::shell::ShellType;
 
 /// Terminal session status
@@ -106,6 +107,10 @@ pub struct TerminalSession {
     /// Maximum size of output history (in bytes)
     #[serde(skip)]
     pub max_history_size: usize,
+
+    /// Sandbox the session's shell runs in, if any
+    #[serde(skip)]
+    pub sandbox: Option<SandboxPolicy>,
 }
 
 impl TerminalSession {
@@ -141,6 +146,7 @@ impl TerminalSession {
             exit_code: None,
             output_history: Vec::new(),
             max_history_size: Self::DEFAULT_MAX_HISTORY_SIZE,
+            sandbox: None,
         }
     }
 
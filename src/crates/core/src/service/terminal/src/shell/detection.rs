@@ -303,7 +303,7 @@ impl ShellDetector {
         None
     }
 
-    fn find_in_path(executable: &str) -> Option<PathBuf> {
+    pub(crate) fn find_in_path(executable: &str) -> Option<PathBuf> {
         #[cfg(windows)]
         let path_var = std::env::var("PATH").ok()?;
         #[cfg(not(windows))]
@@ -20,6 +20,10 @@ pub enum BitFunError {
     #[error("AI client error: {0}")]
     AIClient(String),
 
+    /// The model kept failing with transient errors (rate limit, overload, outage)
+    #[error("Model unavailable: {0}")]
+    ModelUnavailable(String),
+
     #[error("Session error: {0}")]
     Session(String),
 
@@ -1,100 +1,466 @@
-//! Token estimation utility
+//! Token counting utility
+//!
+//! Token counts drive context compression, so they should follow the tokenizer of the model
+//! in use. OpenAI models are counted exactly with the bundled BPE vocabularies (cl100k / o200k),
+//! Anthropic models with cl100k scaled to Claude's tokenizer, and other models with a
+//! character-class estimator that handles CJK text and code.
 
+use crate::util::types::config::AIConfig;
 use crate::util::types::{Message, ToolDefinition};
+use once_cell::sync::Lazy;
+use tiktoken_rs::CoreBPE;
 
-/// Heuristic: ASCII chars 0.3 token, non-ASCII chars 0.6 token
-pub struct TokenCounter;
+/// Fixed overhead of every message (role and separators)
+const MESSAGE_OVERHEAD: usize = 4;
+/// Fixed overhead of every tool call
+const TOOL_CALL_OVERHEAD: usize = 10;
+/// Fixed overhead of every tool definition
+const TOOL_DEFINITION_OVERHEAD: usize = 15;
+/// Fixed overhead of the tool list
+const TOOL_LIST_OVERHEAD: usize = 10;
 
-impl TokenCounter {
-    pub fn estimate_tokens(text: &str) -> usize {
+/// Claude's tokenizer produces roughly 10% more tokens than cl100k for the same text
+const ANTHROPIC_SCALE: f64 = 1.1;
+
+/// Counts the tokens of a piece of text
+pub trait Tokenizer: Send + Sync {
+    /// Identifier stored alongside cached counts
+    fn name(&self) -> &'static str;
+
+    fn count(&self, text: &str) -> usize;
+}
+
+/// Tokenizer families
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenizerKind {
+    /// GPT-4o, GPT-4.1, GPT-5 and o-series models
+    O200k,
+    /// GPT-4, GPT-3.5 and embedding models
+    Cl100k,
+    /// Claude models
+    Anthropic,
+    /// Character-class estimate for everything else
+    Estimate,
+}
+
+impl TokenizerKind {
+    /// Parse the `tokenizer` option of a model config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "o200k" | "o200k_base" => Some(Self::O200k),
+            "cl100k" | "cl100k_base" => Some(Self::Cl100k),
+            "anthropic" | "claude" => Some(Self::Anthropic),
+            "estimate" | "heuristic" => Some(Self::Estimate),
+            _ => None,
+        }
+    }
+
+    /// Detect the tokenizer from the API format and model name
+    pub fn detect(format: &str, model: &str) -> Self {
+        let model = model.to_ascii_lowercase();
+        let model = model.rsplit('/').next().unwrap_or(&model);
+
+        if model.starts_with("claude") || format.eq_ignore_ascii_case("anthropic") {
+            Self::Anthropic
+        } else if model.starts_with("gpt-4o")
+            || model.starts_with("gpt-4.1")
+            || model.starts_with("gpt-4.5")
+            || model.starts_with("gpt-5")
+            || model.starts_with("chatgpt-4o")
+            || model.starts_with("gpt-oss")
+            || is_o_series(model)
+        {
+            Self::O200k
+        } else if model.starts_with("gpt-4")
+            || model.starts_with("gpt-3.5")
+            || model.starts_with("text-embedding-3")
+            || model.starts_with("text-embedding-ada")
+        {
+            Self::Cl100k
+        } else {
+            Self::Estimate
+        }
+    }
+
+    /// Tokenizer for a client config, honoring the explicit `tokenizer` option
+    pub fn for_config(config: &AIConfig) -> Self {
+        config
+            .tokenizer
+            .as_deref()
+            .and_then(Self::parse)
+            .unwrap_or_else(|| Self::detect(&config.format, &config.model))
+    }
+
+    pub fn tokenizer(&self) -> &'static dyn Tokenizer {
+        match self {
+            Self::O200k => &*O200K,
+            Self::Cl100k => &*CL100K,
+            Self::Anthropic => &*ANTHROPIC,
+            Self::Estimate => &ESTIMATE,
+        }
+    }
+}
+
+/// o1, o3, o4-mini, ...
+fn is_o_series(model: &str) -> bool {
+    let mut chars = model.chars();
+    chars.next() == Some('o') && chars.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+/// Exact count with a tiktoken vocabulary, optionally scaled to a related tokenizer
+struct BpeTokenizer {
+    name: &'static str,
+    bpe: &'static CoreBPE,
+    scale: f64,
+}
+
+impl Tokenizer for BpeTokenizer {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn count(&self, text: &str) -> usize {
         if text.is_empty() {
             return 0;
         }
+        let tokens = self.bpe.encode_ordinary(text).len();
+        (tokens as f64 * self.scale).ceil() as usize
+    }
+}
 
-        let mut token_count: f32 = 0.;
+static O200K: Lazy<BpeTokenizer> = Lazy::new(|| BpeTokenizer {
+    name: "o200k",
+    bpe: tiktoken_rs::o200k_base_singleton(),
+    scale: 1.0,
+});
+
+static CL100K: Lazy<BpeTokenizer> = Lazy::new(|| BpeTokenizer {
+    name: "cl100k",
+    bpe: tiktoken_rs::cl100k_base_singleton(),
+    scale: 1.0,
+});
+
+static ANTHROPIC: Lazy<BpeTokenizer> = Lazy::new(|| BpeTokenizer {
+    name: "anthropic",
+    bpe: tiktoken_rs::cl100k_base_singleton(),
+    scale: ANTHROPIC_SCALE,
+});
+
+static ESTIMATE: EstimateTokenizer = EstimateTokenizer;
+
+/// Estimate calibrated against modern BPE vocabularies, without loading one
+///
+/// Text is split into runs of the same character class: words and identifiers cost about one
+/// token per 8 characters, digits and punctuation one per 3, CJK characters about 0.8 each,
+/// while single spaces are merged into the following word.
+struct EstimateTokenizer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Word,
+    Digit,
+    Space,
+    Newline,
+    Punct,
+    Cjk,
+    Letter,
+    Symbol,
+}
+
+impl CharClass {
+    fn of(c: char) -> Self {
+        if c.is_ascii_alphabetic() || c == '_' {
+            Self::Word
+        } else if c.is_ascii_digit() {
+            Self::Digit
+        } else if c == '\n' || c == '\r' {
+            Self::Newline
+        } else if c.is_whitespace() {
+            Self::Space
+        } else if c.is_ascii() {
+            Self::Punct
+        } else if is_cjk(c) {
+            Self::Cjk
+        } else if c.is_alphabetic() {
+            Self::Letter
+        } else {
+            Self::Symbol
+        }
+    }
+
+    fn run_tokens(&self, len: usize, previous: Option<CharClass>) -> f64 {
+        let len = len as f64;
+        match self {
+            Self::Word => 1.0 + ((len - 1.0) / 8.0).floor(),
+            Self::Digit => (len / 3.0).ceil(),
+            Self::Space => {
+                if len <= 1.0 {
+                    0.0
+                } else {
+                    (len / 8.0).ceil()
+                }
+            }
+            // Line breaks merge with preceding punctuation, e.g. `{\n` or `);\n`
+            Self::Newline if previous == Some(Self::Punct) => 0.0,
+            Self::Newline => 1.0,
+            Self::Punct => 1.0 + ((len - 1.0) / 3.0).floor(),
+            Self::Cjk => len * 0.8,
+            Self::Letter => len * 0.4,
+            Self::Symbol => len * 1.5,
+        }
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x11FF       // Hangul Jamo
+        | 0x2E80..=0x303F     // CJK radicals, symbols and punctuation
+        | 0x3040..=0x30FF     // Hiragana, Katakana
+        | 0x3100..=0x31FF     // Bopomofo, Hangul compatibility Jamo
+        | 0x3400..=0x4DBF     // CJK extension A
+        | 0x4E00..=0x9FFF     // CJK unified ideographs
+        | 0xAC00..=0xD7AF     // Hangul syllables
+        | 0xF900..=0xFAFF     // CJK compatibility ideographs
+        | 0xFF00..=0xFFEF     // Fullwidth forms
+        | 0x20000..=0x2FA1F   // CJK extensions B-F
+    )
+}
+
+impl Tokenizer for EstimateTokenizer {
+    fn name(&self) -> &'static str {
+        "estimate"
+    }
+
+    fn count(&self, text: &str) -> usize {
+        let mut total = 0.0;
+        let mut previous: Option<CharClass> = None;
+        let mut run: Option<(CharClass, usize)> = None;
 
         for c in text.chars() {
-            if c.is_ascii() {
-                token_count += 0.3;
-            } else {
-                token_count += 0.6;
+            let class = CharClass::of(c);
+            match run {
+                Some((current, ref mut len)) if current == class => *len += 1,
+                _ => {
+                    if let Some((current, len)) = run {
+                        total += current.run_tokens(len, previous);
+                        previous = Some(current);
+                    }
+                    run = Some((class, 1));
+                }
             }
         }
+        if let Some((current, len)) = run {
+            total += current.run_tokens(len, previous);
+        }
 
-        token_count as usize
+        total.ceil() as usize
     }
+}
 
-    pub fn estimate_message_tokens(message: &Message) -> usize {
-        let mut total = 0;
+/// Token counter bound to a tokenizer
+///
+/// The associated `estimate_*` functions use the model-agnostic estimate; the instance methods
+/// count with the tokenizer of a specific model.
+#[derive(Clone, Copy)]
+pub struct TokenCounter {
+    tokenizer: &'static dyn Tokenizer,
+}
+
+impl Default for TokenCounter {
+    fn default() -> Self {
+        Self::new(TokenizerKind::Estimate)
+    }
+}
+
+impl std::fmt::Debug for TokenCounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenCounter")
+            .field("tokenizer", &self.tokenizer.name())
+            .finish()
+    }
+}
+
+impl TokenCounter {
+    /// Fixed overhead of a request (reply priming)
+    pub const REQUEST_OVERHEAD: usize = 3;
+
+    pub fn new(kind: TokenizerKind) -> Self {
+        Self {
+            tokenizer: kind.tokenizer(),
+        }
+    }
+
+    /// Counter matching the model of a client config
+    pub fn for_config(config: &AIConfig) -> Self {
+        Self::new(TokenizerKind::for_config(config))
+    }
+
+    /// Name of the tokenizer, used to validate cached counts
+    pub fn tokenizer_name(&self) -> &'static str {
+        self.tokenizer.name()
+    }
+
+    pub fn count_text(&self, text: &str) -> usize {
+        self.tokenizer.count(text)
+    }
 
-        total += 4;
+    pub fn count_message(&self, message: &Message) -> usize {
+        let mut total = MESSAGE_OVERHEAD;
 
         if let Some(reasoning_content) = &message.reasoning_content {
-            total += Self::estimate_tokens(reasoning_content);
+            total += self.count_text(reasoning_content);
         }
 
         if let Some(content) = &message.content {
-            total += Self::estimate_tokens(content);
+            total += self.count_text(content);
         }
 
         if let Some(tool_calls) = &message.tool_calls {
             for tool_call in tool_calls {
-                total += Self::estimate_tokens(&tool_call.name);
+                total += self.count_text(&tool_call.name);
                 if let Ok(json_str) = serde_json::to_string(&tool_call.arguments) {
-                    total += Self::estimate_tokens(&json_str);
+                    total += self.count_text(&json_str);
                 }
-                total += 10;
+                total += TOOL_CALL_OVERHEAD;
             }
         }
 
         if let Some(name) = &message.name {
-            total += Self::estimate_tokens(name);
+            total += self.count_text(name);
         }
 
         total
     }
 
-    pub fn estimate_messages_tokens(messages: &[Message]) -> usize {
-        let mut total: usize = messages.iter()
-            .map(Self::estimate_message_tokens)
-            .sum();
-
-        total += 3;
-
-        total
+    pub fn count_messages(&self, messages: &[Message]) -> usize {
+        messages
+            .iter()
+            .map(|message| self.count_message(message))
+            .sum::<usize>()
+            + Self::REQUEST_OVERHEAD
     }
 
-    pub fn estimate_tool_definitions_tokens(tools: &[ToolDefinition]) -> usize {
+    pub fn count_tool_definitions(&self, tools: &[ToolDefinition]) -> usize {
         let mut total = 0;
 
         for tool in tools {
-            total += Self::estimate_tokens(&tool.name);
-            total += Self::estimate_tokens(&tool.description);
+            total += self.count_text(&tool.name);
+            total += self.count_text(&tool.description);
 
             if let Ok(json_str) = serde_json::to_string(&tool.parameters) {
-                total += Self::estimate_tokens(&json_str);
+                total += self.count_text(&json_str);
             }
 
-            total += 15;
+            total += TOOL_DEFINITION_OVERHEAD;
         }
 
         if !tools.is_empty() {
-            total += 10;
+            total += TOOL_LIST_OVERHEAD;
+        }
+
+        total
+    }
+
+    pub fn count_request(&self, messages: &[Message], tools: Option<&[ToolDefinition]>) -> usize {
+        let mut total = self.count_messages(messages);
+
+        if let Some(tool_defs) = tools {
+            total += self.count_tool_definitions(tool_defs);
         }
 
         total
     }
 
+    pub fn estimate_tokens(text: &str) -> usize {
+        Self::default().count_text(text)
+    }
+
+    pub fn estimate_message_tokens(message: &Message) -> usize {
+        Self::default().count_message(message)
+    }
+
+    pub fn estimate_messages_tokens(messages: &[Message]) -> usize {
+        Self::default().count_messages(messages)
+    }
+
+    pub fn estimate_tool_definitions_tokens(tools: &[ToolDefinition]) -> usize {
+        Self::default().count_tool_definitions(tools)
+    }
+
     pub fn estimate_request_tokens(
         messages: &[Message],
         tools: Option<&[ToolDefinition]>,
     ) -> usize {
-        let mut total = Self::estimate_messages_tokens(messages);
+        Self::default().count_request(messages, tools)
+    }
+}
 
-        if let Some(tool_defs) = tools {
-            total += Self::estimate_tool_definitions_tokens(tool_defs);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGLISH: &str = "The compression manager keeps the most recent turns and summarizes \
+        older ones once the context window is nearly full. Token counts must therefore be \
+        close to what the provider reports, otherwise compression triggers too early or too late.";
+
+    const CODE: &str = r#"pub fn get_workspace_path() -> Option<PathBuf> {
+    let guard = WORKSPACE_PATH.read().unwrap();
+    if let Some(path) = guard.as_ref() {
+        return Some(path.clone());
+    }
+    std::env::current_dir().ok()
+}
+
+fn main() {
+    let counter = TokenCounter::new(TokenizerKind::O200k);
+    println!("{}", counter.count_text("hello world"));
+}
+"#;
+
+    const CJK: &str = "上下文压缩会在窗口接近上限时总结较早的对话轮次，只保留最近的内容。\
+        如果令牌数量估算偏低，压缩触发得太晚，请求就会超出模型的上下文长度限制。";
+
+    fn assert_close(name: &str, estimate: usize, exact: usize) {
+        let ratio = estimate as f64 / exact as f64;
+        assert!(
+            (0.75..=1.25).contains(&ratio),
+            "{}: estimate={}, exact={}, ratio={:.2}",
+            name,
+            estimate,
+            exact,
+            ratio
+        );
+    }
+
+    #[test]
+    fn estimate_is_close_to_bpe() {
+        let estimate = TokenizerKind::Estimate.tokenizer();
+        let o200k = TokenizerKind::O200k.tokenizer();
+
+        for (name, text) in [("english", ENGLISH), ("code", CODE), ("cjk", CJK)] {
+            assert_close(name, estimate.count(text), o200k.count(text));
         }
+    }
 
-        total
+    #[test]
+    fn detects_tokenizer_from_model() {
+        assert_eq!(TokenizerKind::detect("openai", "gpt-4o-mini"), TokenizerKind::O200k);
+        assert_eq!(TokenizerKind::detect("openai", "o3-mini"), TokenizerKind::O200k);
+        assert_eq!(TokenizerKind::detect("openai", "openai/gpt-5"), TokenizerKind::O200k);
+        assert_eq!(TokenizerKind::detect("openai", "gpt-4-turbo"), TokenizerKind::Cl100k);
+        assert_eq!(TokenizerKind::detect("openai", "claude-sonnet-4"), TokenizerKind::Anthropic);
+        assert_eq!(TokenizerKind::detect("anthropic", "glm-4.6"), TokenizerKind::Anthropic);
+        assert_eq!(TokenizerKind::detect("openai", "deepseek-chat"), TokenizerKind::Estimate);
+        assert_eq!(TokenizerKind::detect("openai", "qwen3-coder"), TokenizerKind::Estimate);
+        assert_eq!(TokenizerKind::parse("O200K"), Some(TokenizerKind::O200k));
+        assert_eq!(TokenizerKind::parse("unknown"), None);
+    }
+
+    #[test]
+    fn anthropic_scales_cl100k() {
+        let cl100k = TokenizerKind::Cl100k.tokenizer().count(ENGLISH);
+        let anthropic = TokenizerKind::Anthropic.tokenizer().count(ENGLISH);
+        assert!(anthropic > cl100k);
+        assert_eq!(TokenizerKind::Anthropic.tokenizer().count(""), 0);
     }
 }
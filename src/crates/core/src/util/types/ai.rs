@@ -28,6 +28,15 @@ pub struct GeminiUsage {
     pub cached_content_token_count: Option<u32>,
 }
 
+/// Response state - for model state management like GPT-5
+///
+/// Server-side conversation state of the OpenAI Responses API.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseState {
+    pub previous_response_id: Option<String>,
+    pub conversation_id: Option<String>,
+}
+
 /// AI connection test result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionTestResult {
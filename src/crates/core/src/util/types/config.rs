@@ -20,6 +20,8 @@ pub struct AIConfig {
     pub skip_ssl_verify: bool,
     /// Custom JSON overriding default request body fields
     pub custom_request_body: Option<serde_json::Value>,
+    /// Tokenizer override for token counting (see `TokenizerKind`)
+    pub tokenizer: Option<String>,
 }
 
 impl TryFrom<AIModelConfig> for AIConfig {
@@ -52,6 +54,7 @@ impl TryFrom<AIModelConfig> for AIConfig {
             custom_headers_mode: other.custom_headers_mode,
             skip_ssl_verify: other.skip_ssl_verify,
             custom_request_body,
+            tokenizer: other.tokenizer,
         })
     }
 }
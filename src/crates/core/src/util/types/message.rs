@@ -18,6 +18,9 @@ pub struct Message {
     pub tool_call_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// Id of the OpenAI Responses API response that produced this assistant message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_id: Option<String>,
 }
 
 impl Message {
@@ -30,6 +33,7 @@ impl Message {
             tool_calls: None,
             tool_call_id: None,
             name: None,
+            response_id: None,
         }
     }
 
@@ -42,6 +46,7 @@ impl Message {
             tool_calls: None,
             tool_call_id: None,
             name: None,
+            response_id: None,
         }
     }
 
@@ -54,6 +59,7 @@ impl Message {
             tool_calls: Some(tool_calls),
             tool_call_id: None,
             name: None,
+            response_id: None,
         }
     }
 
@@ -66,6 +72,7 @@ impl Message {
             tool_calls: None,
             tool_call_id: None,
             name: None,
+            response_id: None,
         }
     }
 }
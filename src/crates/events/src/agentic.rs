@@ -130,6 +130,16 @@ pub enum AgenticEvent {
         subagent_parent_info: Option<SubagentParentInfo>,
     },
 
+    /// The turn moved to the next model in the agent's fallback chain
+    ModelSwitched {
+        session_id: String,
+        turn_id: String,
+        from_model: String,
+        to_model: String,
+        reason: String,
+        subagent_parent_info: Option<SubagentParentInfo>,
+    },
+
     TextChunk {
         session_id: String,
         turn_id: String,
@@ -296,6 +306,7 @@ impl AgenticEvent {
             | Self::TextChunk { session_id, .. }
             | Self::ThinkingChunk { session_id, .. }
             | Self::ModelRoundCompleted { session_id, .. }
+            | Self::ModelSwitched { session_id, .. }
             | Self::ToolEvent { session_id, .. } => Some(session_id),
             Self::SystemError { session_id, .. } => session_id.as_deref(),
         }
@@ -311,6 +322,7 @@ impl AgenticEvent {
             Self::SessionStateChanged { .. }
             | Self::SessionTitleGenerated { .. }
             | Self::DialogTurnCompleted { .. }
+            | Self::ModelSwitched { .. }
             | Self::ContextCompressionFailed { .. } => AgenticEventPriority::High,
 
             Self::TextChunk { .. }
@@ -172,6 +172,16 @@ impl TransportAdapter for TauriTransportAdapter {
                 "subagentParentInfo": subagent_parent_info,
             }))?;
         }
+        AgenticEvent::ModelSwitched { session_id, turn_id, from_model, to_model, reason, subagent_parent_info } => {
+            self.app_handle.emit("agentic://model-switched", json!({
+                "sessionId": session_id,
+                "turnId": turn_id,
+                "fromModel": from_model,
+                "toModel": to_model,
+                "reason": reason,
+                "subagentParentInfo": subagent_parent_info,
+            }))?;
+        }
         _ => {
             warn!("Unhandled AgenticEvent type in TauriAdapter");
         }
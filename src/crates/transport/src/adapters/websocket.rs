@@ -83,11 +83,104 @@ impl TransportAdapter for WebSocketTransportAdapter {
                     "toolEvent": tool_event,
                 })
             }
-            AgenticEvent::DialogTurnCompleted { session_id, turn_id, .. } => {
+            AgenticEvent::DialogTurnCompleted { session_id, turn_id, total_rounds, total_tools, duration_ms, .. } => {
                 json!({
                     "type": "dialog-turn-completed",
                     "sessionId": session_id,
                     "turnId": turn_id,
+                    "totalRounds": total_rounds,
+                    "totalTools": total_tools,
+                    "durationMs": duration_ms,
+                })
+            }
+            AgenticEvent::DialogTurnCancelled { session_id, turn_id, .. } => {
+                json!({
+                    "type": "dialog-turn-cancelled",
+                    "sessionId": session_id,
+                    "turnId": turn_id,
+                })
+            }
+            AgenticEvent::DialogTurnFailed { session_id, turn_id, error, .. } => {
+                json!({
+                    "type": "dialog-turn-failed",
+                    "sessionId": session_id,
+                    "turnId": turn_id,
+                    "error": error,
+                })
+            }
+            AgenticEvent::ModelRoundCompleted { session_id, turn_id, round_id, has_tool_calls, .. } => {
+                json!({
+                    "type": "model-round-completed",
+                    "sessionId": session_id,
+                    "turnId": turn_id,
+                    "roundId": round_id,
+                    "hasToolCalls": has_tool_calls,
+                })
+            }
+            AgenticEvent::ThinkingChunk { session_id, turn_id, round_id, content, .. } => {
+                json!({
+                    "type": "thinking-chunk",
+                    "sessionId": session_id,
+                    "turnId": turn_id,
+                    "roundId": round_id,
+                    "content": content,
+                })
+            }
+            AgenticEvent::ModelSwitched { session_id, turn_id, from_model, to_model, reason, .. } => {
+                json!({
+                    "type": "model-switched",
+                    "sessionId": session_id,
+                    "turnId": turn_id,
+                    "fromModel": from_model,
+                    "toModel": to_model,
+                    "reason": reason,
+                })
+            }
+            AgenticEvent::TokenUsageUpdated { session_id, turn_id, input_tokens, output_tokens, total_tokens, max_context_tokens } => {
+                json!({
+                    "type": "token-usage-updated",
+                    "sessionId": session_id,
+                    "turnId": turn_id,
+                    "inputTokens": input_tokens,
+                    "outputTokens": output_tokens,
+                    "totalTokens": total_tokens,
+                    "maxContextTokens": max_context_tokens,
+                })
+            }
+            AgenticEvent::SessionCreated { session_id, session_name, agent_type } => {
+                json!({
+                    "type": "session-created",
+                    "sessionId": session_id,
+                    "sessionName": session_name,
+                    "agentType": agent_type,
+                })
+            }
+            AgenticEvent::SessionStateChanged { session_id, new_state } => {
+                json!({
+                    "type": "session-state-changed",
+                    "sessionId": session_id,
+                    "newState": new_state,
+                })
+            }
+            AgenticEvent::SessionDeleted { session_id } => {
+                json!({
+                    "type": "session-deleted",
+                    "sessionId": session_id,
+                })
+            }
+            AgenticEvent::SessionTitleGenerated { session_id, title, .. } => {
+                json!({
+                    "type": "session-title-generated",
+                    "sessionId": session_id,
+                    "title": title,
+                })
+            }
+            AgenticEvent::SystemError { session_id, error, recoverable } => {
+                json!({
+                    "type": "system-error",
+                    "sessionId": session_id,
+                    "error": error,
+                    "recoverable": recoverable,
                 })
             }
             _ => return Ok(()),
@@ -149,7 +242,8 @@ impl TransportAdapter for WebSocketTransportAdapter {
     
     async fn emit_generic(&self, event_name: &str, payload: serde_json::Value) -> anyhow::Result<()> {
         self.send_json(json!({
-            "type": event_name,
+            "type": "event",
+            "event": event_name,
             "payload": payload,
         }))?;
         Ok(())
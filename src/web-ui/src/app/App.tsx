@@ -6,6 +6,7 @@ import { useCurrentModelConfig } from '../hooks/useModelConfigs';
 import { ContextMenuRenderer } from '../shared/context-menu-system/components/ContextMenuRenderer';
 import { NotificationContainer, NotificationCenter } from '../shared/notification-system';
 import { ConfirmDialogRenderer } from '../component-library';
+import { useProjectHooksApproval } from './hooks/useProjectHooksApproval';
 import { createLogger } from '@/shared/utils/logger';
 
 // Toolbar Mode
@@ -32,6 +33,9 @@ function App() {
   const { currentConfig } = useCurrentModelConfig();
   const { isInitialized: aiInitialized, isInitializing: aiInitializing, error: aiError } = useAIInitialization(currentConfig);
   
+  // Ask before running hooks from a project's .bitfun/settings.json
+  useProjectHooksApproval();
+  
   // Onboarding state
   const { isOnboardingActive, forceShowOnboarding, completeOnboarding } = useOnboardingStore();
   
@@ -3,4 +3,5 @@
  */
 
 export * from './useApp';
+export * from './useProjectHooksApproval';
 export * from './useWindowControls';
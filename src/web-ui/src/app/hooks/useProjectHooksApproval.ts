@@ -0,0 +1,75 @@
+/**
+ * Project hooks approval hook.
+ *
+ * Hooks from a workspace's .bitfun/settings.json only run after the user approved them;
+ * the backend announces unapproved hooks once and this hook asks the user.
+ */
+
+import { useEffect } from 'react';
+import { api, configAPI } from '@/infrastructure/api';
+import { confirmWarning } from '@/component-library';
+import { notificationService } from '@/shared/notification-system/services/NotificationService';
+import { useI18n } from '@/infrastructure/i18n';
+import { createLogger } from '@/shared/utils/logger';
+
+const log = createLogger('useProjectHooksApproval');
+
+export const PROJECT_HOOKS_APPROVAL_EVENT = 'project-hooks-approval-required';
+
+interface HookCommand {
+  matcher?: string | null;
+  command: string;
+}
+
+interface ProjectHooksApprovalPayload {
+  workspace: string;
+  hash: string;
+  hooks: Record<string, HookCommand[] | undefined>;
+}
+
+function describeHooks(hooks: ProjectHooksApprovalPayload['hooks']): string {
+  return Object.entries(hooks)
+    .flatMap(([event, commands]) =>
+      (commands ?? []).map(hook =>
+        hook.matcher ? `${event} [${hook.matcher}]: ${hook.command}` : `${event}: ${hook.command}`
+      )
+    )
+    .join('\n');
+}
+
+export function useProjectHooksApproval(): void {
+  const { t } = useI18n('components');
+
+  useEffect(() => {
+    const unlisten = api.listen<ProjectHooksApprovalPayload>(
+      PROJECT_HOOKS_APPROVAL_EVENT,
+      async ({ workspace, hash, hooks }) => {
+        const approved = await confirmWarning(
+          t('projectHooks.title'),
+          t('projectHooks.message', { workspace }),
+          {
+            confirmText: t('projectHooks.approve'),
+            cancelText: t('projectHooks.skip'),
+            preview: describeHooks(hooks),
+            previewMaxHeight: 200,
+          }
+        );
+        if (!approved) {
+          return;
+        }
+
+        try {
+          await configAPI.approveProjectHooks(workspace, hash);
+          notificationService.success(t('projectHooks.approved'));
+        } catch (error) {
+          log.error('Failed to approve project hooks', { workspace, error });
+          notificationService.error(
+            t('projectHooks.approveFailed', { error: error instanceof Error ? error.message : String(error) })
+          );
+        }
+      }
+    );
+
+    return unlisten;
+  }, [t]);
+}
@@ -19,7 +19,7 @@ interface ModelConfigStepProps {
 }
 
 /** Provider display order */
-const PROVIDER_ORDER = ['zhipu', 'qwen', 'deepseek', 'volcengine', 'minimax', 'moonshot', 'anthropic'];
+const PROVIDER_ORDER = ['zhipu', 'qwen', 'deepseek', 'volcengine', 'minimax', 'moonshot', 'anthropic', 'gemini', 'openai'];
 
 type TestStatus = 'idle' | 'testing' | 'success' | 'error';
 
@@ -33,8 +33,8 @@ export const ModelConfigStep: React.FC<ModelConfigStepProps> = ({ onSkipForNow }
   const [apiKey, setApiKey] = useState(modelConfig?.apiKey || '');
   const [baseUrl, setBaseUrl] = useState(modelConfig?.baseUrl || '');
   const [modelName, setModelName] = useState(modelConfig?.modelName || '');
-  const [customFormat, setCustomFormat] = useState<'openai' | 'anthropic'>(
-    (modelConfig?.format as 'openai' | 'anthropic') || 'openai'
+  const [customFormat, setCustomFormat] = useState<'openai' | 'anthropic' | 'gemini' | 'responses'>(
+    (modelConfig?.format as 'openai' | 'anthropic' | 'gemini' | 'responses') || 'openai'
   );
   const [testStatus, setTestStatus] = useState<TestStatus>('idle');
   const [testError, setTestError] = useState<string>('');
@@ -120,7 +120,7 @@ export const ModelConfigStep: React.FC<ModelConfigStepProps> = ({ onSkipForNow }
     const effectiveModelName = modelName || (template?.models[0] || '');
 
     // Derive format
-    let format: 'openai' | 'anthropic' = customFormat;
+    let format: 'openai' | 'anthropic' | 'gemini' | 'responses' = customFormat;
     if (template) {
       if (template.baseUrlOptions?.length) {
         const effectiveUrl = baseUrl || template.baseUrl;
@@ -501,10 +501,12 @@ export const ModelConfigStep: React.FC<ModelConfigStepProps> = ({ onSkipForNow }
                 label={t('model.format.label')}
                 options={[
                   { label: 'OpenAI', value: 'openai' },
-                  { label: 'Anthropic', value: 'anthropic' }
+                  { label: 'Anthropic', value: 'anthropic' },
+                  { label: 'Gemini', value: 'gemini' },
+                  { label: 'OpenAI Responses', value: 'responses' }
                 ]}
                 value={customFormat}
-                onChange={(val) => setCustomFormat(val as 'openai' | 'anthropic')}
+                onChange={(val) => setCustomFormat(val as 'openai' | 'anthropic' | 'gemini' | 'responses')}
                 placeholder={t('model.format.placeholder')}
               />
             </div>
@@ -1,6 +1,8 @@
 import React, { useState } from 'react';
-import { snapshotAPI } from '@/infrastructure/api';
+import { useTranslation } from 'react-i18next';
+import { confirmWarning } from '@/component-library';
 import { notificationService } from '@/shared/notification-system';
+import { rollbackToTurnConfirmingDetected } from '../utils/rollbackUtils';
 import { createLogger } from '@/shared/utils/logger';
 import './TurnRollbackButton.scss';
 
@@ -19,6 +21,7 @@ export const TurnRollbackButton: React.FC<TurnRollbackButtonProps> = ({
   isCurrent,
   onRollbackComplete,
 }) => {
+  const { t } = useTranslation('flow-chat');
   const [loading, setLoading] = useState(false);
   
   const handleRollback = async () => {
@@ -37,7 +40,21 @@ export const TurnRollbackButton: React.FC<TurnRollbackButtonProps> = ({
     
     setLoading(true);
     try {
-      const restoredFiles = await snapshotAPI.rollbackToTurn(sessionId, turnIndex);
+      const restoredFiles = await rollbackToTurnConfirmingDetected(
+        sessionId,
+        turnIndex,
+        false,
+        detectedChanges => confirmWarning(
+          t('message.rollbackDetectedTitle'),
+          t('message.rollbackDetectedMessage', { count: detectedChanges.length }),
+          {
+            confirmText: t('message.rollbackDetectedConfirm'),
+            preview: detectedChanges.join('\n'),
+            previewMaxHeight: 200,
+          }
+        )
+      );
+      if (!restoredFiles) return;
       
       log.debug('Rollback completed', { sessionId, turnIndex, restoredFilesCount: restoredFiles.length });
       
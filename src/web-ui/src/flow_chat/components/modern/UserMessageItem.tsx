@@ -10,10 +10,10 @@ import type { DialogTurn } from '../../types/flow-chat';
 import { useFlowChatContext } from './FlowChatContext';
 import { useActiveSession } from '../../store/modernFlowChatStore';
 import { flowChatStore } from '../../store/FlowChatStore';
-import { snapshotAPI } from '@/infrastructure/api';
 import { notificationService } from '@/shared/notification-system';
 import { globalEventBus } from '@/infrastructure/event-bus';
-import { ReproductionStepsBlock, Tooltip } from '@/component-library';
+import { ReproductionStepsBlock, Tooltip, confirmWarning } from '@/component-library';
+import { rollbackToTurnConfirmingDetected } from '../../utils/rollbackUtils';
 import { createLogger } from '@/shared/utils/logger';
 import './UserMessageItem.scss';
 
@@ -101,7 +101,21 @@ export const UserMessageItem = React.memo<UserMessageItemProps>(
 
       setIsRollingBack(true);
       try {
-        const restoredFiles = await snapshotAPI.rollbackToTurn(sessionId, turnIndex, true);
+        const restoredFiles = await rollbackToTurnConfirmingDetected(
+          sessionId,
+          turnIndex,
+          true,
+          detectedChanges => confirmWarning(
+            t('message.rollbackDetectedTitle'),
+            t('message.rollbackDetectedMessage', { count: detectedChanges.length }),
+            {
+              confirmText: t('message.rollbackDetectedConfirm'),
+              preview: detectedChanges.join('\n'),
+              previewMaxHeight: 200,
+            }
+          )
+        );
+        if (!restoredFiles) return;
 
         // 1) Truncate local dialog turns from this index.
         flowChatStore.truncateDialogTurnsFrom(sessionId, turnIndex);
@@ -28,6 +28,7 @@ export interface AgenticEventCallbacks {
   onContextCompressionStarted?: (event: AgenticEvent) => void;
   onContextCompressionCompleted?: (event: AgenticEvent) => void;
   onContextCompressionFailed?: (event: AgenticEvent) => void;
+  onModelSwitched?: (event: AgenticEvent) => void;
 }
 
 export class AgenticEventListener {
@@ -137,6 +138,14 @@ export class AgenticEventListener {
         this.unlistenFunctions.push(unlisten);
       }
 
+      if (callbacks.onModelSwitched) {
+        const unlisten = agentAPI.onModelSwitched((event) => {
+          logger.warn('Model switched:', event);
+          callbacks.onModelSwitched?.(event);
+        });
+        this.unlistenFunctions.push(unlisten);
+      }
+
       this.isListening = true;
       logger.info(`Registered ${this.unlistenFunctions.length} event listeners`);
     } catch (error) {
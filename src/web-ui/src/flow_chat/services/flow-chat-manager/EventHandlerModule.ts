@@ -173,6 +173,9 @@ export async function initializeEventListeners(
     },
     onContextCompressionFailed: (event) => {
       handleCompressionFailed(context, event);
+    },
+    onModelSwitched: (event) => {
+      handleModelSwitched(event);
     }
   };
 
@@ -635,6 +638,24 @@ function handleCompressionFailed(context: FlowChatContext, event: any): void {
   immediateSaveDialogTurn(context, sessionId, turnId);
 }
 
+/**
+ * Handle model fallback event: the rest of the turn is answered by another model
+ */
+function handleModelSwitched(event: any): void {
+  const { sessionId, turnId, fromModel, toModel, reason, subagentParentInfo } = event;
+
+  log.warn('Model switched', { sessionId, turnId, fromModel, toModel, reason });
+
+  if (subagentParentInfo) {
+    return;
+  }
+
+  notificationService.warning(`${fromModel} is unavailable, answering with ${toModel}`, {
+    title: 'Model switched',
+    duration: 5000
+  });
+}
+
 /**
  * Handle dialog turn completed event
  */
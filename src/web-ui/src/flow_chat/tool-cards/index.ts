@@ -64,6 +64,16 @@ export const TOOL_CARD_CONFIGS: Record<string, ToolCardConfig> = {
     displayMode: 'standard',
     primaryColor: '#f59e0b'
   },
+  'MultiEdit': {
+    toolName: 'MultiEdit',
+    displayName: 'Edit Files',
+    icon: 'E',
+    requiresConfirmation: false, // Snapshot system handles confirmation.
+    resultDisplayType: 'summary',
+    description: 'Apply edits or a patch across files',
+    displayMode: 'standard',
+    primaryColor: '#f59e0b'
+  },
   'Delete': {
     toolName: 'Delete',
     displayName: 'Delete File',
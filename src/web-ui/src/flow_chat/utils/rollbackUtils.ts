@@ -0,0 +1,31 @@
+/**
+ * Turn rollback helpers.
+ */
+
+import { snapshotAPI, DetectedChangesConfirmationError } from '@/infrastructure/api';
+
+/**
+ * Roll back to before a turn.
+ *
+ * When the rollback would revert changes found by a workspace scan, `confirmDetected`
+ * is asked with the affected files and the rollback is retried if the user agrees.
+ * Returns the restored files, or null if the user declined.
+ */
+export async function rollbackToTurnConfirmingDetected(
+  sessionId: string,
+  turnIndex: number,
+  deleteTurns: boolean,
+  confirmDetected: (detectedChanges: string[]) => Promise<boolean>
+): Promise<string[] | null> {
+  try {
+    return await snapshotAPI.rollbackToTurn(sessionId, turnIndex, deleteTurns);
+  } catch (error) {
+    if (!(error instanceof DetectedChangesConfirmationError)) {
+      throw error;
+    }
+    if (!await confirmDetected(error.detectedChanges)) {
+      return null;
+    }
+    return snapshotAPI.rollbackToTurn(sessionId, turnIndex, deleteTurns, true);
+  }
+}
@@ -18,7 +18,7 @@ import { agentAPI } from './service-api/AgentAPI';
 import { systemAPI } from './service-api/SystemAPI';
 import { projectAPI } from './service-api/ProjectAPI';
 import { diffAPI } from './service-api/DiffAPI';
-import { snapshotAPI } from './service-api/SnapshotAPI';
+import { snapshotAPI, DetectedChangesConfirmationError } from './service-api/SnapshotAPI';
 import { globalAPI } from './service-api/GlobalAPI';
 import { contextAPI } from './service-api/ContextAPI';
 import { gitAPI } from './service-api/GitAPI';
@@ -31,6 +31,8 @@ import { i18nAPI } from './service-api/I18nAPI';
 // Export API modules
 export { workspaceAPI, configAPI, aiApi, toolAPI, agentAPI, systemAPI, projectAPI, diffAPI, snapshotAPI, globalAPI, contextAPI, gitAPI, gitAgentAPI, gitRepoHistoryAPI, startchatAgentAPI, conversationAPI, i18nAPI };
 
+export { DetectedChangesConfirmationError };
+
 // Export types
 export type { GitRepoHistory };
 
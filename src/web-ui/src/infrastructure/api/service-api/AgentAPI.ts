@@ -102,6 +102,13 @@ export interface ToolEvent extends AgenticEvent {
 }
 
  
+export interface ModelSwitchedEvent extends AgenticEvent {
+  fromModel: string;
+  toModel: string;
+  reason: string;
+}
+
+ 
 export interface CompressionEvent extends AgenticEvent {
   compressionId: string;          
   
@@ -289,6 +296,11 @@ export class AgentAPI {
   }
 
    
+  onModelSwitched(callback: (event: ModelSwitchedEvent) => void): () => void {
+    return api.listen<ModelSwitchedEvent>('agentic://model-switched', callback);
+  }
+
+   
   async getAvailableTools(): Promise<string[]> {
     try {
       return await api.invoke<string[]>('get_available_tools');
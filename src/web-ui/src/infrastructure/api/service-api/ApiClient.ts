@@ -17,6 +17,19 @@ import { createLogger } from '@/shared/utils/logger';
 
 const log = createLogger('ApiClient');
 
+/** Error object a backend command rejected with, e.g. `{ code, message, detectedChanges }` */
+interface StructuredCommandError {
+  code: string;
+  message: string;
+  [key: string]: unknown;
+}
+
+function isStructuredCommandError(error: unknown): error is StructuredCommandError {
+  return typeof error === 'object' && error !== null && !(error instanceof Error)
+    && typeof (error as StructuredCommandError).code === 'string'
+    && typeof (error as StructuredCommandError).message === 'string';
+}
+
 export class ApiClient implements IApiClient {
   private config: ApiConfig;
   private activeRequests = new Map<string, AbortController>();
@@ -210,7 +223,11 @@ export class ApiClient implements IApiClient {
         timestamp: new Date()
       };
     } catch (error) {
-      const errorMessage = error instanceof Error ? error.message : String(error);
+      // Commands may reject with a structured `{ code, message, ... }` error
+      const structuredError = isStructuredCommandError(error) ? error : null;
+      const errorMessage = structuredError
+        ? structuredError.message
+        : error instanceof Error ? error.message : String(error);
       
       
       const isExpectedError = errorMessage.includes('not found') || 
@@ -232,7 +249,7 @@ export class ApiClient implements IApiClient {
         });
       }
       
-      throw this.createApiError('COMMAND_FAILED', errorMessage, error);
+      throw this.createApiError(structuredError?.code ?? 'COMMAND_FAILED', errorMessage, error);
     }
   }
 
@@ -319,7 +336,8 @@ export class ApiClient implements IApiClient {
     if (originalError) {
       apiError.details = {
         originalError: originalError.message || originalError,
-        stack: originalError.stack
+        stack: originalError.stack,
+        ...(isStructuredCommandError(originalError) ? { payload: originalError } : {})
       };
     }
 
@@ -223,6 +223,17 @@ export class ConfigAPI {
   }
 
    
+  async approveProjectHooks(workspacePath: string, hash: string): Promise<void> {
+    try {
+      await api.invoke('approve_project_hooks', {
+        request: { workspacePath, hash }
+      });
+    } catch (error) {
+      throw createTauriCommandError('approve_project_hooks', error, { workspacePath, hash });
+    }
+  }
+
+   
   async addSkill(sourcePath: string, level: SkillLevel): Promise<string> {
     try {
       return await api.invoke('add_skill', { sourcePath, level });
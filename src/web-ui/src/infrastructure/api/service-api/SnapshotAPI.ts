@@ -6,6 +6,32 @@ import { createLogger } from '@/shared/utils/logger';
 
 const log = createLogger('SnapshotAPI');
 
+/** Error code of a rollback that would revert changes found by a workspace scan */
+export const DETECTED_CHANGES_NEED_CONFIRMATION = 'DETECTED_CHANGES_NEED_CONFIRMATION';
+
+/**
+ * Thrown when a rollback would revert changes that were attributed to a tool by a
+ * workspace scan and may include the user's own edits. Callers ask the user and retry
+ * with `confirmDetected` set.
+ */
+export class DetectedChangesConfirmationError extends Error {
+  constructor(message: string, public readonly detectedChanges: string[]) {
+    super(message);
+    this.name = 'DetectedChangesConfirmationError';
+  }
+}
+
+function detectedChangesError(error: any): DetectedChangesConfirmationError | null {
+  if (error?.code !== DETECTED_CHANGES_NEED_CONFIRMATION) {
+    return null;
+  }
+  const detectedChanges = error.details?.payload?.detectedChanges;
+  return new DetectedChangesConfirmationError(
+    error.message,
+    Array.isArray(detectedChanges) ? detectedChanges : []
+  );
+}
+
 
 export interface SandboxSessionModifications {
   hasModifications: boolean;
@@ -345,7 +371,8 @@ export class SnapshotAPI {
   async rollbackToTurn(
     sessionId: string,
     turnIndex: number,
-    deleteTurns: boolean = false
+    deleteTurns: boolean = false,
+    confirmDetected: boolean = false
   ): Promise<string[]> {
     try {
       return await api.invoke('rollback_to_turn', {
@@ -353,27 +380,32 @@ export class SnapshotAPI {
           session_id: sessionId,
           turn_index: turnIndex,
           delete_turns: deleteTurns,
+          confirm_detected: confirmDetected,
         }
       });
     } catch (error) {
-      throw createTauriCommandError('rollback_to_turn', error, { sessionId, turnIndex, deleteTurns });
+      throw detectedChangesError(error)
+        ?? createTauriCommandError('rollback_to_turn', error, { sessionId, turnIndex, deleteTurns });
     }
   }
 
    
   async rollbackEntireSession(
     sessionId: string,
-    deleteSession: boolean = true
+    deleteSession: boolean = true,
+    confirmDetected: boolean = false
   ): Promise<string[]> {
     try {
       return await api.invoke('rollback_session', {
         request: {
           session_id: sessionId,
           delete_session: deleteSession,
+          confirm_detected: confirmDetected,
         }
       });
     } catch (error) {
-      throw createTauriCommandError('rollback_session', error, { sessionId });
+      throw detectedChangesError(error)
+        ?? createTauriCommandError('rollback_session', error, { sessionId });
     }
   }
 
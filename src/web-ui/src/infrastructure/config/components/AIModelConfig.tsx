@@ -113,7 +113,7 @@ const AIModelConfig: React.FC = () => {
   }, [aiModels, selectedCategoryTab, searchQuery]);
 
   // Provider options with translations (must be at top level, before any conditional returns)
-  const providerOrder = ['zhipu', 'qwen', 'deepseek', 'volcengine', 'minimax', 'moonshot', 'anthropic'];
+  const providerOrder = ['zhipu', 'qwen', 'deepseek', 'volcengine', 'minimax', 'moonshot', 'anthropic', 'gemini', 'openai'];
   const providers = useMemo(() => {
     const sorted = Object.values(PROVIDER_TEMPLATES).sort((a, b) => {
       const indexA = providerOrder.indexOf(a.id);
@@ -671,7 +671,9 @@ const AIModelConfig: React.FC = () => {
                     placeholder={t('form.providerPlaceholder')}
                     options={[
                       { label: 'OpenAI', value: 'openai' },
-                      { label: 'Anthropic', value: 'anthropic' }
+                      { label: 'Anthropic', value: 'anthropic' },
+                      { label: 'Gemini', value: 'gemini' },
+                      { label: 'OpenAI Responses', value: 'responses' }
                     ]}
                   />
                   <small style={{ color: 'var(--color-text-secondary)', fontSize: '12px' }}>
@@ -859,7 +861,9 @@ const AIModelConfig: React.FC = () => {
                     placeholder={t('form.providerPlaceholder')}
                     options={[
                       { label: 'OpenAI', value: 'openai' },
-                      { label: 'Anthropic', value: 'anthropic' }
+                      { label: 'Anthropic', value: 'anthropic' },
+                      { label: 'Gemini', value: 'gemini' },
+                      { label: 'OpenAI Responses', value: 'responses' }
                     ]}
                   />
                 </div>
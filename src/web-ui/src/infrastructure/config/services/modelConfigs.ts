@@ -20,6 +20,32 @@ export const PROVIDER_TEMPLATES: Record<string, ProviderTemplate> = {
     helpUrl: 'https://console.anthropic.com/'
   },
   
+  openai: {
+    id: 'openai',
+    name: t('settings/ai-model:providers.openai.name'),
+    baseUrl: 'https://api.openai.com/v1/responses',
+    format: 'responses',
+    models: ['gpt-5', 'gpt-5-mini', 'gpt-5-codex'],
+    requiresApiKey: true,
+    description: t('settings/ai-model:providers.openai.description'),
+    helpUrl: 'https://platform.openai.com/api-keys',
+    baseUrlOptions: [
+      { url: 'https://api.openai.com/v1/responses', format: 'responses', note: 'default' },
+      { url: 'https://api.openai.com/v1/chat/completions', format: 'openai', note: 'chatCompletions' },
+    ]
+  },
+
+  gemini: {
+    id: 'gemini',
+    name: t('settings/ai-model:providers.gemini.name'),
+    baseUrl: 'https://generativelanguage.googleapis.com/v1beta',
+    format: 'gemini',
+    models: ['gemini-2.5-pro', 'gemini-2.5-flash', 'gemini-2.5-flash-lite'],
+    requiresApiKey: true,
+    description: t('settings/ai-model:providers.gemini.description'),
+    helpUrl: 'https://aistudio.google.com/apikey'
+  },
+
   minimax: {
     id: 'minimax',
     name: t('settings/ai-model:providers.minimax.name'),
@@ -311,6 +337,10 @@ export const getFormatDisplayName = (format: ApiFormat): string => {
       return t('settings/ai-model:formats.openaiCompatible');
     case 'anthropic':
       return t('settings/ai-model:formats.claudeApi');
+    case 'gemini':
+      return t('settings/ai-model:formats.geminiApi');
+    case 'responses':
+      return t('settings/ai-model:formats.openaiResponses');
     default:
       return format;
   }
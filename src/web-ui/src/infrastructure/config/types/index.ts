@@ -135,6 +135,8 @@ export interface AIModelConfig {
   custom_headers_mode?: CustomHeadersMode; 
   skip_ssl_verify?: boolean; 
   custom_request_body?: string; 
+  /** Tokenizer for context accounting; detected from provider/model when unset */
+  tokenizer?: 'o200k' | 'cl100k' | 'anthropic' | 'estimate';
   timeout?: number;
 
   
@@ -157,6 +159,13 @@ export interface ProxyConfig {
   password?: string;
 }
 
+export interface RetryConfig {
+  max_retries: number;
+  initial_delay_ms: number;
+  max_delay_ms: number;
+  jitter: boolean;
+}
+
  
 export interface DefaultModelsConfig {
    
@@ -175,10 +184,12 @@ export interface AIConfig {
   models: AIModelConfig[];  
   default_models: DefaultModelsConfig;  
   agent_models: Record<string, string>;  
+  agent_fallback_models?: Record<string, string[]>;
   func_agent_models: Record<string, string>;  
   mode_configs: Record<string, ModeConfigItem>;  
   subagent_configs: Record<string, SubAgentConfigItem>;  
   proxy: ProxyConfig;  
+  retry?: RetryConfig;
   debug_mode_config: DebugModeConfig;  
   request_timeout: number;
   max_retries: number;
@@ -190,6 +201,31 @@ export interface AIConfig {
   tool_execution_timeout_secs?: number | null;
   tool_confirmation_timeout_secs?: number | null;
   skip_tool_confirmation?: boolean;
+  tool_permissions?: ToolPermissionsConfig;
+  hooks?: HooksConfig;
+}
+
+export interface HookCommandConfig {
+  matcher?: string | null;
+  command: string;
+  timeout_secs?: number | null;
+}
+
+export interface HooksConfig {
+  PreToolUse?: HookCommandConfig[];
+  PostToolUse?: HookCommandConfig[];
+  TurnEnd?: HookCommandConfig[];
+  SessionStart?: HookCommandConfig[];
+}
+
+export interface ToolPermissionRules {
+  allow?: string[];
+  deny?: string[];
+  ask?: string[];
+}
+
+export interface ToolPermissionsConfig extends ToolPermissionRules {
+  modes?: Record<string, ToolPermissionRules>;
 }
 
 